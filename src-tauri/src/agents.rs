@@ -0,0 +1,223 @@
+//! Several characters, each with its own conversation.
+//!
+//! [`OpenClawConfig::agents`] lists the configured agents. Each keeps its
+//! own session key, and with it its own gateway session and stored chat
+//! history ([`crate::memory`]), plus an optional system prompt to give it a
+//! personality, and an optional end-to-end encryption key of its own
+//! ([`e2e_key`]). [`OpenClawConfig::agent_id`] and
+//! [`OpenClawConfig::session_key`] always hold the active agent, so the chat
+//! paths need not know about profiles; [`switch_agent`] swaps them and
+//! emits `"agent-switched"`. To talk to another agent without switching —
+//! say, a researcher working on a long task while the character keeps
+//! chatting — [`config_for`] gives the chat paths a config in which that
+//! agent is the active one ([`crate::openclaw::send_chat_to`]).
+
+use crate::config::{ConfigState, OpenClawConfig};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+
+/// One configured agent.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AgentProfile {
+    pub agent_id: String,
+    /// Display name; the agent id when empty.
+    pub name: String,
+    /// Session key of this agent's conversation; generated on first switch.
+    pub session_key: String,
+    /// Personality and instructions sent ahead of the conversation; empty
+    /// leaves it to the agent.
+    pub system_prompt: String,
+    /// End-to-end encryption key for this agent's chats (see
+    /// [`crate::e2e`]); empty uses [`OpenClawConfig::e2e_key`].
+    pub e2e_key: String,
+}
+
+/// A conversation, as listed by [`list_sessions`].
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    pub agent_id: String,
+    pub name: String,
+    pub session_key: String,
+    /// Whether this is the agent chat messages currently go to.
+    pub active: bool,
+    /// Messages in the stored history.
+    pub message_count: usize,
+    /// Unix time in milliseconds of the last stored message.
+    pub last_message_at: Option<u64>,
+}
+
+/// The profile of the active agent, if it has one.
+fn active_profile(config: &OpenClawConfig) -> Option<&AgentProfile> {
+    config
+        .agents
+        .iter()
+        .find(|profile| profile.agent_id == config.agent_id)
+}
+
+/// System prompt of the active agent, if it has one.
+pub(crate) fn system_prompt(config: &OpenClawConfig) -> Option<&str> {
+    active_profile(config)
+        .map(|profile| profile.system_prompt.as_str())
+        .filter(|prompt| !prompt.trim().is_empty())
+}
+
+/// End-to-end encryption key of the active agent: its profile's, or the
+/// global one. Empty if encryption is off.
+pub(crate) fn e2e_key(config: &OpenClawConfig) -> &str {
+    active_profile(config)
+        .map(|profile| profile.e2e_key.as_str())
+        .filter(|key| !key.is_empty())
+        .unwrap_or(&config.e2e_key)
+}
+
+/// Write the active agent's session key back to its profile, adding one if
+/// it has none, so that switching away keeps its conversation.
+fn sync_active(config: &mut OpenClawConfig) {
+    if config.agent_id.is_empty() {
+        return;
+    }
+    let session_key = config.session_key.clone();
+    let agent_id = config.agent_id.clone();
+    match config.agents.iter_mut().find(|p| p.agent_id == agent_id) {
+        Some(profile) => profile.session_key = session_key,
+        None => config.agents.push(AgentProfile {
+            agent_id,
+            session_key,
+            ..AgentProfile::default()
+        }),
+    }
+}
+
+/// A copy of the config with `agent_id` in place of the active agent, for
+/// chatting with it alongside the active one. An agent without a session
+/// yet gets one, saved to its profile.
+///
+/// # Errors
+///
+/// Returns `Err` if `agent_id` is empty or the config cannot be saved.
+pub(crate) fn config_for(
+    config_state: &ConfigState,
+    agent_id: &str,
+) -> Result<OpenClawConfig, String> {
+    let agent_id = agent_id.trim();
+    if agent_id.is_empty() {
+        return Err("Agent ID must not be empty".to_string());
+    }
+    let mut config = config_state.get()?;
+    if config.agent_id == agent_id {
+        return Ok(config);
+    }
+    let existing = config
+        .agents
+        .iter()
+        .find(|p| p.agent_id == agent_id)
+        .map(|p| p.session_key.clone())
+        .filter(|key| !key.is_empty());
+    let session_key = match existing {
+        Some(key) => key,
+        None => {
+            let key = {
+                let mut stored = config_state.config.write().map_err(|e| e.to_string())?;
+                let index = match stored.agents.iter().position(|p| p.agent_id == agent_id) {
+                    Some(index) => index,
+                    None => {
+                        stored.agents.push(AgentProfile {
+                            agent_id: agent_id.to_string(),
+                            ..AgentProfile::default()
+                        });
+                        stored.agents.len() - 1
+                    }
+                };
+                let profile = &mut stored.agents[index];
+                if profile.session_key.is_empty() {
+                    profile.session_key = crate::config::new_session_key(agent_id);
+                }
+                profile.session_key.clone()
+            };
+            config_state.save()?;
+            key
+        }
+    };
+    config.agent_id = agent_id.to_string();
+    config.session_key = session_key;
+    Ok(config)
+}
+
+/// IPC command: make `agent_id` the active agent.
+///
+/// Chat messages then go to that agent, in its own session. An agent that
+/// is not configured yet gets a new profile.
+///
+/// # Errors
+///
+/// Returns `Err` if `agent_id` is empty or the config cannot be saved.
+#[tauri::command]
+pub fn switch_agent(
+    app: AppHandle,
+    config_state: State<'_, ConfigState>,
+    agent_id: String,
+) -> Result<AgentProfile, String> {
+    let agent_id = agent_id.trim();
+    if agent_id.is_empty() {
+        return Err("Agent ID must not be empty".to_string());
+    }
+    let profile = {
+        let mut config = config_state.config.write().map_err(|e| e.to_string())?;
+        sync_active(&mut config);
+        let index = match config.agents.iter().position(|p| p.agent_id == agent_id) {
+            Some(index) => index,
+            None => {
+                config.agents.push(AgentProfile {
+                    agent_id: agent_id.to_string(),
+                    ..AgentProfile::default()
+                });
+                config.agents.len() - 1
+            }
+        };
+        let profile = &mut config.agents[index];
+        if profile.session_key.is_empty() {
+            profile.session_key = crate::config::new_session_key(agent_id);
+        }
+        let profile = profile.clone();
+        config.agent_id = profile.agent_id.clone();
+        config.session_key = profile.session_key.clone();
+        profile
+    };
+    config_state.save()?;
+    println!(
+        "[agents] Switched to {} ({})",
+        profile.agent_id, profile.session_key
+    );
+    let _ = app.emit("agent-switched", &profile);
+    Ok(profile)
+}
+
+/// IPC command: the conversations of all configured agents, with the size
+/// and age of their stored history.
+#[tauri::command]
+pub fn list_sessions(config_state: State<'_, ConfigState>) -> Result<Vec<SessionInfo>, String> {
+    let mut config = config_state.get()?;
+    sync_active(&mut config);
+    config
+        .agents
+        .iter()
+        .map(|profile| {
+            let (message_count, last_message_at) =
+                crate::memory::history_summary(&profile.session_key)?;
+            Ok(SessionInfo {
+                agent_id: profile.agent_id.clone(),
+                name: if profile.name.is_empty() {
+                    profile.agent_id.clone()
+                } else {
+                    profile.name.clone()
+                },
+                session_key: profile.session_key.clone(),
+                active: profile.agent_id == config.agent_id,
+                message_count,
+                last_message_at,
+            })
+        })
+        .collect()
+}
@@ -1,115 +1,1157 @@
 //! System audio level monitoring via `cpal`.
 //!
-//! Uses the default input device to capture audio samples and compute
-//! an RMS level. The stream is kept alive by leaking it into static
-//! memory (it runs for the lifetime of the application).
+//! Uses the input device chosen with [`set_monitoring_device`] (the system
+//! default unless configured) to capture audio samples and compute an RMS
+//! level. On Windows, output devices are also offered as loopback sources.
+//! The stream is owned by a background thread that opens and closes it
+//! according to the user's capture schedule ([`AudioConfig`]):
+//! during excluded hours, or when `headphones_only` is set and the output
+//! device does not look like headphones, the input device is not opened at
+//! all. Users can also stop monitoring outright ([`stop_audio_monitoring`],
+//! the tray's "Listen to Audio" item), which releases the device — and with
+//! it the macOS microphone indicator — until [`restart_audio_monitoring`].
+//! While the onboarding wizard has not reached its microphone step
+//! ([`crate::onboarding`]) the device is not opened either.
+//! Level analysis can also be switched off (e.g. by the battery saver,
+//! see [`crate::power`]) while the stream keeps running.
+//!
+//! So the webview need not poll the level every frame, the callback also
+//! pushes `"music-started"` / `"music-stopped"` (with hysteresis and a hold
+//! time) and `"loud-noise"` events against [`AudioThresholds`]. A
+//! [`SceneClassifier`] ([`crate::scene`]) tells music from typing, talking
+//! and silence ([`get_audio_scene`], `"audio-scene-changed"`), and
+//! `"music-started"` is held off while the room is typing or talking.
+//!
+//! [`start_recording`] / [`stop_recording`] record voice memos from the
+//! user's microphone to a WAV or FLAC file in `<data dir>/recordings`,
+//! alongside (not through) monitoring.
+//!
+//! [`get_system_volume`], [`set_system_volume`] and [`get_mute_state`]
+//! read and control the system output (CoreAudio on macOS,
+//! `IAudioEndpointVolume` on Windows, `wpctl` on Linux). On macOS,
+//! [`get_playing_apps`] lists the applications producing audio.
+//!
+//! The smoothed level's responsiveness is configurable ([`set_smoothing`])
+//! and can be calibrated against the room's noise floor
+//! ([`calibrate_noise_floor`]).
+//!
+//! Besides the smoothed RMS level, the callback records unsmoothed
+//! [`AudioLevels`] (RMS, peak, dBFS, per channel) for [`get_audio_levels`]
+//! and optional `"audio-level"` events, and computes [`spectrum::BANDS`]
+//! frequency bands ([`crate::spectrum`]) for visualizers, read with
+//! [`get_audio_spectrum`] or pushed as `"audio-spectrum"` events when
+//! [`AudioConfig::spectrum_events`] is on. On microphone streams a
+//! [`VoiceDetector`] ([`crate::vad`]) also emits `"speech-started"` and
+//! `"speech-stopped"`, so the character can tell the user's voice from music.
+//!
+//! Playback goes the other way: [`play_voice`] plays TTS clips and sound
+//! effects from Rust with `rodio`, so they are heard even while the webview
+//! (and its muted `<audio>` elements) is hidden. Effects are ducked while the
+//! character talks (optionally the system volume too, see
+//! [`AudioConfig::system_duck`]), and voice detection ignores the
+//! character's own voice.
+//! Voice clips are analysed up front ([`crate::lipsync`]) and emit
+//! `"viseme"` events as playback reaches each frame.
+//!
+//! cpal has no device-change notifications, so the thread watches for them
+//! itself: a stream error, buffers that stop arriving, or a new system
+//! default (when following it) rebuilds the stream and emits
+//! `"audio-monitoring-restarted"`. Unplugged headphones therefore neither
+//! freeze the level nor end monitoring.
 
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use chrono::{Datelike, Local, Timelike};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::config::ConfigState;
+use crate::lipsync::{self, VisemeFrame};
+use crate::onboarding::{self, OnboardingStep};
+use crate::scene::{AudioScene, SceneClassifier};
+use crate::spectrum::{self, SpectrumAnalyzer};
+use crate::user_error::{user_facing_error, Severity};
+use crate::vad::{VoiceDetector, VoiceTransition};
+use crate::wakeword::{self, WakeListener};
+
+/// How often the capture schedule is re-checked.
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Granularity of the thread's sleep, so device changes apply promptly.
+const WAKE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Voice memos stop by themselves after this long.
+const MAX_RECORDING: Duration = Duration::from_secs(10 * 60);
+
+/// A running stream whose callback has been silent this long is rebuilt.
+const STALL_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Prefix of device ids that capture an output device (WASAPI loopback).
+const LOOPBACK_PREFIX: &str = "loopback:";
+
+/// Minimum spacing of `"audio-spectrum"` events (~30 Hz).
+const SPECTRUM_EVENT_INTERVAL: Duration = Duration::from_millis(33);
 
 /// Shared atomic holding the current audio level as f32 bits (0.0 - 1.0).
 static AUDIO_LEVEL: AtomicU32 = AtomicU32::new(0);
 
-/// Start monitoring system audio input level.
-/// The stream is intentionally leaked to keep it alive for the app's lifetime.
-/// Returns `true` if monitoring started successfully.
-pub fn start_audio_monitoring() -> bool {
-    let host = cpal::default_host();
+/// Default [`AudioConfig::smoothing`].
+const DEFAULT_SMOOTHING: f32 = 0.1;
 
-    // Use default input device (microphone / system audio capture)
-    let device = match host.default_input_device() {
-        Some(d) => d,
-        None => {
-            eprintln!("[audio] No input device found");
-            return false;
+/// Smoothing weight as f32 bits, refreshed by the thread.
+static SMOOTHING: AtomicU32 = AtomicU32::new(DEFAULT_SMOOTHING.to_bits());
+
+/// Calibrated noise floor (RMS) as f32 bits, refreshed by the thread.
+static NOISE_FLOOR: AtomicU32 = AtomicU32::new(0);
+
+/// How long [`calibrate_noise_floor`] listens.
+const CALIBRATION_TIME: Duration = Duration::from_secs(10);
+
+/// A floor above this is not a quiet room; calibration refuses it.
+const MAX_NOISE_FLOOR: f32 = 0.5;
+
+/// Whether incoming buffers are analysed at all.
+static ANALYSIS_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Latest spectrum bands as f32 bits (0.0 - 1.0 each).
+static SPECTRUM: [AtomicU32; spectrum::BANDS] = [const { AtomicU32::new(0) }; spectrum::BANDS];
+
+/// Latest unsmoothed levels, see [`get_audio_levels`].
+static LEVELS: Mutex<Option<AudioLevels>> = Mutex::new(None);
+
+/// The voice memo being recorded, see [`start_recording`].
+static RECORDING: Mutex<Option<thread::JoinHandle<Result<Recording, String>>>> = Mutex::new(None);
+
+/// Set by [`stop_recording`].
+static STOP_RECORDING: AtomicBool = AtomicBool::new(false);
+
+/// Latest ambient scene, see [`get_audio_scene`].
+static SCENE: Mutex<AudioScene> = Mutex::new(AudioScene::Silence);
+
+/// Rate of `"audio-level"` events in Hz; `0` turns them off.
+static LEVEL_EVENT_HZ: AtomicU32 = AtomicU32::new(0);
+
+/// Thresholds for the pushed level events, refreshed by the thread.
+static THRESHOLDS: Mutex<Option<AudioThresholds>> = Mutex::new(None);
+
+/// dBFS reported for digital silence.
+const SILENCE_DBFS: f32 = -100.0;
+
+/// Whether `"audio-spectrum"` events are pushed.
+static SPECTRUM_EVENTS: AtomicBool = AtomicBool::new(false);
+
+/// Whether an input stream is currently open.
+static CAPTURING: AtomicBool = AtomicBool::new(false);
+
+/// Whether capture is currently suspended by the schedule.
+static SUSPENDED: AtomicBool = AtomicBool::new(false);
+
+/// Why capture is suspended, as last decided by the monitoring thread.
+static SUSPEND_REASON: Mutex<Option<&'static str>> = Mutex::new(None);
+
+/// Name of the device the open stream captures.
+static CAPTURE_DEVICE: Mutex<Option<String>> = Mutex::new(None);
+
+/// How long [`stop_audio_monitoring`] waits for the device to be released.
+const RELEASE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Set when the device or on/off setting changes, to reopen the stream
+/// right away.
+static REOPEN: AtomicBool = AtomicBool::new(false);
+
+/// Set by the stream's error callback; the thread rebuilds the stream.
+static STREAM_FAILED: AtomicBool = AtomicBool::new(false);
+
+/// Unix time in milliseconds of the last buffer delivered by the stream.
+static LAST_BUFFER_MS: AtomicU64 = AtomicU64::new(0);
+
+/// Whether microphone streams run voice activity detection.
+static VOICE_DETECTION: AtomicBool = AtomicBool::new(true);
+
+/// Whether a voice clip is playing (see [`play_voice`]).
+static VOICE_PLAYING: AtomicBool = AtomicBool::new(false);
+
+/// System volume before ducking and the value it was lowered to, while a
+/// voice clip has it ducked.
+static SYSTEM_DUCKED: Mutex<Option<(f32, f32)>> = Mutex::new(None);
+
+/// Queue of the playback thread, started on first use.
+static PLAYER: OnceLock<Sender<PlayerCommand>> = OnceLock::new();
+
+/// Effects volume while a voice clip plays.
+const DUCK_VOLUME: f32 = 0.3;
+
+/// Largest share of the system volume [`AudioConfig::system_duck`] takes.
+const MAX_SYSTEM_DUCK: f32 = 0.9;
+
+/// A system-ducked voice is boosted to make up for it, but no further.
+const MAX_VOICE_BOOST: f32 = 2.0;
+
+/// How often the playback thread refreshes ducking.
+const PLAYER_TICK: Duration = Duration::from_millis(50);
+
+/// Payload of `"speech-stopped"` events.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeechStopped {
+    pub duration_ms: u64,
+}
+
+/// Payload of `"audio-scene-changed"` events.
+#[derive(Serialize, Clone, Debug)]
+pub struct SceneChanged {
+    pub scene: AudioScene,
+    pub previous: AudioScene,
+}
+
+/// Payload of `"audio-monitoring-restarted"` events.
+#[derive(Serialize, Clone, Debug)]
+pub struct MonitoringRestarted {
+    /// Name of the device now being monitored.
+    pub device: String,
+    /// `"stream-error"`, `"stalled"` or `"default-changed"`.
+    pub reason: &'static str,
+}
+
+/// A local-time window during which the microphone is never opened, also
+/// used for quiet hours ([`crate::quiet`]).
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExcludedHours {
+    /// First excluded hour (0-23, local time).
+    pub start: u8,
+    /// Hour at which capture may resume (0-23). A range with `end < start`
+    /// wraps past midnight; `start == end` excludes the whole day.
+    pub end: u8,
+    /// Days it applies to, `0` = Sunday … `6` = Saturday. Empty means every day.
+    /// A range that wraps past midnight belongs to the day it starts on.
+    #[serde(default)]
+    pub days: Vec<u8>,
+}
+
+impl ExcludedHours {
+    pub(crate) fn contains(&self, weekday: u8, hour: u8) -> bool {
+        let applies = |day: u8| self.days.is_empty() || self.days.contains(&day);
+        if self.start == self.end {
+            applies(weekday)
+        } else if self.start < self.end {
+            applies(weekday) && (self.start..self.end).contains(&hour)
+        } else if hour >= self.start {
+            applies(weekday)
+        } else {
+            hour < self.end && applies((weekday + 6) % 7)
+        }
+    }
+}
+
+/// Audio section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AudioConfig {
+    /// Monitoring is on at all; see [`stop_audio_monitoring`].
+    pub enabled: bool,
+    /// Schedules during which capture is suspended (e.g. 9–17 on weekdays).
+    pub excluded_hours: Vec<ExcludedHours>,
+    /// Only capture while the default output device looks like headphones.
+    pub headphones_only: bool,
+    /// Case-insensitive substrings of output device names treated as
+    /// headphones for `headphones_only`.
+    pub headphone_keywords: Vec<String>,
+    /// Push `"audio-spectrum"` events (~30 Hz) in addition to the
+    /// [`get_audio_spectrum`] command.
+    pub spectrum_events: bool,
+    /// When `"music-started"`, `"music-stopped"` and `"loud-noise"` fire.
+    pub thresholds: AudioThresholds,
+    /// Rate of pushed `"audio-level"` events in Hz (capped at 60); `0`
+    /// leaves [`get_audio_levels`] as the only way to read them.
+    pub level_event_hz: u32,
+    /// [`AudioDevice::id`] of the monitored device; `None` uses the system
+    /// default input.
+    pub device: Option<String>,
+    /// Emit `"speech-started"` / `"speech-stopped"` from microphone input.
+    pub voice_detection: bool,
+    /// Name of the output device for [`play_voice`]; `None` uses the
+    /// system default.
+    pub output_device: Option<String>,
+    /// Share of the system volume (`0.0`–`0.9`) taken away while the
+    /// character speaks, so music does not drown out the voice; `0.0`
+    /// leaves the system volume alone. [`play_voice`] can override it.
+    pub system_duck: f32,
+    /// Weight of each new buffer in the smoothed level, `0.01`–`1.0`
+    /// (see [`set_smoothing`]). Higher follows beats, lower suits idle
+    /// detection.
+    pub smoothing: f32,
+    /// Ambient RMS measured by [`calibrate_noise_floor`]; the smoothed
+    /// level is relative to it. `0.0` until calibrated.
+    pub noise_floor: f32,
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            excluded_hours: Vec::new(),
+            headphones_only: false,
+            headphone_keywords: ["headphone", "headset", "airpods", "earbuds", "buds"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            spectrum_events: false,
+            thresholds: AudioThresholds::default(),
+            level_event_hz: 0,
+            device: None,
+            voice_detection: true,
+            output_device: None,
+            system_duck: 0.0,
+            smoothing: DEFAULT_SMOOTHING,
+            noise_floor: 0.0,
+        }
+    }
+}
+
+/// Levels at which the callback pushes events.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AudioThresholds {
+    /// Smoothed level (see [`get_audio_level`]) at which music has started.
+    pub music_on: f32,
+    /// Smoothed level below which it has stopped; lower than `music_on` so
+    /// a level hovering at the threshold does not flap.
+    pub music_off: f32,
+    /// How long the level must stay past a threshold before the event.
+    pub music_hold_ms: u64,
+    /// Buffer peak that counts as a loud noise.
+    pub loud_peak: f32,
+    /// Minimum spacing of `"loud-noise"` events.
+    pub loud_cooldown_ms: u64,
+}
+
+impl Default for AudioThresholds {
+    fn default() -> Self {
+        Self {
+            music_on: 0.05,
+            music_off: 0.02,
+            music_hold_ms: 1500,
+            loud_peak: 0.8,
+            loud_cooldown_ms: 2000,
+        }
+    }
+}
+
+/// Payload of `"music-started"`, `"music-stopped"` and `"loud-noise"`.
+#[derive(Serialize, Clone, Debug)]
+pub struct LevelEvent {
+    /// Smoothed level, or the peak for `"loud-noise"`.
+    pub level: f32,
+}
+
+/// Hysteresis state for the threshold events; lives in the callback.
+#[derive(Default)]
+struct ThresholdTracker {
+    thresholds: AudioThresholds,
+    music: bool,
+    /// When the level first crossed toward the other state.
+    crossing_since: Option<Instant>,
+    last_loud: Option<Instant>,
+}
+
+impl ThresholdTracker {
+    /// Check one buffer. `not_music` holds off `"music-started"` while the
+    /// voice detector hears the user.
+    fn update(&mut self, app: &AppHandle, smoothed: f32, peak: f32, not_music: bool) {
+        if let Ok(Some(t)) = THRESHOLDS.try_lock().as_deref() {
+            self.thresholds = *t;
+        }
+        let t = self.thresholds;
+
+        let crossing = if self.music {
+            smoothed <= t.music_off
+        } else {
+            smoothed >= t.music_on && !not_music
+        };
+        if !crossing {
+            self.crossing_since = None;
+        } else if self
+            .crossing_since
+            .get_or_insert_with(Instant::now)
+            .elapsed()
+            >= Duration::from_millis(t.music_hold_ms)
+        {
+            self.music = !self.music;
+            self.crossing_since = None;
+            let event = if self.music {
+                // The players may have something new to report.
+                crate::media::wake();
+                "music-started"
+            } else {
+                "music-stopped"
+            };
+            let _ = app.emit(event, LevelEvent { level: smoothed });
         }
+
+        let cooled = self
+            .last_loud
+            .is_none_or(|at| at.elapsed() >= Duration::from_millis(t.loud_cooldown_ms));
+        if peak >= t.loud_peak && cooled {
+            self.last_loud = Some(Instant::now());
+            let _ = app.emit("loud-noise", LevelEvent { level: peak });
+        }
+    }
+}
+
+/// Why capture is currently not allowed, or `None` if it is.
+fn suspension_reason(config: &AudioConfig) -> Option<&'static str> {
+    if !config.enabled {
+        return Some("stopped by user");
+    }
+    let now = Local::now();
+    let (weekday, hour) = (now.weekday().num_days_from_sunday() as u8, now.hour() as u8);
+    if config
+        .excluded_hours
+        .iter()
+        .any(|r| r.contains(weekday, hour))
+    {
+        return Some("excluded hours");
+    }
+    if config.headphones_only {
+        let output = cpal::default_host()
+            .default_output_device()
+            .and_then(|d| d.name().ok())
+            .unwrap_or_default()
+            .to_lowercase();
+        let headphones = config
+            .headphone_keywords
+            .iter()
+            .any(|k| !k.is_empty() && output.contains(&k.to_lowercase()));
+        if !headphones {
+            return Some("output is not headphones");
+        }
+    }
+    None
+}
+
+/// Enable or disable level analysis. While disabled the level reads `0.0`.
+pub fn set_analysis_enabled(enabled: bool) {
+    ANALYSIS_ENABLED.store(enabled, Ordering::Relaxed);
+    if !enabled {
+        reset_levels();
+    }
+}
+
+/// Zero the level and spectrum (no audio is being analysed).
+fn reset_levels() {
+    AUDIO_LEVEL.store(0f32.to_bits(), Ordering::Relaxed);
+    for band in &SPECTRUM {
+        band.store(0f32.to_bits(), Ordering::Relaxed);
+    }
+    if let Ok(mut levels) = LEVELS.lock() {
+        *levels = None;
+    }
+}
+
+/// Whether the input stream is currently open.
+pub fn is_capturing() -> bool {
+    CAPTURING.load(Ordering::Relaxed)
+}
+
+/// Whether the capture schedule currently keeps the stream closed.
+pub fn is_suspended() -> bool {
+    SUSPENDED.load(Ordering::Relaxed)
+}
+
+/// Start the audio monitoring thread.
+///
+/// The thread opens the input stream whenever the capture schedule allows it
+/// and drops it (closing the device) when it does not, re-checking every
+/// [`SCHEDULE_CHECK_INTERVAL`]. A failure to open the device is reported
+/// once via [`user_facing_error`], and retried in case a device appears.
+/// A stream that fails or goes silent is rebuilt right away (see the module
+/// docs).
+pub fn start_audio_monitoring(app: AppHandle, running: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        // cpal::Stream is !Send on macOS, so it lives and dies on this thread.
+        let mut stream: Option<OpenStream> = None;
+        let mut reported_failure = false;
+        let mut last_reason = None;
+        // Configured device the open stream was opened for.
+        let mut open_device: Option<String> = None;
+        // Why the previous stream was torn down, if it broke.
+        let mut restart_reason: Option<&'static str> = None;
+
+        while running.load(Ordering::Relaxed) {
+            let settings = app
+                .state::<ConfigState>()
+                .get_settings()
+                .unwrap_or_default();
+            let config = settings.audio;
+            let reason = if settings.onboarding.waits_for(OnboardingStep::Microphone) {
+                Some("microphone not set up")
+            } else {
+                suspension_reason(&config)
+            };
+            SPECTRUM_EVENTS.store(config.spectrum_events, Ordering::Relaxed);
+            LEVEL_EVENT_HZ.store(config.level_event_hz.min(60), Ordering::Relaxed);
+            if let Ok(mut t) = THRESHOLDS.lock() {
+                *t = Some(config.thresholds);
+            }
+            VOICE_DETECTION.store(config.voice_detection, Ordering::Relaxed);
+            SMOOTHING.store(
+                config.smoothing.clamp(0.01, 1.0).to_bits(),
+                Ordering::Relaxed,
+            );
+            NOISE_FLOOR.store(
+                config.noise_floor.clamp(0.0, MAX_NOISE_FLOOR).to_bits(),
+                Ordering::Relaxed,
+            );
+            wakeword::set_enabled(settings.wake_word.enabled);
+
+            if (REOPEN.swap(false, Ordering::Relaxed) || open_device != config.device)
+                && stream.take().is_some()
+            {
+                reset_levels();
+                println!("[audio] Reopening input stream");
+            }
+            if let Some(why) = stream.as_ref().and_then(broken_reason) {
+                eprintln!("[audio] Rebuilding stream ({why})");
+                stream = None;
+                reset_levels();
+                restart_reason = Some(why);
+            }
+
+            match (reason, stream.is_some()) {
+                (None, false) => match open_stream(&app, config.device.as_deref()) {
+                    Ok(s) => {
+                        println!("[audio] Audio monitoring started on {}", s.device_name);
+                        if let Some(why) = restart_reason.take() {
+                            let _ = app.emit(
+                                "audio-monitoring-restarted",
+                                MonitoringRestarted {
+                                    device: s.device_name.clone(),
+                                    reason: why,
+                                },
+                            );
+                        }
+                        stream = Some(s);
+                        open_device = config.device.clone();
+                        reported_failure = false;
+                    }
+                    Err(e) => {
+                        if !reported_failure {
+                            eprintln!("[audio] {e}");
+                            let message = match check_mic_permission() {
+                                MicPermission::Denied | MicPermission::Restricted => "I can't hear any audio because microphone access is turned off for me in the system privacy settings.",
+                                _ => "I can't hear any audio, so music detection is off (no usable input device).",
+                            };
+                            user_facing_error(&app, "audio", message, Severity::Warning);
+                            reported_failure = true;
+                        }
+                    }
+                },
+                (Some(why), true) => {
+                    stream = None;
+                    reset_levels();
+                    println!("[audio] Capture suspended ({why})");
+                }
+                (Some(why), false) if last_reason != Some(why) => {
+                    println!("[audio] Capture suspended ({why})");
+                }
+                _ => {}
+            }
+            last_reason = reason;
+            CAPTURING.store(stream.is_some(), Ordering::Relaxed);
+            SUSPENDED.store(reason.is_some(), Ordering::Relaxed);
+            if let Ok(mut r) = SUSPEND_REASON.lock() {
+                *r = reason;
+            }
+            if let Ok(mut d) = CAPTURE_DEVICE.lock() {
+                *d = stream.as_ref().map(|s| s.device_name.clone());
+            }
+
+            let slept = Instant::now();
+            while slept.elapsed() < SCHEDULE_CHECK_INTERVAL
+                && !REOPEN.load(Ordering::Relaxed)
+                && !STREAM_FAILED.load(Ordering::Relaxed)
+                && running.load(Ordering::Relaxed)
+            {
+                thread::sleep(WAKE_INTERVAL);
+            }
+        }
+        CAPTURING.store(false, Ordering::Relaxed);
+    });
+}
+
+/// An open stream and what it was opened on.
+struct OpenStream {
+    /// Dropping it closes the device.
+    _stream: cpal::Stream,
+    device_name: String,
+    /// Opened on the system default rather than a configured device, so a
+    /// new default should move it.
+    follows_default: bool,
+    /// WASAPI loopback delivers no buffers while nothing plays, so silence
+    /// is not a stall.
+    loopback: bool,
+    opened_at: Instant,
+}
+
+/// Why `open` must be rebuilt, if it is broken.
+fn broken_reason(open: &OpenStream) -> Option<&'static str> {
+    if STREAM_FAILED.swap(false, Ordering::Relaxed) {
+        return Some("stream-error");
+    }
+    let silent_ms = unix_millis().saturating_sub(LAST_BUFFER_MS.load(Ordering::Relaxed));
+    if !open.loopback
+        && open.opened_at.elapsed() > STALL_TIMEOUT
+        && silent_ms > STALL_TIMEOUT.as_millis() as u64
+    {
+        return Some("stalled");
+    }
+    if open.follows_default {
+        let default_name = cpal::default_host()
+            .default_input_device()
+            .and_then(|d| d.name().ok());
+        if default_name.is_some_and(|name| name != open.device_name) {
+            return Some("default-changed");
+        }
+    }
+    None
+}
+
+/// Error callback shared by every stream: log and flag for a rebuild.
+fn on_stream_error(err: cpal::StreamError) {
+    eprintln!("[audio] Stream error: {err}");
+    STREAM_FAILED.store(true, Ordering::Relaxed);
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Microphone access as granted by the OS privacy settings.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub enum MicPermission {
+    Granted,
+    Denied,
+    /// Blocked by policy (parental controls, MDM); the user cannot change it.
+    Restricted,
+    /// Never asked; the first stream opened triggers the system prompt.
+    NotDetermined,
+}
+
+/// Check microphone access without prompting.
+///
+/// - **macOS** — `+[AVCaptureDevice authorizationStatusForMediaType:]`
+/// - **Windows** — the "Let apps access your microphone" privacy switch
+///   (`CapabilityAccessManager\ConsentStore\microphone`)
+/// - **Other platforms** — always `Granted`
+#[tauri::command]
+pub fn check_mic_permission() -> MicPermission {
+    #[cfg(target_os = "macos")]
+    {
+        use cocoa::base::id;
+        use objc::runtime::Class;
+        use objc::{msg_send, sel, sel_impl};
+
+        #[link(name = "AVFoundation", kind = "framework")]
+        extern "C" {
+            static AVMediaTypeAudio: id;
+        }
+
+        let Some(class) = Class::get("AVCaptureDevice") else {
+            return MicPermission::Granted;
+        };
+        // SAFETY: class method query with a framework string constant;
+        // returns an AVAuthorizationStatus (NSInteger).
+        let status: isize =
+            unsafe { msg_send![class, authorizationStatusForMediaType: AVMediaTypeAudio] };
+        match status {
+            0 => MicPermission::NotDetermined,
+            1 => MicPermission::Restricted,
+            2 => MicPermission::Denied,
+            _ => MicPermission::Granted,
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        const KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore\microphone";
+        // The machine-wide switch overrides the per-user one.
+        let denied = ["HKLM", "HKCU"].iter().any(|root| {
+            std::process::Command::new("reg")
+                .args(["query", &format!(r"{root}\{KEY}"), "/v", "Value"])
+                .output()
+                .is_ok_and(|out| String::from_utf8_lossy(&out.stdout).contains("Deny"))
+        });
+        if denied {
+            MicPermission::Denied
+        } else {
+            MicPermission::Granted
+        }
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        MicPermission::Granted
+    }
+}
+
+/// How long [`request_mic_permission`] waits for the user to answer.
+const PERMISSION_PROMPT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Ask for microphone access.
+///
+/// If the OS has never asked, briefly opens the default input to trigger
+/// the system prompt and waits (up to a minute) for the answer. If access
+/// was already denied, opens the microphone privacy page of System Settings
+/// (or Windows Settings) instead, since apps cannot prompt again. Returns
+/// the resulting status; once granted, the microphone step of onboarding
+/// is done.
+#[tauri::command]
+pub async fn request_mic_permission(app: AppHandle) -> MicPermission {
+    let status = check_mic_permission();
+    let status = match status {
+        MicPermission::NotDetermined => {
+            tauri::async_runtime::spawn_blocking(|| {
+                // Opening a stream is what makes macOS show the prompt.
+                let stream = cpal::default_host().default_input_device().and_then(|d| {
+                    let config = d.default_input_config().ok()?.into();
+                    d.build_input_stream(
+                        &config,
+                        |_: &[f32], _: &cpal::InputCallbackInfo| {},
+                        |_| {},
+                        None,
+                    )
+                    .ok()
+                });
+                let started = Instant::now();
+                while check_mic_permission() == MicPermission::NotDetermined
+                    && started.elapsed() < PERMISSION_PROMPT_TIMEOUT
+                {
+                    thread::sleep(WAKE_INTERVAL);
+                }
+                drop(stream);
+                // Let the monitoring thread retry with the new status.
+                REOPEN.store(true, Ordering::Relaxed);
+                check_mic_permission()
+            })
+            .await
+            .unwrap_or(status)
+        }
+        MicPermission::Denied => {
+            open_mic_privacy_settings();
+            status
+        }
+        _ => status,
     };
+    if status == MicPermission::Granted {
+        let config_state = app.state::<ConfigState>();
+        if let Err(e) = onboarding::complete(&config_state, OnboardingStep::Microphone) {
+            eprintln!("[audio] Failed to record the microphone step: {e}");
+        }
+    }
+    status
+}
+
+/// Open the OS page where microphone access is granted, best effort.
+fn open_mic_privacy_settings() {
+    #[cfg(target_os = "macos")]
+    let command = Some((
+        "open",
+        vec!["x-apple.systempreferences:com.apple.preference.security?Privacy_Microphone"],
+    ));
+
+    #[cfg(target_os = "windows")]
+    let command = Some((
+        "cmd",
+        vec!["/C", "start", "", "ms-settings:privacy-microphone"],
+    ));
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let command: Option<(&str, Vec<&str>)> = None;
 
-    let config = match device.default_input_config() {
-        Ok(c) => c,
-        Err(e) => {
-            eprintln!("[audio] No input config available: {e}");
-            return false;
+    if let Some((program, args)) = command {
+        if let Err(e) = std::process::Command::new(program).args(args).status() {
+            eprintln!("[audio] Failed to open privacy settings: {e}");
         }
+    }
+}
+
+/// A device that can feed the level meter.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioDevice {
+    /// Pass to [`set_monitoring_device`]. Device names are the only
+    /// identifier cpal exposes, so this is the name (prefixed for loopback).
+    pub id: String,
+    pub name: String,
+    /// The system default input.
+    pub is_default: bool,
+    /// Captures what an output device plays (Windows only).
+    pub loopback: bool,
+}
+
+/// Find a device by [`AudioDevice::id`].
+fn find_device(host: &cpal::Host, id: &str) -> Option<cpal::Device> {
+    if let Some(name) = id.strip_prefix(LOOPBACK_PREFIX) {
+        if !cfg!(target_os = "windows") {
+            return None;
+        }
+        return host
+            .output_devices()
+            .ok()?
+            .find(|d| d.name().is_ok_and(|n| n == name));
+    }
+    host.input_devices()
+        .ok()?
+        .find(|d| d.name().is_ok_and(|n| n == id))
+}
+
+/// Open and start the stream of the configured device, falling back to the
+/// default input if it is gone.
+fn open_stream(app: &AppHandle, device_id: Option<&str>) -> Result<OpenStream, String> {
+    // A denied stream still opens on macOS and just delivers silence.
+    // Loopback capture is not microphone access.
+    let permission = check_mic_permission();
+    if matches!(
+        permission,
+        MicPermission::Denied | MicPermission::Restricted
+    ) && !device_id.is_some_and(|id| id.starts_with(LOOPBACK_PREFIX))
+    {
+        return Err(format!("Microphone access is {permission:?}"));
+    }
+    let host = cpal::default_host();
+
+    let configured = device_id.and_then(|id| find_device(&host, id));
+    if let (Some(id), None) = (device_id, &configured) {
+        eprintln!("[audio] Device {id:?} not found, using the default input");
+    }
+    let follows_default = configured.is_none();
+    let loopback =
+        configured.is_some() && device_id.is_some_and(|id| id.starts_with(LOOPBACK_PREFIX));
+    let device = match configured {
+        Some(d) => d,
+        // Use default input device (microphone / system audio capture)
+        None => host
+            .default_input_device()
+            .ok_or_else(|| "No input device found".to_string())?,
     };
 
+    let config = if loopback {
+        device.default_output_config()
+    } else {
+        device.default_input_config()
+    }
+    .map_err(|e| format!("No input config available: {e}"))?;
+
     let sample_format = config.sample_format();
     let stream_config: cpal::StreamConfig = config.into();
+    let mut analysis = Analysis {
+        app: app.clone(),
+        channel_sums: vec![0.0; stream_config.channels.max(1) as usize],
+        last_level_event: None,
+        spectrum: SpectrumAnalyzer::new(stream_config.sample_rate.0, stream_config.channels),
+        // A loopback source is what the speakers play, never the user.
+        voice: (!loopback)
+            .then(|| VoiceDetector::new(stream_config.sample_rate.0, stream_config.channels)),
+        wake: (!loopback)
+            .then(|| WakeListener::new(stream_config.sample_rate.0, stream_config.channels)),
+        thresholds: ThresholdTracker::default(),
+        scene: SceneClassifier::new(stream_config.sample_rate.0, stream_config.channels),
+        last_event: None,
+    };
+
+    let stream = build_input_stream(
+        &device,
+        &stream_config,
+        sample_format,
+        move |samples| analysis.process(samples),
+        on_stream_error,
+    )?;
+    STREAM_FAILED.store(false, Ordering::Relaxed);
+    stream
+        .play()
+        .map_err(|e| format!("Failed to start stream: {e}"))?;
+    Ok(OpenStream {
+        _stream: stream,
+        device_name: device
+            .name()
+            .unwrap_or_else(|_| "unknown device".to_string()),
+        follows_default,
+        loopback,
+        opened_at: Instant::now(),
+    })
+}
 
+/// Build an input stream that hands every buffer to `on_samples` as
+/// interleaved f32 samples in -1.0..1.0, whatever the device's format.
+pub(crate) fn build_input_stream<F>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    sample_format: cpal::SampleFormat,
+    mut on_samples: F,
+    on_error: fn(cpal::StreamError),
+) -> Result<cpal::Stream, String>
+where
+    F: FnMut(&mut dyn Iterator<Item = f32>) + Send + 'static,
+{
     let stream = match sample_format {
         cpal::SampleFormat::F32 => device.build_input_stream(
-            &stream_config,
-            process_f32,
-            |err| eprintln!("[audio] Stream error: {err}"),
+            config,
+            move |data: &[f32], _: &cpal::InputCallbackInfo| on_samples(&mut data.iter().copied()),
+            on_error,
             None,
         ),
         cpal::SampleFormat::I16 => device.build_input_stream(
-            &stream_config,
-            process_i16,
-            |err| eprintln!("[audio] Stream error: {err}"),
+            config,
+            move |data: &[i16], _: &cpal::InputCallbackInfo| {
+                on_samples(&mut data.iter().map(|&s| s as f32 / i16::MAX as f32))
+            },
+            on_error,
             None,
         ),
         cpal::SampleFormat::U16 => device.build_input_stream(
-            &stream_config,
-            process_u16,
-            |err| eprintln!("[audio] Stream error: {err}"),
+            config,
+            move |data: &[u16], _: &cpal::InputCallbackInfo| {
+                on_samples(
+                    &mut data
+                        .iter()
+                        .map(|&s| (s as f32 / u16::MAX as f32) * 2.0 - 1.0),
+                )
+            },
+            on_error,
             None,
         ),
-        _ => {
-            eprintln!("[audio] Unsupported sample format: {sample_format:?}");
-            return false;
-        }
+        _ => return Err(format!("Unsupported sample format: {sample_format:?}")),
     };
+    stream.map_err(|e| format!("Failed to build stream: {e}"))
+}
+
+/// The microphone to record the user from: the configured device if it is
+/// an input, otherwise the system default. Loopback sources carry what the
+/// speakers play, so they are never used.
+pub(crate) fn microphone(device_id: Option<&str>) -> Result<cpal::Device, String> {
+    let host = cpal::default_host();
+    device_id
+        .filter(|id| !id.starts_with(LOOPBACK_PREFIX))
+        .and_then(|id| find_device(&host, id))
+        .or_else(|| host.default_input_device())
+        .ok_or_else(|| "No input device found".to_string())
+}
+
+/// State owned by the cpal callback.
+struct Analysis {
+    app: AppHandle,
+    /// Per-channel sums of squares, reused across buffers.
+    channel_sums: Vec<f32>,
+    last_level_event: Option<Instant>,
+    spectrum: SpectrumAnalyzer,
+    voice: Option<VoiceDetector>,
+    wake: Option<WakeListener>,
+    thresholds: ThresholdTracker,
+    scene: SceneClassifier,
+    last_event: Option<Instant>,
+}
 
-    match stream {
-        Ok(s) => {
-            if let Err(e) = s.play() {
-                eprintln!("[audio] Failed to start stream: {e}");
-                return false;
+impl Analysis {
+    /// Analyse one buffer of interleaved samples normalised to -1.0..1.0.
+    fn process(&mut self, samples: impl Iterator<Item = f32>) {
+        LAST_BUFFER_MS.store(unix_millis(), Ordering::Relaxed);
+        if !ANALYSIS_ENABLED.load(Ordering::Relaxed) {
+            return;
+        }
+        // The character's own voice through the speakers is not the user.
+        let speech_events = VOICE_DETECTION.load(Ordering::Relaxed);
+        let wake_word = wakeword::is_enabled();
+        let detect_voice = (speech_events || wake_word) && !VOICE_PLAYING.load(Ordering::Relaxed);
+        let channels = self.channel_sums.len();
+        self.channel_sums.fill(0.0);
+        let mut sum = 0.0;
+        let mut peak = 0f32;
+        let mut count = 0usize;
+        for sample in samples {
+            sum += sample * sample;
+            peak = peak.max(sample.abs());
+            self.channel_sums[count % channels] += sample * sample;
+            count += 1;
+            if let Some(bands) = self.spectrum.push(sample) {
+                self.publish_spectrum(bands);
+            }
+            let speaking = self.voice.as_ref().is_some_and(VoiceDetector::is_speaking);
+            if let Some(scene) = self.scene.push(sample, speaking) {
+                self.publish_scene(scene);
             }
-            // Intentionally leak the stream so it stays alive for the entire process.
-            // cpal::Stream is !Send on macOS, so we can't move it to another thread.
-            std::mem::forget(s);
-            true
+            if detect_voice {
+                if wake_word {
+                    if let Some(wake) = self.wake.as_mut() {
+                        wake.push(sample);
+                    }
+                }
+                if let Some(transition) = self.voice.as_mut().and_then(|v| v.push(sample)) {
+                    if let (true, Some(wake)) = (wake_word, self.wake.as_mut()) {
+                        wake.on_transition(&self.app, transition);
+                    }
+                    if speech_events {
+                        self.publish_voice(transition);
+                    }
+                }
+            }
+        }
+        if count > 0 {
+            let rms = (sum / count as f32).sqrt().min(1.0);
+            store_level(rms);
+            let speaking = self.voice.as_ref().is_some_and(VoiceDetector::is_speaking);
+            let not_music = speaking
+                || matches!(
+                    self.scene.scene(),
+                    AudioScene::Keyboard | AudioScene::Talking
+                );
+            self.thresholds
+                .update(&self.app, get_audio_level(), peak, not_music);
+            let frames = (count / channels).max(1) as f32;
+            self.publish_levels(AudioLevels {
+                rms,
+                peak: peak.min(1.0),
+                dbfs: if rms > 0.0 {
+                    (20.0 * rms.log10()).max(SILENCE_DBFS)
+                } else {
+                    SILENCE_DBFS
+                },
+                channels: self
+                    .channel_sums
+                    .iter()
+                    .map(|s| (s / frames).sqrt().min(1.0))
+                    .collect(),
+                smoothed: get_audio_level(),
+            });
+        }
+    }
+
+    fn publish_levels(&mut self, levels: AudioLevels) {
+        let hz = LEVEL_EVENT_HZ.load(Ordering::Relaxed);
+        let due = hz > 0
+            && self
+                .last_level_event
+                .is_none_or(|t| t.elapsed() >= Duration::from_secs(1) / hz);
+        if due {
+            self.last_level_event = Some(Instant::now());
+            let _ = self.app.emit("audio-level", levels.clone());
         }
-        Err(e) => {
-            eprintln!("[audio] Failed to build stream: {e}");
-            false
+        // Never block the audio callback on a reader.
+        if let Ok(mut slot) = LEVELS.try_lock() {
+            *slot = Some(levels);
         }
     }
+
+    fn publish_spectrum(&mut self, bands: [f32; spectrum::BANDS]) {
+        for (slot, value) in SPECTRUM.iter().zip(bands) {
+            slot.store(value.to_bits(), Ordering::Relaxed);
+        }
+        if !SPECTRUM_EVENTS.load(Ordering::Relaxed)
+            || self
+                .last_event
+                .is_some_and(|t| t.elapsed() < SPECTRUM_EVENT_INTERVAL)
+        {
+            return;
+        }
+        self.last_event = Some(Instant::now());
+        let _ = self.app.emit("audio-spectrum", bands.to_vec());
+    }
+
+    fn publish_scene(&self, scene: AudioScene) {
+        let previous = match SCENE.lock() {
+            Ok(mut current) => std::mem::replace(&mut *current, scene),
+            Err(_) => return,
+        };
+        let _ = self
+            .app
+            .emit("audio-scene-changed", SceneChanged { scene, previous });
+    }
+
+    fn publish_voice(&self, transition: VoiceTransition) {
+        let _ = match transition {
+            VoiceTransition::Started => self.app.emit("speech-started", ()),
+            VoiceTransition::Stopped { duration_ms } => self
+                .app
+                .emit("speech-stopped", SpeechStopped { duration_ms }),
+        };
+    }
 }
 
-fn compute_rms(samples: &[f32]) -> f32 {
-    if samples.is_empty() {
-        return 0.0;
+impl Drop for Analysis {
+    /// Close an open utterance or song when the stream goes away (device
+    /// switch, schedule, rebuild), so the frontend is never left waiting.
+    fn drop(&mut self) {
+        if self.voice.as_ref().is_some_and(VoiceDetector::is_speaking) {
+            self.publish_voice(VoiceTransition::Stopped { duration_ms: 0 });
+        }
+        if self.thresholds.music {
+            let _ = self.app.emit("music-stopped", LevelEvent { level: 0.0 });
+        }
+        if self.scene.scene() != AudioScene::Silence {
+            self.publish_scene(AudioScene::Silence);
+        }
     }
-    let sum: f32 = samples.iter().map(|s| s * s).sum();
-    (sum / samples.len() as f32).sqrt().min(1.0)
 }
 
 fn store_level(rms: f32) {
-    // Exponential smoothing: 90% old + 10% new
+    // Relative to the room's noise, so a quiet fan reads as silence.
+    let floor = f32::from_bits(NOISE_FLOOR.load(Ordering::Relaxed));
+    let level = ((rms - floor) / (1.0 - floor)).max(0.0);
+    // Exponential smoothing: `alpha` of the new level, the rest old.
+    let alpha = f32::from_bits(SMOOTHING.load(Ordering::Relaxed));
     let old = f32::from_bits(AUDIO_LEVEL.load(Ordering::Relaxed));
-    let smoothed = old * 0.9 + rms * 0.1;
+    let smoothed = old + (level - old) * alpha;
     AUDIO_LEVEL.store(smoothed.to_bits(), Ordering::Relaxed);
 }
 
-fn process_f32(data: &[f32], _: &cpal::InputCallbackInfo) {
-    store_level(compute_rms(data));
+/// Levels of the latest buffer, all on a 0.0 - 1.0 scale except `dbfs`.
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioLevels {
+    /// RMS over all channels, unsmoothed.
+    pub rms: f32,
+    /// Largest absolute sample, so transients stand out from sustained
+    /// music.
+    pub peak: f32,
+    /// `rms` in dB relative to full scale (down to -100).
+    pub dbfs: f32,
+    /// RMS per channel, in stream order (left, right, …).
+    pub channels: Vec<f32>,
+    /// The smoothed level of [`get_audio_level`].
+    pub smoothed: f32,
 }
 
-fn process_i16(data: &[i16], _: &cpal::InputCallbackInfo) {
-    let floats: Vec<f32> = data.iter().map(|&s| s as f32 / i16::MAX as f32).collect();
-    store_level(compute_rms(&floats));
+/// Get the latest [`AudioLevels`]; all zero while nothing is captured.
+#[tauri::command]
+pub fn get_audio_levels() -> AudioLevels {
+    LEVELS
+        .lock()
+        .ok()
+        .and_then(|levels| levels.clone())
+        .unwrap_or(AudioLevels {
+            dbfs: SILENCE_DBFS,
+            ..AudioLevels::default()
+        })
 }
 
-fn process_u16(data: &[u16], _: &cpal::InputCallbackInfo) {
-    let floats: Vec<f32> = data
-        .iter()
-        .map(|&s| (s as f32 / u16::MAX as f32) * 2.0 - 1.0)
-        .collect();
-    store_level(compute_rms(&floats));
+/// Get the ambient [`AudioScene`]; `silence` while nothing is captured.
+#[tauri::command]
+pub fn get_audio_scene() -> AudioScene {
+    SCENE
+        .lock()
+        .map(|scene| *scene)
+        .unwrap_or(AudioScene::Silence)
 }
 
 /// Get the current audio level (0.0 - 1.0 RMS).
@@ -117,3 +1159,1367 @@ fn process_u16(data: &[u16], _: &cpal::InputCallbackInfo) {
 pub fn get_audio_level() -> f32 {
     f32::from_bits(AUDIO_LEVEL.load(Ordering::Relaxed))
 }
+
+/// Get the latest spectrum: [`spectrum::BANDS`] log-spaced frequency bands
+/// from bass to treble, each 0.0 - 1.0.
+#[tauri::command]
+pub fn get_audio_spectrum() -> Vec<f32> {
+    SPECTRUM
+        .iter()
+        .map(|band| f32::from_bits(band.load(Ordering::Relaxed)))
+        .collect()
+}
+
+/// List devices that can feed the level meter.
+///
+/// Inputs (microphones, virtual devices such as BlackHole or Stereo Mix)
+/// come first; on Windows every output device follows as a loopback source.
+#[tauri::command]
+pub fn list_devices() -> Result<Vec<AudioDevice>, String> {
+    let host = cpal::default_host();
+    let default_name = host.default_input_device().and_then(|d| d.name().ok());
+
+    let mut devices: Vec<AudioDevice> = host
+        .input_devices()
+        .map_err(|e| format!("Failed to list input devices: {e}"))?
+        .filter_map(|d| d.name().ok())
+        .map(|name| AudioDevice {
+            id: name.clone(),
+            is_default: default_name.as_deref() == Some(name.as_str()),
+            name,
+            loopback: false,
+        })
+        .collect();
+
+    if cfg!(target_os = "windows") {
+        if let Ok(outputs) = host.output_devices() {
+            devices.extend(
+                outputs
+                    .filter_map(|d| d.name().ok())
+                    .map(|name| AudioDevice {
+                        id: format!("{LOOPBACK_PREFIX}{name}"),
+                        name,
+                        is_default: false,
+                        loopback: true,
+                    }),
+            );
+        }
+    }
+    Ok(devices)
+}
+
+/// Choose the monitored device (`None` = system default) and persist it.
+///
+/// The stream is reopened on the new device within a fraction of a second.
+///
+/// # Errors
+///
+/// Returns `Err` if no device has that id or the settings cannot be saved.
+#[tauri::command]
+pub fn set_monitoring_device(
+    config_state: State<'_, ConfigState>,
+    device_id: Option<String>,
+) -> Result<(), String> {
+    if let Some(id) = &device_id {
+        if find_device(&cpal::default_host(), id).is_none() {
+            return Err(format!("Unknown audio device: {id}"));
+        }
+    }
+    {
+        let mut settings = config_state.settings.write().map_err(|e| e.to_string())?;
+        settings.audio.device = device_id;
+    }
+    config_state.save_settings()?;
+    REOPEN.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Audio for [`play_voice`]: a file path or the encoded bytes themselves
+/// (WAV, MP3, Ogg Vorbis or FLAC).
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum VoiceSource {
+    Path(String),
+    Bytes(Vec<u8>),
+}
+
+/// Which queue a clip plays on.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaybackKind {
+    /// Character speech: queued in order, ducks effects.
+    #[default]
+    Voice,
+    /// Sound effects: mixed over whatever plays.
+    Effect,
+}
+
+/// A decoded clip ready for a sink.
+type Clip = Box<dyn rodio::Source<Item = f32> + Send>;
+
+enum PlayerCommand {
+    Play {
+        clip: Clip,
+        volume: f32,
+        kind: PlaybackKind,
+        output_device: Option<String>,
+        system_duck: f32,
+    },
+    StopVoice,
+}
+
+/// Output stream and its two queues, owned by the playback thread.
+struct Player {
+    // Dropping the stream silences both sinks, so it is kept alongside them.
+    _stream: rodio::OutputStream,
+    device: Option<String>,
+    voice: rodio::Sink,
+    effects: rodio::Sink,
+}
+
+impl Player {
+    fn open(device: Option<String>) -> Result<Self, String> {
+        let named = device.as_deref().and_then(|name| {
+            cpal::default_host()
+                .output_devices()
+                .ok()?
+                .find(|d| d.name().is_ok_and(|n| n == name))
+        });
+        if let (Some(name), None) = (&device, &named) {
+            eprintln!("[audio] Output device {name:?} not found, using the default");
+        }
+        let (stream, handle) = match &named {
+            Some(d) => rodio::OutputStream::try_from_device(d),
+            None => rodio::OutputStream::try_default(),
+        }
+        .map_err(|e| format!("Failed to open audio output: {e}"))?;
+        let sink = || rodio::Sink::try_new(&handle).map_err(|e| e.to_string());
+        Ok(Self {
+            _stream: stream,
+            device,
+            voice: sink()?,
+            effects: sink()?,
+        })
+    }
+}
+
+/// Lower the system volume by `amount`. Returns the original volume and
+/// the ducked one.
+fn duck_system_volume(amount: f32) -> Option<(f32, f32)> {
+    let result = output_volume::read().and_then(|(original, _)| {
+        let ducked = original * (1.0 - amount);
+        output_volume::write(ducked).map(|()| (original, ducked))
+    });
+    result
+        .map_err(|e| eprintln!("[audio] Cannot duck system volume: {e}"))
+        .ok()
+}
+
+/// Undo [`duck_system_volume`], unless the user has moved the volume since.
+fn restore_system_volume(original: f32, ducked: f32) {
+    if output_volume::read().is_ok_and(|(current, _)| (current - ducked).abs() > 0.02) {
+        return;
+    }
+    if let Err(e) = output_volume::write(original) {
+        eprintln!("[audio] Cannot restore system volume: {e}");
+    }
+}
+
+/// Put back a system volume ducked for the character's voice. Called when
+/// the app exits, so it is not left lowered.
+pub fn restore_ducked_volume() {
+    let ducked = SYSTEM_DUCKED.lock().ok().and_then(|mut d| d.take());
+    if let Some((original, ducked)) = ducked {
+        restore_system_volume(original, ducked);
+    }
+}
+
+/// Playback thread: `rodio::OutputStream` is !Send, so it lives here.
+fn run_player(commands: Receiver<PlayerCommand>) {
+    let mut player: Option<Player> = None;
+    loop {
+        match commands.recv_timeout(PLAYER_TICK) {
+            Ok(PlayerCommand::Play {
+                clip,
+                volume,
+                kind,
+                output_device,
+                system_duck,
+            }) => {
+                if player.as_ref().is_none_or(|p| p.device != output_device) {
+                    player = None;
+                    match Player::open(output_device) {
+                        Ok(p) => player = Some(p),
+                        Err(e) => eprintln!("[audio] {e}"),
+                    }
+                }
+                if let Some(p) = &player {
+                    use rodio::Source;
+                    let sink = match kind {
+                        PlaybackKind::Voice => &p.voice,
+                        PlaybackKind::Effect => &p.effects,
+                    };
+                    sink.append(clip.amplify(volume));
+                    // The system volume covers our own output too, so the
+                    // voice is boosted by as much as everything else drops.
+                    if kind == PlaybackKind::Voice && system_duck > 0.0 {
+                        let mut ducked = SYSTEM_DUCKED.lock().unwrap_or_else(|e| e.into_inner());
+                        if ducked.is_none() {
+                            *ducked = duck_system_volume(system_duck);
+                            if ducked.is_some() {
+                                p.voice
+                                    .set_volume((1.0 / (1.0 - system_duck)).min(MAX_VOICE_BOOST));
+                            }
+                        }
+                    }
+                }
+            }
+            Ok(PlayerCommand::StopVoice) => {
+                if let Some(p) = &player {
+                    p.voice.clear();
+                    p.voice.play();
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+
+        let talking = player.as_ref().is_some_and(|p| !p.voice.empty());
+        VOICE_PLAYING.store(talking, Ordering::Relaxed);
+        if !talking && SYSTEM_DUCKED.lock().is_ok_and(|d| d.is_some()) {
+            restore_ducked_volume();
+            if let Some(p) = &player {
+                p.voice.set_volume(1.0);
+            }
+        }
+        if let Some(p) = &player {
+            p.effects
+                .set_volume(if talking { DUCK_VOLUME } else { 1.0 });
+        }
+    }
+}
+
+fn player() -> &'static Sender<PlayerCommand> {
+    PLAYER.get_or_init(|| {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || run_player(rx));
+        tx
+    })
+}
+
+/// Wraps a voice clip and emits its [`VisemeFrame`]s as playback reaches
+/// them, then a closing `sil` when the clip ends or is stopped.
+struct VisemeSource {
+    inner: rodio::buffer::SamplesBuffer<f32>,
+    app: AppHandle,
+    frames: std::vec::IntoIter<VisemeFrame>,
+    next: Option<VisemeFrame>,
+    channels: u64,
+    sample_rate: u64,
+    played: u64,
+    closed: bool,
+}
+
+impl VisemeSource {
+    fn new(app: AppHandle, samples: Vec<f32>, channels: u16, sample_rate: u32) -> Self {
+        let mono: Vec<f32> = samples
+            .chunks(channels.max(1) as usize)
+            .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+            .collect();
+        let mut frames = lipsync::analyze(&mono, sample_rate).into_iter();
+        Self {
+            next: frames.next(),
+            inner: rodio::buffer::SamplesBuffer::new(channels, sample_rate, samples),
+            app,
+            frames,
+            channels: channels.max(1) as u64,
+            sample_rate: sample_rate.max(1) as u64,
+            played: 0,
+            closed: false,
+        }
+    }
+
+    fn close(&mut self) {
+        if !self.closed {
+            self.closed = true;
+            let _ = self.app.emit("viseme", VisemeFrame::SILENT);
+        }
+    }
+}
+
+impl Iterator for VisemeSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let Some(sample) = self.inner.next() else {
+            self.close();
+            return None;
+        };
+        self.played += 1;
+        let now_ms = self.played / self.channels * 1000 / self.sample_rate;
+        while let Some(frame) = self.next.filter(|f| f.time_ms <= now_ms) {
+            let _ = self.app.emit("viseme", frame);
+            self.next = self.frames.next();
+        }
+        Some(sample)
+    }
+}
+
+impl rodio::Source for VisemeSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+impl Drop for VisemeSource {
+    /// A stopped clip must not leave the mouth open.
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Play a TTS clip or sound effect on the configured output device.
+///
+/// `volume` scales the clip (default `1.0`). Voice clips (the default
+/// `kind`) queue behind each other, duck effects while they play and drive
+/// lip-sync through `"viseme"` events. `system_duck` overrides
+/// [`AudioConfig::system_duck`]: the share by which the system volume — and
+/// with it the user's music — is lowered until the voice queue is empty.
+///
+/// A `path` is only played if it is inside the data directory or the
+/// app's bundled resources.
+///
+/// # Errors
+///
+/// Returns `Err` if the path is outside those directories, the file cannot
+/// be read or the audio cannot be decoded.
+#[tauri::command]
+pub async fn play_voice(
+    app: AppHandle,
+    config_state: State<'_, ConfigState>,
+    source: VoiceSource,
+    volume: Option<f32>,
+    kind: Option<PlaybackKind>,
+    system_duck: Option<f32>,
+) -> Result<(), String> {
+    let bytes = match source {
+        VoiceSource::Path(path) => {
+            let path = playable_path(&app, &path)?;
+            tokio::fs::read(&path)
+                .await
+                .map_err(|e| format!("Failed to read {}: {e}", path.display()))?
+        }
+        VoiceSource::Bytes(bytes) => bytes,
+    };
+    let config = config_state.get_settings()?.audio;
+    play_clip(
+        app,
+        bytes,
+        volume.unwrap_or(1.0),
+        kind.unwrap_or_default(),
+        config.output_device,
+        system_duck.unwrap_or(config.system_duck),
+    )
+    .await
+}
+
+/// `path`, resolved, if it is a file [`play_voice`] may play: one inside
+/// the data directory or the app's resources.
+fn playable_path(app: &AppHandle, path: &str) -> Result<PathBuf, String> {
+    let resolved =
+        std::fs::canonicalize(path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+    let allowed = [
+        Some(crate::memory::data_dir()),
+        app.path().resource_dir().ok(),
+    ];
+    allowed
+        .into_iter()
+        .flatten()
+        .filter_map(|dir| std::fs::canonicalize(dir).ok())
+        .any(|dir| resolved.starts_with(dir))
+        .then_some(resolved)
+        .ok_or_else(|| format!("{path} is outside the data and resource directories"))
+}
+
+/// Decode an encoded clip (WAV, MP3, …) and queue it on the player; the
+/// body of [`play_voice`], also used by [`crate::tts`]. Decoding runs on a
+/// blocking thread.
+pub(crate) async fn play_clip(
+    app: AppHandle,
+    bytes: Vec<u8>,
+    volume: f32,
+    kind: PlaybackKind,
+    output_device: Option<String>,
+    system_duck: f32,
+) -> Result<(), String> {
+    let clip = tauri::async_runtime::spawn_blocking(move || decode_clip(app, bytes, kind))
+        .await
+        .map_err(|e| format!("Audio decoding task failed: {e}"))??;
+    player()
+        .send(PlayerCommand::Play {
+            clip,
+            volume: volume.clamp(0.0, 2.0),
+            kind,
+            output_device,
+            system_duck: system_duck.clamp(0.0, MAX_SYSTEM_DUCK),
+        })
+        .map_err(|_| "Audio playback thread stopped".to_string())
+}
+
+/// Decode `bytes` into a clip for the player; voice clips are decoded in
+/// full so [`VisemeSource`] can analyse them.
+fn decode_clip(app: AppHandle, bytes: Vec<u8>, kind: PlaybackKind) -> Result<Clip, String> {
+    let decoder = rodio::Decoder::new(std::io::Cursor::new(bytes))
+        .map_err(|e| format!("Failed to decode audio: {e}"))?;
+    Ok(match kind {
+        PlaybackKind::Voice => {
+            use rodio::Source;
+            let (channels, sample_rate) = (decoder.channels(), decoder.sample_rate());
+            let samples: Vec<f32> = decoder.convert_samples().collect();
+            Box::new(VisemeSource::new(app, samples, channels, sample_rate))
+        }
+        PlaybackKind::Effect => Box::new(rodio::Source::convert_samples(decoder)),
+    })
+}
+
+/// Stop the current voice clip and drop any queued ones.
+#[tauri::command]
+pub fn stop_voice() {
+    if let Some(tx) = PLAYER.get() {
+        let _ = tx.send(PlayerCommand::StopVoice);
+    }
+}
+
+/// Payload of [`get_audio_monitoring_status`].
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioMonitoringStatus {
+    /// Monitoring is switched on ([`AudioConfig::enabled`]).
+    pub enabled: bool,
+    /// The input device is open right now.
+    pub capturing: bool,
+    /// Why the device is closed, e.g. `"stopped by user"`,
+    /// `"excluded hours"` or `"microphone not set up"` (while onboarding
+    /// has not reached that step).
+    pub suspended: Option<&'static str>,
+    /// Device being captured.
+    pub device: Option<String>,
+}
+
+/// Current state of the monitoring thread.
+#[tauri::command]
+pub fn get_audio_monitoring_status(
+    config_state: State<'_, ConfigState>,
+) -> Result<AudioMonitoringStatus, String> {
+    Ok(AudioMonitoringStatus {
+        enabled: config_state.get_settings()?.audio.enabled,
+        capturing: is_capturing(),
+        suspended: SUSPEND_REASON
+            .lock()
+            .map_err(|e| e.to_string())?
+            .as_ref()
+            .copied(),
+        device: CAPTURE_DEVICE.lock().map_err(|e| e.to_string())?.clone(),
+    })
+}
+
+/// Switch monitoring on or off, persist it and wake the thread.
+pub fn set_monitoring_enabled(config_state: &ConfigState, enabled: bool) -> Result<(), String> {
+    {
+        let mut settings = config_state.settings.write().map_err(|e| e.to_string())?;
+        settings.audio.enabled = enabled;
+    }
+    config_state.save_settings()?;
+    REOPEN.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Stop monitoring and release the input device.
+///
+/// Waits until the thread has closed the stream, so `capturing: false` in
+/// the returned status means the microphone is really released; the wait
+/// runs off the main thread. Stays off across restarts until
+/// [`restart_audio_monitoring`].
+#[tauri::command]
+pub async fn stop_audio_monitoring(
+    config_state: State<'_, ConfigState>,
+) -> Result<AudioMonitoringStatus, String> {
+    set_monitoring_enabled(&config_state, false)?;
+    let started = Instant::now();
+    while is_capturing() && started.elapsed() < RELEASE_TIMEOUT {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    get_audio_monitoring_status(config_state)
+}
+
+/// Turn monitoring back on, reopening the stream from scratch.
+#[tauri::command]
+pub fn restart_audio_monitoring(
+    config_state: State<'_, ConfigState>,
+) -> Result<AudioMonitoringStatus, String> {
+    set_monitoring_enabled(&config_state, true)?;
+    get_audio_monitoring_status(config_state)
+}
+
+/// Set how quickly the smoothed level follows the input and persist it.
+///
+/// `alpha` is the weight of each new buffer: `1.0` is no smoothing, the
+/// default `0.1` is the previous fixed behaviour.
+///
+/// # Errors
+///
+/// Returns `Err` if `alpha` is outside `0.01`–`1.0` or the settings cannot
+/// be saved.
+#[tauri::command]
+pub fn set_smoothing(config_state: State<'_, ConfigState>, alpha: f32) -> Result<(), String> {
+    if !(0.01..=1.0).contains(&alpha) {
+        return Err("Smoothing must be between 0.01 and 1.0".to_string());
+    }
+    {
+        let mut settings = config_state.settings.write().map_err(|e| e.to_string())?;
+        settings.audio.smoothing = alpha;
+    }
+    config_state.save_settings()?;
+    SMOOTHING.store(alpha.to_bits(), Ordering::Relaxed);
+    Ok(())
+}
+
+/// Measure the ambient noise floor for [`CALIBRATION_TIME`] and make the
+/// smoothed level relative to it. Returns the floor (RMS) and persists it.
+///
+/// Run it while the room is as quiet as it usually gets.
+///
+/// # Errors
+///
+/// Returns `Err` if monitoring is not capturing, the room is too loud to
+/// be a floor, or the settings cannot be saved.
+#[tauri::command]
+pub async fn calibrate_noise_floor(app: AppHandle) -> Result<f32, String> {
+    if !is_capturing() || !ANALYSIS_ENABLED.load(Ordering::Relaxed) {
+        return Err("Audio monitoring is not running".to_string());
+    }
+    let floor = tauri::async_runtime::spawn_blocking(|| {
+        let mut samples = Vec::new();
+        let started = Instant::now();
+        while started.elapsed() < CALIBRATION_TIME {
+            samples.push(get_audio_levels().rms);
+            thread::sleep(Duration::from_millis(50));
+        }
+        // The median ignores the odd click or cough.
+        samples.sort_by(f32::total_cmp);
+        samples.get(samples.len() / 2).copied().unwrap_or(0.0)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    if floor > MAX_NOISE_FLOOR {
+        return Err("Too loud to calibrate; try again when it is quiet".to_string());
+    }
+
+    let config_state = app.state::<ConfigState>();
+    {
+        let mut settings = config_state.settings.write().map_err(|e| e.to_string())?;
+        settings.audio.noise_floor = floor;
+    }
+    config_state.save_settings()?;
+    NOISE_FLOOR.store(floor.to_bits(), Ordering::Relaxed);
+    println!("[audio] Calibrated noise floor: {floor:.4}");
+    Ok(floor)
+}
+
+/// Change the thresholds of the pushed level events and persist them.
+///
+/// # Errors
+///
+/// Returns `Err` if `music_off` is above `music_on` or the settings cannot
+/// be saved.
+#[tauri::command]
+pub fn set_audio_event_thresholds(
+    config_state: State<'_, ConfigState>,
+    thresholds: AudioThresholds,
+) -> Result<(), String> {
+    if thresholds.music_off > thresholds.music_on {
+        return Err("musicOff must not be above musicOn".to_string());
+    }
+    {
+        let mut settings = config_state.settings.write().map_err(|e| e.to_string())?;
+        settings.audio.thresholds = thresholds;
+    }
+    config_state.save_settings()?;
+    if let Ok(mut t) = THRESHOLDS.lock() {
+        *t = Some(thresholds);
+    }
+    Ok(())
+}
+
+/// A finished voice memo; also the payload of `"recording-stopped"`.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Recording {
+    pub path: String,
+    pub duration_ms: u64,
+}
+
+/// Record the microphone to `path` until stopped. `opened` hears once the
+/// stream is running.
+fn record(
+    path: &Path,
+    device_id: Option<&str>,
+    opened: &Sender<Result<(), String>>,
+) -> Result<Recording, String> {
+    let device = microphone(device_id)?;
+    let supported = device
+        .default_input_config()
+        .map_err(|e| format!("No input config available: {e}"))?;
+    let sample_format = supported.sample_format();
+    let stream_config: cpal::StreamConfig = supported.into();
+    let spec = hound::WavSpec {
+        channels: stream_config.channels,
+        sample_rate: stream_config.sample_rate.0,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    // FLAC is encoded from a WAV once the recording is complete.
+    let flac = has_extension(path, "flac");
+    let wav_path = if flac {
+        path.with_extension("flac.partial")
+    } else {
+        path.to_path_buf()
+    };
+    let writer = hound::WavWriter::create(&wav_path, spec)
+        .map_err(|e| format!("Failed to create {}: {e}", path.display()))?;
+
+    let writer = Arc::new(Mutex::new(writer));
+    let write_failed = Arc::new(AtomicBool::new(false));
+    let (sink, failed) = (writer.clone(), write_failed.clone());
+    let stream = build_input_stream(
+        &device,
+        &stream_config,
+        sample_format,
+        move |samples| {
+            let Ok(mut w) = sink.lock() else { return };
+            for sample in samples {
+                let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+                if w.write_sample(value).is_err() {
+                    failed.store(true, Ordering::Relaxed);
+                    return;
+                }
+            }
+        },
+        |err| eprintln!("[audio] Recording stream error: {err}"),
+    )?;
+    stream
+        .play()
+        .map_err(|e| format!("Failed to start stream: {e}"))?;
+    let _ = opened.send(Ok(()));
+
+    let started = Instant::now();
+    while !STOP_RECORDING.load(Ordering::Relaxed)
+        && !write_failed.load(Ordering::Relaxed)
+        && started.elapsed() < MAX_RECORDING
+    {
+        thread::sleep(Duration::from_millis(50));
+    }
+    // Dropping the stream drops the callback's handle on the writer.
+    drop(stream);
+    let writer = Arc::try_unwrap(writer)
+        .map_err(|_| "Recording stream is still running".to_string())?
+        .into_inner()
+        .map_err(|e| e.to_string())?;
+    let frames = writer.len() as u64 / spec.channels.max(1) as u64;
+    writer
+        .finalize()
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    let written = if write_failed.load(Ordering::Relaxed) {
+        Err(format!("Failed to write {}", path.display()))
+    } else if flac {
+        wav_to_flac(&wav_path, path)
+    } else {
+        Ok(())
+    };
+    if flac {
+        let _ = std::fs::remove_file(&wav_path);
+    }
+    written?;
+    Ok(Recording {
+        path: path.to_string_lossy().into_owned(),
+        duration_ms: frames * 1000 / spec.sample_rate.max(1) as u64,
+    })
+}
+
+/// Reads a complete WAV file as a FLAC [`flacenc::source::Source`].
+struct WavSource {
+    reader: hound::WavReader<std::io::BufReader<std::fs::File>>,
+    buffer: Vec<i32>,
+}
+
+impl flacenc::source::Source for WavSource {
+    fn channels(&self) -> usize {
+        self.reader.spec().channels as usize
+    }
+
+    fn bits_per_sample(&self) -> usize {
+        self.reader.spec().bits_per_sample as usize
+    }
+
+    fn sample_rate(&self) -> usize {
+        self.reader.spec().sample_rate as usize
+    }
+
+    fn read_samples<F: flacenc::source::Fill>(
+        &mut self,
+        block_size: usize,
+        dest: &mut F,
+    ) -> Result<usize, flacenc::error::SourceError> {
+        let channels = self.channels().max(1);
+        self.buffer.clear();
+        for sample in self.reader.samples::<i32>().take(block_size * channels) {
+            self.buffer
+                .push(sample.map_err(flacenc::error::SourceError::from_io_error)?);
+        }
+        dest.fill_interleaved(&self.buffer)?;
+        Ok(self.buffer.len() / channels)
+    }
+
+    fn len_hint(&self) -> Option<usize> {
+        Some(self.reader.duration() as usize)
+    }
+}
+
+/// Encode the WAV file at `wav` as FLAC at `flac`.
+fn wav_to_flac(wav: &Path, flac: &Path) -> Result<(), String> {
+    use flacenc::component::BitRepr;
+    use flacenc::error::Verify;
+
+    let reader = hound::WavReader::open(wav)
+        .map_err(|e| format!("Failed to read {}: {e}", wav.display()))?;
+    let config = flacenc::config::Encoder::default()
+        .into_verified()
+        .map_err(|(_, e)| format!("Invalid FLAC encoder config: {e}"))?;
+    let source = WavSource {
+        reader,
+        buffer: Vec::new(),
+    };
+    let stream = flacenc::encode_with_fixed_block_size(&config, source, config.block_size)
+        .map_err(|e| format!("Failed to encode FLAC: {e}"))?;
+    let mut sink = flacenc::bitsink::ByteSink::new();
+    stream
+        .write(&mut sink)
+        .map_err(|e| format!("Failed to encode FLAC: {e}"))?;
+    std::fs::write(flac, sink.as_slice())
+        .map_err(|e| format!("Failed to write {}: {e}", flac.display()))
+}
+
+fn has_extension(path: &Path, ext: &str) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case(ext))
+}
+
+/// Where [`start_recording`] writes `path`: a file name or relative path
+/// under `<data dir>/recordings`, or an absolute path inside it.
+fn recording_path(path: Option<String>) -> Result<PathBuf, String> {
+    use std::path::Component;
+
+    let dir = crate::memory::data_dir().join("recordings");
+    let Some(path) = path else {
+        return Ok(dir.join(format!("memo-{}.wav", Local::now().format("%Y%m%d-%H%M%S"))));
+    };
+    let requested = PathBuf::from(&path);
+    let resolved = if requested.is_absolute() {
+        requested
+    } else {
+        dir.join(requested)
+    };
+    let inside = resolved
+        .strip_prefix(&dir)
+        .is_ok_and(|rest| rest.components().all(|c| matches!(c, Component::Normal(_))));
+    if !inside {
+        return Err(format!("Recordings are saved in {}", dir.display()));
+    }
+    if !has_extension(&resolved, "wav") && !has_extension(&resolved, "flac") {
+        return Err("Recordings are saved as WAV or FLAC; use a .wav or .flac path".to_string());
+    }
+    Ok(resolved)
+}
+
+/// Start recording a voice memo from the user's microphone (the monitoring
+/// device unless that is a loopback source).
+///
+/// `path` is a `.wav` or `.flac` file name under `<data dir>/recordings`
+/// (or an absolute path inside it); `None` records to
+/// `<data dir>/recordings/memo-<timestamp>.wav`. Returns the full path. The
+/// recording ends with [`stop_recording`] or after [`MAX_RECORDING`], and
+/// `"recording-stopped"` (a [`Recording`]) is emitted either way.
+///
+/// # Errors
+///
+/// Returns `Err` if a recording is already running or the file or device
+/// cannot be opened.
+#[tauri::command]
+pub fn start_recording(
+    app: AppHandle,
+    config_state: State<'_, ConfigState>,
+    path: Option<String>,
+) -> Result<String, String> {
+    let mut slot = RECORDING.lock().map_err(|e| e.to_string())?;
+    if slot.as_ref().is_some_and(|h| !h.is_finished()) {
+        return Err("A recording is already running".to_string());
+    }
+    let path = recording_path(path)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    }
+
+    let device_id = config_state.get_settings()?.audio.device;
+    STOP_RECORDING.store(false, Ordering::Relaxed);
+    let (opened_tx, opened_rx) = mpsc::channel();
+    let file = path.clone();
+    let handle = thread::spawn(move || {
+        let result = record(&file, device_id.as_deref(), &opened_tx);
+        match &result {
+            Ok(recording) => {
+                let _ = app.emit("recording-stopped", recording.clone());
+            }
+            Err(e) => {
+                eprintln!("[audio] Recording failed: {e}");
+                let _ = opened_tx.send(Err(e.clone()));
+            }
+        }
+        result
+    });
+    match opened_rx.recv() {
+        Ok(Ok(())) => {
+            *slot = Some(handle);
+            Ok(path.to_string_lossy().into_owned())
+        }
+        Ok(Err(e)) => Err(e),
+        Err(_) => Err("Recording thread exited".to_string()),
+    }
+}
+
+/// Stop the voice memo and return it once the file is complete.
+///
+/// # Errors
+///
+/// Returns `Err` if nothing was recorded or the file could not be written.
+#[tauri::command]
+pub fn stop_recording() -> Result<Recording, String> {
+    let handle = RECORDING
+        .lock()
+        .map_err(|e| e.to_string())?
+        .take()
+        .ok_or("No recording is running")?;
+    STOP_RECORDING.store(true, Ordering::Relaxed);
+    handle
+        .join()
+        .map_err(|_| "Recording thread panicked".to_string())?
+}
+
+/// Output volume and mute state of the default output device. The
+/// CoreAudio property helpers are shared with [`playing_apps`].
+#[cfg(target_os = "macos")]
+mod output_volume {
+    use std::ffi::c_void;
+
+    #[repr(C)]
+    pub(super) struct PropertyAddress {
+        selector: u32,
+        scope: u32,
+        element: u32,
+    }
+
+    type GetFn = unsafe extern "C" fn(
+        u32,
+        *const PropertyAddress,
+        u32,
+        *const c_void,
+        *mut u32,
+        *mut c_void,
+    ) -> i32;
+
+    #[link(name = "CoreAudio", kind = "framework")]
+    extern "C" {
+        pub(super) fn AudioObjectGetPropertyDataSize(
+            object: u32,
+            address: *const PropertyAddress,
+            qualifier_size: u32,
+            qualifier: *const c_void,
+            data_size: *mut u32,
+        ) -> i32;
+        pub(super) fn AudioObjectGetPropertyData(
+            object: u32,
+            address: *const PropertyAddress,
+            qualifier_size: u32,
+            qualifier: *const c_void,
+            data_size: *mut u32,
+            data: *mut c_void,
+        ) -> i32;
+    }
+
+    // The "virtual main" volume spans all channels; it lives in
+    // AudioHardwareService rather than on the device object.
+    #[link(name = "AudioToolbox", kind = "framework")]
+    extern "C" {
+        fn AudioHardwareServiceGetPropertyData(
+            object: u32,
+            address: *const PropertyAddress,
+            qualifier_size: u32,
+            qualifier: *const c_void,
+            data_size: *mut u32,
+            data: *mut c_void,
+        ) -> i32;
+        fn AudioHardwareServiceSetPropertyData(
+            object: u32,
+            address: *const PropertyAddress,
+            qualifier_size: u32,
+            qualifier: *const c_void,
+            data_size: u32,
+            data: *const c_void,
+        ) -> i32;
+    }
+
+    pub(super) const SYSTEM_OBJECT: u32 = 1;
+    const DEFAULT_OUTPUT_DEVICE: u32 = u32::from_be_bytes(*b"dOut");
+    const VIRTUAL_MAIN_VOLUME: u32 = u32::from_be_bytes(*b"vmvc");
+    const MUTE: u32 = u32::from_be_bytes(*b"mute");
+    pub(super) const SCOPE_GLOBAL: u32 = u32::from_be_bytes(*b"glob");
+    const SCOPE_OUTPUT: u32 = u32::from_be_bytes(*b"outp");
+
+    pub(super) fn address(selector: u32, scope: u32) -> PropertyAddress {
+        PropertyAddress {
+            selector,
+            scope,
+            element: 0,
+        }
+    }
+
+    /// Read a fixed-size property.
+    pub(super) fn get<T: Copy + Default>(
+        get: GetFn,
+        object: u32,
+        selector: u32,
+        scope: u32,
+    ) -> Result<T, String> {
+        let mut value = T::default();
+        let mut size = std::mem::size_of::<T>() as u32;
+        // SAFETY: `value` is a `size`-byte buffer for a plain-data property.
+        let status = unsafe {
+            get(
+                object,
+                &address(selector, scope),
+                0,
+                std::ptr::null(),
+                &mut size,
+                &mut value as *mut T as *mut c_void,
+            )
+        };
+        if status != 0 {
+            return Err(format!("CoreAudio error {status}"));
+        }
+        Ok(value)
+    }
+
+    fn default_device() -> Result<u32, String> {
+        get(
+            AudioObjectGetPropertyData,
+            SYSTEM_OBJECT,
+            DEFAULT_OUTPUT_DEVICE,
+            SCOPE_GLOBAL,
+        )
+    }
+
+    pub fn read() -> Result<(f32, bool), String> {
+        let device = default_device()?;
+        let volume: f32 = get(
+            AudioHardwareServiceGetPropertyData,
+            device,
+            VIRTUAL_MAIN_VOLUME,
+            SCOPE_OUTPUT,
+        )?;
+        // Devices without a mute control (e.g. HDMI) just report unmuted.
+        let muted: u32 = get(AudioObjectGetPropertyData, device, MUTE, SCOPE_OUTPUT).unwrap_or(0);
+        Ok((volume, muted != 0))
+    }
+
+    pub fn write(volume: f32) -> Result<(), String> {
+        let device = default_device()?;
+        // SAFETY: passes a 4-byte Float32 for the virtual main volume.
+        let status = unsafe {
+            AudioHardwareServiceSetPropertyData(
+                device,
+                &address(VIRTUAL_MAIN_VOLUME, SCOPE_OUTPUT),
+                0,
+                std::ptr::null(),
+                std::mem::size_of::<f32>() as u32,
+                &volume as *const f32 as *const c_void,
+            )
+        };
+        if status != 0 {
+            return Err(format!("CoreAudio error {status}"));
+        }
+        Ok(())
+    }
+}
+
+/// Applications producing audio, from CoreAudio's process objects
+/// (macOS 14.2+, the API process taps are built on).
+#[cfg(target_os = "macos")]
+mod playing_apps {
+    use super::output_volume::{
+        address, get, AudioObjectGetPropertyData, AudioObjectGetPropertyDataSize, SCOPE_GLOBAL,
+        SYSTEM_OBJECT,
+    };
+    use super::PlayingApp;
+    use core_foundation::base::TCFType;
+    use core_foundation::string::{CFString, CFStringRef};
+    use std::ffi::c_void;
+
+    const PROCESS_OBJECT_LIST: u32 = u32::from_be_bytes(*b"prs#");
+    const PROCESS_PID: u32 = u32::from_be_bytes(*b"ppid");
+    const PROCESS_BUNDLE_ID: u32 = u32::from_be_bytes(*b"pbid");
+    const PROCESS_IS_RUNNING_OUTPUT: u32 = u32::from_be_bytes(*b"piro");
+
+    fn process_objects() -> Result<Vec<u32>, String> {
+        let address = address(PROCESS_OBJECT_LIST, SCOPE_GLOBAL);
+        let mut size = 0u32;
+        // SAFETY: queries the byte size of the system object's process list.
+        let status = unsafe {
+            AudioObjectGetPropertyDataSize(SYSTEM_OBJECT, &address, 0, std::ptr::null(), &mut size)
+        };
+        if status != 0 {
+            return Err("Per-app audio needs macOS 14.2 or later".to_string());
+        }
+        let mut objects = vec![0u32; size as usize / std::mem::size_of::<u32>()];
+        // SAFETY: `objects` holds `size` bytes of AudioObjectIDs.
+        let status = unsafe {
+            AudioObjectGetPropertyData(
+                SYSTEM_OBJECT,
+                &address,
+                0,
+                std::ptr::null(),
+                &mut size,
+                objects.as_mut_ptr() as *mut c_void,
+            )
+        };
+        if status != 0 {
+            return Err(format!("CoreAudio error {status}"));
+        }
+        objects.truncate(size as usize / std::mem::size_of::<u32>());
+        Ok(objects)
+    }
+
+    fn bundle_id(process: u32) -> Option<String> {
+        let mut value: CFStringRef = std::ptr::null();
+        let mut size = std::mem::size_of::<CFStringRef>() as u32;
+        // SAFETY: the property is a CFString returned at +1, released by
+        // `wrap_under_create_rule`.
+        let status = unsafe {
+            AudioObjectGetPropertyData(
+                process,
+                &address(PROCESS_BUNDLE_ID, SCOPE_GLOBAL),
+                0,
+                std::ptr::null(),
+                &mut size,
+                &mut value as *mut CFStringRef as *mut c_void,
+            )
+        };
+        if status != 0 || value.is_null() {
+            return None;
+        }
+        let id = unsafe { CFString::wrap_under_create_rule(value) }.to_string();
+        (!id.is_empty()).then_some(id)
+    }
+
+    /// The localized application name of `pid`, with browser and Electron
+    /// helper processes ("Google Chrome Helper (Renderer)") folded into
+    /// their app.
+    fn app_name(pid: i32) -> Option<String> {
+        use cocoa::base::{id, nil};
+        use objc::runtime::Class;
+        use objc::{msg_send, sel, sel_impl};
+
+        let class = Class::get("NSRunningApplication")?;
+        // SAFETY: class method and NSString getters; nil is checked at
+        // every step.
+        let name = unsafe {
+            let app: id = msg_send![class, runningApplicationWithProcessIdentifier: pid];
+            if app == nil {
+                return None;
+            }
+            let name: id = msg_send![app, localizedName];
+            if name == nil {
+                return None;
+            }
+            let utf8: *const std::os::raw::c_char = msg_send![name, UTF8String];
+            if utf8.is_null() {
+                return None;
+            }
+            std::ffi::CStr::from_ptr(utf8)
+                .to_string_lossy()
+                .into_owned()
+        };
+        Some(match name.find(" Helper") {
+            Some(at) => name[..at].to_string(),
+            None => name,
+        })
+    }
+
+    pub fn query() -> Result<Vec<PlayingApp>, String> {
+        let mut apps: Vec<PlayingApp> = Vec::new();
+        for process in process_objects()? {
+            let running: u32 = get(
+                AudioObjectGetPropertyData,
+                process,
+                PROCESS_IS_RUNNING_OUTPUT,
+                SCOPE_GLOBAL,
+            )
+            .unwrap_or(0);
+            if running == 0 {
+                continue;
+            }
+            let Ok(pid) = get::<i32>(
+                AudioObjectGetPropertyData,
+                process,
+                PROCESS_PID,
+                SCOPE_GLOBAL,
+            ) else {
+                continue;
+            };
+            let bundle_id = bundle_id(process);
+            let Some(name) = app_name(pid).or_else(|| bundle_id.clone()) else {
+                continue;
+            };
+            // One entry per app, however many helpers it plays through.
+            if apps.iter().any(|a| a.name == name) {
+                continue;
+            }
+            apps.push(PlayingApp {
+                name,
+                bundle_id,
+                pid: pid as u32,
+            });
+        }
+        Ok(apps)
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod playing_apps {
+    pub fn query() -> Result<Vec<super::PlayingApp>, String> {
+        Ok(Vec::new())
+    }
+}
+
+/// An application currently producing audio.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayingApp {
+    /// Localized app name (e.g. `"Spotify"`, `"zoom.us"`).
+    pub name: String,
+    /// e.g. `"com.spotify.client"`; for browsers this is the helper
+    /// process's id.
+    pub bundle_id: Option<String>,
+    pub pid: u32,
+}
+
+/// Applications producing audio right now, so the character can tell
+/// Spotify from a video or a call. Redacted apps are left out.
+///
+/// Only implemented on macOS (14.2+); other platforms return an empty list.
+#[tauri::command]
+pub fn get_playing_apps(config_state: State<'_, ConfigState>) -> Result<Vec<PlayingApp>, String> {
+    let redaction = config_state.get_settings()?.redaction;
+    let mut apps = playing_apps::query()?;
+    apps.retain(|app| !redaction.is_app_redacted(&app.name));
+    Ok(apps)
+}
+
+#[cfg(target_os = "windows")]
+mod output_volume {
+    use windows::Win32::Media::Audio::Endpoints::IAudioEndpointVolume;
+    use windows::Win32::Media::Audio::{
+        eConsole, eRender, IMMDeviceEnumerator, MMDeviceEnumerator,
+    };
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_ALL, COINIT_MULTITHREADED,
+    };
+
+    /// Run `f` against the default render endpoint's volume control.
+    fn with_endpoint<T>(
+        f: impl FnOnce(&IAudioEndpointVolume) -> windows::core::Result<T>,
+    ) -> Result<T, String> {
+        // SAFETY: COM is initialised for this call and released after it;
+        // interfaces are dropped before CoUninitialize.
+        unsafe {
+            // S_OK and S_FALSE each take a reference to release. A thread
+            // already in another apartment (RPC_E_CHANGED_MODE) can still
+            // make the calls, but must not be uninitialised here.
+            let initialized = CoInitializeEx(None, COINIT_MULTITHREADED).is_ok();
+            let result = (|| {
+                let enumerator: IMMDeviceEnumerator =
+                    CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+                let device = enumerator.GetDefaultAudioEndpoint(eRender, eConsole)?;
+                let volume: IAudioEndpointVolume = device.Activate(CLSCTX_ALL, None)?;
+                f(&volume)
+            })();
+            if initialized {
+                CoUninitialize();
+            }
+            result.map_err(|e| format!("Endpoint volume error: {e}"))
+        }
+    }
+
+    pub fn read() -> Result<(f32, bool), String> {
+        // SAFETY: plain getters on a live interface.
+        with_endpoint(|v| unsafe { Ok((v.GetMasterVolumeLevelScalar()?, v.GetMute()?.as_bool())) })
+    }
+
+    pub fn write(volume: f32) -> Result<(), String> {
+        // SAFETY: no event context GUID is passed.
+        with_endpoint(|v| unsafe { v.SetMasterVolumeLevelScalar(volume, std::ptr::null()) })
+    }
+}
+
+/// PipeWire's `wpctl`, which ships with every current desktop distro.
+#[cfg(target_os = "linux")]
+mod output_volume {
+    const SINK: &str = "@DEFAULT_AUDIO_SINK@";
+
+    pub fn read() -> Result<(f32, bool), String> {
+        let output = std::process::Command::new("wpctl")
+            .args(["get-volume", SINK])
+            .output()
+            .map_err(|e| format!("Failed to run wpctl: {e}"))?;
+        // "Volume: 0.40" or "Volume: 0.40 [MUTED]"
+        let text = String::from_utf8_lossy(&output.stdout);
+        let volume = text
+            .split_whitespace()
+            .nth(1)
+            .and_then(|v| v.parse::<f32>().ok())
+            .ok_or_else(|| format!("Unexpected wpctl output: {}", text.trim()))?;
+        Ok((volume, text.contains("[MUTED]")))
+    }
+
+    pub fn write(volume: f32) -> Result<(), String> {
+        let status = std::process::Command::new("wpctl")
+            .args(["set-volume", SINK, &format!("{volume:.2}")])
+            .status()
+            .map_err(|e| format!("Failed to run wpctl: {e}"))?;
+        if !status.success() {
+            return Err("wpctl set-volume failed".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+mod output_volume {
+    pub fn read() -> Result<(f32, bool), String> {
+        Err("System volume is not supported on this platform".to_string())
+    }
+
+    pub fn write(_volume: f32) -> Result<(), String> {
+        Err("System volume is not supported on this platform".to_string())
+    }
+}
+
+/// System output volume of the default device, 0.0 - 1.0.
+#[tauri::command]
+pub fn get_system_volume() -> Result<f32, String> {
+    output_volume::read().map(|(volume, _)| volume)
+}
+
+/// Set the system output volume (clamped to 0.0 - 1.0).
+#[tauri::command]
+pub fn set_system_volume(volume: f32) -> Result<(), String> {
+    if !volume.is_finite() {
+        return Err("Volume must be a number".to_string());
+    }
+    output_volume::write(volume.clamp(0.0, 1.0))
+}
+
+/// Whether the default output device is muted.
+#[tauri::command]
+pub fn get_mute_state() -> Result<bool, String> {
+    output_volume::read().map(|(_, muted)| muted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUNDAY: u8 = 0;
+    const FRIDAY: u8 = 5;
+    const SATURDAY: u8 = 6;
+
+    fn hours(start: u8, end: u8, days: &[u8]) -> ExcludedHours {
+        ExcludedHours {
+            start,
+            end,
+            days: days.to_vec(),
+        }
+    }
+
+    #[test]
+    fn daytime_range_excludes_its_end() {
+        let range = hours(9, 17, &[]);
+        assert!(!range.contains(SUNDAY, 8));
+        assert!(range.contains(SUNDAY, 9));
+        assert!(range.contains(SUNDAY, 16));
+        assert!(!range.contains(SUNDAY, 17));
+    }
+
+    #[test]
+    fn range_wraps_past_midnight() {
+        let range = hours(22, 8, &[]);
+        assert!(!range.contains(SUNDAY, 21));
+        assert!(range.contains(SUNDAY, 22));
+        assert!(range.contains(SUNDAY, 23));
+        assert!(range.contains(SUNDAY, 0));
+        assert!(range.contains(SUNDAY, 7));
+        assert!(!range.contains(SUNDAY, 8));
+        assert!(!range.contains(SUNDAY, 12));
+    }
+
+    #[test]
+    fn equal_start_and_end_is_the_whole_day() {
+        let range = hours(6, 6, &[FRIDAY]);
+        assert!((0..24).all(|hour| range.contains(FRIDAY, hour)));
+        assert!((0..24).all(|hour| !range.contains(SATURDAY, hour)));
+    }
+
+    #[test]
+    fn days_limit_the_range() {
+        let range = hours(9, 17, &[FRIDAY]);
+        assert!(range.contains(FRIDAY, 10));
+        assert!(!range.contains(SATURDAY, 10));
+    }
+
+    #[test]
+    fn wrapped_hours_belong_to_the_starting_day() {
+        let range = hours(22, 8, &[FRIDAY]);
+        assert!(range.contains(FRIDAY, 23));
+        assert!(range.contains(SATURDAY, 2));
+        assert!(!range.contains(FRIDAY, 2));
+        assert!(!range.contains(SATURDAY, 23));
+    }
+
+    #[test]
+    fn saturday_night_wraps_into_sunday() {
+        let range = hours(23, 1, &[SATURDAY]);
+        assert!(range.contains(SUNDAY, 0));
+        assert!(!range.contains(SUNDAY, 23));
+    }
+}
@@ -0,0 +1,183 @@
+//! History of config changes.
+//!
+//! Every time `config.json` or `settings.json` is saved, or edited outside
+//! the app and reloaded, the fields that changed since the last known
+//! version are appended to `config-history.json` next to them, with their
+//! old and new values. Tokens and API keys are reduced to a short SHA-256
+//! fingerprint, enough to tell which one was in use without revealing any
+//! of it. Only the latest [`MAX_ENTRIES`] changes are kept.
+//! [`get_config_history`] returns them, e.g. to find what changed before
+//! the companion stopped connecting.
+
+use crate::config;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Changes kept in the history file.
+pub const MAX_ENTRIES: usize = 200;
+
+/// Hex digits of a masked secret's fingerprint.
+const FINGERPRINT_LEN: usize = 8;
+
+/// One save (or reload) that changed something.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigChange {
+    /// Unix time in milliseconds.
+    pub timestamp: u64,
+    /// `"config"` or `"settings"`.
+    pub file: String,
+    /// `"app"` when saved by the app, `"external"` when the file was edited
+    /// by something else.
+    pub source: String,
+    pub fields: Vec<FieldChange>,
+}
+
+/// A changed field; secrets are masked.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    /// camelCase path, e.g. `"gatewayUrl"` or `"push.port"`.
+    pub field: String,
+    /// `null` when the field did not exist before.
+    pub old: Value,
+    /// `null` when the field was removed.
+    pub new: Value,
+}
+
+/// Last known contents of both files, masked, to diff the next save
+/// against.
+pub(crate) struct Baseline {
+    config: Mutex<Value>,
+    settings: Mutex<Value>,
+}
+
+/// Serializes reads and writes of the history file.
+static HISTORY: Mutex<()> = Mutex::new(());
+
+fn history_path() -> PathBuf {
+    config::config_dir().join("config-history.json")
+}
+
+/// Replace the non-empty `secrets` by a fingerprint: the first
+/// [`FINGERPRINT_LEN`] hex digits of their SHA-256.
+pub(crate) fn mask(secrets: &mut crate::secrets::Fields) {
+    for (_, value) in secrets.iter_mut().filter(|(_, v)| !v.is_empty()) {
+        let digest = Sha256::digest(value.as_bytes());
+        let hex: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+        **value = format!("sha256:{}", &hex[..FINGERPRINT_LEN]);
+    }
+}
+
+/// The leaves that differ between `old` and `new`, by path under `prefix`.
+/// Arrays are compared as a whole.
+fn diff(prefix: &str, old: &Value, new: &Value, changes: &mut Vec<FieldChange>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let keys = old
+                .keys()
+                .chain(new.keys().filter(|k| !old.contains_key(*k)));
+            for key in keys {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                let (old, new) = (
+                    old.get(key).unwrap_or(&Value::Null),
+                    new.get(key).unwrap_or(&Value::Null),
+                );
+                diff(&path, old, new, changes);
+            }
+        }
+        _ if old != new => changes.push(FieldChange {
+            field: prefix.to_string(),
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        _ => {}
+    }
+}
+
+/// The history file's entries, oldest first.
+pub(crate) fn load_history() -> Vec<ConfigChange> {
+    config::read_file(&history_path())
+}
+
+/// Replace the history file with `history`, encrypted if the config files
+/// are.
+pub(crate) fn save_history(history: &[ConfigChange]) -> Result<(), String> {
+    let path = history_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create config dir: {e}"))?;
+    }
+    let json = serde_json::to_string_pretty(history).map_err(|e| e.to_string())?;
+    config::write_atomic(&path, config::encrypt(json)?)
+        .map_err(|e| format!("Failed to write config history: {e}"))
+}
+
+impl Baseline {
+    pub(crate) fn new(config: Value, settings: Value) -> Self {
+        Self {
+            config: Mutex::new(config),
+            settings: Mutex::new(settings),
+        }
+    }
+
+    /// Log what changed in `file` (`"config"` or `"settings"`) now that it
+    /// holds `current`, which becomes the new baseline.
+    ///
+    /// Failures to write the history are logged; they never fail the save.
+    pub(crate) fn record(&self, file: &str, source: &str, current: Value) {
+        let baseline = if file == "config" {
+            &self.config
+        } else {
+            &self.settings
+        };
+        let Ok(mut baseline) = baseline.lock() else {
+            return;
+        };
+        let mut fields = Vec::new();
+        diff("", &baseline, &current, &mut fields);
+        *baseline = current;
+        if fields.is_empty() {
+            return;
+        }
+        let change = ConfigChange {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            file: file.to_string(),
+            source: source.to_string(),
+            fields,
+        };
+        if let Err(e) = append(change) {
+            eprintln!("[audit] Failed to record config change: {e}");
+        }
+    }
+}
+
+fn append(change: ConfigChange) -> Result<(), String> {
+    let _guard = HISTORY.lock().map_err(|e| e.to_string())?;
+    let mut history = load_history();
+    history.push(change);
+    let excess = history.len().saturating_sub(MAX_ENTRIES);
+    history.drain(..excess);
+    save_history(&history)
+}
+
+/// IPC command: recorded config changes, newest first, at most `limit`.
+#[tauri::command]
+pub fn get_config_history(limit: Option<usize>) -> Result<Vec<ConfigChange>, String> {
+    let _guard = HISTORY.lock().map_err(|e| e.to_string())?;
+    let mut history = load_history();
+    history.reverse();
+    history.truncate(limit.unwrap_or(MAX_ENTRIES));
+    Ok(history)
+}
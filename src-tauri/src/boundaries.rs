@@ -0,0 +1,208 @@
+//! Collision boundaries for the walking/gravity simulation.
+//!
+//! Instead of the frontend polling [`crate::screen::window_list`] and
+//! [`crate::window::get_dock_info`] every frame, a background thread samples
+//! them (plus the monitor layout) twice a second and keeps a versioned
+//! [`Boundaries`] snapshot. Whenever the snapshot changes — a window moves,
+//! a display is attached, the Dock hides — `version` is bumped and the new
+//! snapshot is pushed to the frontend as a `"boundaries-changed"` event.
+//!
+//! All coordinates are screen-space with a top-left origin, the same space
+//! as [`crate::screen::WindowInfo`].
+
+use crate::screen;
+use crate::snap::{self, Rect};
+use crate::window;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// How often the window list and display layout are re-sampled.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The top edge of a desktop window, usable as a platform to stand on.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Platform {
+    pub window_id: u32,
+    pub app_name: String,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    /// Height of the window below the platform, used to tell which
+    /// platforms it hides.
+    pub height: f64,
+}
+
+/// The Dock/taskbar area on the primary screen.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DockBoundary {
+    /// `"bottom"`, `"top"`, `"left"` or `"right"`.
+    pub position: String,
+    pub rect: Rect,
+}
+
+/// Versioned set of collision boundaries.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct Boundaries {
+    /// Incremented every time the contents change. `0` before the first sample.
+    pub version: u64,
+    /// Primary screen minus the Dock/taskbar.
+    pub work_area: Option<Rect>,
+    /// Every monitor's full frame.
+    pub screens: Vec<Rect>,
+    /// Window tops, front-most window first.
+    pub platforms: Vec<Platform>,
+    pub dock: Option<DockBoundary>,
+}
+
+impl Boundaries {
+    /// Whether the geometry (everything except `version`) is the same.
+    fn same_geometry(&self, other: &Boundaries) -> bool {
+        self.work_area == other.work_area
+            && self.screens == other.screens
+            && self.platforms == other.platforms
+            && self.dock == other.dock
+    }
+}
+
+/// Latest snapshot, written by the sampling thread.
+static CURRENT: Mutex<Option<Boundaries>> = Mutex::new(None);
+
+/// Dock rectangle and the work area it leaves on the primary screen.
+fn dock_and_work_area() -> (Option<DockBoundary>, Rect) {
+    let screen = window::get_screen_size();
+    let (w, h) = (screen.width as f64, screen.height as f64);
+    let full = Rect {
+        x: 0.0,
+        y: 0.0,
+        width: w,
+        height: h,
+    };
+
+    let dock = window::get_dock_info();
+    if dock.is_hidden || dock.height == 0 {
+        return (None, full);
+    }
+    let d = dock.height as f64;
+    let (rect, work_area) = match dock.position.as_str() {
+        "left" => (
+            Rect { width: d, ..full },
+            Rect {
+                x: d,
+                width: w - d,
+                ..full
+            },
+        ),
+        "right" => (
+            Rect {
+                x: w - d,
+                width: d,
+                ..full
+            },
+            Rect {
+                width: w - d,
+                ..full
+            },
+        ),
+        "top" => (
+            Rect { height: d, ..full },
+            Rect {
+                y: d,
+                height: h - d,
+                ..full
+            },
+        ),
+        _ => (
+            Rect {
+                y: h - d,
+                height: d,
+                ..full
+            },
+            Rect {
+                height: h - d,
+                ..full
+            },
+        ),
+    };
+    (
+        Some(DockBoundary {
+            position: dock.position,
+            rect,
+        }),
+        work_area,
+    )
+}
+
+/// A fresh snapshot, or `None` once the event loop has shut down.
+fn sample(app: &AppHandle) -> Option<Boundaries> {
+    // The screens are queried on the main thread (see
+    // [`window::on_main_thread`]); the window list can be read from here.
+    let ((dock, work_area), screens) =
+        window::on_main_thread(app, || (dock_and_work_area(), snap::monitor_rects()))?;
+    let platforms = screen::window_list()
+        .into_iter()
+        .map(|w| Platform {
+            window_id: w.window_id,
+            app_name: w.app_name,
+            x: w.x as f64,
+            y: w.y as f64,
+            width: w.width as f64,
+            height: w.height as f64,
+        })
+        .collect();
+
+    Some(Boundaries {
+        version: 0,
+        work_area: Some(work_area),
+        screens,
+        platforms,
+        dock,
+    })
+}
+
+/// Start the boundary sampling thread.
+///
+/// The first sample is always emitted.
+pub fn start_boundary_service(app: AppHandle, running: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        let mut version = 0u64;
+        while running.load(Ordering::Relaxed) {
+            let Some(mut next) = sample(&app) else {
+                break;
+            };
+            let changed = CURRENT
+                .lock()
+                .map(|current| current.as_ref().is_none_or(|c| !c.same_geometry(&next)))
+                .unwrap_or(true);
+
+            if changed {
+                version += 1;
+                next.version = version;
+                if let Ok(mut current) = CURRENT.lock() {
+                    *current = Some(next.clone());
+                }
+                let _ = app.emit("boundaries-changed", next);
+            }
+
+            thread::sleep(SAMPLE_INTERVAL);
+        }
+    });
+}
+
+/// IPC command: the current boundary snapshot.
+///
+/// Used once at startup; afterwards the frontend follows
+/// `"boundaries-changed"` events. Samples synchronously if the service has
+/// not produced a snapshot yet.
+#[tauri::command]
+pub fn get_boundaries(app: AppHandle) -> Boundaries {
+    CURRENT
+        .lock()
+        .ok()
+        .and_then(|c| c.clone())
+        .or_else(|| sample(&app))
+        .unwrap_or_default()
+}
@@ -0,0 +1,113 @@
+//! Per-browser strategies for reading the active tab's URL.
+//!
+//! [`crate::screen::get_browser_url`] looks the frontmost app up in this
+//! table to decide how (and whether) to query it. The built-in table covers
+//! the common browsers; users can add or override entries in the `browsers`
+//! section of [`crate::config::AppSettings`] without a code change, e.g.
+//!
+//! ```json
+//! { "browsers": { "rules": [
+//!   { "match": "sidekick", "strategy": "chromium" },
+//!   { "match": "mybrowser", "strategy": "script",
+//!     "script": "tell application \"{app}\" to get URL of tab 1 of window 1" }
+//! ] } }
+//! ```
+//!
+//! User rules are checked first, then the built-ins; the first rule whose
+//! `match` is a substring of the lower-cased app name wins.
+
+use serde::{Deserialize, Serialize};
+
+/// How to read the URL from a browser.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum BrowserStrategy {
+    /// WebKit AppleScript dictionary: `URL of current tab of front window`.
+    Safari,
+    /// Chromium AppleScript dictionary: `URL of active tab of front window`.
+    Chromium,
+    /// Gecko browsers have no AppleScript tab access; address-bar UI
+    /// Automation still works on Windows.
+    Firefox,
+    /// A custom AppleScript from the rule's `script` field.
+    Script,
+}
+
+/// One entry of the strategy table.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BrowserRule {
+    /// Lower-case substring of the app name.
+    #[serde(rename = "match")]
+    pub pattern: String,
+    pub strategy: BrowserStrategy,
+    /// AppleScript for [`BrowserStrategy::Script`]; `{app}` is replaced with
+    /// the app name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<String>,
+}
+
+/// Browser section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BrowsersConfig {
+    /// Extra rules, checked before the built-in table.
+    pub rules: Vec<BrowserRule>,
+}
+
+/// Built-in table as `(pattern, strategy)`.
+///
+/// Orion is WebKit-based and uses Safari's dictionary; Zen is a Firefox fork.
+const BUILTIN_RULES: &[(&str, BrowserStrategy)] = &[
+    ("safari", BrowserStrategy::Safari),
+    ("orion", BrowserStrategy::Safari),
+    ("chrome", BrowserStrategy::Chromium),
+    ("chromium", BrowserStrategy::Chromium),
+    ("arc", BrowserStrategy::Chromium),
+    ("brave", BrowserStrategy::Chromium),
+    ("edge", BrowserStrategy::Chromium),
+    ("opera", BrowserStrategy::Chromium),
+    ("vivaldi", BrowserStrategy::Chromium),
+    ("firefox", BrowserStrategy::Firefox),
+    ("zen", BrowserStrategy::Firefox),
+];
+
+/// The rule for `app_name`, if it is a known browser.
+pub fn lookup(config: &BrowsersConfig, app_name: &str) -> Option<BrowserRule> {
+    let lower = app_name.to_lowercase();
+    config
+        .rules
+        .iter()
+        .find(|r| !r.pattern.is_empty() && lower.contains(&r.pattern.to_lowercase()))
+        .cloned()
+        .or_else(|| {
+            BUILTIN_RULES
+                .iter()
+                .find(|(pattern, _)| lower.contains(pattern))
+                .map(|(pattern, strategy)| BrowserRule {
+                    pattern: pattern.to_string(),
+                    strategy: *strategy,
+                    script: None,
+                })
+        })
+}
+
+impl BrowserRule {
+    /// The AppleScript that reads the URL, or `None` if this browser cannot
+    /// be queried with AppleScript.
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    pub fn apple_script(&self, app_name: &str) -> Option<String> {
+        match self.strategy {
+            BrowserStrategy::Safari => Some(format!(
+                r#"tell application "{}" to get URL of current tab of front window"#,
+                app_name
+            )),
+            BrowserStrategy::Chromium => Some(format!(
+                r#"tell application "{}" to get URL of active tab of front window"#,
+                app_name
+            )),
+            BrowserStrategy::Firefox => None,
+            BrowserStrategy::Script => self.script.as_ref().map(|s| s.replace("{app}", app_name)),
+        }
+    }
+}
@@ -0,0 +1,317 @@
+//! Keeping chat requests inside the model's context window.
+//!
+//! Backends without server-side history (everything but OpenClaw, which
+//! keeps the conversation itself) are sent the recent exchanges of the
+//! session with every message. This module holds those exchanges, counts
+//! their tokens with a rough estimator ([`estimate_tokens`]) and keeps them
+//! under [`ProviderConfig::context_tokens`]: turns that no longer fit, or
+//! go beyond [`ProviderConfig::history_turns`], are folded into a running
+//! summary by the model itself, [`SUMMARY_BATCH`] messages at a time so
+//! that a full history costs one summary every few turns rather than one
+//! per turn. Summaries count towards [`crate::usage`] like chat requests. The summary is stored next to the chat
+//! history ([`crate::memory`]) and leads the system prompt, so a long
+//! session keeps its gist instead of losing it or outgrowing the model.
+//!
+//! After a restart a session picks up its last turns and summary from
+//! disk.
+
+use crate::config::{ConfigState, OpenClawConfig};
+use crate::llm::{self, ChatMessage, ProviderConfig, ProviderKind};
+use crate::usage::UsageState;
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+/// Tokens counted per message on top of its content (role, separators).
+const MESSAGE_OVERHEAD: usize = 4;
+
+/// Tokens counted per attached image; providers charge roughly this for a
+/// medium-sized picture.
+const IMAGE_TOKENS: usize = 800;
+
+/// Share of the budget the stored turns may fill, leaving the rest for the
+/// system prompt, the new message and the reply.
+const HISTORY_SHARE: f32 = 0.75;
+
+/// Messages pushed out of the history at once when it is full, and so
+/// summarized together.
+const SUMMARY_BATCH: usize = 8;
+
+const SUMMARY_PROMPT: &str = "You keep a running summary of a conversation between a user and \
+their desktop companion character. Merge the earlier summary with the new messages. Keep names, \
+facts about the user, plans, promises and open topics; drop small talk. Reply with the summary \
+only, in at most 200 words.";
+
+/// The remembered part of one session.
+#[derive(Default)]
+struct Session {
+    turns: VecDeque<ChatMessage>,
+    summary: String,
+}
+
+static SESSIONS: Mutex<BTreeMap<String, Session>> = Mutex::new(BTreeMap::new());
+
+/// Held while summarizing, so two batches cannot both start from the same
+/// earlier summary.
+static SUMMARIZING: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Returned by [`get_context_usage`].
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextUsage {
+    pub session: String,
+    /// Messages kept verbatim.
+    pub messages: usize,
+    pub history_tokens: usize,
+    pub summary_tokens: usize,
+    /// Token budget of a whole request.
+    pub budget: usize,
+}
+
+/// Approximate token count of `text`, close to what BPE tokenizers such as
+/// tiktoken produce: about four characters per token for ASCII text, and a
+/// token per character for other scripts (CJK, emoji, …).
+pub fn estimate_tokens(text: &str) -> usize {
+    let (ascii, other) = text.chars().fold((0usize, 0usize), |(ascii, other), c| {
+        if c.is_ascii() {
+            (ascii + 1, other)
+        } else {
+            (ascii, other + 1)
+        }
+    });
+    ascii.div_ceil(4) + other
+}
+
+/// Token estimate of a message sent to a model.
+pub fn message_tokens(message: &ChatMessage) -> usize {
+    estimate_tokens(&message.content) + MESSAGE_OVERHEAD + message.images.len() * IMAGE_TOKENS
+}
+
+/// Token estimate of a whole request.
+pub fn request_tokens(messages: &[ChatMessage]) -> usize {
+    messages.iter().map(message_tokens).sum()
+}
+
+/// Token budget of a whole request, capped by the built-in model's
+/// context size when it is the backend.
+fn budget(provider: &ProviderConfig) -> usize {
+    if provider.kind == ProviderKind::Local {
+        let local = &provider.local;
+        let window = (local.context_tokens as usize).saturating_sub(local.max_tokens as usize);
+        provider.context_tokens.min(window)
+    } else {
+        provider.context_tokens
+    }
+}
+
+fn history_budget(provider: &ProviderConfig) -> usize {
+    (budget(provider) as f32 * HISTORY_SHARE) as usize
+}
+
+/// The session of `config`, loaded from disk the first time.
+fn session<'a>(
+    sessions: &'a mut BTreeMap<String, Session>,
+    config: &OpenClawConfig,
+) -> &'a mut Session {
+    sessions
+        .entry(config.session_key.clone())
+        .or_insert_with(|| {
+            let limit = config.provider.history_turns * 2;
+            let mut turns: VecDeque<ChatMessage> =
+                crate::memory::recent_chat_history(&config.session_key, limit)
+                    .into_iter()
+                    .filter_map(|m| match m.role.as_str() {
+                        "user" => Some(ChatMessage::user(m.content)),
+                        "assistant" => Some(ChatMessage::assistant(m.content)),
+                        _ => None,
+                    })
+                    .collect();
+            while turns.front().is_some_and(|m| m.role != "user") {
+                turns.pop_front();
+            }
+            Session {
+                turns,
+                summary: crate::memory::read_summary(&config.session_key).unwrap_or_default(),
+            }
+        })
+}
+
+/// The summary and the most recent turns of the session that fit in the
+/// budget next to `reserved` tokens (system prompt and new message).
+pub(crate) fn context(
+    config: &OpenClawConfig,
+    reserved: usize,
+) -> (Option<String>, Vec<ChatMessage>) {
+    let Ok(mut sessions) = SESSIONS.lock() else {
+        return (None, Vec::new());
+    };
+    let session = session(&mut sessions, config);
+    let mut room = budget(&config.provider).saturating_sub(reserved);
+    let summary = (!session.summary.is_empty()).then(|| session.summary.clone());
+    if let Some(summary) = &summary {
+        room = room.saturating_sub(estimate_tokens(summary));
+    }
+    let mut recent = Vec::new();
+    for message in session.turns.iter().rev() {
+        let Some(left) = room.checked_sub(message_tokens(message)) else {
+            break;
+        };
+        room = left;
+        recent.push(message.clone());
+    }
+    // Start with a user message rather than half an exchange.
+    while recent.last().is_some_and(|m| m.role != "user") {
+        recent.pop();
+    }
+    recent.reverse();
+    (summary, recent)
+}
+
+/// The system prompt text carrying `summary`.
+pub(crate) fn summary_prompt(summary: &str) -> String {
+    format!("Summary of the earlier conversation:\n{summary}")
+}
+
+/// Remember a completed exchange. Returns the turns pushed out by
+/// [`ProviderConfig::history_turns`] or the budget, oldest first, for
+/// [`summarize`]: nothing while the history fits, else at least
+/// [`SUMMARY_BATCH`] messages (as long as the last exchange stays).
+pub(crate) fn remember(
+    config: &OpenClawConfig,
+    message: String,
+    reply: String,
+) -> Vec<ChatMessage> {
+    let Ok(mut sessions) = SESSIONS.lock() else {
+        return Vec::new();
+    };
+    let session = session(&mut sessions, config);
+    session.turns.push_back(ChatMessage::user(message));
+    session.turns.push_back(ChatMessage::assistant(reply));
+
+    let max_messages = config.provider.history_turns * 2;
+    let max_tokens = history_budget(&config.provider);
+    let mut tokens: usize = session.turns.iter().map(message_tokens).sum();
+    let mut overflow = Vec::new();
+    if session.turns.len() <= max_messages && tokens <= max_tokens {
+        return overflow;
+    }
+    while session.turns.len() > 2
+        && (overflow.len() < SUMMARY_BATCH
+            || session.turns.len() > max_messages
+            || tokens > max_tokens)
+    {
+        let Some(message) = session.turns.pop_front() else {
+            break;
+        };
+        tokens -= message_tokens(&message);
+        overflow.push(message);
+        // Never keep an assistant reply without its question.
+        while session.turns.front().is_some_and(|m| m.role != "user") {
+            if let Some(message) = session.turns.pop_front() {
+                tokens -= message_tokens(&message);
+                overflow.push(message);
+            }
+        }
+    }
+    overflow
+}
+
+/// Fold `overflow` into the session's summary, using the chat backend.
+///
+/// Failures, including a request refused by the [`crate::usage`] caps, are
+/// logged; the turns are then simply forgotten.
+pub(crate) async fn summarize(
+    app: AppHandle,
+    client: reqwest::Client,
+    config: OpenClawConfig,
+    overflow: Vec<ChatMessage>,
+) {
+    let _guard = SUMMARIZING.lock().await;
+    let (config_state, usage) = (app.state::<ConfigState>(), app.state::<UsageState>());
+    if let Err(e) = usage.admit(&app, &config_state, &config) {
+        eprintln!("[budget] Not summarizing {} messages: {e}", overflow.len());
+        return;
+    }
+    let earlier = SESSIONS
+        .lock()
+        .ok()
+        .and_then(|mut sessions| {
+            let summary = &session(&mut sessions, &config).summary;
+            (!summary.is_empty()).then(|| summary.clone())
+        })
+        .unwrap_or_else(|| "(none)".to_string());
+    let transcript: Vec<String> = overflow
+        .iter()
+        .map(|m| {
+            let speaker = if m.role == "user" {
+                "User"
+            } else {
+                "Character"
+            };
+            format!("{speaker}: {}", m.content)
+        })
+        .collect();
+    let request = [
+        ChatMessage::new("system", SUMMARY_PROMPT.to_string()),
+        ChatMessage::user(format!(
+            "Earlier summary:\n{earlier}\n\nNew messages:\n{}",
+            transcript.join("\n")
+        )),
+    ];
+    let summary = match llm::complete(&client, &config, &request, None, &mut |_: &str| {}).await {
+        Ok(summary) => {
+            usage.record(&config_state, &config, request_tokens(&request), &summary);
+            summary.trim().to_string()
+        }
+        Err(e) => {
+            eprintln!(
+                "[budget] Failed to summarize {} messages: {e}",
+                overflow.len()
+            );
+            return;
+        }
+    };
+    println!(
+        "[budget] Summarized {} messages of {} into ~{} tokens",
+        overflow.len(),
+        config.session_key,
+        estimate_tokens(&summary)
+    );
+    if let Err(e) = crate::memory::write_summary(&config.session_key, &summary) {
+        eprintln!("[budget] {e}");
+    }
+    if let Ok(mut sessions) = SESSIONS.lock() {
+        session(&mut sessions, &config).summary = summary;
+    }
+}
+
+/// Drop what is remembered of `session`, e.g. after its history was
+/// cleared.
+pub(crate) fn forget(session: &str) {
+    if let Ok(mut sessions) = SESSIONS.lock() {
+        sessions.remove(session);
+    }
+}
+
+/// IPC command: how much of the context budget `session` (default: the
+/// configured session key) uses.
+#[tauri::command]
+pub fn get_context_usage(
+    config_state: State<'_, ConfigState>,
+    session: Option<String>,
+) -> Result<ContextUsage, String> {
+    let mut config = config_state.get()?;
+    if let Some(session) = session {
+        config.session_key = session;
+    }
+    let mut sessions = SESSIONS.lock().map_err(|e| e.to_string())?;
+    let remembered = self::session(&mut sessions, &config);
+    Ok(ContextUsage {
+        session: config.session_key.clone(),
+        messages: remembered.turns.len(),
+        history_tokens: remembered.turns.iter().map(message_tokens).sum(),
+        summary_tokens: estimate_tokens(&remembered.summary),
+        budget: budget(&config.provider),
+    })
+}
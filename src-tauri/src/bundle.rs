@@ -0,0 +1,247 @@
+//! Settings bundles for moving the companion to another machine.
+//!
+//! [`export_settings`] writes one JSON file holding the OpenClaw config and
+//! every [`AppSettings`] section (character card, exclusion rules, …) and,
+//! on request, the memory files (stored facts, chat histories, diary).
+//! Tokens and API keys are left out unless asked for, as the file is plain
+//! text. [`import_settings`] applies such a file; secrets the bundle does
+//! not carry keep their current values on this machine, and so does the
+//! OpenClaw CLI path, which only makes sense on the machine it came from.
+
+use crate::config::{AppSettings, ConfigState, OpenClawConfig};
+use crate::secrets::Fields;
+use crate::validation::{self, ConfigError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+/// Version written in [`SettingsBundle::version`].
+const BUNDLE_VERSION: u32 = 1;
+
+/// Subdirectories of the data directory exported as memory.
+const MEMORY_DIRS: [&str; 2] = ["history", "diary"];
+
+/// Files at the top of the data directory that are not memory.
+const NOT_MEMORY: [&str; 5] = [
+    "config.json",
+    "settings.json",
+    "config-history.json",
+    "outbox.json",
+    "events-queue.json",
+];
+
+/// The file written by [`export_settings`].
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsBundle {
+    pub version: u32,
+    /// Unix time in milliseconds.
+    pub exported_at: u64,
+    pub config: OpenClawConfig,
+    pub settings: AppSettings,
+    /// Whether tokens and API keys were exported.
+    pub includes_secrets: bool,
+    /// Memory files by path relative to the data directory; empty unless
+    /// exported with `include_memory`.
+    #[serde(default)]
+    pub memory: BTreeMap<String, String>,
+}
+
+/// What [`export_settings`] includes beyond config and settings.
+#[derive(Deserialize, Clone, Copy, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportOptions {
+    pub include_secrets: bool,
+    pub include_memory: bool,
+}
+
+/// Returned by [`import_settings`].
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleImport {
+    /// Whether the bundle's secrets replaced this machine's.
+    pub secrets_imported: bool,
+    /// Memory files written.
+    pub memory_files: usize,
+}
+
+/// Memory files under the data directory, by relative path.
+fn read_memory() -> Result<BTreeMap<String, String>, String> {
+    let root = crate::memory::data_dir();
+    let mut files = BTreeMap::new();
+    let mut add = |path: PathBuf| -> Result<(), String> {
+        let relative = path
+            .strip_prefix(&root)
+            .map_err(|e| e.to_string())?
+            .to_string_lossy()
+            .replace('\\', "/");
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        files.insert(relative, content);
+        Ok(())
+    };
+
+    if let Ok(entries) = fs::read_dir(&root) {
+        for path in entries.flatten().map(|e| e.path()) {
+            let name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default();
+            if path.is_file() && name.ends_with(".json") && !NOT_MEMORY.contains(&name) {
+                add(path)?;
+            }
+        }
+    }
+    for dir in MEMORY_DIRS {
+        let Ok(entries) = fs::read_dir(root.join(dir)) else {
+            continue;
+        };
+        for path in entries.flatten().map(|e| e.path()) {
+            let extension = path.extension().and_then(|e| e.to_str());
+            if path.is_file() && matches!(extension, Some("json" | "jsonl")) {
+                add(path)?;
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Where a memory file from a bundle goes, or `None` if `relative` would
+/// leave the data directory or is not a memory file: the files
+/// [`read_memory`] exports, `<name>.json` at the top and `.json` or
+/// `.jsonl` in [`MEMORY_DIRS`].
+fn memory_path(relative: &str) -> Option<PathBuf> {
+    let path = Path::new(relative);
+    if !path.components().all(|c| matches!(c, Component::Normal(_))) {
+        return None;
+    }
+    let name = path.file_name()?.to_str()?;
+    let extension = path.extension()?.to_str()?;
+    let is_memory = match path.parent().and_then(|p| p.to_str()) {
+        Some("") => extension == "json" && !NOT_MEMORY.contains(&name),
+        Some(dir) => MEMORY_DIRS.contains(&dir) && matches!(extension, "json" | "jsonl"),
+        None => false,
+    };
+    is_memory.then(|| crate::memory::data_dir().join(path))
+}
+
+/// IPC command: write the config, settings and, with `options`, secrets and
+/// memory files to `path` as one JSON bundle.
+///
+/// # Errors
+///
+/// Returns `Err` if a memory file cannot be read or `path` cannot be
+/// written.
+#[tauri::command]
+pub fn export_settings(
+    state: State<'_, ConfigState>,
+    path: String,
+    options: Option<ExportOptions>,
+) -> Result<(), String> {
+    let options = options.unwrap_or_default();
+    let mut config = state.get()?;
+    let mut settings = state.get_settings()?;
+    if !options.include_secrets {
+        config.secrets().into_iter().for_each(|(_, v)| v.clear());
+        settings.secrets().into_iter().for_each(|(_, v)| v.clear());
+    }
+    let bundle = SettingsBundle {
+        version: BUNDLE_VERSION,
+        exported_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+        config,
+        settings,
+        includes_secrets: options.include_secrets,
+        memory: if options.include_memory {
+            read_memory()?
+        } else {
+            BTreeMap::new()
+        },
+    };
+    let json = serde_json::to_string_pretty(&bundle)
+        .map_err(|e| format!("Failed to serialize bundle: {e}"))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write {path}: {e}"))?;
+    println!(
+        "[bundle] Exported settings to {path} ({} memory files)",
+        bundle.memory.len()
+    );
+    Ok(())
+}
+
+/// IPC command: apply a bundle written by [`export_settings`], replacing
+/// the config and settings and writing its memory files (overwriting
+/// files of the same name). The CLI path stays as it is on this machine.
+///
+/// # Errors
+///
+/// Returns [`ConfigError::Invalid`] if the bundled config or settings fail
+/// validation, in which case nothing is changed, and
+/// [`ConfigError::Other`] if the file cannot be read, is not a bundle or
+/// comes from a newer version.
+#[tauri::command]
+pub fn import_settings(
+    state: State<'_, ConfigState>,
+    path: String,
+) -> Result<BundleImport, ConfigError> {
+    let text = fs::read_to_string(&path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+    let mut bundle: SettingsBundle =
+        serde_json::from_str(&text).map_err(|e| format!("Not a settings bundle: {e}"))?;
+    if bundle.version > BUNDLE_VERSION {
+        return Err(format!(
+            "Bundle version {} is newer than this app supports",
+            bundle.version
+        )
+        .into());
+    }
+    bundle.config.cli_path = state.get()?.cli_path;
+    let mut issues = validation::validate_config(&bundle.config);
+    issues.extend(validation::validate_settings(&bundle.settings));
+    validation::check(issues)?;
+
+    if !bundle.includes_secrets {
+        // By name: the bundle may list other agent profiles than this app.
+        let keep = |fields: &mut Fields, mine: &Fields| {
+            for (name, value) in fields.iter_mut() {
+                match mine.iter().find(|(n, _)| n == name) {
+                    Some((_, mine)) => value.clone_from(mine),
+                    None => value.clear(),
+                }
+            }
+        };
+        keep(&mut bundle.config.secrets(), &state.get()?.secrets());
+        keep(
+            &mut bundle.settings.secrets(),
+            &state.get_settings()?.secrets(),
+        );
+    }
+
+    let mut memory_files = 0;
+    for (relative, content) in &bundle.memory {
+        let Some(target) = memory_path(relative) else {
+            eprintln!("[bundle] Skipping {relative}: not a memory file");
+            continue;
+        };
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+        }
+        fs::write(&target, content)
+            .map_err(|e| format!("Failed to write {}: {e}", target.display()))?;
+        memory_files += 1;
+    }
+
+    *state.config.write().map_err(|e| e.to_string())? = bundle.config;
+    *state.settings.write().map_err(|e| e.to_string())? = bundle.settings;
+    state.save()?;
+    state.save_settings()?;
+    println!("[bundle] Imported settings from {path} ({memory_files} memory files)");
+    Ok(BundleImport {
+        secrets_imported: bundle.includes_secrets,
+        memory_files,
+    })
+}
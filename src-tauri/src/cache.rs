@@ -0,0 +1,156 @@
+//! Short-lived cache of chat replies.
+//!
+//! Proactive prompts ("comment on this song", "say something about the
+//! weather") tend to repeat within moments, e.g. when the frontend reacts
+//! to the same event twice. [`crate::openclaw::send_chat`] and
+//! [`crate::openclaw::send_chat_stream`] look the request up here first and
+//! answer a repeat from the cache for [`CacheConfig::ttl_secs`] instead of
+//! asking the backend again.
+//!
+//! The key is a hash of the backend, agent, session, context and message,
+//! with case and whitespace normalized. Messages with images are never
+//! cached. Cached replies do not count towards [`crate::usage`], but do
+//! join the conversation history like any other reply.
+//!
+//! The cache is off by default: a repeated message can deserve a fresh
+//! answer.
+
+use crate::config::{ConfigState, OpenClawConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Chat cache section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CacheConfig {
+    pub enabled: bool,
+    /// How long a reply is reused; 0 disables the cache.
+    pub ttl_secs: u64,
+    pub max_entries: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_secs: 60,
+            max_entries: 64,
+        }
+    }
+}
+
+/// Returned by [`get_chat_cache_stats`].
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Replies currently cached, expired ones included until they are
+    /// pushed out.
+    pub entries: usize,
+}
+
+struct Entry {
+    key: u64,
+    reply: String,
+    stored: Instant,
+}
+
+#[derive(Default)]
+struct Cache {
+    entries: VecDeque<Entry>,
+    hits: u64,
+    misses: u64,
+}
+
+static CACHE: Mutex<Cache> = Mutex::new(Cache {
+    entries: VecDeque::new(),
+    hits: 0,
+    misses: 0,
+});
+
+fn settings(config_state: &ConfigState) -> Option<CacheConfig> {
+    let config = config_state.get_settings().ok()?.chat_cache;
+    (config.enabled && config.ttl_secs > 0 && config.max_entries > 0).then_some(config)
+}
+
+/// Lowercase `text` with whitespace runs collapsed to one space.
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Cache key of a chat request.
+pub fn key(config: &OpenClawConfig, message: &str, context: Option<&str>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    config.provider.kind.hash(&mut hasher);
+    config.agent_id.hash(&mut hasher);
+    config.session_key.hash(&mut hasher);
+    normalize(context.unwrap_or_default()).hash(&mut hasher);
+    normalize(message).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The cached reply for `key`, if one is fresh enough. Counts a hit or a
+/// miss while the cache is enabled.
+pub fn get(config_state: &ConfigState, key: u64) -> Option<String> {
+    let config = settings(config_state)?;
+    let ttl = Duration::from_secs(config.ttl_secs);
+    let mut cache = CACHE.lock().ok()?;
+    let reply = cache
+        .entries
+        .iter()
+        .find(|e| e.key == key && e.stored.elapsed() < ttl)
+        .map(|e| e.reply.clone());
+    if reply.is_some() {
+        cache.hits += 1;
+    } else {
+        cache.misses += 1;
+    }
+    reply
+}
+
+/// Remember `reply` for `key`.
+pub fn put(config_state: &ConfigState, key: u64, reply: &str) {
+    let Some(config) = settings(config_state) else {
+        return;
+    };
+    let Ok(mut cache) = CACHE.lock() else {
+        return;
+    };
+    cache.entries.retain(|e| e.key != key);
+    while cache.entries.len() >= config.max_entries {
+        cache.entries.pop_front();
+    }
+    cache.entries.push_back(Entry {
+        key,
+        reply: reply.to_string(),
+        stored: Instant::now(),
+    });
+}
+
+/// IPC command: cache hits and misses since launch or the last
+/// [`clear_chat_cache`].
+#[tauri::command]
+pub fn get_chat_cache_stats() -> Result<CacheStats, String> {
+    let cache = CACHE.lock().map_err(|e| e.to_string())?;
+    Ok(CacheStats {
+        hits: cache.hits,
+        misses: cache.misses,
+        entries: cache.entries.len(),
+    })
+}
+
+/// IPC command: forget all cached replies and reset the counters.
+#[tauri::command]
+pub fn clear_chat_cache() -> Result<(), String> {
+    let mut cache = CACHE.lock().map_err(|e| e.to_string())?;
+    *cache = Cache::default();
+    Ok(())
+}
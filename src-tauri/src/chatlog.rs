@@ -0,0 +1,163 @@
+//! Opt-in trace log of chat requests.
+//!
+//! When [`ChatLogConfig::enabled`] is set, every chat message appends one
+//! JSON line to `<config dir>/logs/chat.jsonl`: which backend and transport
+//! answered, how long it took, estimated token counts
+//! ([`crate::budget::estimate_tokens`]) and the message, context and reply
+//! cut to [`ChatLogConfig::max_body_chars`]. Secrets are masked the same
+//! way as in [`crate::prompt_snapshot`]. [`get_chat_log`] reads the tail
+//! back for the debug panel.
+//!
+//! The file is rotated to `chat.1.jsonl` once it passes 5 MB.
+
+use crate::config::{ConfigState, OpenClawConfig};
+use crate::llm::ProviderKind;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Size at which the log is rotated.
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Entries returned by [`get_chat_log`] when no tail is given.
+const DEFAULT_TAIL: usize = 50;
+
+/// Serializes appends and rotation.
+static LOG_LOCK: Mutex<()> = Mutex::new(());
+
+/// Chat log section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ChatLogConfig {
+    pub enabled: bool,
+    /// Characters kept of each message, context and reply.
+    pub max_body_chars: usize,
+}
+
+impl Default for ChatLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_body_chars: 2000,
+        }
+    }
+}
+
+/// One line of the log.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatLogEntry {
+    /// Unix time in milliseconds when the reply (or error) came back.
+    pub timestamp: u64,
+    pub session: String,
+    pub agent_id: String,
+    pub provider: ProviderKind,
+    /// `"cli"`, `"http"` or `"stream"`.
+    pub transport: String,
+    pub latency_ms: u64,
+    pub request_tokens: usize,
+    pub response_tokens: usize,
+    pub error: Option<String>,
+    pub message: String,
+    pub context: Option<String>,
+    pub response: String,
+}
+
+/// A finished chat request, as passed to [`record`].
+pub(crate) struct Trace<'a> {
+    pub transport: &'static str,
+    pub started: Instant,
+    pub message: &'a str,
+    pub context: Option<&'a str>,
+    pub result: Result<&'a str, &'a str>,
+}
+
+fn log_path() -> PathBuf {
+    crate::config::config_dir().join("logs").join("chat.jsonl")
+}
+
+/// `text` redacted and cut to `max` characters.
+fn body(text: &str, config: &OpenClawConfig, max: usize) -> String {
+    let text = crate::prompt_snapshot::redact(text, config);
+    match text.char_indices().nth(max) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text,
+    }
+}
+
+/// Append `trace` to the log if it is enabled. Failures are logged, never
+/// passed on to the chat.
+pub(crate) fn record(config_state: &ConfigState, config: &OpenClawConfig, trace: Trace) {
+    let settings = match config_state.get_settings() {
+        Ok(settings) if settings.chat_log.enabled => settings.chat_log,
+        _ => return,
+    };
+    let max = settings.max_body_chars;
+    let (response, error) = match trace.result {
+        Ok(reply) => (reply, None),
+        Err(e) => ("", Some(body(e, config, max))),
+    };
+    let request_tokens = crate::budget::estimate_tokens(trace.message)
+        + trace.context.map_or(0, crate::budget::estimate_tokens);
+    let entry = ChatLogEntry {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+        session: config.session_key.clone(),
+        agent_id: config.agent_id.clone(),
+        provider: config.provider.kind,
+        transport: trace.transport.to_string(),
+        latency_ms: trace.started.elapsed().as_millis() as u64,
+        request_tokens,
+        response_tokens: crate::budget::estimate_tokens(response),
+        error,
+        message: body(trace.message, config, max),
+        context: trace.context.map(|c| body(c, config, max)),
+        response: body(response, config, max),
+    };
+    if let Err(e) = append(&entry) {
+        eprintln!("[chatlog] {e}");
+    }
+}
+
+fn append(entry: &ChatLogEntry) -> Result<(), String> {
+    let _guard = LOG_LOCK.lock().map_err(|e| e.to_string())?;
+    let path = log_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create log dir: {e}"))?;
+    }
+    if fs::metadata(&path).is_ok_and(|m| m.len() > MAX_LOG_BYTES) {
+        fs::rename(&path, path.with_extension("1.jsonl"))
+            .map_err(|e| format!("Failed to rotate chat log: {e}"))?;
+    }
+    let mut line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+    line.push('\n');
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|e| format!("Failed to write chat log: {e}"))
+}
+
+/// IPC command: the last `tail` (default 50) log entries, oldest first.
+/// Empty if the log is off or nothing was logged.
+#[tauri::command]
+pub fn get_chat_log(tail: Option<usize>) -> Result<Vec<ChatLogEntry>, String> {
+    let _guard = LOG_LOCK.lock().map_err(|e| e.to_string())?;
+    let contents = match fs::read_to_string(log_path()) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read chat log: {e}")),
+    };
+    let mut entries: Vec<ChatLogEntry> = contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    let start = entries.len().saturating_sub(tail.unwrap_or(DEFAULT_TAIL));
+    Ok(entries.split_off(start))
+}
@@ -1,4 +1,4 @@
-//! Persistent configuration for the OpenClaw connection.
+//! Persistent configuration for the OpenClaw connection and backend behaviour.
 //!
 //! Configuration is stored as JSON at:
 //! ```text
@@ -6,16 +6,88 @@
 //! ~/Library/Application Support/ai-desktop-companion/config.json  (macOS fallback)
 //! ```
 //!
+//! Backend behaviour settings ([`AppSettings`]) live next to it in
+//! `settings.json`, so saving the OpenClaw section from the Settings UI never
+//! clobbers them (and vice versa).
+//!
+//! For editing by hand, both files can be kept as TOML instead
+//! (`config.toml`, `settings.toml`; see [`convert_config_format`]). Each
+//! file's format is detected from the files present. Comments in a TOML file are
+//! kept when the app saves it, as are the order and layout of its keys.
+//!
 //! If neither `dirs::config_dir()` nor `dirs::home_dir()` is available,
 //! the config falls back to `./config/ai-desktop-companion/config.json`.
 //!
-//! The config is loaded once at app startup into a `RwLock<OpenClawConfig>`
-//! and exposed as Tauri managed state via [`ConfigState`].
+//! The whole directory, memory files included, can be moved with the
+//! `AIDC_CONFIG_DIR` environment variable or `--config-dir <dir>` flag, for
+//! portable installs (e.g. `--config-dir data` keeps everything next to the
+//! executable on a USB stick) or isolated test profiles; see
+//! [`config_dir`]. Tokens and API keys then stay in the files.
+//!
+//! Both files are loaded once at app startup into `RwLock`s and exposed as
+//! Tauri managed state via [`ConfigState`].
+//!
+//! Tokens and API keys are not written to either file but kept in the OS
+//! keychain (see [`crate::secrets`]); existing plain-text values are moved
+//! there on the next start.
+//!
+//! On shared machines both files can also be encrypted as a whole
+//! ([`set_config_encryption`]) with ChaCha20-Poly1305, in the envelope of
+//! [`crate::e2e`], under a random key kept in the keychain. They are
+//! decrypted transparently on load; a file that cannot be decrypted is
+//! moved aside to `<name>.undecryptable` and defaults are used instead.
+//! While the keychain itself cannot be read (e.g. it is locked) the files
+//! are left alone: the app runs on defaults and refuses to save over them.
+//!
+//! For scripted and demo setups, the main connection fields can be
+//! overridden for a single run by `AIDC_*` environment variables or the
+//! matching command-line flags (see [`OVERRIDABLE`]); flags win over the
+//! environment. Tokens have no flag, as command lines are visible to other
+//! processes. Overrides are never written back to `config.json`, and
+//! [`get_effective_config`] reports where each of those values came from.
+//!
+//! Each change to either file is recorded in a history with the old and
+//! new values (see [`crate::audit`]).
 
+use crate::agents::AgentProfile;
+use crate::audio::AudioConfig;
+use crate::audit::{self, Baseline};
+use crate::browsers::BrowsersConfig;
+use crate::cache::CacheConfig;
+use crate::chatlog::ChatLogConfig;
+use crate::events::EventsConfig;
+use crate::gateway::GatewayConfig;
+use crate::hittest::{HitTestConfig, IdleConfig};
+use crate::hotcorners::HotCornersConfig;
+use crate::keyboard::KeyboardConfig;
+use crate::llm::ProviderConfig;
+use crate::localapi::LocalApiConfig;
+use crate::lookat::LookAtConfig;
+use crate::onboarding::OnboardingConfig;
+use crate::openai_api::OpenAiApiConfig;
+use crate::openclaw::{ProxyConfig, RetryConfig, TimeoutConfig, TlsConfig};
+use crate::persona::PersonaConfig;
+use crate::power::PowerConfig;
+use crate::proactive::ProactiveConfig;
+use crate::push::PushConfig;
+use crate::quiet::QuietHoursConfig;
+use crate::redaction::RedactionConfig;
+use crate::secrets;
+use crate::shake::ShakeConfig;
+use crate::stt::SttConfig;
+use crate::tray::TrayConfig;
+use crate::tts::TtsConfig;
+use crate::usage::UsageConfig;
+use crate::validation::{self, ConfigError};
+use crate::wakeword::WakeWordConfig;
+use crate::window::DisplayConfig;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::fs;
-use std::path::PathBuf;
-use std::sync::RwLock;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{OnceLock, RwLock};
 use tauri::State;
 
 // ---------- Types ----------
@@ -37,6 +109,77 @@ pub struct OpenClawConfig {
     /// Path to the `openclaw` CLI binary (default: "openclaw").
     #[serde(default = "default_cli_path")]
     pub cli_path: String,
+    /// Hex-encoded 32-byte key shared with the gateway for end-to-end
+    /// encryption of chat payloads (see [`crate::e2e`]), for agents whose
+    /// profile has no key of its own. Empty disables it.
+    #[serde(default)]
+    pub e2e_key: String,
+    /// Gateway auth token (`gateway.auth.token`) for the HTTP chat endpoint
+    /// used by [`crate::openclaw::send_chat_stream`]. Empty falls back to
+    /// `hooks_token`.
+    #[serde(default)]
+    pub gateway_token: String,
+    /// Shared secret [`crate::openclaw::send_webhook`] signs its payloads
+    /// with (see [`crate::signing`]). Empty sends them unsigned.
+    #[serde(default)]
+    pub webhook_secret: String,
+    /// Which model answers chat messages: the OpenClaw agent (default), an
+    /// OpenAI-compatible API, Anthropic, or a local Ollama server.
+    #[serde(default)]
+    pub provider: ProviderConfig,
+    /// Connect, read and total time limits for gateway requests.
+    #[serde(default)]
+    pub timeouts: TimeoutConfig,
+    /// Proxy for gateway and other HTTP traffic.
+    #[serde(default)]
+    pub proxy: ProxyConfig,
+    /// Custom CA, client certificate and verification for HTTPS gateways.
+    #[serde(default)]
+    pub tls: TlsConfig,
+    /// Desktop tools the model may call over the HTTP chat paths (see
+    /// [`crate::openclaw::AVAILABLE_TOOLS`]); none by default.
+    #[serde(default)]
+    pub tools: Vec<String>,
+    /// Configured agents with their own sessions and system prompts (see
+    /// [`crate::agents`]); `agent_id` and `session_key` are the active one.
+    #[serde(default)]
+    pub agents: Vec<AgentProfile>,
+}
+
+impl OpenClawConfig {
+    /// Fields kept in the keychain rather than `config.json`, including
+    /// each agent profile's key as `e2eKey/<agent id>`.
+    pub(crate) fn secrets(&mut self) -> Vec<(Cow<'static, str>, &mut String)> {
+        let mut fields: Vec<(Cow<'static, str>, &mut String)> = vec![
+            ("hooksToken".into(), &mut self.hooks_token),
+            ("gatewayToken".into(), &mut self.gateway_token),
+            ("webhookSecret".into(), &mut self.webhook_secret),
+            ("e2eKey".into(), &mut self.e2e_key),
+            ("openaiApiKey".into(), &mut self.provider.openai.api_key),
+            (
+                "anthropicApiKey".into(),
+                &mut self.provider.anthropic.api_key,
+            ),
+            ("proxyPassword".into(), &mut self.proxy.password),
+        ];
+        fields.extend(
+            self.agents
+                .iter_mut()
+                .map(|p| (format!("e2eKey/{}", p.agent_id).into(), &mut p.e2e_key)),
+        );
+        fields
+    }
+
+    /// The non-empty secrets, for masking them in logs.
+    pub(crate) fn secret_values(&self) -> Vec<String> {
+        let mut config = self.clone();
+        config
+            .secrets()
+            .into_iter()
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(_, value)| value.clone())
+            .collect()
+    }
 }
 
 /// Default CLI path — looks up `openclaw` from `$PATH`.
@@ -52,10 +195,24 @@ impl Default for OpenClawConfig {
             hooks_token: String::new(),
             session_key: format!("desktop-companion-{}", rand_hex()),
             cli_path: default_cli_path(),
+            e2e_key: String::new(),
+            gateway_token: String::new(),
+            webhook_secret: String::new(),
+            provider: ProviderConfig::default(),
+            timeouts: TimeoutConfig::default(),
+            proxy: ProxyConfig::default(),
+            tls: TlsConfig::default(),
+            tools: Vec::new(),
+            agents: Vec::new(),
         }
     }
 }
 
+/// A fresh session key for `agent_id`.
+pub(crate) fn new_session_key(agent_id: &str) -> String {
+    format!("desktop-companion-{}-{}", agent_id, rand_hex())
+}
+
 /// Generate a short random hex string derived from the current timestamp.
 ///
 /// Used only for default session key generation. Not cryptographically
@@ -69,51 +226,213 @@ fn rand_hex() -> String {
     format!("{:x}", t)
 }
 
+/// Backend behaviour settings that Rust threads need to see.
+///
+/// Every section defaults independently, so older or partial files load
+/// cleanly.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppSettings {
+    /// Mouse-move de-duplication in the hit-test thread.
+    pub hittest: HitTestConfig,
+    /// Screen-corner triggers detected by the hit-test thread.
+    pub hot_corners: HotCornersConfig,
+    /// Smoothed `"look-target"` cursor stream for the character's gaze.
+    pub look_at: LookAtConfig,
+    /// Opt-in typing-burst detection (counts and timing only).
+    pub keyboard: KeyboardConfig,
+    /// System idle detection (`"user-idle"` / `"user-active"`).
+    pub idle: IdleConfig,
+    /// Mouse-shake gesture to summon the character.
+    pub shake: ShakeConfig,
+    /// Extra entries for the browser URL strategy table.
+    pub browsers: BrowsersConfig,
+    /// Apps and screen zones excluded from screen-awareness output.
+    pub redaction: RedactionConfig,
+    /// Battery-saver policy.
+    pub power: PowerConfig,
+    /// Tray icon left-click behaviour.
+    pub tray: TrayConfig,
+    /// Audio capture device and schedule.
+    pub audio: AudioConfig,
+    /// Loopback HTTP API (`/healthz`).
+    pub local_api: LocalApiConfig,
+    /// Incoming push server (`/notify`, `/speak`, `/emote`).
+    pub push: PushConfig,
+    /// OpenAI-compatible chat API for other local tools.
+    pub openai_api: OpenAiApiConfig,
+    /// Local Whisper dictation.
+    pub stt: SttConfig,
+    /// Opt-in wake-word activation.
+    pub wake_word: WakeWordConfig,
+    /// Text-to-speech engine for the character's voice.
+    pub tts: TtsConfig,
+    /// Retries and offline queueing for chat and webhook messages.
+    pub retry: RetryConfig,
+    /// Local gateway process started by the app.
+    pub gateway: GatewayConfig,
+    /// Character card and preferences for the system prompt.
+    pub persona: PersonaConfig,
+    /// Opt-in trace log of chat requests.
+    pub chat_log: ChatLogConfig,
+    /// Request rate and cost caps per chat backend.
+    pub usage: UsageConfig,
+    /// Outgoing event webhooks.
+    pub events: EventsConfig,
+    /// Reuse of replies to repeated chat requests.
+    pub chat_cache: CacheConfig,
+    /// Check-ins the character starts on its own.
+    pub proactive: ProactiveConfig,
+    /// Monitor the overlay covers.
+    pub display: DisplayConfig,
+    /// Daily times the character keeps quiet.
+    pub quiet_hours: QuietHoursConfig,
+    /// First-run setup steps done so far.
+    #[serde(default = "OnboardingConfig::existing_install")]
+    pub onboarding: OnboardingConfig,
+}
+
+impl AppSettings {
+    /// Fields kept in the keychain rather than `settings.json`.
+    pub(crate) fn secrets(&mut self) -> [(Cow<'static, str>, &mut String); 6] {
+        [
+            ("pushToken".into(), &mut self.push.token),
+            ("openAiApiToken".into(), &mut self.openai_api.token),
+            ("pushSigningSecret".into(), &mut self.push.signing_secret),
+            ("eventsToken".into(), &mut self.events.token),
+            (
+                "eventsSigningSecret".into(),
+                &mut self.events.signing_secret,
+            ),
+            ("ttsApiKey".into(), &mut self.tts.http.api_key),
+        ]
+    }
+}
+
 // ---------- State ----------
 
-/// Thread-safe wrapper around [`OpenClawConfig`], registered as Tauri managed state.
+/// Thread-safe wrapper around [`OpenClawConfig`] and [`AppSettings`],
+/// registered as Tauri managed state.
 ///
 /// Uses `RwLock` so multiple commands can read the config concurrently while
 /// writes (from Settings UI) are exclusive.
 pub struct ConfigState {
     pub config: RwLock<OpenClawConfig>,
+    pub settings: RwLock<AppSettings>,
+    /// Environment and command-line overrides found at startup.
+    overrides: Vec<Override>,
+    /// Saved values the next change is compared against for the history.
+    audit: Baseline,
+    /// The files could not be read at startup because the keychain holding
+    /// their key was unreadable; saves fail until a reload succeeds.
+    locked: AtomicBool,
 }
 
 impl ConfigState {
-    /// Load configuration from disk, or return defaults if the file does not
-    /// exist or is malformed.
+    /// Load configuration from disk, or return defaults if the files do not
+    /// exist or are malformed.
+    ///
+    /// Secrets are filled in from the keychain; any still found in the files
+    /// are moved there and the files rewritten without them.
     pub fn load() -> Self {
-        let path = config_path();
-        let config = if path.exists() {
-            fs::read_to_string(&path)
-                .ok()
-                .and_then(|s| serde_json::from_str(&s).ok())
-                .unwrap_or_default()
-        } else {
-            OpenClawConfig::default()
-        };
-        Self {
+        if let Some(dir) = custom_config_dir() {
+            println!("[config] Using config directory {}", dir.display());
+        }
+        let (config, settings) = (read_config(), read_settings());
+        let locked = (config.is_err() || settings.is_err()) && secrets::config_key().is_err();
+        let (mut config, config_migrated) = config.unwrap_or_else(|e| {
+            eprintln!("[config] {e}");
+            Default::default()
+        });
+        let (settings, settings_migrated) = settings.unwrap_or_else(|e| {
+            eprintln!("[config] {e}");
+            Default::default()
+        });
+        let audit = Baseline::new(config_snapshot(&config), settings_snapshot(&settings));
+        let overrides = find_overrides(std::env::vars(), &std::env::args().collect::<Vec<_>>());
+        apply_overrides(&mut config, &overrides);
+        validation::report(&config, &settings);
+        let state = Self {
             config: RwLock::new(config),
+            settings: RwLock::new(settings),
+            overrides,
+            audit,
+            locked: AtomicBool::new(locked),
+        };
+        if config_migrated {
+            if let Err(e) = state.save() {
+                eprintln!("[config] Failed to remove secrets from config.json: {e}");
+            }
+        }
+        if settings_migrated {
+            if let Err(e) = state.save_settings() {
+                eprintln!("[config] Failed to remove secrets from settings.json: {e}");
+            }
+        }
+        state
+    }
+
+    /// Re-read both files from disk, replacing the in-memory values.
+    ///
+    /// Used when the files are edited outside the app.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err`, keeping the current values, if either file cannot be
+    /// read or parsed, e.g. while it is half-way through being edited.
+    pub fn reload(&self) -> Result<(), String> {
+        let (mut config, _) = read_config()?;
+        let (settings, _) = read_settings()?;
+        self.audit
+            .record("config", "external", config_snapshot(&config));
+        self.audit
+            .record("settings", "external", settings_snapshot(&settings));
+        apply_overrides(&mut config, &self.overrides);
+        *self.config.write().map_err(|e| e.to_string())? = config;
+        *self.settings.write().map_err(|e| e.to_string())? = settings;
+        self.locked.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Refuse to save while the files on disk could not be read.
+    fn check_unlocked(&self) -> Result<(), String> {
+        if self.locked.load(Ordering::Relaxed) {
+            return Err("The config files could not be decrypted because the keychain was locked. Unlock it and restart the app before changing settings.".to_string());
         }
+        Ok(())
     }
 
     /// Persist the current config to disk.
     ///
-    /// Creates the parent directory if it does not exist.
+    /// Creates the parent directory if it does not exist. Secrets go to the
+    /// keychain instead, unless it is unavailable. Fields still holding an
+    /// override keep their saved value; ones changed since are saved.
     ///
     /// # Errors
     ///
     /// Returns `Err` if the directory cannot be created, the `RwLock` is
     /// poisoned, serialization fails, or the file cannot be written.
     pub fn save(&self) -> Result<(), String> {
-        let path = config_path();
+        self.save_config_as(ConfigFormat::detect("config"))
+    }
+
+    /// [`ConfigState::save`] to the file for `format`.
+    fn save_config_as(&self, format: ConfigFormat) -> Result<(), String> {
+        self.check_unlocked()?;
+        let path = config_file("config", format);
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {e}"))?;
         }
-        let config = self.config.read().map_err(|e| e.to_string())?;
-        let json = serde_json::to_string_pretty(&*config)
+        let mut config = self.config.read().map_err(|e| e.to_string())?.clone();
+        self.strip_overrides(&mut config);
+        let snapshot = config_snapshot(&config);
+        secrets::stash(&mut config.secrets());
+        let text = format
+            .serialize(&config, &path)
             .map_err(|e| format!("Failed to serialize config: {e}"))?;
-        fs::write(&path, json).map_err(|e| format!("Failed to write config: {e}"))?;
+        crate::memory::note_own_write("config");
+        write_atomic(&path, encrypt(text)?).map_err(|e| format!("Failed to write config: {e}"))?;
+        self.audit.record("config", "app", snapshot);
         Ok(())
     }
 
@@ -126,6 +445,254 @@ impl ConfigState {
         let config = self.config.read().map_err(|e| e.to_string())?;
         Ok(config.clone())
     }
+
+    /// Persist the current app settings to disk.
+    ///
+    /// # Errors
+    ///
+    /// Same failure modes as [`ConfigState::save`].
+    pub fn save_settings(&self) -> Result<(), String> {
+        self.save_settings_as(ConfigFormat::detect("settings"))
+    }
+
+    /// [`ConfigState::save_settings`] to the file for `format`.
+    fn save_settings_as(&self, format: ConfigFormat) -> Result<(), String> {
+        self.check_unlocked()?;
+        let path = config_file("settings", format);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {e}"))?;
+        }
+        let mut settings = self.settings.read().map_err(|e| e.to_string())?.clone();
+        let snapshot = settings_snapshot(&settings);
+        secrets::stash(&mut settings.secrets());
+        let text = format
+            .serialize(&settings, &path)
+            .map_err(|e| format!("Failed to serialize settings: {e}"))?;
+        crate::memory::note_own_write("settings");
+        write_atomic(&path, encrypt(text)?)
+            .map_err(|e| format!("Failed to write settings: {e}"))?;
+        self.audit.record("settings", "app", snapshot);
+        Ok(())
+    }
+
+    /// Put the saved values back into fields of `config` that still hold
+    /// their override.
+    fn strip_overrides(&self, config: &mut OpenClawConfig) {
+        strip_overrides(config, &self.overrides, || {
+            read_config().map(|(saved, _)| saved).unwrap_or_default()
+        });
+    }
+
+    /// Read a clone of the current app settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the `RwLock` is poisoned.
+    pub fn get_settings(&self) -> Result<AppSettings, String> {
+        let settings = self.settings.read().map_err(|e| e.to_string())?;
+        Ok(settings.clone())
+    }
+}
+
+/// Read `config.json` with its secrets restored, and whether any had to be
+/// migrated out of it.
+fn read_config() -> Result<(OpenClawConfig, bool), String> {
+    let mut config: OpenClawConfig = try_read_file(&config_path())?;
+    let migrated = secrets::restore(&mut config.secrets()).unwrap_or_else(|e| {
+        eprintln!("[secrets] Running without the config secrets: {e}");
+        false
+    });
+    Ok((config, migrated))
+}
+
+/// [`read_config`] for `settings.json`.
+fn read_settings() -> Result<(AppSettings, bool), String> {
+    let mut settings: AppSettings = try_read_file(&settings_path())?;
+    let migrated = secrets::restore(&mut settings.secrets()).unwrap_or_else(|e| {
+        eprintln!("[secrets] Running without the settings secrets: {e}");
+        false
+    });
+    Ok((settings, migrated))
+}
+
+/// Replace `path` with `contents` by writing a temporary file next to it
+/// and renaming it over, so a crash never leaves a truncated file.
+pub(crate) fn write_atomic(path: &Path, contents: String) -> std::io::Result<()> {
+    let partial = path.with_extension(format!("{}.partial", ConfigFormat::of(path).extension()));
+    fs::write(&partial, contents)?;
+    fs::rename(&partial, path)
+}
+
+/// `config` as JSON for the history, with secrets masked.
+fn config_snapshot(config: &OpenClawConfig) -> serde_json::Value {
+    let mut config = config.clone();
+    audit::mask(&mut config.secrets());
+    serde_json::to_value(config).unwrap_or_default()
+}
+
+/// [`config_snapshot`] for `settings`.
+fn settings_snapshot(settings: &AppSettings) -> serde_json::Value {
+    let mut settings = settings.clone();
+    audit::mask(&mut settings.secrets());
+    serde_json::to_value(settings).unwrap_or_default()
+}
+
+/// Read a JSON or TOML file (by extension) into `T`, falling back to
+/// `T::default()` if the file is missing or malformed.
+///
+/// Encrypted files are decrypted first. One that cannot be is moved aside
+/// so that the next save does not overwrite it.
+pub(crate) fn read_file<T: DeserializeOwned + Default>(path: &Path) -> T {
+    try_read_file(path).unwrap_or_else(|e| {
+        eprintln!("[config] {e}");
+        T::default()
+    })
+}
+
+/// [`read_file`] without the fallback: `T::default()` only if the file is
+/// missing.
+///
+/// # Errors
+///
+/// Returns `Err` if the file cannot be read, decrypted or parsed. A file
+/// that is encrypted while the keychain cannot be read stays in place.
+pub(crate) fn try_read_file<T: DeserializeOwned + Default>(path: &Path) -> Result<T, String> {
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    let text =
+        fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {e}", path.display()))?;
+    let text = if crate::e2e::is_sealed(&text) {
+        let key =
+            secrets::config_key().map_err(|e| format!("Cannot decrypt {}: {e}", path.display()))?;
+        match decrypt(key.as_deref(), &text) {
+            Ok(text) => text,
+            Err(e) => {
+                let aside = path.with_extension(format!(
+                    "{}.undecryptable",
+                    ConfigFormat::of(path).extension()
+                ));
+                let _ = fs::rename(path, &aside);
+                return Err(format!(
+                    "Cannot decrypt {}, moved it to {}: {e}",
+                    path.display(),
+                    aside.display()
+                ));
+            }
+        }
+    } else {
+        text
+    };
+    ConfigFormat::of(path)
+        .parse(&text)
+        .map_err(|e| format!("Cannot parse {}: {e}", path.display()))
+}
+
+/// File format of `config` and `settings`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+}
+
+impl ConfigFormat {
+    /// The format of file `name` (`"config"` or `"settings"`) on disk: TOML
+    /// if there is a `<name>.toml`, else JSON.
+    pub fn detect(name: &str) -> Self {
+        if config_file(name, Self::Toml).exists() {
+            Self::Toml
+        } else {
+            Self::Json
+        }
+    }
+
+    /// The format of `path`, by extension.
+    fn of(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Self::Toml,
+            _ => Self::Json,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Toml => "toml",
+        }
+    }
+
+    fn parse<T: DeserializeOwned>(self, text: &str) -> Result<T, String> {
+        match self {
+            Self::Json => serde_json::from_str(text).map_err(|e| e.to_string()),
+            Self::Toml => toml::from_str(text).map_err(|e| e.to_string()),
+        }
+    }
+
+    /// `value` as the contents of `path`. For TOML, the values are written
+    /// into the file's current document, so its comments survive.
+    fn serialize<T: Serialize>(self, value: &T, path: &Path) -> Result<String, String> {
+        match self {
+            Self::Json => serde_json::to_string_pretty(value).map_err(|e| e.to_string()),
+            Self::Toml => {
+                let text = toml::to_string_pretty(value).map_err(|e| e.to_string())?;
+                let existing = fs::read_to_string(path)
+                    .ok()
+                    .and_then(|old| old.parse::<toml_edit::DocumentMut>().ok());
+                let Some(mut document) = existing else {
+                    return Ok(text);
+                };
+                let fresh: toml_edit::DocumentMut = text.parse().map_err(|e| format!("{e}"))?;
+                merge_toml(document.as_table_mut(), fresh.as_table());
+                Ok(document.to_string())
+            }
+        }
+    }
+}
+
+/// Make `existing` hold the values of `fresh`, keeping the comments and
+/// layout of keys that are in both.
+fn merge_toml(existing: &mut toml_edit::Table, fresh: &toml_edit::Table) {
+    let stale: Vec<String> = existing
+        .iter()
+        .map(|(key, _)| key.to_string())
+        .filter(|key| !fresh.contains_key(key))
+        .collect();
+    for key in stale {
+        existing.remove(&key);
+    }
+    for (key, item) in fresh.iter() {
+        let merged = match (existing.get_mut(key), item) {
+            (Some(toml_edit::Item::Table(old)), toml_edit::Item::Table(new)) => {
+                merge_toml(old, new);
+                true
+            }
+            (Some(toml_edit::Item::Value(old)), toml_edit::Item::Value(new)) => {
+                let decor = old.decor().clone();
+                *old = new.clone();
+                *old.decor_mut() = decor;
+                true
+            }
+            _ => false,
+        };
+        if !merged {
+            existing.insert(key, item.clone());
+        }
+    }
+}
+
+/// `json` as it should be written: sealed while encryption at rest is on.
+pub(crate) fn encrypt(json: String) -> Result<String, String> {
+    match secrets::config_key()? {
+        Some(key) => crate::e2e::seal(&key, &json),
+        None => Ok(json),
+    }
+}
+
+/// Contents of an encrypted config file, given the stored `key`.
+fn decrypt(key: Option<&str>, sealed: &str) -> Result<String, String> {
+    let key = key.ok_or("encryption key missing from the keychain")?;
+    crate::e2e::open(key, sealed).map_err(|_| "wrong key or corrupted file".to_string())
 }
 
 /// Resolve the config file path with fallback chain:
@@ -135,8 +702,63 @@ impl ConfigState {
 /// 2. `dirs::home_dir() / .config` — if `config_dir()` is unavailable.
 /// 3. `./.config` — last resort if neither `config_dir` nor `home_dir` works.
 ///
-/// The final path is always `<base>/ai-desktop-companion/config.json`.
+/// The final path is `<base>/ai-desktop-companion/config.json`, or
+/// `config.toml` in the TOML format.
 fn config_path() -> PathBuf {
+    config_file("config", ConfigFormat::detect("config"))
+}
+
+/// Path of the [`AppSettings`] file, next to `config.json`.
+fn settings_path() -> PathBuf {
+    config_file("settings", ConfigFormat::detect("settings"))
+}
+
+/// `<config dir>/<name>.json` or `<name>.toml`.
+fn config_file(name: &str, format: ConfigFormat) -> PathBuf {
+    config_dir().join(format!("{name}.{}", format.extension()))
+}
+
+/// Environment variable that moves the config directory.
+pub const CONFIG_DIR_ENV: &str = "AIDC_CONFIG_DIR";
+
+/// Command-line flag that moves the config directory; wins over
+/// [`CONFIG_DIR_ENV`].
+pub const CONFIG_DIR_FLAG: &str = "--config-dir";
+
+/// The directory given by [`CONFIG_DIR_FLAG`] or [`CONFIG_DIR_ENV`], read
+/// once.
+static CUSTOM_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// The directory given by [`CONFIG_DIR_FLAG`] or [`CONFIG_DIR_ENV`], if
+/// any. A relative path is taken from the executable's directory, so a
+/// portable install finds its data wherever it is started from.
+pub(crate) fn custom_config_dir() -> Option<&'static Path> {
+    CUSTOM_DIR
+        .get_or_init(|| {
+            let args: Vec<String> = std::env::args().collect();
+            let dir = flag_value(&args, CONFIG_DIR_FLAG)
+                .map(str::to_string)
+                .or_else(|| std::env::var(CONFIG_DIR_ENV).ok())
+                .filter(|dir| !dir.trim().is_empty())?;
+            let dir = PathBuf::from(dir.trim());
+            let exe_dir = std::env::current_exe()
+                .ok()
+                .and_then(|exe| exe.parent().map(Path::to_path_buf));
+            match exe_dir {
+                Some(exe_dir) if dir.is_relative() => Some(exe_dir.join(dir)),
+                _ => Some(dir),
+            }
+        })
+        .as_deref()
+}
+
+/// The directory holding the config, settings and memory files:
+/// [`custom_config_dir`] if set, else `<base>/ai-desktop-companion` using
+/// the fallback chain of [`config_path`].
+pub(crate) fn config_dir() -> PathBuf {
+    if let Some(dir) = custom_config_dir() {
+        return dir.to_path_buf();
+    }
     dirs::config_dir()
         .unwrap_or_else(|| {
             dirs::home_dir()
@@ -144,7 +766,128 @@ fn config_path() -> PathBuf {
                 .join(".config")
         })
         .join("ai-desktop-companion")
-        .join("config.json")
+}
+
+// ---------- Overrides ----------
+
+/// Fields that can be overridden at startup: field name, environment
+/// variable and command-line flag (`--gateway-url <url>` or
+/// `--gateway-url=<url>`). Tokens are environment-only.
+pub const OVERRIDABLE: [(&str, &str, Option<&str>); 6] = [
+    ("gatewayUrl", "AIDC_GATEWAY_URL", Some("--gateway-url")),
+    ("agentId", "AIDC_AGENT_ID", Some("--agent-id")),
+    ("sessionKey", "AIDC_SESSION_KEY", Some("--session-key")),
+    ("cliPath", "AIDC_CLI_PATH", Some("--cli-path")),
+    ("hooksToken", "AIDC_HOOKS_TOKEN", None),
+    ("gatewayToken", "AIDC_GATEWAY_TOKEN", None),
+];
+
+/// A value from the environment or command line that replaces the saved
+/// one for this run.
+#[derive(Clone, Debug)]
+struct Override {
+    field: &'static str,
+    /// `"env"` or `"cli"`.
+    source: &'static str,
+    /// The variable or flag it came from.
+    origin: &'static str,
+    value: String,
+}
+
+/// A field of [`OVERRIDABLE`] in `config`.
+fn overridable_field<'a>(config: &'a mut OpenClawConfig, field: &str) -> Option<&'a mut String> {
+    match field {
+        "gatewayUrl" => Some(&mut config.gateway_url),
+        "agentId" => Some(&mut config.agent_id),
+        "sessionKey" => Some(&mut config.session_key),
+        "cliPath" => Some(&mut config.cli_path),
+        "hooksToken" => Some(&mut config.hooks_token),
+        "gatewayToken" => Some(&mut config.gateway_token),
+        _ => None,
+    }
+}
+
+/// Value of `flag` in `args`, as `--flag value` or `--flag=value`.
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == flag {
+            args.get(i + 1).map(String::as_str)
+        } else {
+            arg.strip_prefix(flag)?.strip_prefix('=')
+        }
+    })
+}
+
+/// The overrides set in `vars` (environment) and `args` (command line).
+/// Empty values are ignored.
+fn find_overrides(vars: impl Iterator<Item = (String, String)>, args: &[String]) -> Vec<Override> {
+    let vars: Vec<(String, String)> = vars.collect();
+    OVERRIDABLE
+        .iter()
+        .filter_map(|&(field, var, flag)| {
+            let cli = flag.and_then(|flag| Some((flag, flag_value(args, flag)?)));
+            let (source, origin, value) = match cli {
+                Some((flag, value)) => ("cli", flag, value.to_string()),
+                None => {
+                    let (_, value) = vars.iter().find(|(name, _)| name == var)?;
+                    ("env", var, value.clone())
+                }
+            };
+            (!value.trim().is_empty()).then(|| Override {
+                field,
+                source,
+                origin,
+                value: value.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Put the values from `saved`, read only if needed, back into fields of
+/// `config` that still hold their override. Fields changed since keep
+/// their new value.
+fn strip_overrides(
+    config: &mut OpenClawConfig,
+    overrides: &[Override],
+    saved: impl FnOnce() -> OpenClawConfig,
+) {
+    let pinned: Vec<&Override> = overrides
+        .iter()
+        .filter(|o| overridable_field(config, o.field).is_some_and(|v| *v == o.value))
+        .collect();
+    if pinned.is_empty() {
+        return;
+    }
+    let mut saved = saved();
+    for o in pinned {
+        if let (Some(value), Some(saved)) = (
+            overridable_field(config, o.field),
+            overridable_field(&mut saved, o.field),
+        ) {
+            *value = std::mem::take(saved);
+        }
+    }
+}
+
+fn apply_overrides(config: &mut OpenClawConfig, overrides: &[Override]) {
+    for o in overrides {
+        if let Some(value) = overridable_field(config, o.field) {
+            value.clone_from(&o.value);
+        }
+    }
+}
+
+/// One value as reported by [`get_effective_config`].
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveValue {
+    pub field: &'static str,
+    /// The value in use; secrets are masked by [`audit::mask`].
+    pub value: String,
+    /// `"default"`, `"file"`, `"env"` or `"cli"`.
+    pub source: &'static str,
+    /// Environment variable or flag, for `"env"` and `"cli"`.
+    pub origin: Option<&'static str>,
 }
 
 // ---------- Commands ----------
@@ -158,14 +901,319 @@ pub fn get_openclaw_config(state: State<'_, ConfigState>) -> Result<OpenClawConf
 /// IPC command: replace the OpenClaw configuration and persist to disk.
 ///
 /// Called from the Settings UI when the user saves changes.
+///
+/// # Errors
+///
+/// Returns [`ConfigError::Invalid`] listing every problem found by
+/// [`validation::validate_config`], without saving anything.
 #[tauri::command]
 pub fn save_openclaw_config(
     state: State<'_, ConfigState>,
     config: OpenClawConfig,
-) -> Result<(), String> {
+) -> Result<(), ConfigError> {
+    validation::check(validation::validate_config(&config))?;
     {
         let mut current = state.config.write().map_err(|e| e.to_string())?;
         *current = config;
     }
-    state.save()
+    Ok(state.save()?)
+}
+
+/// IPC command: the fields of [`OVERRIDABLE`] with the value in use and
+/// where it came from.
+#[tauri::command]
+pub fn get_effective_config(state: State<'_, ConfigState>) -> Result<Vec<EffectiveValue>, String> {
+    let mut config = state.get()?;
+    let mut masked = config.clone();
+    audit::mask(&mut masked.secrets());
+    let mut defaults = OpenClawConfig::default();
+    Ok(OVERRIDABLE
+        .iter()
+        .filter_map(|&(field, _, _)| {
+            let overridden = state.overrides.iter().find(|o| o.field == field);
+            let default = overridable_field(&mut defaults, field)?.clone();
+            let value = overridable_field(&mut config, field)?.clone();
+            let (source, origin) = match overridden {
+                Some(o) if o.value == value => (o.source, Some(o.origin)),
+                _ if value == default => ("default", None),
+                _ => ("file", None),
+            };
+            Some(EffectiveValue {
+                field,
+                value: overridable_field(&mut masked, field)?.clone(),
+                source,
+                origin,
+            })
+        })
+        .collect())
+}
+
+/// IPC command: the format the config file is kept in.
+#[tauri::command]
+pub fn get_config_format() -> ConfigFormat {
+    ConfigFormat::detect("config")
+}
+
+/// IPC command: rewrite the config and settings files in format `to`,
+/// removing the files in the other format.
+///
+/// Converting to JSON drops any comments in the TOML files.
+///
+/// # Errors
+///
+/// Returns `Err` if a value cannot be represented in `to` or a file cannot
+/// be written or removed. The old files are only removed once both new
+/// ones are written.
+#[tauri::command]
+pub fn convert_config_format(
+    state: State<'_, ConfigState>,
+    to: ConfigFormat,
+) -> Result<(), String> {
+    let names = ["config", "settings"];
+    let from = names.map(ConfigFormat::detect);
+    state.save_config_as(to)?;
+    state.save_settings_as(to)?;
+    for (name, from) in names.into_iter().zip(from) {
+        if from == to {
+            continue;
+        }
+        let path = config_file(name, from);
+        crate::memory::note_own_write(name);
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(format!("Failed to remove {}: {e}", path.display()));
+            }
+            _ => {}
+        }
+    }
+    println!("[config] Converted config files to {}", to.extension());
+    Ok(())
+}
+
+/// IPC command: whether `config.json` and `settings.json` are encrypted.
+#[tauri::command]
+pub fn get_config_encryption() -> Result<bool, String> {
+    Ok(secrets::config_key()?.is_some())
+}
+
+/// IPC command: turn encryption of `config.json` and `settings.json` on or
+/// off, rewriting both files and the change history.
+///
+/// # Errors
+///
+/// Returns `Err` if the keychain is unavailable (encryption needs it to
+/// hold the key) or the files cannot be written.
+#[tauri::command]
+pub fn set_config_encryption(state: State<'_, ConfigState>, enabled: bool) -> Result<(), String> {
+    if enabled == secrets::config_key()?.is_some() {
+        return Ok(());
+    }
+    let history = audit::load_history();
+    let rewrite = || -> Result<(), String> {
+        state.save()?;
+        state.save_settings()?;
+        audit::save_history(&history)
+    };
+    if enabled {
+        // The key is stored before any file is sealed with it.
+        secrets::set_config_key(Some(&crate::openclaw::generate_token()?))?;
+        rewrite()?;
+    } else {
+        // The files are back in plain text before the key is deleted, so a
+        // failure half-way never leaves a sealed file without its key.
+        let key = secrets::config_key()?;
+        secrets::use_config_key(None);
+        if let Err(e) = rewrite() {
+            secrets::use_config_key(key);
+            return Err(e);
+        }
+        secrets::set_config_key(None)?;
+    }
+    println!(
+        "[config] Encryption at rest {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+    Ok(())
+}
+
+/// IPC command: return the current backend behaviour settings.
+#[tauri::command]
+pub fn get_app_settings(state: State<'_, ConfigState>) -> Result<AppSettings, String> {
+    state.get_settings()
+}
+
+/// IPC command: replace the backend behaviour settings and persist to disk.
+///
+/// Background threads (e.g. the hit-test thread) pick up the new values
+/// within about a second.
+///
+/// # Errors
+///
+/// Returns [`ConfigError::Invalid`] listing every problem found by
+/// [`validation::validate_settings`], without saving anything.
+#[tauri::command]
+pub fn save_app_settings(
+    state: State<'_, ConfigState>,
+    settings: AppSettings,
+) -> Result<(), ConfigError> {
+    validation::check(validation::validate_settings(&settings))?;
+    {
+        let mut current = state.settings.write().map_err(|e| e.to_string())?;
+        *current = settings;
+    }
+    Ok(state.save_settings()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    fn vars(vars: &[(&str, &str)]) -> impl Iterator<Item = (String, String)> {
+        vars.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    fn override_of(field: &'static str, value: &str) -> Override {
+        Override {
+            field,
+            source: "cli",
+            origin: "--test",
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn flag_value_reads_both_forms() {
+        let a = args(&["app", "--agent-id", "a1", "--gateway-url=http://gw"]);
+        assert_eq!(flag_value(&a, "--agent-id"), Some("a1"));
+        assert_eq!(flag_value(&a, "--gateway-url"), Some("http://gw"));
+        assert_eq!(flag_value(&a, "--session-key"), None);
+    }
+
+    #[test]
+    fn flag_value_needs_an_exact_flag() {
+        let a = args(&["--agent-idx=a1", "--agent-id"]);
+        assert_eq!(flag_value(&a, "--agent-id"), None);
+    }
+
+    #[test]
+    fn find_overrides_prefers_the_command_line() {
+        let found = find_overrides(
+            vars(&[
+                ("AIDC_AGENT_ID", "from-env"),
+                ("AIDC_CLI_PATH", " /bin/oc "),
+            ]),
+            &args(&["--agent-id", "from-cli"]),
+        );
+        let found: Vec<_> = found
+            .iter()
+            .map(|o| (o.field, o.source, o.origin, o.value.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("agentId", "cli", "--agent-id", "from-cli"),
+                ("cliPath", "env", "AIDC_CLI_PATH", "/bin/oc"),
+            ]
+        );
+    }
+
+    #[test]
+    fn find_overrides_reads_tokens_from_the_environment_only() {
+        let found = find_overrides(
+            vars(&[("AIDC_HOOKS_TOKEN", "from-env")]),
+            &args(&["--hooks-token", "from-cli", "--gateway-token=from-cli"]),
+        );
+        let found: Vec<_> = found
+            .iter()
+            .map(|o| (o.field, o.source, o.value.as_str()))
+            .collect();
+        assert_eq!(found, [("hooksToken", "env", "from-env")]);
+    }
+
+    #[test]
+    fn find_overrides_skips_empty_values() {
+        let found = find_overrides(vars(&[("AIDC_SESSION_KEY", "  ")]), &args(&["--agent-id="]));
+        assert!(found.is_empty());
+    }
+
+    #[test]
+    fn strip_overrides_restores_saved_values() {
+        let mut config = OpenClawConfig {
+            agent_id: "override".into(),
+            session_key: "changed".into(),
+            ..Default::default()
+        };
+        let overrides = [
+            override_of("agentId", "override"),
+            override_of("sessionKey", "override"),
+        ];
+        let saved = OpenClawConfig {
+            agent_id: "saved".into(),
+            session_key: "saved".into(),
+            ..Default::default()
+        };
+        strip_overrides(&mut config, &overrides, || saved);
+        assert_eq!(config.agent_id, "saved");
+        // Changed since the override was applied, so it is kept.
+        assert_eq!(config.session_key, "changed");
+    }
+
+    #[test]
+    fn strip_overrides_reads_nothing_without_overridden_fields() {
+        let mut config = OpenClawConfig {
+            agent_id: "changed".into(),
+            ..Default::default()
+        };
+        strip_overrides(&mut config, &[override_of("agentId", "override")], || {
+            panic!("saved config read")
+        });
+        assert_eq!(config.agent_id, "changed");
+    }
+
+    fn merged(existing: &str, fresh: &str) -> String {
+        let mut document: toml_edit::DocumentMut = existing.parse().unwrap();
+        let fresh: toml_edit::DocumentMut = fresh.parse().unwrap();
+        merge_toml(document.as_table_mut(), fresh.as_table());
+        document.to_string()
+    }
+
+    #[test]
+    fn merge_toml_keeps_comments_of_updated_keys() {
+        let existing = "# gateway\nurl = \"old\" # in use\n\n[push]\n# LAN only\nport = 1\n";
+        assert_eq!(
+            merged(existing, "url = \"new\"\n[push]\nport = 2\n"),
+            "# gateway\nurl = \"new\" # in use\n\n[push]\n# LAN only\nport = 2\n"
+        );
+    }
+
+    #[test]
+    fn merge_toml_removes_keys_missing_from_fresh() {
+        let existing = "a = 1\nb = 2\n\n[old]\nx = 1\n\n[kept]\ny = 1\nz = 2\n";
+        assert_eq!(
+            merged(existing, "a = 1\n[kept]\ny = 1\n"),
+            "a = 1\n\n[kept]\ny = 1\n"
+        );
+    }
+
+    #[test]
+    fn merge_toml_inserts_new_keys() {
+        let merged = merged("a = 1\n", "a = 1\nb = 2\n[push]\nport = 3\n");
+        let document: toml_edit::DocumentMut = merged.parse().unwrap();
+        assert_eq!(document["b"].as_integer(), Some(2));
+        assert_eq!(document["push"]["port"].as_integer(), Some(3));
+    }
+
+    #[test]
+    fn merge_toml_replaces_a_key_that_changed_kind() {
+        let merged = merged("push = 1\n", "[push]\nport = 3\n");
+        let document: toml_edit::DocumentMut = merged.parse().unwrap();
+        assert_eq!(document["push"]["port"].as_integer(), Some(3));
+    }
 }
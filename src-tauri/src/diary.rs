@@ -0,0 +1,131 @@
+//! Companion diary — entries the character writes about its own day.
+//!
+//! Unlike [`crate::memory`], which backs up frontend state the user created,
+//! the diary is written by the agent itself: when its reply contains a
+//! `write_diary_entry` tool call, the frontend relays it to
+//! [`write_diary_entry`]. The diary viewer reads entries back with
+//! [`get_diary`].
+//!
+//! Entries live in their own namespace, one JSON object per line:
+//! ```text
+//! ~/.config/ai-desktop-companion/diary/entries.jsonl
+//! ```
+//! Appending a line never rewrites earlier entries, so a crash mid-write can
+//! lose at most the entry being written.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Longest accepted entry text, in characters.
+const MAX_ENTRY_CHARS: usize = 4000;
+
+/// Default number of entries returned by [`get_diary`].
+const DEFAULT_LIMIT: usize = 50;
+
+/// One diary entry.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DiaryEntry {
+    /// Unix time in milliseconds; also serves as the entry's identifier.
+    pub timestamp: u64,
+    pub text: String,
+    /// Free-form mood label chosen by the agent (e.g. `"happy"`).
+    pub mood: String,
+}
+
+/// Time range and page size for [`get_diary`]. All fields are optional.
+#[derive(Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiaryRange {
+    /// Inclusive lower bound (Unix ms).
+    pub from: Option<u64>,
+    /// Exclusive upper bound (Unix ms).
+    pub to: Option<u64>,
+    /// Maximum number of entries, newest first. Defaults to 50.
+    pub limit: Option<usize>,
+}
+
+fn diary_path() -> PathBuf {
+    crate::memory::data_dir().join("diary").join("entries.jsonl")
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Read every entry, skipping lines that fail to parse.
+fn read_entries() -> Result<Vec<DiaryEntry>, String> {
+    let path = diary_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read diary: {}", e))?;
+    Ok(contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                eprintln!("[diary] Skipping malformed entry: {}", e);
+                None
+            }
+        })
+        .collect())
+}
+
+/// IPC command: append a diary entry written by the agent.
+///
+/// Returns the stored entry (with its timestamp).
+#[tauri::command]
+pub fn write_diary_entry(text: String, mood: String) -> Result<DiaryEntry, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("Diary entry must not be empty".to_string());
+    }
+    if text.chars().count() > MAX_ENTRY_CHARS {
+        return Err(format!(
+            "Diary entry too long (max {} characters)",
+            MAX_ENTRY_CHARS
+        ));
+    }
+
+    let entry = DiaryEntry {
+        timestamp: now_ms(),
+        text: text.to_string(),
+        mood: mood.trim().to_string(),
+    };
+    let line = serde_json::to_string(&entry)
+        .map_err(|e| format!("Failed to serialize diary entry: {}", e))?;
+
+    let path = diary_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create diary dir: {}", e))?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open diary: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write diary: {}", e))?;
+    Ok(entry)
+}
+
+/// IPC command: diary entries within `range`, newest first.
+#[tauri::command]
+pub fn get_diary(range: Option<DiaryRange>) -> Result<Vec<DiaryEntry>, String> {
+    let range = range.unwrap_or_default();
+    let mut entries: Vec<DiaryEntry> = read_entries()?
+        .into_iter()
+        .filter(|e| range.from.is_none_or(|from| e.timestamp >= from))
+        .filter(|e| range.to.is_none_or(|to| e.timestamp < to))
+        .collect();
+    entries.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+    entries.truncate(range.limit.unwrap_or(DEFAULT_LIMIT));
+    Ok(entries)
+}
@@ -0,0 +1,125 @@
+//! Optional end-to-end encryption for chat payloads.
+//!
+//! Some users relay their OpenClaw traffic through infrastructure they don't
+//! trust (shared VPS, third-party tunnels). When the active agent has a key
+//! (its profile's, else `OpenClawConfig::e2e_key`; see
+//! [`crate::agents::e2e_key`]), [`crate::openclaw::send_chat`] seals the
+//! outgoing message with ChaCha20-Poly1305 using a key shared with the user's
+//! own gateway, and opens the sealed reply before handing it to the frontend.
+//! [`crate::openclaw::send_webhook`] seals its message the same way; the
+//! streaming path, which cannot be sealed, refuses to run.
+//!
+//! Sealed payloads are plain ASCII so they survive CLI argument passing:
+//! ```text
+//! oce2e1:<hex(nonce || ciphertext || tag)>
+//! ```
+//!
+//! The same envelope holds the config files when they are encrypted at
+//! rest (see [`crate::config`]).
+
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+
+/// Version prefix marking a sealed payload.
+const ENVELOPE_PREFIX: &str = "oce2e1:";
+
+/// ChaCha20-Poly1305 nonce length in bytes.
+const NONCE_LEN: usize = 12;
+
+/// Key length in bytes (64 hex characters).
+const KEY_LEN: usize = 32;
+
+/// Build a cipher from a hex-encoded 32-byte key.
+fn cipher(key_hex: &str) -> Result<ChaCha20Poly1305, String> {
+    let key = decode_hex(key_hex.trim())
+        .map_err(|e| format!("Invalid end-to-end key: {e}"))?;
+    if key.len() != KEY_LEN {
+        return Err(format!(
+            "Invalid end-to-end key: expected {} hex characters, got {}",
+            KEY_LEN * 2,
+            key_hex.trim().len()
+        ));
+    }
+    Ok(ChaCha20Poly1305::new(Key::from_slice(&key)))
+}
+
+/// Encrypt `plaintext` into a sealed envelope string.
+///
+/// A fresh random nonce is generated for every call.
+///
+/// # Errors
+///
+/// Returns `Err` if the key is malformed or the OS RNG is unavailable.
+pub fn seal(key_hex: &str, plaintext: &str) -> Result<String, String> {
+    let cipher = cipher(key_hex)?;
+
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::getrandom(&mut nonce)
+        .map_err(|e| format!("Failed to generate nonce: {}", e))?;
+
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_bytes())
+        .map_err(|_| "Failed to encrypt chat payload".to_string())?;
+
+    let mut sealed = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(format!("{}{}", ENVELOPE_PREFIX, encode_hex(&sealed)))
+}
+
+/// Whether `text` is a sealed envelope.
+pub(crate) fn is_sealed(text: &str) -> bool {
+    text.trim_start().starts_with(ENVELOPE_PREFIX)
+}
+
+/// Decrypt a sealed envelope produced by the gateway.
+///
+/// # Errors
+///
+/// Returns `Err` if the reply is not a sealed envelope (the gateway is not
+/// encrypting), the key is wrong, or the payload has been tampered with.
+pub fn open(key_hex: &str, envelope: &str) -> Result<String, String> {
+    let cipher = cipher(key_hex)?;
+
+    let body = envelope.trim().strip_prefix(ENVELOPE_PREFIX).ok_or_else(|| {
+        "Gateway returned an unencrypted reply while end-to-end encryption is enabled".to_string()
+    })?;
+    let sealed = decode_hex(body).map_err(|e| format!("Malformed encrypted reply: {e}"))?;
+    if sealed.len() < NONCE_LEN {
+        return Err("Malformed encrypted reply: payload too short".to_string());
+    }
+
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Failed to decrypt gateway reply (key mismatch or tampered payload)".to_string())?;
+
+    String::from_utf8(plaintext).map_err(|e| format!("Decrypted reply is not valid UTF-8: {e}"))
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>, String> {
+    if !s.len().is_multiple_of(2) {
+        return Err("odd number of hex characters".to_string());
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            s.get(i..i + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| format!("invalid hex at offset {i}"))
+        })
+        .collect()
+}
+
+/// IPC command: generate a new random end-to-end key.
+///
+/// The Settings UI shows this once so the user can copy it into their
+/// gateway's configuration; it is not saved until `save_openclaw_config`.
+#[tauri::command]
+pub fn generate_e2e_key() -> Result<String, String> {
+    crate::openclaw::generate_token()
+}
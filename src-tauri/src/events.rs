@@ -0,0 +1,441 @@
+//! Outgoing event pipeline to webhook endpoints.
+//!
+//! Where [`crate::openclaw::send_webhook`] sends one message to the
+//! gateway, this forwards what the companion notices to other servers
+//! (home automation, dashboards, scripts). Backend subsystems [`publish`]
+//! events; the dispatcher started by [`start_event_dispatcher`] collects
+//! them for [`EventsConfig::batch_window_ms`] and posts each endpoint the
+//! events its filters match, as one JSON body:
+//!
+//! ```json
+//! { "source": "ai-desktop-companion", "events": [
+//!   { "event": "meeting.started", "timestamp": 1760000000000, "data": { "app": "zoom.us" } }
+//! ] }
+//! ```
+//!
+//! Events:
+//!
+//! - `window.changed` — the active app changed (titles redacted as for chat)
+//! - `meeting.started` / `meeting.ended` — a call window of a known
+//!   conferencing app appeared or went away ([`MEETING_WINDOWS`])
+//! - `media.changed` — what is playing changed
+//! - `user.idle` / `user.active` — see [`crate::hittest::IdleConfig`]
+//! - `stats.process` — the app's memory use, every five minutes
+//!
+//! Filters are event names, `prefix.*` or `*`; an endpoint without filters
+//! gets everything. Batches that cannot be delivered are kept in a queue
+//! file and retried with backoff, oldest dropped past
+//! [`EventsConfig::max_queued`] events. With [`EventsConfig::signing_secret`]
+//! set, bodies are signed as described in [`crate::signing`].
+//!
+//! The pipeline is off by default, and nothing is published while it is.
+
+use crate::config::ConfigState;
+use crate::openclaw::HttpClient;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+/// Value of `"source"` in every body.
+const SOURCE: &str = "ai-desktop-companion";
+
+/// Per-request limit for endpoint deliveries.
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Backoff after a failed delivery, doubled per further failure.
+const RETRY_BASE: Duration = Duration::from_secs(5);
+const RETRY_MAX: Duration = Duration::from_secs(300);
+
+/// How often the watcher thread looks at the windows.
+const WATCH_INTERVAL: Duration = Duration::from_secs(3);
+
+const STATS_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Windows that mean a call is in progress, as (app name, title)
+/// substrings matched in lowercase; empty matches anything.
+pub const MEETING_WINDOWS: &[(&str, &str)] = &[
+    ("zoom", "meeting"),
+    ("teams", "meeting"),
+    ("teams", "call"),
+    ("webex", "meeting"),
+    ("facetime", ""),
+    ("", "google meet"),
+    ("", "meet - "),
+];
+
+/// Events section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EventsConfig {
+    pub enabled: bool,
+    pub endpoints: Vec<EventEndpoint>,
+    /// How long events are collected before they are sent.
+    pub batch_window_ms: u64,
+    /// Most events in one request.
+    pub max_batch: usize,
+    /// Undelivered events kept for retrying.
+    pub max_queued: usize,
+    /// Bearer token sent to every endpoint; empty for none.
+    pub token: String,
+    /// Secret bodies are signed with; empty sends them unsigned.
+    pub signing_secret: String,
+}
+
+impl Default for EventsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoints: Vec::new(),
+            batch_window_ms: 2000,
+            max_batch: 50,
+            max_queued: 500,
+            token: String::new(),
+            signing_secret: String::new(),
+        }
+    }
+}
+
+/// A server events are posted to.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EventEndpoint {
+    pub url: String,
+    /// Event names, `prefix.*` or `*`; empty for all events.
+    pub events: Vec<String>,
+}
+
+impl EventEndpoint {
+    fn wants(&self, event: &str) -> bool {
+        self.events.is_empty()
+            || self
+                .events
+                .iter()
+                .any(|filter| match filter.strip_suffix('*') {
+                    Some(prefix) => event.starts_with(prefix),
+                    None => filter == event,
+                })
+    }
+}
+
+/// One published event.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Event {
+    pub event: String,
+    /// Unix time in milliseconds.
+    pub timestamp: u64,
+    pub data: serde_json::Value,
+}
+
+/// Events waiting to be posted to `url`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Batch {
+    url: String,
+    events: Vec<Event>,
+}
+
+#[derive(Serialize)]
+struct Body<'a> {
+    source: &'static str,
+    events: &'a [Event],
+}
+
+/// Published since the last dispatch.
+static PENDING: Mutex<Vec<Event>> = Mutex::new(Vec::new());
+
+/// Batches not delivered yet, mirrored in [`queue_path`].
+static QUEUE: Mutex<Vec<Batch>> = Mutex::new(Vec::new());
+
+fn queue_path() -> std::path::PathBuf {
+    crate::memory::data_dir().join("events-queue.json")
+}
+
+fn save_queue(queue: &[Batch]) -> Result<(), String> {
+    let path = queue_path();
+    if queue.is_empty() {
+        return match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(format!("Failed to clear event queue: {e}"))
+            }
+            _ => Ok(()),
+        };
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create data dir: {e}"))?;
+    }
+    let json = serde_json::to_string(queue).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write event queue: {e}"))
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Queue `event` for the endpoints that want it. Does nothing while the
+/// pipeline is off or no endpoint matches.
+pub fn publish(app: &AppHandle, event: &str, data: impl Serialize) {
+    let Ok(settings) = app.state::<ConfigState>().get_settings() else {
+        return;
+    };
+    let config = settings.events;
+    if !config.enabled || !config.endpoints.iter().any(|e| e.wants(event)) {
+        return;
+    }
+    let data = match serde_json::to_value(data) {
+        Ok(data) => data,
+        Err(e) => {
+            eprintln!("[events] Failed to serialize {event}: {e}");
+            return;
+        }
+    };
+    if let Ok(mut pending) = PENDING.lock() {
+        if pending.len() >= config.max_queued.max(1) {
+            pending.remove(0);
+        }
+        pending.push(Event {
+            event: event.to_string(),
+            timestamp: now_ms(),
+            data,
+        });
+    }
+}
+
+/// Move the pending events into per-endpoint batches and trim the queue.
+fn enqueue(config: &EventsConfig) -> Result<(), String> {
+    let events = std::mem::take(&mut *PENDING.lock().map_err(|e| e.to_string())?);
+    let mut queue = QUEUE.lock().map_err(|e| e.to_string())?;
+    let before = queue.len();
+    for endpoint in config.endpoints.iter().filter(|e| !e.url.is_empty()) {
+        let wanted: Vec<Event> = events
+            .iter()
+            .filter(|e| endpoint.wants(&e.event))
+            .cloned()
+            .collect();
+        for chunk in wanted.chunks(config.max_batch.max(1)) {
+            queue.push(Batch {
+                url: endpoint.url.clone(),
+                events: chunk.to_vec(),
+            });
+        }
+    }
+    let mut queued: usize = queue.iter().map(|b| b.events.len()).sum();
+    let mut dropped = 0;
+    while queued > config.max_queued && !queue.is_empty() {
+        queued -= queue.remove(0).events.len();
+        dropped += 1;
+    }
+    if dropped > 0 {
+        eprintln!("[events] Queue full, dropped {dropped} oldest batches");
+    }
+    if queue.len() != before || dropped > 0 {
+        save_queue(&queue)?;
+    }
+    Ok(())
+}
+
+async fn deliver(
+    client: &reqwest::Client,
+    config: &EventsConfig,
+    batch: &Batch,
+) -> Result<(), String> {
+    let body = serde_json::to_vec(&Body {
+        source: SOURCE,
+        events: &batch.events,
+    })
+    .map_err(|e| e.to_string())?;
+    let mut request = client
+        .post(&batch.url)
+        .timeout(SEND_TIMEOUT)
+        .header(reqwest::header::CONTENT_TYPE, "application/json");
+    if !config.token.is_empty() {
+        request = request.bearer_auth(&config.token);
+    }
+    if !config.signing_secret.is_empty() {
+        for (name, value) in crate::signing::sign(&config.signing_secret, &body) {
+            request = request.header(name, value);
+        }
+    }
+    let response = request
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach {}: {e}", batch.url))?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} returned status {}",
+            batch.url,
+            response.status()
+        ))
+    }
+}
+
+/// Endpoints that failed lately: failures in a row and when to try again.
+type Backoff = HashMap<String, (u32, Instant)>;
+
+/// Post the queued batches whose endpoints are not backing off.
+async fn dispatch(app: &AppHandle, config: &EventsConfig, backoff: &mut Backoff) {
+    let batches = match QUEUE.lock() {
+        Ok(queue) => queue.clone(),
+        Err(_) => return,
+    };
+    if batches.is_empty() {
+        return;
+    }
+    let client = match app
+        .state::<ConfigState>()
+        .get()
+        .and_then(|c| app.state::<HttpClient>().client(&c))
+    {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("[events] {e}");
+            return;
+        }
+    };
+    let now = Instant::now();
+    let mut delivered = Vec::new();
+    for (i, batch) in batches.iter().enumerate() {
+        if backoff.get(&batch.url).is_some_and(|(_, at)| *at > now) {
+            continue;
+        }
+        match deliver(&client, config, batch).await {
+            Ok(()) => {
+                backoff.remove(&batch.url);
+                delivered.push(i);
+            }
+            Err(e) => {
+                let failures = backoff.get(&batch.url).map_or(0, |(n, _)| *n) + 1;
+                let delay = RETRY_BASE
+                    .saturating_mul(1 << (failures - 1).min(16))
+                    .min(RETRY_MAX);
+                eprintln!("[events] {e}, retrying in {}s", delay.as_secs());
+                backoff.insert(batch.url.clone(), (failures, Instant::now() + delay));
+            }
+        }
+    }
+    if delivered.is_empty() {
+        return;
+    }
+    if let Ok(mut queue) = QUEUE.lock() {
+        let mut index = 0;
+        queue.retain(|_| {
+            let keep = !delivered.contains(&index);
+            index += 1;
+            keep
+        });
+        if let Err(e) = save_queue(&queue) {
+            eprintln!("[events] {e}");
+        }
+    }
+}
+
+/// Start the dispatcher task, picking up batches left from the last run.
+pub fn start_event_dispatcher(app: AppHandle) {
+    let left: Vec<Batch> = std::fs::read_to_string(queue_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    if !left.is_empty() {
+        println!("[events] {} batches queued from the last run", left.len());
+    }
+    if let Ok(mut queue) = QUEUE.lock() {
+        *queue = left;
+    }
+    tauri::async_runtime::spawn(async move {
+        let mut backoff = Backoff::new();
+        loop {
+            let config = app
+                .state::<ConfigState>()
+                .get_settings()
+                .map(|s| s.events)
+                .unwrap_or_default();
+            tokio::time::sleep(Duration::from_millis(config.batch_window_ms.max(250))).await;
+            if !config.enabled {
+                continue;
+            }
+            if let Err(e) = enqueue(&config) {
+                eprintln!("[events] {e}");
+            }
+            dispatch(&app, &config, &mut backoff).await;
+        }
+    });
+}
+
+/// The conferencing app with a call window open, if any.
+fn meeting_app() -> Option<String> {
+    crate::screen::window_list().into_iter().find_map(|w| {
+        let app = w.app_name.to_lowercase();
+        let title = w.title.to_lowercase();
+        MEETING_WINDOWS
+            .iter()
+            .any(|(a, t)| app.contains(a) && title.contains(t))
+            .then_some(w.app_name)
+    })
+}
+
+/// Start the thread publishing `window.*`, `meeting.*` and `stats.*`.
+pub fn start_window_watcher(app: AppHandle, running: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        let mut active: Option<String> = None;
+        let mut meeting: Option<(String, Instant)> = None;
+        let mut last_stats: Option<Instant> = None;
+        while running.load(Ordering::Relaxed) {
+            thread::sleep(WATCH_INTERVAL);
+            let config_state = app.state::<ConfigState>();
+            if !config_state
+                .get_settings()
+                .map(|s| s.events.enabled)
+                .unwrap_or(false)
+            {
+                continue;
+            }
+
+            if let Some(window) = crate::screen::redacted_active_window(&config_state) {
+                if active.as_deref() != Some(window.app_name.as_str()) {
+                    active = Some(window.app_name.clone());
+                    publish(
+                        &app,
+                        "window.changed",
+                        serde_json::json!({ "app": window.app_name, "title": window.title }),
+                    );
+                }
+            }
+
+            match (meeting_app(), &meeting) {
+                (Some(app_name), None) => {
+                    publish(
+                        &app,
+                        "meeting.started",
+                        serde_json::json!({ "app": app_name }),
+                    );
+                    meeting = Some((app_name, Instant::now()));
+                }
+                (None, Some((app_name, since))) => {
+                    publish(
+                        &app,
+                        "meeting.ended",
+                        serde_json::json!({
+                            "app": app_name,
+                            "durationSecs": since.elapsed().as_secs(),
+                        }),
+                    );
+                    meeting = None;
+                }
+                _ => {}
+            }
+
+            if last_stats.is_none_or(|at| at.elapsed() >= STATS_INTERVAL) {
+                last_stats = Some(Instant::now());
+                publish(&app, "stats.process", crate::stats::get_process_stats());
+            }
+        }
+    });
+}
@@ -0,0 +1,330 @@
+//! Running the local OpenClaw gateway from the app.
+//!
+//! Instead of keeping `openclaw gateway` open in a terminal, the user can
+//! have the companion start it ([`start_gateway`]), optionally at launch
+//! ([`GatewayConfig::autostart`]). The process is started with the
+//! configured `cli_path` and the port of `gateway_url`; its output is kept
+//! in memory for [`get_gateway_logs`], and a monitor thread notices when it
+//! exits, emits `"gateway-exited"` and restarts it after a crash.
+//!
+//! Only a gateway started here is managed; one that was already running is
+//! left alone. The managed process is stopped when the app quits.
+
+use crate::config::{ConfigState, OpenClawConfig};
+use crate::openclaw::{self, HttpClient};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+use std::process::Child;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Output lines kept for [`get_gateway_logs`].
+const MAX_LOG_LINES: usize = 2000;
+
+/// Lines returned by [`get_gateway_logs`] when no tail is given.
+const DEFAULT_LOG_TAIL: usize = 200;
+
+/// How often the monitor thread checks the process.
+const MONITOR_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Wait before restarting a crashed gateway.
+const RESTART_DELAY: Duration = Duration::from_secs(5);
+
+/// A gateway that exits sooner than this after starting is not restarted,
+/// to avoid a crash loop (bad config, port in use, …).
+const MIN_UPTIME: Duration = Duration::from_secs(30);
+
+/// Gateway process section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GatewayConfig {
+    /// Start the gateway at launch unless one is already reachable.
+    pub autostart: bool,
+    /// Restart the managed gateway when it crashes.
+    pub restart_on_crash: bool,
+}
+
+impl Default for GatewayConfig {
+    fn default() -> Self {
+        Self {
+            autostart: false,
+            restart_on_crash: true,
+        }
+    }
+}
+
+/// Returned by [`get_gateway_status`] and the start commands.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GatewayStatus {
+    /// Whether a gateway started by the app is running.
+    pub managed: bool,
+    pub pid: Option<u32>,
+    pub uptime_secs: Option<u64>,
+    /// Whether `gateway_url` answers, whoever started the gateway.
+    pub reachable: bool,
+    /// Exit code of the last managed process, if it has exited.
+    pub last_exit_code: Option<i32>,
+}
+
+/// Payload of `"gateway-exited"`.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct GatewayExited {
+    /// `None` when killed by a signal.
+    code: Option<i32>,
+    uptime_secs: u64,
+    /// Whether it will be restarted.
+    restarting: bool,
+}
+
+/// The gateway process started by the app.
+struct Managed {
+    child: Child,
+    started: Instant,
+}
+
+static GATEWAY: Mutex<Option<Managed>> = Mutex::new(None);
+
+static LOGS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+static LAST_EXIT_CODE: Mutex<Option<i32>> = Mutex::new(None);
+
+fn push_log(line: String) {
+    if let Ok(mut logs) = LOGS.lock() {
+        if logs.len() == MAX_LOG_LINES {
+            logs.pop_front();
+        }
+        logs.push_back(line);
+    }
+}
+
+/// Copy the lines of a process pipe into [`LOGS`] until it closes.
+fn capture(pipe: impl Read + Send + 'static) {
+    std::thread::spawn(move || {
+        for line in BufReader::new(pipe).lines() {
+            match line {
+                Ok(line) => push_log(line),
+                Err(_) => break,
+            }
+        }
+    });
+}
+
+/// `--port` for the gateway, if `gateway_url` names one.
+fn gateway_port(config: &OpenClawConfig) -> Option<u16> {
+    reqwest::Url::parse(&config.gateway_url).ok()?.port()
+}
+
+/// Start `openclaw gateway` and its monitor thread.
+fn spawn(app: &AppHandle, config: &OpenClawConfig) -> Result<u32, String> {
+    let mut gateway = GATEWAY.lock().map_err(|e| e.to_string())?;
+    if gateway.is_some() {
+        return Err("The gateway is already running".to_string());
+    }
+    let cli = if config.cli_path.is_empty() {
+        "openclaw"
+    } else {
+        &config.cli_path
+    };
+    let mut cmd = openclaw::build_openclaw_cmd(cli);
+    cmd.arg("gateway");
+    if let Some(port) = gateway_port(config) {
+        cmd.arg("--port").arg(port.to_string());
+    }
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to start the gateway with {cli}: {e}"))?;
+    if let Some(stdout) = child.stdout.take() {
+        capture(stdout);
+    }
+    if let Some(stderr) = child.stderr.take() {
+        capture(stderr);
+    }
+    let pid = child.id();
+    println!("[gateway] Started {cli} gateway (pid {pid})");
+    *gateway = Some(Managed {
+        child,
+        started: Instant::now(),
+    });
+
+    let app = app.clone();
+    std::thread::spawn(move || monitor(app, pid));
+    Ok(pid)
+}
+
+/// Wait for process `pid` to exit, then report it and restart it if
+/// wanted. Returns quietly once it has been stopped or replaced.
+fn monitor(app: AppHandle, pid: u32) {
+    let (code, uptime) = loop {
+        std::thread::sleep(MONITOR_INTERVAL);
+        let Ok(mut gateway) = GATEWAY.lock() else {
+            return;
+        };
+        let Some(managed) = gateway.as_mut().filter(|m| m.child.id() == pid) else {
+            return;
+        };
+        if let Ok(Some(status)) = managed.child.try_wait() {
+            let uptime = managed.started.elapsed();
+            *gateway = None;
+            break (status.code(), uptime);
+        }
+    };
+    if let Ok(mut last) = LAST_EXIT_CODE.lock() {
+        *last = code;
+    }
+
+    let config_state = app.state::<ConfigState>();
+    let restart_on_crash = config_state
+        .get_settings()
+        .map(|s| s.gateway.restart_on_crash)
+        .unwrap_or(false);
+    let restarting = restart_on_crash && code != Some(0) && uptime >= MIN_UPTIME;
+    eprintln!(
+        "[gateway] Exited with {} after {}s{}",
+        code.map_or("a signal".to_string(), |c| format!("code {c}")),
+        uptime.as_secs(),
+        if restarting { ", restarting" } else { "" }
+    );
+    let _ = app.emit(
+        "gateway-exited",
+        GatewayExited {
+            code,
+            uptime_secs: uptime.as_secs(),
+            restarting,
+        },
+    );
+    if restarting {
+        std::thread::sleep(RESTART_DELAY);
+        let result = config_state.get().and_then(|config| spawn(&app, &config));
+        if let Err(e) = result {
+            eprintln!("[gateway] Restart failed: {e}");
+        }
+    }
+}
+
+/// Stop the managed gateway, if any. Returns whether one was running.
+pub fn stop() -> bool {
+    let managed = GATEWAY.lock().ok().and_then(|mut g| g.take());
+    let Some(mut managed) = managed else {
+        return false;
+    };
+    println!("[gateway] Stopping pid {}", managed.child.id());
+    let _ = managed.child.kill();
+    if let Ok(status) = managed.child.wait() {
+        if let Ok(mut last) = LAST_EXIT_CODE.lock() {
+            *last = status.code();
+        }
+    }
+    true
+}
+
+/// Start the gateway at launch if [`GatewayConfig::autostart`] is set and
+/// none is reachable yet.
+pub fn start_gateway_on_launch(app: AppHandle) {
+    let config_state = app.state::<ConfigState>();
+    if !config_state
+        .get_settings()
+        .map(|s| s.gateway.autostart)
+        .unwrap_or(false)
+    {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        let Ok(config) = app.state::<ConfigState>().get() else {
+            return;
+        };
+        if openclaw::gateway_reachable(&app.state::<HttpClient>(), &config).await {
+            println!("[gateway] Already running at {}", config.gateway_url);
+            return;
+        }
+        if let Err(e) = spawn(&app, &config) {
+            eprintln!("[gateway] {e}");
+        }
+    });
+}
+
+async fn status(http: &HttpClient, config: &OpenClawConfig) -> Result<GatewayStatus, String> {
+    let (pid, uptime_secs) = {
+        let gateway = GATEWAY.lock().map_err(|e| e.to_string())?;
+        match gateway.as_ref() {
+            Some(managed) => (
+                Some(managed.child.id()),
+                Some(managed.started.elapsed().as_secs()),
+            ),
+            None => (None, None),
+        }
+    };
+    let last_exit_code = *LAST_EXIT_CODE.lock().map_err(|e| e.to_string())?;
+    Ok(GatewayStatus {
+        managed: pid.is_some(),
+        pid,
+        uptime_secs,
+        reachable: openclaw::gateway_reachable(http, config).await,
+        last_exit_code,
+    })
+}
+
+/// IPC command: start the local gateway with the configured CLI.
+///
+/// # Errors
+///
+/// Returns `Err` if a gateway is already running (managed or not) or the
+/// CLI cannot be started.
+#[tauri::command]
+pub async fn start_gateway(
+    app: AppHandle,
+    http: State<'_, HttpClient>,
+    config_state: State<'_, ConfigState>,
+) -> Result<GatewayStatus, String> {
+    let config = config_state.get()?;
+    let managed = GATEWAY.lock().map_err(|e| e.to_string())?.is_some();
+    if !managed && openclaw::gateway_reachable(&http, &config).await {
+        return Err(format!(
+            "A gateway is already running at {}",
+            config.gateway_url
+        ));
+    }
+    spawn(&app, &config)?;
+    status(&http, &config).await
+}
+
+/// IPC command: stop the gateway started by [`start_gateway`]. Returns
+/// whether one was running.
+#[tauri::command]
+pub fn stop_gateway() -> bool {
+    stop()
+}
+
+/// IPC command: stop the managed gateway, if any, and start it again.
+#[tauri::command]
+pub async fn restart_gateway(
+    app: AppHandle,
+    http: State<'_, HttpClient>,
+    config_state: State<'_, ConfigState>,
+) -> Result<GatewayStatus, String> {
+    let config = config_state.get()?;
+    stop();
+    spawn(&app, &config)?;
+    status(&http, &config).await
+}
+
+/// IPC command: whether the gateway is running and reachable.
+#[tauri::command]
+pub async fn get_gateway_status(
+    http: State<'_, HttpClient>,
+    config_state: State<'_, ConfigState>,
+) -> Result<GatewayStatus, String> {
+    status(&http, &config_state.get()?).await
+}
+
+/// IPC command: the last `tail` (default 200) output lines of the managed
+/// gateway, oldest first. Kept across restarts.
+#[tauri::command]
+pub fn get_gateway_logs(tail: Option<usize>) -> Result<Vec<String>, String> {
+    let logs = LOGS.lock().map_err(|e| e.to_string())?;
+    let tail = tail.unwrap_or(DEFAULT_LOG_TAIL).min(logs.len());
+    Ok(logs.iter().skip(logs.len() - tail).cloned().collect())
+}
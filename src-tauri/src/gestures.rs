@@ -0,0 +1,175 @@
+//! Double-click and long-press recognition on the character.
+//!
+//! Mouse events only reach the webview while cursor capture is enabled, so
+//! JS-side click timers misfire whenever capture toggles mid-gesture. The
+//! hit-test thread ([`crate::hittest`]) sees every button event regardless,
+//! and feeds them into a [`GestureRecognizer`] together with the character's
+//! hit regions (registered via [`crate::hittest::set_hit_regions`]).
+//!
+//! - `"pet-double-click"` — two left-button presses inside the same region
+//!   within the OS double-click interval.
+//! - `"pet-long-press"` — the left button held inside a region for
+//!   [`LONG_PRESS_DURATION`] without moving.
+
+use crate::snap::Rect;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// How long the button must be held for a long press.
+pub const LONG_PRESS_DURATION: Duration = Duration::from_millis(600);
+
+/// Movement that cancels a long press and separates two clicks.
+const GESTURE_SLOP_PX: i32 = 5;
+
+/// Used when the OS double-click interval cannot be read.
+const DEFAULT_DOUBLE_CLICK: Duration = Duration::from_millis(500);
+
+/// Payload of `"pet-double-click"` / `"pet-long-press"` events.
+#[derive(Clone, Serialize)]
+pub struct PetGestureEvent {
+    /// Index into the regions passed to `set_hit_regions`.
+    pub region: usize,
+    /// Window-relative position, logical pixels.
+    pub x: i32,
+    pub y: i32,
+    /// How long the button has been held (long press only; `0` otherwise).
+    pub duration_ms: u64,
+}
+
+/// A recognised gesture.
+pub enum Gesture {
+    DoubleClick(PetGestureEvent),
+    LongPress(PetGestureEvent),
+}
+
+/// Index of the first region containing the point.
+pub(crate) fn region_at(regions: &[Rect], x: i32, y: i32) -> Option<usize> {
+    let (x, y) = (x as f64, y as f64);
+    regions
+        .iter()
+        .position(|r| x >= r.x && x < r.x + r.width && y >= r.y && y < r.y + r.height)
+}
+
+fn within_slop(a: (i32, i32), b: (i32, i32)) -> bool {
+    (a.0 - b.0).abs() <= GESTURE_SLOP_PX && (a.1 - b.1).abs() <= GESTURE_SLOP_PX
+}
+
+/// A left-button press inside a region.
+struct Press {
+    at: Instant,
+    pos: (i32, i32),
+    region: usize,
+    /// Long press already fired, or cancelled by movement.
+    done: bool,
+}
+
+/// Click-timing state machine for the left button.
+#[derive(Default)]
+pub struct GestureRecognizer {
+    press: Option<Press>,
+    /// The previous press that could start a double click.
+    last_click: Option<(Instant, (i32, i32), usize)>,
+}
+
+impl GestureRecognizer {
+    /// Left button pressed at window-relative `(x, y)`.
+    pub fn on_press(
+        &mut self,
+        regions: &[Rect],
+        double_click: Duration,
+        x: i32,
+        y: i32,
+    ) -> Option<Gesture> {
+        let now = Instant::now();
+        let Some(region) = region_at(regions, x, y) else {
+            self.press = None;
+            self.last_click = None;
+            return None;
+        };
+        self.press = Some(Press {
+            at: now,
+            pos: (x, y),
+            region,
+            done: false,
+        });
+
+        let is_double = self.last_click.take().is_some_and(|(at, pos, r)| {
+            r == region && now.duration_since(at) <= double_click && within_slop(pos, (x, y))
+        });
+        if is_double {
+            return Some(Gesture::DoubleClick(PetGestureEvent {
+                region,
+                x,
+                y,
+                duration_ms: 0,
+            }));
+        }
+        self.last_click = Some((now, (x, y), region));
+        None
+    }
+
+    /// Left button released.
+    pub fn on_release(&mut self) {
+        self.press = None;
+    }
+
+    /// Cursor moved while tracking; cancels a pending long press.
+    pub fn on_move(&mut self, x: i32, y: i32) {
+        if let Some(press) = self.press.as_mut() {
+            if !within_slop(press.pos, (x, y)) {
+                press.done = true;
+                self.last_click = None;
+            }
+        }
+    }
+
+    /// Check the long-press timer. Call on every sample and tick.
+    pub fn poll(&mut self) -> Option<Gesture> {
+        let press = self.press.as_mut().filter(|p| !p.done)?;
+        let held = press.at.elapsed();
+        if held < LONG_PRESS_DURATION {
+            return None;
+        }
+        press.done = true;
+        self.last_click = None;
+        Some(Gesture::LongPress(PetGestureEvent {
+            region: press.region,
+            x: press.pos.0,
+            y: press.pos.1,
+            duration_ms: held.as_millis() as u64,
+        }))
+    }
+}
+
+/// The user's double-click interval from OS settings.
+///
+/// - **macOS** — `+[NSEvent doubleClickInterval]`
+/// - **Windows** — `GetDoubleClickTime()`
+/// - **Other platforms** — 500 ms
+pub fn os_double_click_interval() -> Duration {
+    #[cfg(target_os = "macos")]
+    {
+        use objc::runtime::Class;
+        use objc::{msg_send, sel, sel_impl};
+
+        if let Some(class) = Class::get("NSEvent") {
+            // SAFETY: class property getter returning a plain NSTimeInterval.
+            let secs: f64 = unsafe { msg_send![class, doubleClickInterval] };
+            if secs > 0.0 {
+                return Duration::from_secs_f64(secs);
+            }
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::UI::Input::KeyboardAndMouse::GetDoubleClickTime;
+
+        let ms = unsafe { GetDoubleClickTime() };
+        if ms > 0 {
+            return Duration::from_millis(ms as u64);
+        }
+    }
+
+    DEFAULT_DOUBLE_CLICK
+}
@@ -0,0 +1,160 @@
+//! Subsystem health report.
+//!
+//! Collects the status of the long-running parts of the backend into one
+//! [`HealthReport`], served as `/healthz` by [`crate::localapi`] so the
+//! companion can be monitored from scripts or uptime tools.
+//!
+//! The webview is considered alive while it keeps calling
+//! [`webview_heartbeat`] (the frontend does so every few seconds).
+
+use crate::audio;
+use crate::config::ConfigState;
+use crate::hittest;
+use crate::openclaw::{self, HttpClient};
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+/// A webview heartbeat older than this marks the webview unhealthy.
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// When the app started, for `uptime_secs`.
+static STARTED: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Time of the last [`webview_heartbeat`] call.
+static LAST_HEARTBEAT: Mutex<Option<Instant>> = Mutex::new(None);
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioHealth {
+    pub ok: bool,
+    /// The input stream is open.
+    pub capturing: bool,
+    /// Capture is off because of the user's schedule (not an error).
+    pub suspended: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GatewayHealth {
+    pub ok: bool,
+    pub url: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadHealth {
+    pub ok: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct KeychainHealth {
+    /// Every secret could be read ([`crate::secrets::unavailable`]).
+    pub ok: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WebviewHealth {
+    pub ok: bool,
+    /// Seconds since the last heartbeat; `None` if none arrived yet.
+    pub last_heartbeat_secs: Option<f64>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Subsystems {
+    pub audio: AudioHealth,
+    pub gateway: GatewayHealth,
+    /// Mouse tracking thread ([`crate::hittest`]).
+    pub hittest: ThreadHealth,
+    pub keychain: KeychainHealth,
+    pub webview: WebviewHealth,
+}
+
+/// Payload of `/healthz`.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    /// `"ok"` when every subsystem is healthy, otherwise `"degraded"`.
+    pub status: &'static str,
+    pub version: &'static str,
+    pub uptime_secs: u64,
+    pub subsystems: Subsystems,
+}
+
+impl HealthReport {
+    pub fn is_ok(&self) -> bool {
+        self.status == "ok"
+    }
+}
+
+/// Remember the app start time. Called once from setup.
+pub fn mark_started() {
+    if let Ok(mut started) = STARTED.lock() {
+        started.get_or_insert_with(Instant::now);
+    }
+}
+
+/// Build a report, checking the gateway over HTTP.
+pub async fn report(app: &AppHandle) -> HealthReport {
+    let capturing = audio::is_capturing();
+    let suspended = audio::is_suspended();
+    let audio = AudioHealth {
+        ok: capturing || suspended,
+        capturing,
+        suspended,
+    };
+
+    let config = app.state::<ConfigState>().get().unwrap_or_default();
+    let gateway = GatewayHealth {
+        ok: openclaw::gateway_reachable(&app.state::<HttpClient>(), &config).await,
+        url: config.gateway_url,
+    };
+
+    let hittest = ThreadHealth {
+        ok: hittest::is_tracking(),
+    };
+
+    let keychain = KeychainHealth {
+        ok: !crate::secrets::unavailable(),
+    };
+
+    let last_heartbeat_secs = LAST_HEARTBEAT
+        .lock()
+        .ok()
+        .and_then(|t| *t)
+        .map(|t| t.elapsed().as_secs_f64());
+    let webview = WebviewHealth {
+        ok: last_heartbeat_secs.is_some_and(|s| s < HEARTBEAT_TIMEOUT.as_secs_f64()),
+        last_heartbeat_secs,
+    };
+
+    let all_ok = audio.ok && gateway.ok && hittest.ok && keychain.ok && webview.ok;
+    HealthReport {
+        status: if all_ok { "ok" } else { "degraded" },
+        version: env!("CARGO_PKG_VERSION"),
+        uptime_secs: STARTED
+            .lock()
+            .ok()
+            .and_then(|t| *t)
+            .map_or(0, |t| t.elapsed().as_secs()),
+        subsystems: Subsystems {
+            audio,
+            gateway,
+            hittest,
+            keychain,
+            webview,
+        },
+    }
+}
+
+/// IPC command: the frontend is alive. Called periodically.
+#[tauri::command]
+pub fn webview_heartbeat() {
+    if let Ok(mut last) = LAST_HEARTBEAT.lock() {
+        *last = Some(Instant::now());
+    }
+}
@@ -1,112 +1,2328 @@
-//! Mouse-position polling for transparent-window hit-testing.
+//! Global mouse tracking for transparent-window hit-testing.
 //!
 //! Because the Tauri window is transparent and covers the entire screen,
 //! native mouse events pass through to underlying applications. To detect
-//! when the cursor is over the VRM character, we poll the global mouse
-//! position at ~60 Hz and emit window-relative coordinates to the frontend.
+//! when the cursor is over the VRM character, we observe the global mouse
+//! position and emit window-relative coordinates to the frontend.
+//!
+//! Two backends feed the same [`MouseSink`]:
+//!
+//! - **Native event taps** — a listen-only `CGEventTap` on macOS and a
+//!   `WH_MOUSE_LL` low-level hook on Windows. Events arrive as the OS
+//!   delivers them, so there is no idle CPU cost and no polling latency.
+//! - **Polling** — reads the cursor position at ~60 Hz. Used on other
+//!   platforms, or when the native hook cannot be installed (e.g. missing
+//!   Accessibility permission).
+//!
+//! Button presses are reported as `"mouse-down"` / `"mouse-up"` events even
+//! while the window ignores cursor events, so fast clicks on the character
+//! are not lost to the capture-toggling race. Moving with a button held past
+//! [`DRAG_THRESHOLD_PX`] synthesizes `"drag-start"` / `"drag-move"` /
+//! `"drag-end"`, and wheel input is reported as `"mouse-scroll"` (native
+//! backends only — scrolling cannot be polled). On macOS, trackpad pinches
+//! and swipes are reported as `"gesture-pinch"` / `"gesture-swipe"`.
+//!
+//! Samples from a pen or graphics tablet carry a [`PenState`] in
+//! `"mouse-move"` and drag events, with pressure and tilt: on macOS from the
+//! tablet fields of the `CGEvent`, on Windows from the pen digitizer's raw
+//! input, since the low-level mouse hook does not see `WM_POINTER` data.
+//!
+//! The sink also drives shake detection ([`crate::shake`]), double-click and
+//! long-press recognition on the character's hit regions
+//! ([`crate::gestures`], regions set via [`set_hit_regions`]) and hot-corner
+//! detection ([`crate::hotcorners`]); the
+//! native backends wake every [`TICK_INTERVAL`] so dwell timers complete
+//! even while the cursor is stationary.
+//!
+//! Every raw sample is also collected into a `"mouse-batch"` event, emitted
+//! at most once per [`MOUSE_BATCH_INTERVAL`] with the path travelled since
+//! the previous batch, so trail and velocity effects get full-rate input
+//! without one IPC message per sample from high-polling-rate mice.
+//!
+//! `"mouse-move"` is only emitted when the cursor actually moves (by at least
+//! [`HitTestConfig::move_epsilon_px`]); once it has been still for
+//! [`HitTestConfig::mouse_idle_ms`] a single `"mouse-idle"` is sent instead
+//! of a stream of identical positions.
+//!
+//! The sampling rate is saved as [`HitTestConfig::poll_interval_ms`].
+//! [`set_poll_interval_ms`] slows it for the current run only, low-power
+//! mode caps it at 15 Hz, and tracking can be paused with [`set_polling_enabled`]; it
+//! is also paused automatically while the main window is hidden from the
+//! tray.
+//!
+//! The current system cursor shape (I-beam, pointing hand, resize, busy) is
+//! available on demand via [`get_cursor_kind`].
+//!
+//! System-wide idle time (any keyboard/mouse input) is exposed via
+//! [`get_idle_seconds`], and a low-frequency monitor emits `"user-idle"` /
+//! `"user-active"` when it crosses the configured threshold.
 //!
 //! The frontend uses these coordinates with a Three.js raycaster to decide
 //! whether `setIgnoreCursorEvents(false)` should be called (cursor is over
 //! the character) or `setIgnoreCursorEvents(true)` (cursor should pass
-//! through to the desktop).
+//! through to the desktop). Alternatively it can register the interactive
+//! rects once via [`set_interactive_regions`] and let this thread toggle
+//! click-through itself, avoiding an IPC round-trip on the first click.
 
+use crate::config::ConfigState;
+use crate::gestures::{self, Gesture, GestureRecognizer};
+use crate::hotcorners::{self, HotCornerEvent, HotCornerTracker, HotCornersConfig};
+use crate::shake::{self, ShakeConfig, ShakeDetector, ShakeEvent};
+use crate::snap::Rect;
+use crate::user_error::{user_facing_error, Severity};
 use mouse_position::mouse_position::Mouse;
-use serde::Serialize;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tauri::{AppHandle, Emitter, Manager};
 
-/// Window-relative mouse coordinates in logical pixels.
+/// Consecutive emit failures after which the webview is assumed destroyed.
+const MAX_CONSECUTIVE_FAILURES: u32 = 300; // ~5 seconds at 60Hz
+
+/// How often the cached window geometry and settings are refreshed.
+const WINDOW_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often native backends wake up when no input arrives, and how often
+/// the polling fallback re-checks while paused.
+const TICK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Default interval between `"mouse-move"` emissions (~60 Hz).
+const DEFAULT_POLL_INTERVAL_MS: u64 = 16;
+
+/// Accepted range for the sampling interval.
+const MIN_POLL_INTERVAL_MS: u64 = 4;
+const MAX_POLL_INTERVAL_MS: u64 = 1000;
+
+/// Slowest-allowed sampling while in low-power mode (~15 Hz).
+const LOW_POWER_POLL_INTERVAL_MS: u64 = 66;
+
+/// [`HitTestConfig::poll_interval_ms`] as last read from the settings.
+static POLL_INTERVAL_SETTING_MS: AtomicU64 = AtomicU64::new(DEFAULT_POLL_INTERVAL_MS);
+
+/// Interval requested via [`set_poll_interval_ms`] for this run; `0` if none.
+static POLL_INTERVAL_OVERRIDE_MS: AtomicU64 = AtomicU64::new(0);
+
+/// User-controlled pause switch (see [`set_polling_enabled`]).
+static POLLING_ENABLED: AtomicBool = AtomicBool::new(true);
+
+fn poll_interval() -> Duration {
+    Duration::from_millis(current_poll_interval_ms())
+}
+
+/// The mouse sampling interval currently in effect: the slowest of the
+/// saved setting, the runtime override and the low-power floor.
+///
+/// This is the interval of the polling fallback and the minimum spacing of
+/// `"mouse-move"` emissions from the native backends.
+pub fn current_poll_interval_ms() -> u64 {
+    let floor = if LOW_POWER.load(Ordering::Relaxed) {
+        LOW_POWER_POLL_INTERVAL_MS
+    } else {
+        0
+    };
+    POLL_INTERVAL_SETTING_MS
+        .load(Ordering::Relaxed)
+        .max(POLL_INTERVAL_OVERRIDE_MS.load(Ordering::Relaxed))
+        .max(floor)
+        .clamp(MIN_POLL_INTERVAL_MS, MAX_POLL_INTERVAL_MS)
+}
+
+/// Current Unix time in milliseconds.
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// Minimum spacing of `"mouse-batch"` emissions (one animation frame).
+const MOUSE_BATCH_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Samples kept per batch; older ones are dropped if the webview stalls.
+const MAX_BATCH_POINTS: usize = 256;
+
+/// Distance the cursor must travel with a button held before a drag starts.
+const DRAG_THRESHOLD_PX: i32 = 4;
+
+/// How often the idle monitor checks system idle time.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Hit-test section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HitTestConfig {
+    /// Minimum change (logical pixels, either axis) before another
+    /// `"mouse-move"` is emitted.
+    pub move_epsilon_px: i32,
+    /// Time without movement before the single `"mouse-idle"` heartbeat.
+    pub mouse_idle_ms: u64,
+    /// Distance outside the character anchor's radius that counts as "near".
+    pub near_distance_px: f64,
+    /// Saved mouse sampling interval. [`set_poll_interval_ms`] and low-power
+    /// mode can slow sampling further but do not change it.
+    pub poll_interval_ms: u64,
+}
+
+impl Default for HitTestConfig {
+    fn default() -> Self {
+        Self {
+            move_epsilon_px: 1,
+            mouse_idle_ms: 250,
+            near_distance_px: 120.0,
+            poll_interval_ms: DEFAULT_POLL_INTERVAL_MS,
+        }
+    }
+}
+
+/// Idle section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct IdleConfig {
+    /// Seconds without any input before `"user-idle"` is emitted.
+    pub threshold_secs: u64,
+}
+
+impl Default for IdleConfig {
+    fn default() -> Self {
+        Self { threshold_secs: 300 }
+    }
+}
+
+/// Payload of `"user-idle"` / `"user-active"` events.
+#[derive(Clone, Serialize)]
+pub struct IdleEvent {
+    /// For `"user-idle"`, the current idle time; for `"user-active"`, how
+    /// long the user was away.
+    pub idle_seconds: f64,
+}
+
+/// Latest window-relative cursor position, packed as `(x << 32) | y`, for
+/// consumers outside the hit-test thread. `u64::MAX` until the first sample.
+static LAST_CURSOR: AtomicU64 = AtomicU64::new(u64::MAX);
+
+/// Latest window-relative cursor position seen by the hit-test thread.
+pub fn last_cursor_position() -> Option<(i32, i32)> {
+    let packed = LAST_CURSOR.load(Ordering::Relaxed);
+    if packed == u64::MAX {
+        return None;
+    }
+    Some(((packed >> 32) as u32 as i32, packed as u32 as i32))
+}
+
+/// The character's hit regions (window-relative, logical pixels), set by
+/// the frontend via [`set_hit_regions`].
+static HIT_REGIONS: Mutex<Vec<Rect>> = Mutex::new(Vec::new());
+
+/// Rects in which the window captures the cursor, set via
+/// [`set_interactive_regions`]. `None` leaves click-through to the frontend.
+static INTERACTIVE_REGIONS: Mutex<Option<Vec<Rect>>> = Mutex::new(None);
+
+/// Set when [`INTERACTIVE_REGIONS`] changes so the sink re-applies capture.
+static INTERACTIVE_REGIONS_DIRTY: AtomicBool = AtomicBool::new(false);
+
+/// The character's bounding circle in screen space, `(x, y, radius)`, set
+/// via [`set_character_anchor`].
+static CHARACTER_ANCHOR: Mutex<Option<(f64, f64, f64)>> = Mutex::new(None);
+
+/// Set by the power policy; suppresses raw `"mouse-move"` while an anchor is
+/// registered and slows sampling to [`LOW_POWER_POLL_INTERVAL_MS`].
+static LOW_POWER: AtomicBool = AtomicBool::new(false);
+
+/// Cursor distance band relative to the character anchor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Proximity {
+    Far,
+    Near,
+    Inside,
+}
+
+/// Payload of `"cursor-near"` / `"cursor-enter"` / `"cursor-leave"` events.
+#[derive(Clone, Serialize)]
+pub struct ProximityEvent {
+    /// For `"cursor-near"`: whether the cursor entered (`true`) or left the
+    /// near band. Otherwise whether it is still near after the transition.
+    pub near: bool,
+    /// Distance from the anchor's edge (negative inside the circle).
+    pub distance: f64,
+    /// Screen-space cursor position.
+    pub global_x: i32,
+    pub global_y: i32,
+}
+
+/// Payload of `"cursor-capture-changed"` events.
+#[derive(Clone, Serialize)]
+pub struct CursorCaptureEvent {
+    /// `true` while the cursor is over an interactive region and the window
+    /// receives mouse events.
+    pub captured: bool,
+}
+
+/// Payload of `"mouse-move"` events.
 #[derive(Clone, Serialize)]
 pub struct MousePosition {
+    /// Window-relative position, logical pixels.
+    pub x: i32,
+    pub y: i32,
+    /// Screen-space position (top-left origin of the primary monitor).
+    pub global_x: i32,
+    pub global_y: i32,
+    /// Index into [`crate::window::get_all_monitors`] of the monitor under
+    /// the cursor, if any.
+    pub monitor_index: Option<usize>,
+    /// Position relative to that monitor's top-left corner.
+    pub local_x: i32,
+    pub local_y: i32,
+    /// Buttons held at the time of the sample, read from the OS (same layout
+    /// as the DOM's `MouseEvent.buttons`: 1 = left, 2 = right, 4 = middle).
+    pub buttons: u8,
+    /// Set when the sample came from a pen or tablet.
+    pub pen: Option<PenState>,
+}
+
+/// One sample of a `"mouse-batch"` path.
+#[derive(Clone, Serialize)]
+pub struct PathPoint {
+    /// Window-relative position, logical pixels.
+    pub x: i32,
+    pub y: i32,
+    /// Milliseconds since the batch's `start` timestamp.
+    pub t: u32,
+}
+
+/// Payload of `"mouse-batch"` events: the cursor path since the last batch.
+#[derive(Clone, Serialize)]
+pub struct MouseBatch {
+    /// Unix timestamp (ms) the point offsets are relative to.
+    pub start: u64,
+    pub points: Vec<PathPoint>,
+}
+
+/// Stylus data attached to samples that come from a pen or tablet.
+#[derive(Clone, Copy, Debug, Serialize, PartialEq)]
+#[cfg_attr(not(any(target_os = "macos", target_os = "windows")), allow(dead_code))]
+#[serde(rename_all = "camelCase")]
+pub struct PenState {
+    /// Tip pressure, `0.0`–`1.0`, if the platform reports it.
+    pub pressure: Option<f64>,
+    /// Tilt along each axis, `-1.0`–`1.0` (0 = upright), if reported.
+    pub tilt_x: Option<f64>,
+    pub tilt_y: Option<f64>,
+}
+
+/// Payload of `"mouse-idle"` events: where the cursor came to rest.
+#[derive(Clone, Serialize)]
+pub struct MouseIdleEvent {
     pub x: i32,
     pub y: i32,
+    pub global_x: i32,
+    pub global_y: i32,
+}
+
+/// A physical mouse button.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PointerButton {
+    Left,
+    Right,
+    Middle,
 }
 
-/// Start a background thread that polls the global mouse position at ~60 Hz
-/// and emits `"mouse-move"` events containing window-relative coordinates.
+impl PointerButton {
+    /// Bit of this button in a pressed-buttons mask (same layout as the DOM's
+    /// `MouseEvent.buttons`: 1 = left, 2 = right, 4 = middle).
+    fn mask(self) -> u8 {
+        match self {
+            PointerButton::Left => 1,
+            PointerButton::Right => 2,
+            PointerButton::Middle => 4,
+        }
+    }
+}
+
+/// Payload of `"mouse-down"` / `"mouse-up"` events (window-relative, logical pixels).
+#[derive(Clone, Serialize)]
+pub struct MouseButtonEvent {
+    pub button: PointerButton,
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Phase of a continuous trackpad gesture.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GesturePhase {
+    Began,
+    Changed,
+    Ended,
+    Cancelled,
+}
+
+/// Payload of `"gesture-pinch"` events.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+#[derive(Clone, Serialize)]
+pub struct PinchEvent {
+    /// Relative scale change since the previous event (e.g. `0.02` = 2 % larger).
+    pub magnification: f64,
+    pub phase: GesturePhase,
+    pub x: i32,
+    pub y: i32,
+    /// Hit region under the cursor (see [`set_hit_regions`]), if any.
+    pub region: Option<usize>,
+}
+
+/// Direction of a swipe gesture.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Payload of `"gesture-swipe"` events.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+#[derive(Clone, Serialize)]
+pub struct SwipeEvent {
+    pub direction: SwipeDirection,
+    pub x: i32,
+    pub y: i32,
+    /// Hit region under the cursor (see [`set_hit_regions`]), if any.
+    pub region: Option<usize>,
+}
+
+/// Payload of `"mouse-scroll"` events.
 ///
-/// # Coordinate conversion
+/// Deltas follow the DOM `WheelEvent` convention: positive `delta_y` scrolls
+/// down, positive `delta_x` scrolls right. Units are approximate pixels.
+#[derive(Clone, Serialize)]
+pub struct MouseScrollEvent {
+    pub delta_x: f64,
+    pub delta_y: f64,
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Payload of `"drag-start"` / `"drag-move"` / `"drag-end"` events.
+#[derive(Clone, Serialize)]
+pub struct DragEvent {
+    pub button: PointerButton,
+    /// Current position (window-relative, logical pixels).
+    pub x: i32,
+    pub y: i32,
+    /// Where the button was pressed.
+    pub start_x: i32,
+    pub start_y: i32,
+    /// Set when dragging with a pen (e.g. stroking the character).
+    pub pen: Option<PenState>,
+}
+
+/// A held button that may turn into a drag.
+struct DragState {
+    button: PointerButton,
+    start: (i32, i32),
+    active: bool,
+}
+
+// ---------- Sink ----------
+
+/// Window geometry and settings read for the [`MouseSink`] off the input
+/// path.
+struct CachedState {
+    double_click: Duration,
+    /// Main window visibility; `None` if there is no main window.
+    window_visible: Option<bool>,
+    /// Logical outer position of the main window, if it could be read.
+    window_position: Option<(f64, f64)>,
+    /// Logical inner size of the main window, if it could be read.
+    window_size: Option<(f64, f64)>,
+    settings: Option<(HotCornersConfig, ShakeConfig, HitTestConfig)>,
+}
+
+impl CachedState {
+    /// Read the current state. Blocks on main-thread round trips, so this
+    /// must not run inside an input callback.
+    fn read(app: &AppHandle, monitors: &Arc<Mutex<Vec<Rect>>>) -> Self {
+        let mut state = Self {
+            double_click: gestures::os_double_click_interval(),
+            window_visible: None,
+            window_position: None,
+            window_size: None,
+            settings: None,
+        };
+        if let Some(window) = app.get_webview_window("main") {
+            state.window_visible = Some(window.is_visible().unwrap_or(true));
+            let scale_factor = window.scale_factor().unwrap_or(1.0);
+            state.window_position = window
+                .outer_position()
+                .ok()
+                .map(|pos| (pos.x as f64 / scale_factor, pos.y as f64 / scale_factor));
+            state.window_size = window.inner_size().ok().map(|size| {
+                (
+                    size.width as f64 / scale_factor,
+                    size.height as f64 / scale_factor,
+                )
+            });
+        }
+        // NSScreen may only be queried on the main thread; the frames arrive
+        // for later samples.
+        let monitors = Arc::clone(monitors);
+        let _ = app.run_on_main_thread(move || {
+            if let Ok(mut monitors) = monitors.lock() {
+                *monitors = crate::snap::monitor_rects();
+            }
+        });
+        if let Ok(settings) = app.state::<ConfigState>().get_settings() {
+            POLL_INTERVAL_SETTING_MS.store(settings.hittest.poll_interval_ms, Ordering::Relaxed);
+            state.settings = Some((settings.hot_corners, settings.shake, settings.hittest));
+        }
+        state
+    }
+}
+
+/// Re-read the [`CachedState`] every [`WINDOW_REFRESH_INTERVAL`] on its own
+/// thread, until the sink owning `refreshed` is dropped.
 ///
-/// `Mouse::get_mouse_position()` returns global screen coordinates in logical
-/// points (macOS CGEvent coordinate space). We subtract the window's outer
-/// position (also in logical pixels) to get window-relative coordinates that
-/// the frontend can feed directly into its Three.js raycaster.
+/// The `WH_MOUSE_LL` hook and the event tap run on the tracking thread, and
+/// Windows removes a hook that takes longer than `LowLevelHooksTimeout`, so
+/// they only pick up what this thread has read.
+fn spawn_state_refresher(
+    app: AppHandle,
+    refreshed: &Arc<Mutex<Option<CachedState>>>,
+    monitors: &Arc<Mutex<Vec<Rect>>>,
+) {
+    let refreshed = Arc::downgrade(refreshed);
+    let monitors = Arc::downgrade(monitors);
+    thread::spawn(move || loop {
+        thread::sleep(WINDOW_REFRESH_INTERVAL);
+        let (Some(refreshed), Some(monitors)) = (refreshed.upgrade(), monitors.upgrade()) else {
+            return;
+        };
+        let state = CachedState::read(&app, &monitors);
+        if let Ok(mut slot) = refreshed.lock() {
+            *slot = Some(state);
+        };
+    });
+}
+
+/// Converts global cursor samples into window-relative `"mouse-move"` events.
+///
+/// Shared by the native and polling backends so both produce identical
+/// payloads. The window geometry and relevant settings are cached; a
+/// separate thread re-reads them about once a second (see
+/// [`spawn_state_refresher`]), so handling a sample never waits on Tauri IPC
+/// or the main thread.
+struct MouseSink {
+    app: AppHandle,
+    win_logical_x: f64,
+    win_logical_y: f64,
+    win_logical_w: f64,
+    win_logical_h: f64,
+    /// Latest [`CachedState`] from the refresher thread, not yet applied.
+    refreshed: Arc<Mutex<Option<CachedState>>>,
+    consecutive_failures: u32,
+    /// Last window-relative cursor position.
+    last_pos: Option<(i32, i32)>,
+    /// Last screen-space cursor position.
+    last_global: (i32, i32),
+    /// Monitor frames in screen space, refreshed with the window geometry
+    /// from the main thread.
+    monitors: Arc<Mutex<Vec<Rect>>>,
+    hot_corner_config: HotCornersConfig,
+    hot_corners: HotCornerTracker,
+    shake_config: ShakeConfig,
+    shake: ShakeDetector,
+    gestures: GestureRecognizer,
+    /// OS double-click interval, refreshed with the window geometry.
+    double_click: Duration,
+    drag: Option<DragState>,
+    /// Capture state last applied for the interactive regions.
+    captured: Option<bool>,
+    /// Last band relative to the character anchor.
+    proximity: Option<Proximity>,
+    /// Whether the main window was visible at the last refresh.
+    window_visible: bool,
+    /// When the last `"mouse-move"` was emitted, for throttling.
+    last_move_emit: Option<Instant>,
+    /// A throttled move is waiting to be flushed.
+    pending_move: bool,
+    hittest_config: HitTestConfig,
+    /// Position of the last `"mouse-move"` emission.
+    last_emitted_pos: Option<(i32, i32)>,
+    /// When the cursor last moved past the epsilon.
+    last_motion: Instant,
+    /// `"mouse-idle"` was sent for the current stationary period.
+    idle_sent: bool,
+    /// Samples collected for the next `"mouse-batch"`.
+    batch: Vec<PathPoint>,
+    /// Start of the current batch (monotonic and wall-clock).
+    batch_start: Option<(Instant, u64)>,
+    /// When the last `"mouse-batch"` was emitted.
+    last_batch_emit: Option<Instant>,
+    /// Stylus data of the latest sample, set by the native backends.
+    pen: Option<PenState>,
+}
+
+impl MouseSink {
+    fn new(app: AppHandle) -> Self {
+        let monitors = Arc::new(Mutex::new(Vec::new()));
+        let refreshed = Arc::new(Mutex::new(Some(CachedState::read(&app, &monitors))));
+        spawn_state_refresher(app.clone(), &refreshed, &monitors);
+        Self {
+            app,
+            win_logical_x: 0.0,
+            win_logical_y: 0.0,
+            win_logical_w: 0.0,
+            win_logical_h: 0.0,
+            refreshed,
+            consecutive_failures: 0,
+            last_pos: None,
+            last_global: (0, 0),
+            monitors,
+            hot_corner_config: HotCornersConfig::default(),
+            hot_corners: HotCornerTracker::default(),
+            shake_config: ShakeConfig::default(),
+            shake: ShakeDetector::default(),
+            gestures: GestureRecognizer::default(),
+            double_click: gestures::os_double_click_interval(),
+            drag: None,
+            captured: None,
+            proximity: None,
+            window_visible: true,
+            last_move_emit: None,
+            pending_move: false,
+            hittest_config: HitTestConfig::default(),
+            last_emitted_pos: None,
+            last_motion: Instant::now(),
+            idle_sent: false,
+            batch: Vec::new(),
+            batch_start: None,
+            last_batch_emit: None,
+            pen: None,
+        }
+    }
+
+    /// Whether events should be processed at all: not paused by the user and
+    /// the window is not hidden.
+    fn is_active(&mut self) -> bool {
+        self.refresh_cached_state();
+        POLLING_ENABLED.load(Ordering::Relaxed) && self.window_visible
+    }
+
+    /// Apply the refresher thread's latest read, if there is a new one.
+    ///
+    /// Only `try_lock`s, so the input callbacks never block on the refresher.
+    fn refresh_cached_state(&mut self) {
+        let Some(state) = self.refreshed.try_lock().ok().and_then(|mut s| s.take()) else {
+            return;
+        };
+        self.double_click = state.double_click;
+        if let Some(visible) = state.window_visible {
+            self.window_visible = visible;
+        }
+        if let Some((x, y)) = state.window_position {
+            self.win_logical_x = x;
+            self.win_logical_y = y;
+        }
+        if let Some((w, h)) = state.window_size {
+            self.win_logical_w = w;
+            self.win_logical_h = h;
+        }
+        if let Some((hot_corners, shake, hittest)) = state.settings {
+            self.hot_corner_config = hot_corners;
+            self.shake_config = shake;
+            self.hittest_config = hittest;
+        }
+    }
+
+    /// Convert global coordinates to window-relative ones.
+    ///
+    /// Coordinates are in the same space `Mouse::get_mouse_position()`
+    /// returns (logical points on macOS). We subtract the window's outer
+    /// position to get window-relative coordinates that the frontend can feed
+    /// directly into its Three.js raycaster.
+    fn window_relative(&mut self, x: f64, y: f64) -> (i32, i32) {
+        self.refresh_cached_state();
+        (
+            (x - self.win_logical_x) as i32,
+            (y - self.win_logical_y) as i32,
+        )
+    }
+
+    /// Handle one global cursor sample.
+    ///
+    /// Emissions are spaced at least [`poll_interval`] apart; a sample that
+    /// arrives sooner is held and flushed by the next sample or tick.
+    ///
+    /// Returns `false` once the webview appears to be gone and the backend
+    /// should stop.
+    fn on_move(&mut self, x: f64, y: f64) -> bool {
+        if !self.is_active() {
+            return true;
+        }
+        self.last_global = (x as i32, y as i32);
+        let (x, y) = self.window_relative(x, y);
+        self.last_pos = Some((x, y));
+        LAST_CURSOR.store(((x as u32 as u64) << 32) | y as u32 as u64, Ordering::Relaxed);
+        if !self.record_batch_point(x, y) || !self.check_shake(x, y) {
+            return false;
+        }
+        if !self.update_capture() || !self.update_proximity() {
+            return false;
+        }
+        self.gestures.on_move(x, y);
+        let long_press = self.gestures.poll();
+        if !self.emit_gesture(long_press) {
+            return false;
+        }
+
+        let epsilon = self.hittest_config.move_epsilon_px.max(1);
+        let moved = self
+            .last_emitted_pos
+            .is_none_or(|(lx, ly)| (x - lx).abs() >= epsilon || (y - ly).abs() >= epsilon);
+        if !moved && !self.pending_move {
+            return self.check_mouse_idle() && self.check_hot_corners();
+        }
+
+        if self
+            .last_move_emit
+            .is_some_and(|t| t.elapsed() < poll_interval())
+        {
+            self.pending_move = true;
+            return true;
+        }
+        self.flush_move()
+    }
+
+    /// Append a raw sample to the current batch and flush it if due.
+    fn record_batch_point(&mut self, x: i32, y: i32) -> bool {
+        if self.batch.last().is_some_and(|p| p.x == x && p.y == y) {
+            return self.flush_batch(false);
+        }
+        let (started, _) = *self
+            .batch_start
+            .get_or_insert_with(|| (Instant::now(), unix_millis()));
+        if self.batch.len() >= MAX_BATCH_POINTS {
+            self.batch.remove(0);
+        }
+        self.batch.push(PathPoint {
+            x,
+            y,
+            t: started.elapsed().as_millis() as u32,
+        });
+        self.flush_batch(false)
+    }
+
+    /// Emit the collected path as `"mouse-batch"` once [`MOUSE_BATCH_INTERVAL`]
+    /// has passed since the previous batch (or immediately with `force`).
+    fn flush_batch(&mut self, force: bool) -> bool {
+        if self.batch.is_empty() {
+            return true;
+        }
+        if !force
+            && self
+                .last_batch_emit
+                .is_some_and(|t| t.elapsed() < MOUSE_BATCH_INTERVAL)
+        {
+            return true;
+        }
+        self.last_batch_emit = Some(Instant::now());
+        let start = self.batch_start.take().map_or(0, |(_, wall)| wall);
+        let points = std::mem::take(&mut self.batch);
+        if LOW_POWER.load(Ordering::Relaxed) {
+            return true;
+        }
+        self.emit("mouse-batch", MouseBatch { start, points })
+    }
+
+    /// Emit `"mouse-idle"` once the cursor has been still long enough.
+    fn check_mouse_idle(&mut self) -> bool {
+        if self.idle_sent || self.pending_move {
+            return true;
+        }
+        let Some((x, y)) = self.last_emitted_pos else {
+            return true;
+        };
+        if self.last_motion.elapsed() < Duration::from_millis(self.hittest_config.mouse_idle_ms) {
+            return true;
+        }
+        self.idle_sent = true;
+        let (global_x, global_y) = self.last_global;
+        self.emit(
+            "mouse-idle",
+            MouseIdleEvent {
+                x,
+                y,
+                global_x,
+                global_y,
+            },
+        )
+    }
+
+    /// Emit the last known position and everything derived from it.
+    fn flush_move(&mut self) -> bool {
+        let Some((x, y)) = self.last_pos else {
+            return true;
+        };
+        self.pending_move = false;
+        self.last_move_emit = Some(Instant::now());
+        self.last_emitted_pos = Some((x, y));
+        self.last_motion = Instant::now();
+        self.idle_sent = false;
+        let (global_x, global_y) = self.last_global;
+        let (gx, gy) = (global_x as f64, global_y as f64);
+        let monitor = self.monitors.lock().ok().and_then(|monitors| {
+            monitors
+                .iter()
+                .enumerate()
+                .find(|(_, m)| gx >= m.x && gx < m.x + m.width && gy >= m.y && gy < m.y + m.height)
+                .map(|(i, m)| (i, *m))
+        });
+        let (monitor_index, local_x, local_y) = match monitor {
+            Some((i, m)) => (Some(i), (gx - m.x) as i32, (gy - m.y) as i32),
+            None => (None, global_x, global_y),
+        };
+        let position = MousePosition {
+            x,
+            y,
+            global_x,
+            global_y,
+            monitor_index,
+            local_x,
+            local_y,
+            buttons: pressed_buttons(),
+            pen: self.pen,
+        };
+        let suppressed = LOW_POWER.load(Ordering::Relaxed)
+            && CHARACTER_ANCHOR.lock().is_ok_and(|a| a.is_some());
+        (suppressed || self.emit("mouse-move", position))
+            && self.update_drag(x, y)
+            && self.check_hot_corners()
+    }
+
+    /// Emit drag events for a held button once it passes the threshold.
+    fn update_drag(&mut self, x: i32, y: i32) -> bool {
+        let Some(drag) = self.drag.as_mut() else {
+            return true;
+        };
+        let (start_x, start_y) = drag.start;
+        let event = DragEvent {
+            button: drag.button,
+            x,
+            y,
+            start_x,
+            start_y,
+            pen: self.pen,
+        };
+        if drag.active {
+            return self.emit("drag-move", event);
+        }
+        if (x - start_x).abs() >= DRAG_THRESHOLD_PX || (y - start_y).abs() >= DRAG_THRESHOLD_PX {
+            drag.active = true;
+            return self.emit("drag-start", event.clone()) && self.emit("drag-move", event);
+        }
+        true
+    }
+
+    /// Record stylus data for the next samples (`None` for a plain mouse).
+    #[cfg_attr(not(any(target_os = "macos", target_os = "windows")), allow(dead_code))]
+    fn set_pen(&mut self, pen: Option<PenState>) {
+        self.pen = pen;
+    }
+
+    /// Handle wheel input at global coordinates.
+    #[cfg_attr(not(any(target_os = "macos", target_os = "windows")), allow(dead_code))]
+    fn on_scroll(&mut self, delta_x: f64, delta_y: f64, x: f64, y: f64) -> bool {
+        if !self.is_active() {
+            return true;
+        }
+        let (x, y) = self.window_relative(x, y);
+        self.emit(
+            "mouse-scroll",
+            MouseScrollEvent {
+                delta_x,
+                delta_y,
+                x,
+                y,
+            },
+        )
+    }
+
+    /// Hit region under a window-relative point.
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    fn region_at(&self, x: i32, y: i32) -> Option<usize> {
+        let regions = HIT_REGIONS.lock().ok()?;
+        gestures::region_at(&regions, x, y)
+    }
+
+    /// Handle a trackpad pinch at global coordinates.
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    fn on_pinch(&mut self, magnification: f64, phase: GesturePhase, x: f64, y: f64) -> bool {
+        if !self.is_active() {
+            return true;
+        }
+        let (x, y) = self.window_relative(x, y);
+        let region = self.region_at(x, y);
+        self.emit(
+            "gesture-pinch",
+            PinchEvent {
+                magnification,
+                phase,
+                x,
+                y,
+                region,
+            },
+        )
+    }
+
+    /// Handle a trackpad swipe at global coordinates.
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    fn on_swipe(&mut self, direction: SwipeDirection, x: f64, y: f64) -> bool {
+        if !self.is_active() {
+            return true;
+        }
+        let (x, y) = self.window_relative(x, y);
+        let region = self.region_at(x, y);
+        self.emit(
+            "gesture-swipe",
+            SwipeEvent {
+                direction,
+                x,
+                y,
+                region,
+            },
+        )
+    }
+
+    /// Periodic wake-up while no input arrives.
+    fn tick(&mut self) -> bool {
+        if !self.is_active() {
+            return true;
+        }
+        let long_press = self.gestures.poll();
+        if !self.emit_gesture(long_press) || !self.update_capture() || !self.flush_batch(true) {
+            return false;
+        }
+        if self.pending_move {
+            return self.flush_move();
+        }
+        self.check_mouse_idle() && self.check_hot_corners()
+    }
+
+    /// Feed the shake detector with an unthrottled sample.
+    fn check_shake(&mut self, x: i32, y: i32) -> bool {
+        if !self.shake_config.enabled || !self.shake.update(&self.shake_config, x) {
+            return true;
+        }
+        let action = self.shake_config.action;
+        shake::run_action(&self.app, action);
+        self.emit("mouse-shake", ShakeEvent { x, y, action })
+    }
+
+    /// Toggle click-through when the cursor enters or leaves an interactive
+    /// region. No-op until the frontend registers regions.
+    fn update_capture(&mut self) -> bool {
+        if INTERACTIVE_REGIONS_DIRTY.swap(false, Ordering::Relaxed) {
+            self.captured = None;
+        }
+        let Some((x, y)) = self.last_pos else {
+            return true;
+        };
+        let inside = match INTERACTIVE_REGIONS.lock().ok().as_deref() {
+            Some(Some(regions)) => {
+                let (x, y) = (x as f64, y as f64);
+                regions
+                    .iter()
+                    .any(|r| x >= r.x && x < r.x + r.width && y >= r.y && y < r.y + r.height)
+            }
+            _ => return true,
+        };
+        if self.captured == Some(inside) {
+            return true;
+        }
+        self.captured = Some(inside);
+        if let Some(window) = self.app.get_webview_window("main") {
+            if let Err(e) = window.set_ignore_cursor_events(!inside) {
+                eprintln!("[hittest] set_ignore_cursor_events failed: {e}");
+            }
+        }
+        self.emit("cursor-capture-changed", CursorCaptureEvent { captured: inside })
+    }
+
+    /// Emit proximity transitions relative to the character anchor.
+    fn update_proximity(&mut self) -> bool {
+        let Some((ax, ay, radius)) = CHARACTER_ANCHOR.lock().ok().and_then(|a| *a) else {
+            self.proximity = None;
+            return true;
+        };
+        let (global_x, global_y) = self.last_global;
+        let distance = (global_x as f64 - ax).hypot(global_y as f64 - ay) - radius;
+        let band = if distance <= 0.0 {
+            Proximity::Inside
+        } else if distance <= self.hittest_config.near_distance_px {
+            Proximity::Near
+        } else {
+            Proximity::Far
+        };
+        let previous = self.proximity.replace(band);
+        if previous == Some(band) {
+            return true;
+        }
+        let previous = previous.unwrap_or(Proximity::Far);
+        let event = ProximityEvent {
+            near: band != Proximity::Far,
+            distance,
+            global_x,
+            global_y,
+        };
+
+        let mut ok = true;
+        if previous == Proximity::Inside {
+            ok &= self.emit("cursor-leave", event.clone());
+        }
+        if (previous == Proximity::Far) != (band == Proximity::Far) {
+            ok &= self.emit("cursor-near", event.clone());
+        }
+        if band == Proximity::Inside {
+            ok &= self.emit("cursor-enter", event);
+        }
+        ok
+    }
+
+    /// Emit a recognised pet gesture, if any.
+    fn emit_gesture(&mut self, gesture: Option<Gesture>) -> bool {
+        match gesture {
+            Some(Gesture::DoubleClick(event)) => self.emit("pet-double-click", event),
+            Some(Gesture::LongPress(event)) => self.emit("pet-long-press", event),
+            None => true,
+        }
+    }
+
+    /// Advance hot-corner dwell timers using the last known position.
+    fn check_hot_corners(&mut self) -> bool {
+        let Some((x, y)) = self.last_pos else {
+            return true;
+        };
+        let fired = self.hot_corners.update(
+            &self.hot_corner_config,
+            x,
+            y,
+            self.win_logical_w as i32,
+            self.win_logical_h as i32,
+        );
+        match fired {
+            Some(corner) => {
+                let action = self.hot_corner_config.rule(corner).action;
+                hotcorners::run_action(&self.app, action);
+                self.emit("hot-corner", HotCornerEvent { corner, action })
+            }
+            None => true,
+        }
+    }
+
+    /// Handle a button press or release at global coordinates.
+    ///
+    /// The first button pressed becomes the drag candidate; releasing it ends
+    /// the drag (emitting `"drag-end"` if the threshold was crossed).
+    fn on_button(&mut self, button: PointerButton, pressed: bool, x: f64, y: f64) -> bool {
+        if !self.is_active() {
+            self.drag = None;
+            return true;
+        }
+        let (x, y) = self.window_relative(x, y);
+        let event = if pressed { "mouse-down" } else { "mouse-up" };
+        if !self.emit(event, MouseButtonEvent { button, x, y }) {
+            return false;
+        }
+
+        if button == PointerButton::Left {
+            if pressed {
+                let regions = HIT_REGIONS.lock().map(|r| r.clone()).unwrap_or_default();
+                let gesture = self.gestures.on_press(&regions, self.double_click, x, y);
+                if !self.emit_gesture(gesture) {
+                    return false;
+                }
+            } else {
+                self.gestures.on_release();
+            }
+        }
+
+        if pressed {
+            if self.drag.is_none() {
+                self.drag = Some(DragState {
+                    button,
+                    start: (x, y),
+                    active: false,
+                });
+            }
+        } else if self.drag.as_ref().is_some_and(|d| d.button == button) {
+            if let Some(drag) = self.drag.take().filter(|d| d.active) {
+                let (start_x, start_y) = drag.start;
+                return self.emit(
+                    "drag-end",
+                    DragEvent {
+                        button,
+                        x,
+                        y,
+                        start_x,
+                        start_y,
+                        pen: self.pen,
+                    },
+                );
+            }
+        }
+        true
+    }
+
+    /// Emit an event, tracking consecutive failures.
+    fn emit<S: Serialize + Clone>(&mut self, event: &str, payload: S) -> bool {
+        if let Err(e) = self.app.emit(event, payload) {
+            self.consecutive_failures += 1;
+            if self.consecutive_failures == 1 || self.consecutive_failures.is_multiple_of(60) {
+                eprintln!("[hittest] emit failed ({}x): {e}", self.consecutive_failures);
+            }
+            if self.consecutive_failures >= MAX_CONSECUTIVE_FAILURES {
+                eprintln!("[hittest] too many consecutive emit failures, stopping");
+                return false;
+            }
+            return true;
+        }
+        self.consecutive_failures = 0;
+        true
+    }
+}
+
+// ---------- Entry point ----------
+
+/// Start a background thread that tracks the global mouse position and emits
+/// `"mouse-move"`, `"mouse-down"` and `"mouse-up"` events containing
+/// window-relative coordinates.
 ///
-/// The window position is cached and refreshed every ~60 frames (~1 second)
-/// to avoid per-frame Tauri IPC overhead.
+/// The thread first tries to install a native event tap (see
+/// [`native::run`]); if that fails it falls back to polling at ~60 Hz.
 ///
 /// # Shutdown
 ///
 /// Returns an `Arc<AtomicBool>` that the caller can set to `false` to
-/// gracefully stop the polling thread. This is wired to the "Quit" tray
-/// menu action in `lib.rs`.
+/// gracefully stop the thread. This is wired to the "Quit" tray menu action
+/// in `lib.rs`.
 ///
-/// The thread also stops after `MAX_CONSECUTIVE_FAILURES` (300, ~5 seconds)
-/// consecutive emit failures, which indicates the webview has been destroyed.
+/// The thread also stops after `MAX_CONSECUTIVE_FAILURES` (300) consecutive
+/// emit failures, which indicates the webview has been destroyed.
+///
+/// # Supervision
+///
+/// A supervisor thread joins the tracking thread. If it ends (or panics)
+/// while `running` is still set — emit failures, a hook removed by the OS —
+/// a `"hittest-stopped"` event is emitted and tracking can be resumed with
+/// [`restart_polling`].
 pub fn start_mouse_polling(app: AppHandle) -> Arc<AtomicBool> {
-    let running = Arc::new(AtomicBool::new(true));
-    let running_clone = running.clone();
+    let running = RUNNING
+        .get_or_init(|| Arc::new(AtomicBool::new(true)))
+        .clone();
+    TRACKER_ALIVE.store(true, Ordering::SeqCst);
+    spawn_supervised(app, running.clone());
+    running
+}
 
+/// App-wide shutdown flag returned by [`start_mouse_polling`].
+static RUNNING: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+
+/// Whether a tracking thread is currently running.
+static TRACKER_ALIVE: AtomicBool = AtomicBool::new(false);
+
+/// Payload of `"hittest-stopped"` events.
+#[derive(Clone, Serialize)]
+pub struct HitTestStopped {
+    /// `"exited"` or `"panicked"`.
+    pub reason: &'static str,
+}
+
+/// Run the tracking thread under a supervisor that reports its exit.
+///
+/// The caller must have set [`TRACKER_ALIVE`].
+fn spawn_supervised(app: AppHandle, running: Arc<AtomicBool>) {
     thread::spawn(move || {
-        // Cache window position (logical pixels) and scale factor.
-        // Updated every ~1 second to avoid per-frame overhead.
-        let mut win_logical_x: f64 = 0.0;
-        let mut win_logical_y: f64 = 0.0;
-        let mut scale_factor: f64 = 1.0;
-        let mut frame_count: u64 = 0;
-        let mut consecutive_failures: u32 = 0;
-        const MAX_CONSECUTIVE_FAILURES: u32 = 300; // ~5 seconds at 60Hz
-
-        while running_clone.load(Ordering::Relaxed) {
-            // Refresh window position every ~60 frames (~1 second)
-            if frame_count % 60 == 0 {
-                if let Some(window) = app.get_webview_window("main") {
-                    if let Ok(factor) = window.scale_factor() {
-                        scale_factor = factor;
-                    }
-                    if let Ok(pos) = window.outer_position() {
-                        win_logical_x = pos.x as f64 / scale_factor;
-                        win_logical_y = pos.y as f64 / scale_factor;
+        let worker = {
+            let app = app.clone();
+            let running = running.clone();
+            thread::spawn(move || track(&app, &running))
+        };
+        let reason = match worker.join() {
+            Ok(()) => "exited",
+            Err(_) => "panicked",
+        };
+        TRACKER_ALIVE.store(false, Ordering::SeqCst);
+        if !running.load(Ordering::Relaxed) {
+            return;
+        }
+        eprintln!("[hittest] tracking thread {reason}, hit-testing stopped");
+        let _ = app.emit("hittest-stopped", HitTestStopped { reason });
+    });
+}
+
+/// Body of the tracking thread: native backend, else polling.
+fn track(app: &AppHandle, running: &AtomicBool) {
+    if native::run(app, running) {
+        return;
+    }
+    eprintln!("[hittest] native event tap unavailable, falling back to polling");
+    if cfg!(any(target_os = "macos", target_os = "windows")) {
+        user_facing_error(
+            app,
+            "hittest",
+            "Precise mouse tracking is unavailable (Accessibility permission may be missing), so quick clicks on me can be missed.",
+            Severity::Info,
+        );
+    }
+    run_polling(app, running);
+}
+
+/// Whether the mouse tracking thread is running.
+pub fn is_tracking() -> bool {
+    TRACKER_ALIVE.load(Ordering::Relaxed)
+}
+
+/// IPC command: restart mouse tracking after a `"hittest-stopped"` event.
+///
+/// Returns `Ok(false)` if tracking is still running (nothing to restart).
+///
+/// # Errors
+///
+/// Returns `Err` if tracking was never started or the app is quitting.
+#[tauri::command]
+pub fn restart_polling(app: AppHandle) -> Result<bool, String> {
+    let running = RUNNING.get().ok_or("Mouse tracking was never started")?;
+    if !running.load(Ordering::Relaxed) {
+        return Err("The app is shutting down".to_string());
+    }
+    if TRACKER_ALIVE.swap(true, Ordering::SeqCst) {
+        return Ok(false);
+    }
+    eprintln!("[hittest] restarting mouse tracking");
+    spawn_supervised(app, running.clone());
+    Ok(true)
+}
+
+/// Polling fallback: sample the cursor position every [`poll_interval`].
+///
+/// Button transitions are synthesized by diffing [`pressed_buttons`] between
+/// samples, so clicks shorter than one poll interval may be missed.
+fn run_polling(app: &AppHandle, running: &AtomicBool) {
+    let mut sink = MouseSink::new(app.clone());
+    let mut prev_buttons: u8 = 0;
+
+    'poll: while running.load(Ordering::Relaxed) {
+        if !sink.is_active() {
+            prev_buttons = 0;
+            thread::sleep(TICK_INTERVAL);
+            continue;
+        }
+        match Mouse::get_mouse_position() {
+            Mouse::Position { x, y } => {
+                let (x, y) = (x as f64, y as f64);
+                if !sink.on_move(x, y) {
+                    break;
+                }
+
+                let buttons = pressed_buttons();
+                for button in [PointerButton::Left, PointerButton::Right, PointerButton::Middle] {
+                    if (buttons ^ prev_buttons) & button.mask() != 0
+                        && !sink.on_button(button, buttons & button.mask() != 0, x, y)
+                    {
+                        break 'poll;
                     }
                 }
+                prev_buttons = buttons;
+            }
+            Mouse::Error => {
+                // Silently skip frames where position cannot be read
             }
-            frame_count = frame_count.wrapping_add(1);
+        }
+        thread::sleep(poll_interval());
+    }
+}
 
-            match Mouse::get_mouse_position() {
-                Mouse::Position { x, y } => {
-                    // Mouse::get_mouse_position() returns global screen coords
-                    // in logical points (macOS CGEvent coordinate space).
-                    // Convert to window-relative by subtracting window position.
-                    let rel_x = x as f64 - win_logical_x;
-                    let rel_y = y as f64 - win_logical_y;
+/// IPC command: register the character's hit regions for gesture recognition.
+///
+/// Rects are window-relative logical pixels, the same space as
+/// `"mouse-move"`. Gesture events report the index of the region they hit.
+/// Pass an empty list to disable `"pet-double-click"` / `"pet-long-press"`.
+#[tauri::command]
+pub fn set_hit_regions(regions: Vec<Rect>) -> Result<(), String> {
+    let mut current = HIT_REGIONS.lock().map_err(|e| e.to_string())?;
+    *current = regions;
+    Ok(())
+}
 
-                    let pos = MousePosition {
-                        x: rel_x as i32,
-                        y: rel_y as i32,
-                    };
-                    if let Err(e) = app.emit("mouse-move", pos) {
-                        consecutive_failures += 1;
-                        if consecutive_failures == 1 || consecutive_failures % 60 == 0 {
-                            eprintln!("[hittest] emit failed ({}x): {e}", consecutive_failures);
+/// IPC command: let the hit-test thread manage click-through.
+///
+/// While the cursor is inside any of `regions` (window-relative logical
+/// pixels) the window captures mouse events; elsewhere they pass through to
+/// the desktop. Transitions are reported as `"cursor-capture-changed"`.
+/// Pass `None` to hand control back to the frontend.
+#[tauri::command]
+pub fn set_interactive_regions(regions: Option<Vec<Rect>>) -> Result<(), String> {
+    let mut current = INTERACTIVE_REGIONS.lock().map_err(|e| e.to_string())?;
+    *current = regions;
+    INTERACTIVE_REGIONS_DIRTY.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// IPC command: register the character's screen-space bounding circle.
+///
+/// The hit-test thread then emits `"cursor-near"` (entering or leaving
+/// [`HitTestConfig::near_distance_px`] of the circle), `"cursor-enter"` and
+/// `"cursor-leave"`. In low-power mode raw `"mouse-move"` events are
+/// suppressed while an anchor is set. Pass a radius of `0` or less to clear.
+#[tauri::command]
+pub fn set_character_anchor(x: f64, y: f64, radius: f64) -> Result<(), String> {
+    let mut anchor = CHARACTER_ANCHOR.lock().map_err(|e| e.to_string())?;
+    *anchor = (radius > 0.0).then_some((x, y, radius));
+    Ok(())
+}
+
+/// Enter or leave low-power mode (driven by [`crate::power`]).
+pub fn set_low_power_mode(enabled: bool) {
+    LOW_POWER.store(enabled, Ordering::Relaxed);
+}
+
+/// IPC command: slow mouse sampling to at least `interval_ms` until the app
+/// restarts; `0` removes the override.
+///
+/// It can only lengthen the interval: [`current_poll_interval_ms`] uses the
+/// slowest of this, [`HitTestConfig::poll_interval_ms`] and the low-power
+/// floor. Returns the interval now in effect.
+#[tauri::command]
+pub fn set_poll_interval_ms(interval_ms: u64) -> u64 {
+    let interval_ms = if interval_ms == 0 {
+        0
+    } else {
+        interval_ms.clamp(MIN_POLL_INTERVAL_MS, MAX_POLL_INTERVAL_MS)
+    };
+    POLL_INTERVAL_OVERRIDE_MS.store(interval_ms, Ordering::Relaxed);
+    current_poll_interval_ms()
+}
+
+/// IPC command: pause or resume mouse tracking.
+///
+/// While paused no mouse events are emitted and the polling fallback only
+/// wakes every 100 ms to check the flag.
+#[tauri::command]
+pub fn set_polling_enabled(enabled: bool) {
+    POLLING_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Read the currently pressed mouse buttons from the OS as a
+/// [`PointerButton::mask`] bitmask. Returns `0` on unsupported platforms.
+fn pressed_buttons() -> u8 {
+    #[cfg(target_os = "macos")]
+    {
+        /// `kCGEventSourceStateCombinedSessionState`
+        const COMBINED_SESSION_STATE: i32 = 0;
+
+        #[link(name = "CoreGraphics", kind = "framework")]
+        extern "C" {
+            fn CGEventSourceButtonState(state_id: i32, button: u32) -> bool;
+        }
+
+        let mut mask = 0;
+        // SAFETY: CGEventSourceButtonState is a side-effect-free query.
+        // Button numbers: 0 = left, 1 = right, 2 = center.
+        unsafe {
+            if CGEventSourceButtonState(COMBINED_SESSION_STATE, 0) {
+                mask |= PointerButton::Left.mask();
+            }
+            if CGEventSourceButtonState(COMBINED_SESSION_STATE, 1) {
+                mask |= PointerButton::Right.mask();
+            }
+            if CGEventSourceButtonState(COMBINED_SESSION_STATE, 2) {
+                mask |= PointerButton::Middle.mask();
+            }
+        }
+        mask
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::UI::Input::KeyboardAndMouse::{
+            GetAsyncKeyState, VK_LBUTTON, VK_MBUTTON, VK_RBUTTON,
+        };
+
+        // The high bit of GetAsyncKeyState is set while the key is down.
+        let down = |vk: u16| unsafe { GetAsyncKeyState(vk as i32) } as u16 & 0x8000 != 0;
+        let mut mask = 0;
+        if down(VK_LBUTTON.0) {
+            mask |= PointerButton::Left.mask();
+        }
+        if down(VK_RBUTTON.0) {
+            mask |= PointerButton::Right.mask();
+        }
+        if down(VK_MBUTTON.0) {
+            mask |= PointerButton::Middle.mask();
+        }
+        mask
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        0
+    }
+}
+
+/// Shape of the system cursor, as reported by [`get_cursor_kind`].
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+#[serde(rename_all = "camelCase")]
+pub enum CursorKind {
+    Arrow,
+    /// I-beam: the user is editing text.
+    Text,
+    /// Pointing hand over a link or button.
+    Pointer,
+    ResizeHorizontal,
+    ResizeVertical,
+    ResizeDiagonal,
+    Move,
+    Crosshair,
+    NotAllowed,
+    /// Wait / app-starting cursor (Windows only; the macOS spinner is not a
+    /// system cursor).
+    Busy,
+    /// The cursor is hidden (e.g. while typing in some apps).
+    Hidden,
+    /// A custom or unrecognised cursor, or an unsupported platform.
+    Unknown,
+}
+
+/// IPC command: the shape of the current system cursor.
+///
+/// Meant to be polled occasionally (it is not part of `"mouse-move"`):
+/// - **macOS** — compares `+[NSCursor currentSystemCursor]`'s image with the
+///   standard `NSCursor` shapes.
+/// - **Windows** — compares `GetCursorInfo`'s handle with the shared
+///   `IDC_*` system cursors.
+/// - **Other platforms** — always [`CursorKind::Unknown`].
+#[tauri::command]
+pub fn get_cursor_kind() -> CursorKind {
+    #[cfg(target_os = "macos")]
+    {
+        use cocoa::base::{id, nil};
+        use objc::runtime::{Class, NO};
+        use objc::{msg_send, sel, sel_impl};
+
+        let Some(ns_cursor) = Class::get("NSCursor") else {
+            return CursorKind::Unknown;
+        };
+        // SAFETY: class getters returning autoreleased NSCursor / NSImage /
+        // NSData objects that are only compared, never retained.
+        unsafe {
+            let current: id = msg_send![ns_cursor, currentSystemCursor];
+            if current == nil {
+                return CursorKind::Unknown;
+            }
+            let image: id = msg_send![current, image];
+            let data: id = msg_send![image, TIFFRepresentation];
+            if data == nil {
+                return CursorKind::Unknown;
+            }
+            let known: [(id, CursorKind); 9] = [
+                (msg_send![ns_cursor, arrowCursor], CursorKind::Arrow),
+                (msg_send![ns_cursor, IBeamCursor], CursorKind::Text),
+                (msg_send![ns_cursor, IBeamCursorForVerticalLayout], CursorKind::Text),
+                (msg_send![ns_cursor, pointingHandCursor], CursorKind::Pointer),
+                (msg_send![ns_cursor, resizeLeftRightCursor], CursorKind::ResizeHorizontal),
+                (msg_send![ns_cursor, resizeUpDownCursor], CursorKind::ResizeVertical),
+                (msg_send![ns_cursor, openHandCursor], CursorKind::Move),
+                (msg_send![ns_cursor, crosshairCursor], CursorKind::Crosshair),
+                (msg_send![ns_cursor, operationNotAllowedCursor], CursorKind::NotAllowed),
+            ];
+            for (cursor, kind) in known {
+                let image: id = msg_send![cursor, image];
+                let other: id = msg_send![image, TIFFRepresentation];
+                let same: objc::runtime::BOOL = msg_send![data, isEqual: other];
+                if same != NO {
+                    return kind;
+                }
+            }
+        }
+        CursorKind::Unknown
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows::core::PCWSTR;
+        use windows::Win32::UI::WindowsAndMessaging::{
+            GetCursorInfo, LoadCursorW, CURSORINFO, CURSOR_SHOWING, IDC_APPSTARTING, IDC_ARROW,
+            IDC_CROSS, IDC_HAND, IDC_IBEAM, IDC_NO, IDC_SIZEALL, IDC_SIZENESW, IDC_SIZENS,
+            IDC_SIZENWSE, IDC_SIZEWE, IDC_WAIT,
+        };
+
+        let mut info = CURSORINFO {
+            cbSize: std::mem::size_of::<CURSORINFO>() as u32,
+            ..Default::default()
+        };
+        // SAFETY: `info` is a properly sized CURSORINFO out-parameter.
+        if unsafe { GetCursorInfo(&mut info) }.is_err() {
+            return CursorKind::Unknown;
+        }
+        if info.flags.0 & CURSOR_SHOWING.0 == 0 {
+            return CursorKind::Hidden;
+        }
+        let known: [(PCWSTR, CursorKind); 12] = [
+            (IDC_ARROW, CursorKind::Arrow),
+            (IDC_IBEAM, CursorKind::Text),
+            (IDC_HAND, CursorKind::Pointer),
+            (IDC_SIZEWE, CursorKind::ResizeHorizontal),
+            (IDC_SIZENS, CursorKind::ResizeVertical),
+            (IDC_SIZENWSE, CursorKind::ResizeDiagonal),
+            (IDC_SIZENESW, CursorKind::ResizeDiagonal),
+            (IDC_SIZEALL, CursorKind::Move),
+            (IDC_CROSS, CursorKind::Crosshair),
+            (IDC_NO, CursorKind::NotAllowed),
+            (IDC_WAIT, CursorKind::Busy),
+            (IDC_APPSTARTING, CursorKind::Busy),
+        ];
+        for (name, kind) in known {
+            // SAFETY: loading a shared system cursor returns the same handle
+            // the OS uses, and needs no cleanup.
+            if let Ok(cursor) = unsafe { LoadCursorW(None, name) } {
+                if cursor == info.hCursor {
+                    return kind;
+                }
+            }
+        }
+        CursorKind::Unknown
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        CursorKind::Unknown
+    }
+}
+
+// ---------- Native backends ----------
+
+/// macOS backend: a listen-only `CGEventTap` on the session event stream.
+#[cfg(target_os = "macos")]
+mod native {
+    use super::{GesturePhase, MouseSink, PenState, PointerButton, SwipeDirection, TICK_INTERVAL};
+    use core_foundation::base::TCFType;
+    use core_foundation::mach_port::{CFMachPort, CFMachPortRef};
+    use core_foundation::runloop::{kCFRunLoopCommonModes, kCFRunLoopDefaultMode, CFRunLoop};
+    use core_graphics::geometry::CGPoint;
+    use core_graphics::event::{
+        CGEvent, CGEventTap, CGEventTapLocation, CGEventTapOptions, CGEventTapPlacement,
+        CGEventType, EventField,
+    };
+    use std::cell::{Cell, RefCell};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use tauri::AppHandle;
+
+    /// `kCGEventMouseSubtypeTabletPoint`: a mouse event generated by a tablet.
+    const SUBTYPE_TABLET_POINT: i64 = 1;
+
+    /// Pressure and tilt of a tablet-generated mouse event.
+    fn pen_state(event: &CGEvent) -> Option<PenState> {
+        if event.get_integer_value_field(EventField::MOUSE_EVENT_SUB_TYPE) != SUBTYPE_TABLET_POINT {
+            return None;
+        }
+        Some(PenState {
+            pressure: Some(event.get_double_value_field(EventField::MOUSE_EVENT_PRESSURE)),
+            tilt_x: Some(event.get_double_value_field(EventField::TABLET_EVENT_TILT_X)),
+            tilt_y: Some(event.get_double_value_field(EventField::TABLET_EVENT_TILT_Y)),
+        })
+    }
+
+    /// Install the tap and pump the current thread's run loop until `running`
+    /// is cleared, ticking the sink between run-loop slices.
+    ///
+    /// Returns `false` without blocking if the tap could not be created.
+    /// The OS may disable a tap that is slow to respond; it is re-enabled on
+    /// the next run-loop slice.
+    pub fn run(app: &AppHandle, running: &AtomicBool) -> bool {
+        let sink = RefCell::new(MouseSink::new(app.clone()));
+        let tap_disabled = Cell::new(false);
+        // Set when the sink gives up; ends this thread only, so the
+        // supervisor can report it.
+        let stop = Cell::new(false);
+
+        let tap = CGEventTap::new(
+            CGEventTapLocation::Session,
+            CGEventTapPlacement::HeadInsertEventTap,
+            CGEventTapOptions::ListenOnly,
+            vec![
+                CGEventType::MouseMoved,
+                CGEventType::LeftMouseDragged,
+                CGEventType::RightMouseDragged,
+                CGEventType::OtherMouseDragged,
+                CGEventType::LeftMouseDown,
+                CGEventType::LeftMouseUp,
+                CGEventType::RightMouseDown,
+                CGEventType::RightMouseUp,
+                CGEventType::OtherMouseDown,
+                CGEventType::OtherMouseUp,
+                CGEventType::ScrollWheel,
+            ],
+            |_proxy, event_type, event| {
+                let p = event.location();
+                let mut sink = sink.borrow_mut();
+                if !matches!(event_type, CGEventType::ScrollWheel) {
+                    sink.set_pen(pen_state(event));
+                }
+                let ok = match event_type {
+                    CGEventType::TapDisabledByTimeout | CGEventType::TapDisabledByUserInput => {
+                        tap_disabled.set(true);
+                        true
+                    }
+                    CGEventType::LeftMouseDown => sink.on_button(PointerButton::Left, true, p.x, p.y),
+                    CGEventType::LeftMouseUp => sink.on_button(PointerButton::Left, false, p.x, p.y),
+                    CGEventType::RightMouseDown => sink.on_button(PointerButton::Right, true, p.x, p.y),
+                    CGEventType::RightMouseUp => sink.on_button(PointerButton::Right, false, p.x, p.y),
+                    CGEventType::OtherMouseDown | CGEventType::OtherMouseUp => {
+                        // Only the middle button (2) is reported; extra side
+                        // buttons are ignored.
+                        let number =
+                            event.get_integer_value_field(EventField::MOUSE_EVENT_BUTTON_NUMBER);
+                        if number == 2 {
+                            let pressed = matches!(event_type, CGEventType::OtherMouseDown);
+                            sink.on_button(PointerButton::Middle, pressed, p.x, p.y)
+                        } else {
+                            true
                         }
-                        if consecutive_failures >= MAX_CONSECUTIVE_FAILURES {
-                            eprintln!("[hittest] too many consecutive emit failures, stopping");
-                            break;
+                    }
+                    CGEventType::ScrollWheel => {
+                        // Axis 1 is vertical, axis 2 horizontal; positive means
+                        // up/left, so negate to match the DOM convention.
+                        let dy = event.get_integer_value_field(
+                            EventField::SCROLL_WHEEL_EVENT_POINT_DELTA_AXIS_1,
+                        );
+                        let dx = event.get_integer_value_field(
+                            EventField::SCROLL_WHEEL_EVENT_POINT_DELTA_AXIS_2,
+                        );
+                        sink.on_scroll(-dx as f64, -dy as f64, p.x, p.y)
+                    }
+                    _ => sink.on_move(p.x, p.y),
+                };
+                if !ok {
+                    stop.set(true);
+                }
+                None
+            },
+        );
+
+        let tap = match tap {
+            Ok(t) => t,
+            Err(()) => return false,
+        };
+        let source = match tap.mach_port.create_runloop_source(0) {
+            Ok(s) => s,
+            Err(()) => return false,
+        };
+
+        let run_loop = CFRunLoop::get_current();
+        // SAFETY: kCFRunLoopCommonModes / kCFRunLoopDefaultMode are immutable
+        // CFString constants exported by CoreFoundation.
+        unsafe { run_loop.add_source(&source, kCFRunLoopCommonModes) };
+        tap.enable();
+        println!("[hittest] CGEventTap installed");
+
+        // SAFETY: `sink` outlives the gesture tap, which is dropped before
+        // this function returns.
+        let gesture_tap = unsafe { gesture_tap::GestureTap::install(&sink) };
+        if let Some(g) = &gesture_tap {
+            unsafe { run_loop.add_source(&g.source, kCFRunLoopCommonModes) };
+        }
+
+        while running.load(Ordering::Relaxed) && !stop.get() {
+            CFRunLoop::run_in_mode(unsafe { kCFRunLoopDefaultMode }, TICK_INTERVAL, false);
+            if !sink.borrow_mut().tick() {
+                break;
+            }
+            if tap_disabled.replace(false) {
+                eprintln!("[hittest] CGEventTap was disabled by the OS, re-enabling");
+                tap.enable();
+            }
+        }
+
+        unsafe { run_loop.remove_source(&source, kCFRunLoopCommonModes) };
+        if let Some(g) = gesture_tap {
+            unsafe { run_loop.remove_source(&g.source, kCFRunLoopCommonModes) };
+        }
+        true
+    }
+
+    /// Trackpad gesture tap.
+    ///
+    /// The `core-graphics` event-type enum has no gesture variants, so this
+    /// second tap is created through raw FFI with the NSEvent gesture masks
+    /// and decoded via `+[NSEvent eventWithCGEvent:]`.
+    mod gesture_tap {
+        use super::*;
+        use core_foundation::runloop::CFRunLoopSource;
+        use objc::runtime::{Class, Object};
+        use objc::{msg_send, sel, sel_impl};
+        use std::ffi::c_void;
+
+        /// `NSEventTypeGesture` / `NSEventTypeMagnify` / `NSEventTypeSwipe`.
+        const EVENT_GESTURE: u32 = 29;
+        const EVENT_MAGNIFY: u64 = 30;
+        const EVENT_SWIPE: u64 = 31;
+
+        /// `kCGEventTapDisabledByTimeout` / `kCGEventTapDisabledByUserInput`.
+        const TAP_DISABLED_BY_TIMEOUT: u32 = 0xFFFF_FFFE;
+        const TAP_DISABLED_BY_USER_INPUT: u32 = 0xFFFF_FFFF;
+
+        /// `NSEventPhase` bits.
+        const PHASE_BEGAN: u64 = 1;
+        const PHASE_ENDED: u64 = 8;
+        const PHASE_CANCELLED: u64 = 16;
+
+        type TapCallback =
+            extern "C" fn(*mut c_void, u32, *mut c_void, *mut c_void) -> *mut c_void;
+
+        #[link(name = "CoreGraphics", kind = "framework")]
+        extern "C" {
+            fn CGEventTapCreate(
+                tap: u32,
+                place: u32,
+                options: u32,
+                events_of_interest: u64,
+                callback: TapCallback,
+                user_info: *mut c_void,
+            ) -> CFMachPortRef;
+            fn CGEventTapEnable(tap: CFMachPortRef, enable: bool);
+            fn CGEventGetLocation(event: *mut c_void) -> CGPoint;
+        }
+
+        #[link(name = "CoreFoundation", kind = "framework")]
+        extern "C" {
+            fn CFMachPortInvalidate(port: CFMachPortRef);
+        }
+
+        /// `kCGSessionEventTap`, `kCGHeadInsertEventTap`, `kCGEventTapOptionListenOnly`.
+        const SESSION_TAP: u32 = 1;
+        const HEAD_INSERT: u32 = 0;
+        const LISTEN_ONLY: u32 = 1;
+
+        struct Context {
+            sink: *const RefCell<MouseSink>,
+            port: CFMachPortRef,
+        }
+
+        extern "C" fn callback(
+            _proxy: *mut c_void,
+            event_type: u32,
+            event: *mut c_void,
+            user_info: *mut c_void,
+        ) -> *mut c_void {
+            // SAFETY: `user_info` is the leaked `Context` from `install`,
+            // whose sink outlives the tap.
+            let ctx = unsafe { &*(user_info as *const Context) };
+            if event_type == TAP_DISABLED_BY_TIMEOUT || event_type == TAP_DISABLED_BY_USER_INPUT {
+                unsafe { CGEventTapEnable(ctx.port, true) };
+                return event;
+            }
+            let Ok(mut sink) = (unsafe { &*ctx.sink }).try_borrow_mut() else {
+                return event;
+            };
+
+            // SAFETY: NSEvent class methods/properties on a valid CGEventRef;
+            // the returned NSEvent is autoreleased and not retained.
+            unsafe {
+                let Some(class) = Class::get("NSEvent") else {
+                    return event;
+                };
+                let ns_event: *mut Object = msg_send![class, eventWithCGEvent: event];
+                if ns_event.is_null() {
+                    return event;
+                }
+                let p = CGEventGetLocation(event);
+                let kind: u64 = msg_send![ns_event, type];
+                match kind {
+                    EVENT_MAGNIFY => {
+                        let magnification: f64 = msg_send![ns_event, magnification];
+                        let phase_bits: u64 = msg_send![ns_event, phase];
+                        let phase = if phase_bits & PHASE_BEGAN != 0 {
+                            GesturePhase::Began
+                        } else if phase_bits & PHASE_ENDED != 0 {
+                            GesturePhase::Ended
+                        } else if phase_bits & PHASE_CANCELLED != 0 {
+                            GesturePhase::Cancelled
+                        } else {
+                            GesturePhase::Changed
+                        };
+                        sink.on_pinch(magnification, phase, p.x, p.y);
+                    }
+                    EVENT_SWIPE => {
+                        // deltaX is positive for a swipe to the left.
+                        let dx: f64 = msg_send![ns_event, deltaX];
+                        let dy: f64 = msg_send![ns_event, deltaY];
+                        let direction = if dx.abs() >= dy.abs() {
+                            if dx > 0.0 {
+                                SwipeDirection::Left
+                            } else {
+                                SwipeDirection::Right
+                            }
+                        } else if dy > 0.0 {
+                            SwipeDirection::Up
+                        } else {
+                            SwipeDirection::Down
+                        };
+                        if dx != 0.0 || dy != 0.0 {
+                            sink.on_swipe(direction, p.x, p.y);
                         }
-                        continue;
                     }
-                    consecutive_failures = 0;
+                    _ => {}
                 }
-                Mouse::Error => {
-                    // Silently skip frames where position cannot be read
+            }
+            event
+        }
+
+        /// An installed gesture tap; disabled and freed on drop.
+        pub struct GestureTap {
+            port: CFMachPort,
+            pub source: CFRunLoopSource,
+            ctx: *mut Context,
+        }
+
+        impl GestureTap {
+            /// Create the gesture tap. Returns `None` if the OS refuses it.
+            ///
+            /// # Safety
+            ///
+            /// `sink` must outlive the returned tap.
+            pub unsafe fn install(sink: &RefCell<MouseSink>) -> Option<Self> {
+                let mask =
+                    (1u64 << EVENT_GESTURE) | (1u64 << EVENT_MAGNIFY) | (1u64 << EVENT_SWIPE);
+                let ctx = Box::into_raw(Box::new(Context {
+                    sink,
+                    port: std::ptr::null_mut(),
+                }));
+                let port = CGEventTapCreate(
+                    SESSION_TAP,
+                    HEAD_INSERT,
+                    LISTEN_ONLY,
+                    mask,
+                    callback,
+                    ctx as *mut c_void,
+                );
+                if port.is_null() {
+                    drop(Box::from_raw(ctx));
+                    eprintln!("[hittest] Trackpad gesture tap unavailable");
+                    return None;
                 }
+                (*ctx).port = port;
+                let port = CFMachPort::wrap_under_create_rule(port);
+                let Ok(source) = port.create_runloop_source(0) else {
+                    CFMachPortInvalidate(port.as_concrete_TypeRef());
+                    drop(Box::from_raw(ctx));
+                    return None;
+                };
+                CGEventTapEnable(port.as_concrete_TypeRef(), true);
+                Some(Self { port, source, ctx })
             }
-            thread::sleep(Duration::from_millis(16)); // ~60Hz
         }
-    });
 
-    running
+        impl Drop for GestureTap {
+            fn drop(&mut self) {
+                // SAFETY: the tap is disabled and invalidated before its
+                // context is freed, so the callback cannot observe it.
+                unsafe {
+                    CGEventTapEnable(self.port.as_concrete_TypeRef(), false);
+                    CFMachPortInvalidate(self.port.as_concrete_TypeRef());
+                    drop(Box::from_raw(self.ctx));
+                }
+            }
+        }
+    }
+}
+
+/// Windows backend: a `WH_MOUSE_LL` low-level mouse hook.
+///
+/// Low-level hooks are called on the installing thread while it pumps
+/// messages, so the sink lives in a thread-local.
+#[cfg(target_os = "windows")]
+mod native {
+    use super::{MouseSink, PenState, PointerButton, TICK_INTERVAL};
+    use std::cell::{Cell, RefCell};
+    use std::collections::HashMap;
+    use std::ffi::c_void;
+    use std::mem::{offset_of, size_of};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use tauri::AppHandle;
+    use windows::core::w;
+    use windows::Win32::Devices::HumanInterfaceDevice::{
+        HidP_GetSpecificValueCaps, HidP_GetUsageValue, HidP_Input, HIDP_STATUS_SUCCESS,
+        HIDP_VALUE_CAPS, PHIDP_PREPARSED_DATA,
+    };
+    use windows::Win32::Foundation::{HANDLE, HMODULE, HWND, LPARAM, LRESULT, WPARAM};
+    use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows::Win32::UI::Input::{
+        GetRawInputData, GetRawInputDeviceInfoW, RegisterRawInputDevices, HRAWINPUT, RAWHID,
+        RAWINPUT, RAWINPUTDEVICE, RAWINPUTHEADER, RIDEV_INPUTSINK, RIDEV_REMOVE,
+        RIDI_PREPARSEDDATA, RID_INPUT, RIM_TYPEHID,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CallNextHookEx, CreateWindowExW, DefWindowProcW, DestroyWindow, DispatchMessageW,
+        GetMessageW, KillTimer, RegisterClassW, SetTimer, SetWindowsHookExW, TranslateMessage,
+        UnhookWindowsHookEx, HWND_MESSAGE, MSG, MSLLHOOKSTRUCT, WHEEL_DELTA, WH_MOUSE_LL,
+        WINDOW_EX_STYLE, WINDOW_STYLE, WM_INPUT, WM_LBUTTONDOWN, WM_LBUTTONUP, WM_MBUTTONDOWN,
+        WM_MBUTTONUP, WM_MOUSEHWHEEL, WM_MOUSEMOVE, WM_MOUSEWHEEL, WM_RBUTTONDOWN, WM_RBUTTONUP,
+        WNDCLASSW,
+    };
+
+    /// Approximate pixels per wheel detent, matching browser defaults.
+    const PIXELS_PER_DETENT: f64 = 100.0;
+
+    /// `MI_WP_SIGNATURE`: `dwExtraInfo` of mouse input synthesized from pen
+    /// or touch; bit `0x80` is set for touch.
+    const PEN_OR_TOUCH_SIGNATURE: usize = 0xFF51_5700;
+    const SIGNATURE_MASK: usize = 0xFFFF_FF00;
+    const TOUCH_FLAG: usize = 0x80;
+
+    /// HID digitizer usage page, the pen collection and the values read
+    /// from its reports.
+    const DIGITIZER_PAGE: u16 = 0x0D;
+    const PEN_USAGE: u16 = 0x02;
+    const TIP_PRESSURE: u16 = 0x30;
+    const X_TILT: u16 = 0x3D;
+    const Y_TILT: u16 = 0x3E;
+
+    const NO_PEN: PenState = PenState {
+        pressure: None,
+        tilt_x: None,
+        tilt_y: None,
+    };
+
+    /// The hook only tells pen input apart; pressure and tilt come from the
+    /// latest digitizer report seen by [`read_pen`].
+    fn pen_state(extra_info: usize) -> Option<PenState> {
+        let pen = extra_info & SIGNATURE_MASK == PEN_OR_TOUCH_SIGNATURE && extra_info & TOUCH_FLAG == 0;
+        pen.then(|| PEN.with(Cell::get))
+    }
+
+    thread_local! {
+        static SINK: RefCell<Option<MouseSink>> = const { RefCell::new(None) };
+        static STOP: Cell<bool> = const { Cell::new(false) };
+        static PEN: Cell<PenState> = const { Cell::new(NO_PEN) };
+        /// Report layouts by device handle; `None` for devices without one.
+        static DIGITIZERS: RefCell<HashMap<isize, Option<Digitizer>>> =
+            RefCell::new(HashMap::new());
+    }
+
+    /// Logical range of a report value.
+    #[derive(Clone, Copy)]
+    struct Axis {
+        min: i32,
+        max: i32,
+        bits: u16,
+    }
+
+    impl Axis {
+        /// `raw`, sign-extended if the range is signed, as 0.0–1.0 of the
+        /// range.
+        fn fraction(self, raw: u32) -> f64 {
+            let value = if self.min < 0 && (1..32).contains(&self.bits) {
+                let shift = 32 - u32::from(self.bits);
+                ((raw << shift) as i32 >> shift) as i64
+            } else {
+                raw as i64
+            };
+            let span = (self.max as i64 - self.min as i64) as f64;
+            ((value - self.min as i64) as f64 / span).clamp(0.0, 1.0)
+        }
+    }
+
+    /// A pen digitizer's preparsed report descriptor.
+    struct Digitizer {
+        /// `u64`s to keep the opaque structure aligned.
+        preparsed: Vec<u64>,
+        pressure: Option<Axis>,
+        tilt_x: Option<Axis>,
+        tilt_y: Option<Axis>,
+    }
+
+    impl Digitizer {
+        /// SAFETY: `device` must be a raw input device handle.
+        unsafe fn open(device: HANDLE) -> Option<Self> {
+            let mut size = 0u32;
+            GetRawInputDeviceInfoW(Some(device), RIDI_PREPARSEDDATA, None, &mut size);
+            if size == 0 {
+                return None;
+            }
+            let mut preparsed = vec![0u64; (size as usize).div_ceil(8)];
+            let read = GetRawInputDeviceInfoW(
+                Some(device),
+                RIDI_PREPARSEDDATA,
+                Some(preparsed.as_mut_ptr().cast()),
+                &mut size,
+            );
+            if read == u32::MAX {
+                return None;
+            }
+            let data = PHIDP_PREPARSED_DATA(preparsed.as_ptr() as isize);
+            let axis = |usage| {
+                let mut caps = [HIDP_VALUE_CAPS::default(); 4];
+                let mut count = caps.len() as u16;
+                let status = HidP_GetSpecificValueCaps(
+                    HidP_Input,
+                    Some(DIGITIZER_PAGE),
+                    None,
+                    Some(usage),
+                    caps.as_mut_ptr(),
+                    &mut count,
+                    data,
+                );
+                let caps = caps[0];
+                (status == HIDP_STATUS_SUCCESS && count > 0 && caps.LogicalMax > caps.LogicalMin)
+                    .then_some(Axis {
+                        min: caps.LogicalMin,
+                        max: caps.LogicalMax,
+                        bits: caps.BitSize,
+                    })
+            };
+            let (pressure, tilt_x, tilt_y) = (axis(TIP_PRESSURE), axis(X_TILT), axis(Y_TILT));
+            Some(Self {
+                preparsed,
+                pressure,
+                tilt_x,
+                tilt_y,
+            })
+        }
+
+        /// Pressure (0–1) and tilt (−1–1) in `report`, where present.
+        fn read(&self, report: &[u8]) -> PenState {
+            let data = PHIDP_PREPARSED_DATA(self.preparsed.as_ptr() as isize);
+            let value = |usage, axis: Option<Axis>| {
+                let axis = axis?;
+                let mut raw = 0u32;
+                // SAFETY: `data` is this device's preparsed data and `report`
+                // one of its input reports.
+                let status = unsafe {
+                    HidP_GetUsageValue(
+                        HidP_Input,
+                        DIGITIZER_PAGE,
+                        None,
+                        usage,
+                        &mut raw,
+                        data,
+                        report,
+                    )
+                };
+                (status == HIDP_STATUS_SUCCESS).then(|| axis.fraction(raw))
+            };
+            PenState {
+                pressure: value(TIP_PRESSURE, self.pressure),
+                tilt_x: value(X_TILT, self.tilt_x).map(|t| t * 2.0 - 1.0),
+                tilt_y: value(Y_TILT, self.tilt_y).map(|t| t * 2.0 - 1.0),
+            }
+        }
+    }
+
+    /// Keep the pen state from a `WM_INPUT` of the digitizer.
+    ///
+    /// SAFETY: `lparam` must be the `HRAWINPUT` of a `WM_INPUT` message.
+    unsafe fn read_pen(lparam: LPARAM) {
+        let input = HRAWINPUT(lparam.0 as *mut c_void);
+        let header = size_of::<RAWINPUTHEADER>() as u32;
+        let mut size = 0u32;
+        GetRawInputData(input, RID_INPUT, None, &mut size, header);
+        let mut buffer = vec![0u64; (size as usize).div_ceil(8)];
+        let read = GetRawInputData(
+            input,
+            RID_INPUT,
+            Some(buffer.as_mut_ptr().cast()),
+            &mut size,
+            header,
+        );
+        let offset = offset_of!(RAWINPUT, data) + offset_of!(RAWHID, bRawData);
+        if read == u32::MAX || (read as usize) < offset {
+            return;
+        }
+        let raw = &*(buffer.as_ptr() as *const RAWINPUT);
+        if raw.header.dwType != RIM_TYPEHID.0 {
+            return;
+        }
+        let hid = &raw.data.hid;
+        let (report_size, count) = (hid.dwSizeHid as usize, hid.dwCount as usize);
+        let bytes = std::slice::from_raw_parts(buffer.as_ptr().cast::<u8>(), read as usize);
+        let Some(reports) = bytes.get(offset..offset + report_size * count) else {
+            return;
+        };
+        // Reports queue up only when the thread falls behind; the last one
+        // is the current state.
+        let Some(report) = reports.chunks(report_size.max(1)).last() else {
+            return;
+        };
+        let device = raw.header.hDevice;
+        let state = DIGITIZERS.with(|d| {
+            let mut digitizers = d.borrow_mut();
+            let digitizer = digitizers
+                .entry(device.0 as isize)
+                .or_insert_with(|| Digitizer::open(device));
+            digitizer.as_ref().map(|d| d.read(report))
+        });
+        if let Some(state) = state {
+            PEN.with(|pen| pen.set(state));
+        }
+    }
+
+    unsafe extern "system" fn pen_window_proc(
+        hwnd: HWND,
+        msg: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> LRESULT {
+        if msg == WM_INPUT {
+            read_pen(lparam);
+        }
+        DefWindowProcW(hwnd, msg, wparam, lparam)
+    }
+
+    /// A message-only window receiving the pen digitizer's raw input, even
+    /// while other apps have focus. Without one, pen input is still told
+    /// apart but has no pressure or tilt.
+    ///
+    /// SAFETY: `module` must be this process's module handle.
+    unsafe fn listen_to_pen(module: HMODULE) -> Option<HWND> {
+        let class = w!("OpenMaiWaifuPenInput");
+        let wc = WNDCLASSW {
+            lpfnWndProc: Some(pen_window_proc),
+            hInstance: module.into(),
+            lpszClassName: class,
+            ..Default::default()
+        };
+        // Fails harmlessly when the class is left from an earlier run.
+        RegisterClassW(&wc);
+        let hwnd = CreateWindowExW(
+            WINDOW_EX_STYLE(0),
+            class,
+            None,
+            WINDOW_STYLE(0),
+            0,
+            0,
+            0,
+            0,
+            Some(HWND_MESSAGE),
+            None,
+            Some(module.into()),
+            None,
+        )
+        .ok()?;
+        let device = RAWINPUTDEVICE {
+            usUsagePage: DIGITIZER_PAGE,
+            usUsage: PEN_USAGE,
+            dwFlags: RIDEV_INPUTSINK,
+            hwndTarget: hwnd,
+        };
+        if let Err(e) = RegisterRawInputDevices(&[device], size_of::<RAWINPUTDEVICE>() as u32) {
+            eprintln!("[hittest] RegisterRawInputDevices failed: {e}");
+            let _ = DestroyWindow(hwnd);
+            return None;
+        }
+        Some(hwnd)
+    }
+
+    /// Undo [`listen_to_pen`].
+    unsafe fn stop_listening_to_pen(hwnd: HWND) {
+        let device = RAWINPUTDEVICE {
+            usUsagePage: DIGITIZER_PAGE,
+            usUsage: PEN_USAGE,
+            dwFlags: RIDEV_REMOVE,
+            hwndTarget: HWND::default(),
+        };
+        let _ = RegisterRawInputDevices(&[device], size_of::<RAWINPUTDEVICE>() as u32);
+        let _ = DestroyWindow(hwnd);
+        DIGITIZERS.with(|d| d.borrow_mut().clear());
+        PEN.with(|pen| pen.set(NO_PEN));
+    }
+
+    unsafe extern "system" fn hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if code >= 0 {
+            // SAFETY: for WH_MOUSE_LL, lParam points to an MSLLHOOKSTRUCT that
+            // is valid for the duration of the callback.
+            let info = &*(lparam.0 as *const MSLLHOOKSTRUCT);
+            let (x, y) = (info.pt.x as f64, info.pt.y as f64);
+            // Wheel delta is the signed high word of mouseData, in units of
+            // WHEEL_DELTA (120) per detent; positive = forward/right.
+            let wheel = (info.mouseData >> 16) as i16 as f64 / WHEEL_DELTA as f64;
+            SINK.with(|s| {
+                if let Some(sink) = s.borrow_mut().as_mut() {
+                    sink.set_pen(pen_state(info.dwExtraInfo));
+                    let ok = match wparam.0 as u32 {
+                        WM_MOUSEMOVE => sink.on_move(x, y),
+                        WM_LBUTTONDOWN => sink.on_button(PointerButton::Left, true, x, y),
+                        WM_LBUTTONUP => sink.on_button(PointerButton::Left, false, x, y),
+                        WM_RBUTTONDOWN => sink.on_button(PointerButton::Right, true, x, y),
+                        WM_RBUTTONUP => sink.on_button(PointerButton::Right, false, x, y),
+                        WM_MBUTTONDOWN => sink.on_button(PointerButton::Middle, true, x, y),
+                        WM_MBUTTONUP => sink.on_button(PointerButton::Middle, false, x, y),
+                        WM_MOUSEWHEEL => sink.on_scroll(0.0, -wheel * PIXELS_PER_DETENT, x, y),
+                        WM_MOUSEHWHEEL => sink.on_scroll(wheel * PIXELS_PER_DETENT, 0.0, x, y),
+                        _ => true,
+                    };
+                    if !ok {
+                        STOP.with(|stop| stop.set(true));
+                    }
+                }
+            });
+        }
+        CallNextHookEx(None, code, wparam, lparam)
+    }
+
+    /// Install the hook and pump messages until `running` is cleared.
+    ///
+    /// Returns `false` without blocking if the hook could not be installed.
+    /// A thread timer wakes `GetMessageW` every [`TICK_INTERVAL`] so the flag
+    /// is re-checked and the sink ticked.
+    pub fn run(app: &AppHandle, running: &AtomicBool) -> bool {
+        SINK.with(|s| *s.borrow_mut() = Some(MouseSink::new(app.clone())));
+
+        // SAFETY: standard hook installation on the current thread; the hook
+        // and the pen window are removed before this function returns.
+        let (hook, pen_window) = unsafe {
+            let module = match GetModuleHandleW(None) {
+                Ok(m) => m,
+                Err(_) => return false,
+            };
+            match SetWindowsHookExW(WH_MOUSE_LL, Some(hook_proc), Some(module.into()), 0) {
+                Ok(h) => (h, listen_to_pen(module)),
+                Err(e) => {
+                    eprintln!("[hittest] SetWindowsHookExW failed: {e}");
+                    return false;
+                }
+            }
+        };
+        println!("[hittest] WH_MOUSE_LL hook installed");
+
+        let timer = unsafe { SetTimer(None, 0, TICK_INTERVAL.as_millis() as u32, None) };
+        let mut msg = MSG::default();
+        while running.load(Ordering::Relaxed) && !STOP.with(|s| s.get()) {
+            let ret = unsafe { GetMessageW(&mut msg, None, 0, 0) };
+            if ret.0 <= 0 {
+                break;
+            }
+            unsafe {
+                let _ = TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+            let ok = SINK.with(|s| s.borrow_mut().as_mut().is_none_or(|sink| sink.tick()));
+            if !ok {
+                STOP.with(|stop| stop.set(true));
+            }
+        }
+        unsafe {
+            let _ = KillTimer(None, timer);
+            let _ = UnhookWindowsHookEx(hook);
+            if let Some(hwnd) = pen_window {
+                stop_listening_to_pen(hwnd);
+            }
+        }
+        SINK.with(|s| *s.borrow_mut() = None);
+        true
+    }
+}
+
+/// No native backend on other platforms — always use polling.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod native {
+    use std::sync::atomic::AtomicBool;
+    use tauri::AppHandle;
+
+    pub fn run(_app: &AppHandle, _running: &AtomicBool) -> bool {
+        false
+    }
+}
+
+// ---------- Idle time ----------
+
+/// Seconds since the last keyboard or mouse input anywhere on the system.
+///
+/// On macOS, uses `CGEventSourceSecondsSinceLastEventType` for any input
+/// type; on Windows, `GetLastInputInfo`. Returns `0.0` on other platforms
+/// (the user is never reported idle).
+#[tauri::command]
+pub fn get_idle_seconds() -> f64 {
+    #[cfg(target_os = "macos")]
+    {
+        /// `kCGEventSourceStateHIDSystemState`
+        const HID_SYSTEM_STATE: i32 = 1;
+        /// `kCGAnyInputEventType`
+        const ANY_INPUT_EVENT: u32 = !0;
+
+        #[link(name = "CoreGraphics", kind = "framework")]
+        extern "C" {
+            fn CGEventSourceSecondsSinceLastEventType(state_id: i32, event_type: u32) -> f64;
+        }
+        // SAFETY: side-effect-free query.
+        unsafe { CGEventSourceSecondsSinceLastEventType(HID_SYSTEM_STATE, ANY_INPUT_EVENT) }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::System::SystemInformation::GetTickCount;
+        use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+        let mut info = LASTINPUTINFO {
+            cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+            dwTime: 0,
+        };
+        // SAFETY: `info` is a properly sized LASTINPUTINFO. Both tick values
+        // wrap every ~49 days, so the subtraction wraps too.
+        unsafe {
+            if !GetLastInputInfo(&mut info).as_bool() {
+                return 0.0;
+            }
+            GetTickCount().wrapping_sub(info.dwTime) as f64 / 1000.0
+        }
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        0.0
+    }
+}
+
+/// Start a thread that emits `"user-idle"` when system idle time crosses
+/// [`IdleConfig::threshold_secs`], and `"user-active"` on the next input.
+pub fn start_idle_monitor(app: AppHandle, running: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        let mut idle_since: Option<Instant> = None;
+        let mut peak_idle: f64 = 0.0;
+
+        while running.load(Ordering::Relaxed) {
+            thread::sleep(IDLE_CHECK_INTERVAL);
+
+            let threshold = app
+                .state::<ConfigState>()
+                .get_settings()
+                .map(|s| s.idle.threshold_secs)
+                .unwrap_or_else(|_| IdleConfig::default().threshold_secs);
+            let idle = get_idle_seconds();
+
+            match idle_since {
+                None if threshold > 0 && idle >= threshold as f64 => {
+                    idle_since = Some(Instant::now());
+                    peak_idle = idle;
+                    let _ = app.emit("user-idle", IdleEvent { idle_seconds: idle });
+                    crate::events::publish(&app, "user.idle", IdleEvent { idle_seconds: idle });
+                }
+                Some(_) if idle < peak_idle => {
+                    // Idle time went backwards: there was input since the last check.
+                    idle_since = None;
+                    let event = IdleEvent {
+                        idle_seconds: peak_idle,
+                    };
+                    let _ = app.emit("user-active", event.clone());
+                    crate::events::publish(&app, "user.active", event);
+                }
+                Some(_) => peak_idle = idle,
+                None => {}
+            }
+        }
+    });
 }
@@ -0,0 +1,203 @@
+//! Hot corners — actions triggered by parking the cursor in a screen corner.
+//!
+//! Detection runs inside the hit-test thread ([`crate::hittest`]), which
+//! feeds every cursor sample (and a periodic tick while the cursor is
+//! stationary) into a [`HotCornerTracker`]. When the cursor has dwelled in an
+//! enabled corner for the configured time, a `"hot-corner"` event is emitted
+//! and the corner's [`HotCornerAction`] is run.
+//!
+//! A corner fires once per visit; the cursor must leave and re-enter it to
+//! fire again.
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// One of the four screen corners.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// What happens when a hot corner fires, in addition to the event.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum HotCornerAction {
+    /// Only emit the `"hot-corner"` event.
+    #[default]
+    None,
+    /// Show the window and open the chat (same as the tray item).
+    OpenChat,
+    /// Show the window and open Settings (same as the tray item).
+    OpenSettings,
+    /// Toggle window visibility (the default tray left-click).
+    ToggleWindow,
+}
+
+/// Per-corner configuration.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HotCornerRule {
+    pub enabled: bool,
+    /// How long the cursor must stay in the corner before it fires.
+    pub dwell_ms: u64,
+    pub action: HotCornerAction,
+}
+
+impl Default for HotCornerRule {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dwell_ms: 500,
+            action: HotCornerAction::None,
+        }
+    }
+}
+
+/// Hot-corner section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HotCornersConfig {
+    /// Side length of the square trigger zone in each corner (logical pixels).
+    pub corner_size: u32,
+    pub top_left: HotCornerRule,
+    pub top_right: HotCornerRule,
+    pub bottom_left: HotCornerRule,
+    pub bottom_right: HotCornerRule,
+}
+
+impl Default for HotCornersConfig {
+    fn default() -> Self {
+        Self {
+            corner_size: 6,
+            top_left: HotCornerRule::default(),
+            top_right: HotCornerRule::default(),
+            bottom_left: HotCornerRule::default(),
+            bottom_right: HotCornerRule {
+                action: HotCornerAction::OpenChat,
+                ..HotCornerRule::default()
+            },
+        }
+    }
+}
+
+impl HotCornersConfig {
+    /// The rule for a specific corner.
+    pub fn rule(&self, corner: Corner) -> &HotCornerRule {
+        match corner {
+            Corner::TopLeft => &self.top_left,
+            Corner::TopRight => &self.top_right,
+            Corner::BottomLeft => &self.bottom_left,
+            Corner::BottomRight => &self.bottom_right,
+        }
+    }
+}
+
+/// Payload of `"hot-corner"` events.
+#[derive(Clone, Serialize)]
+pub struct HotCornerEvent {
+    pub corner: Corner,
+    pub action: HotCornerAction,
+}
+
+/// Dwell-time state machine for hot corners.
+#[derive(Default)]
+pub struct HotCornerTracker {
+    /// Corner the cursor is currently in, and since when.
+    current: Option<(Corner, Instant)>,
+    /// Whether the current visit has already fired.
+    fired: bool,
+}
+
+impl HotCornerTracker {
+    /// Feed the current cursor position (window-relative logical pixels) and
+    /// the window size. Returns the corner that fired, if any.
+    pub fn update(
+        &mut self,
+        config: &HotCornersConfig,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) -> Option<Corner> {
+        let corner = locate(config.corner_size as i32, x, y, width, height)
+            .filter(|c| config.rule(*c).enabled);
+
+        match (corner, self.current) {
+            (Some(c), Some((current, _))) if c == current => {}
+            (Some(c), _) => {
+                self.current = Some((c, Instant::now()));
+                self.fired = false;
+            }
+            (None, _) => {
+                self.current = None;
+                return None;
+            }
+        }
+
+        let (corner, since) = self.current?;
+        let dwell = Duration::from_millis(config.rule(corner).dwell_ms);
+        if !self.fired && since.elapsed() >= dwell {
+            self.fired = true;
+            return Some(corner);
+        }
+        None
+    }
+}
+
+/// Which corner zone (if any) contains the point.
+fn locate(size: i32, x: i32, y: i32, width: i32, height: i32) -> Option<Corner> {
+    if width <= 0 || height <= 0 {
+        return None;
+    }
+    let left = x < size;
+    let right = x >= width - size;
+    let top = y < size;
+    let bottom = y >= height - size;
+    match (left, right, top, bottom) {
+        (true, _, true, _) => Some(Corner::TopLeft),
+        (_, true, true, _) => Some(Corner::TopRight),
+        (true, _, _, true) => Some(Corner::BottomLeft),
+        (_, true, _, true) => Some(Corner::BottomRight),
+        _ => None,
+    }
+}
+
+/// Run the side effect of a fired hot corner.
+///
+/// Mirrors the corresponding tray menu handlers in `lib.rs` so the frontend
+/// reuses its existing `tray-*` listeners.
+pub fn run_action(app: &AppHandle, action: HotCornerAction) {
+    let window = app.get_webview_window("main");
+    match action {
+        HotCornerAction::None => {}
+        HotCornerAction::OpenChat => {
+            if let Some(w) = window {
+                let _ = w.show();
+                let _ = w.set_focus();
+            }
+            let _ = app.emit("tray-open-chat", ());
+        }
+        HotCornerAction::OpenSettings => {
+            if let Some(w) = window {
+                let _ = w.show();
+                let _ = w.set_focus();
+            }
+            let _ = app.emit("tray-settings", ());
+        }
+        HotCornerAction::ToggleWindow => {
+            if let Some(w) = window {
+                if w.is_visible().unwrap_or(false) {
+                    let _ = w.hide();
+                } else {
+                    let _ = w.show();
+                    let _ = w.set_focus();
+                }
+            }
+        }
+    }
+}
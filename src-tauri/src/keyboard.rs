@@ -0,0 +1,292 @@
+//! Opt-in keyboard activity monitoring — counts and timing only.
+//!
+//! This module never records which keys are pressed. It samples a keystroke
+//! *count* ten times a second and turns it into typing bursts:
+//!
+//! - **macOS** — `CGEventSourceCounterForEventType(kCGEventKeyDown)`, a
+//!   system-wide counter that requires no Input Monitoring permission and
+//!   exposes no key codes.
+//! - **Windows** — the "pressed since last call" bit of `GetAsyncKeyState`,
+//!   summed over all keyboard virtual keys and immediately discarded.
+//! - **Other platforms** — unsupported; the monitor stays idle.
+//!
+//! A burst starts once [`KeyboardConfig::min_burst_keys`] keys arrive within
+//! the idle window and ends after [`KeyboardConfig::idle_stop_ms`] without a
+//! key. Transitions are emitted as `"typing-started"` / `"typing-stopped"`
+//! with a words-per-minute estimate (5 keystrokes = 1 word).
+
+use crate::config::ConfigState;
+use crate::user_error::{user_facing_error, Severity};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Sampling interval of the keystroke counter.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Sleep between settings checks while the monitor is disabled.
+const DISABLED_SLEEP: Duration = Duration::from_millis(1000);
+
+/// Rolling window used for the words-per-minute estimate.
+const WPM_WINDOW: Duration = Duration::from_secs(10);
+
+/// Keystrokes per word for WPM estimates (the usual typing-test convention).
+const KEYS_PER_WORD: f64 = 5.0;
+
+/// Keyboard section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct KeyboardConfig {
+    /// Opt-in: nothing is sampled while this is `false`.
+    pub enabled: bool,
+    /// Quiet time after the last keystroke before a burst ends.
+    pub idle_stop_ms: u64,
+    /// Keystrokes within `idle_stop_ms` needed to start a burst.
+    pub min_burst_keys: u32,
+}
+
+impl Default for KeyboardConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            idle_stop_ms: 2000,
+            min_burst_keys: 4,
+        }
+    }
+}
+
+/// Payload of `"typing-started"` / `"typing-stopped"` events.
+#[derive(Clone, Serialize, Default)]
+pub struct TypingBurst {
+    /// Time since the burst started.
+    pub duration_ms: u64,
+    /// Keystrokes counted in the burst.
+    pub keystrokes: u32,
+    /// Words-per-minute over the last 10 seconds.
+    pub wpm: f64,
+}
+
+/// Snapshot returned by [`get_typing_stats`].
+#[derive(Clone, Serialize, Default)]
+pub struct TypingStats {
+    pub typing: bool,
+    #[serde(flatten)]
+    pub burst: TypingBurst,
+}
+
+/// Latest stats, written by the monitor thread.
+static STATS: Mutex<Option<TypingStats>> = Mutex::new(None);
+
+// ---------- Counter sampling ----------
+
+/// Return the number of key presses since the previous call.
+struct KeystrokeSampler {
+    #[cfg(target_os = "macos")]
+    last_count: Option<u32>,
+}
+
+impl KeystrokeSampler {
+    fn new() -> Self {
+        Self {
+            #[cfg(target_os = "macos")]
+            last_count: None,
+        }
+    }
+
+    /// `None` if the platform is unsupported.
+    fn sample(&mut self) -> Option<u32> {
+        #[cfg(target_os = "macos")]
+        {
+            /// `kCGEventSourceStateCombinedSessionState`
+            const COMBINED_SESSION_STATE: i32 = 0;
+            /// `kCGEventKeyDown`
+            const KEY_DOWN: u32 = 10;
+
+            #[link(name = "CoreGraphics", kind = "framework")]
+            extern "C" {
+                fn CGEventSourceCounterForEventType(state_id: i32, event_type: u32) -> u32;
+            }
+
+            // SAFETY: side-effect-free query returning a plain counter.
+            let count = unsafe { CGEventSourceCounterForEventType(COMBINED_SESSION_STATE, KEY_DOWN) };
+            let delta = self.last_count.map_or(0, |last| count.wrapping_sub(last));
+            self.last_count = Some(count);
+            Some(delta)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            use windows::Win32::UI::Input::KeyboardAndMouse::GetAsyncKeyState;
+
+            // 0x08 (Backspace) .. 0xFE covers every keyboard virtual key and
+            // skips the mouse buttons (0x01–0x06). The low bit reports a press
+            // since the previous call; the key code itself is discarded.
+            let presses = (0x08..=0xFE)
+                .filter(|vk| unsafe { GetAsyncKeyState(*vk) } & 1 != 0)
+                .count();
+            Some(presses as u32)
+        }
+
+        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        {
+            None
+        }
+    }
+}
+
+// ---------- Burst detection ----------
+
+/// A typing transition produced by [`TypingDetector::update`].
+enum Transition {
+    Started(TypingBurst),
+    Stopped(TypingBurst),
+}
+
+/// Turns keystroke deltas into typing bursts.
+#[derive(Default)]
+struct TypingDetector {
+    /// Recent samples with at least one key, for the WPM window.
+    history: VecDeque<(Instant, u32)>,
+    /// Start of the current burst, if typing.
+    burst_start: Option<Instant>,
+    burst_keys: u32,
+    last_key: Option<Instant>,
+}
+
+impl TypingDetector {
+    fn update(&mut self, config: &KeyboardConfig, now: Instant, keys: u32) -> Option<Transition> {
+        let idle = Duration::from_millis(config.idle_stop_ms);
+        if keys > 0 {
+            self.history.push_back((now, keys));
+            self.last_key = Some(now);
+            if self.burst_start.is_some() {
+                self.burst_keys += keys;
+            }
+        }
+        while self
+            .history
+            .front()
+            .is_some_and(|(t, _)| now.duration_since(*t) > WPM_WINDOW)
+        {
+            self.history.pop_front();
+        }
+
+        match self.burst_start {
+            None => {
+                let recent: u32 = self
+                    .history
+                    .iter()
+                    .filter(|(t, _)| now.duration_since(*t) <= idle)
+                    .map(|(_, k)| k)
+                    .sum();
+                if recent >= config.min_burst_keys.max(1) {
+                    self.burst_start = Some(now);
+                    self.burst_keys = recent;
+                    return Some(Transition::Started(self.snapshot(now)));
+                }
+                None
+            }
+            Some(_) => {
+                if self.last_key.is_some_and(|t| now.duration_since(t) >= idle) {
+                    let burst = self.snapshot(now);
+                    self.burst_start = None;
+                    self.burst_keys = 0;
+                    return Some(Transition::Stopped(burst));
+                }
+                None
+            }
+        }
+    }
+
+    fn snapshot(&self, now: Instant) -> TypingBurst {
+        let window_keys: u32 = self.history.iter().map(|(_, k)| k).sum();
+        TypingBurst {
+            duration_ms: self
+                .burst_start
+                .map_or(0, |t| now.duration_since(t).as_millis() as u64),
+            keystrokes: self.burst_keys,
+            wpm: window_keys as f64 / KEYS_PER_WORD / (WPM_WINDOW.as_secs_f64() / 60.0),
+        }
+    }
+
+    fn stats(&self, now: Instant) -> TypingStats {
+        TypingStats {
+            typing: self.burst_start.is_some(),
+            burst: self.snapshot(now),
+        }
+    }
+}
+
+// ---------- Thread ----------
+
+/// Start the keyboard activity thread.
+///
+/// While [`KeyboardConfig::enabled`] is `false` the thread only re-checks
+/// settings once a second and samples nothing.
+pub fn start_keyboard_monitor(app: AppHandle, running: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        let mut sampler: Option<KeystrokeSampler> = None;
+        let mut detector = TypingDetector::default();
+
+        while running.load(Ordering::Relaxed) {
+            let config = app
+                .state::<ConfigState>()
+                .get_settings()
+                .map(|s| s.keyboard)
+                .unwrap_or_default();
+            if !config.enabled {
+                if sampler.take().is_some() {
+                    detector = TypingDetector::default();
+                    if let Ok(mut stats) = STATS.lock() {
+                        *stats = None;
+                    }
+                }
+                thread::sleep(DISABLED_SLEEP);
+                continue;
+            }
+
+            let sampler = sampler.get_or_insert_with(KeystrokeSampler::new);
+            let Some(keys) = sampler.sample() else {
+                user_facing_error(
+                    &app,
+                    "keyboard",
+                    "Typing detection is not supported on this platform.",
+                    Severity::Info,
+                );
+                return;
+            };
+
+            let now = Instant::now();
+            match detector.update(&config, now, keys) {
+                Some(Transition::Started(burst)) => {
+                    let _ = app.emit("typing-started", burst);
+                }
+                Some(Transition::Stopped(burst)) => {
+                    let _ = app.emit("typing-stopped", burst);
+                }
+                None => {}
+            }
+            if let Ok(mut stats) = STATS.lock() {
+                *stats = Some(detector.stats(now));
+            }
+
+            thread::sleep(SAMPLE_INTERVAL);
+        }
+    });
+}
+
+/// IPC command: current typing state and words-per-minute estimate.
+///
+/// Returns an idle snapshot while the monitor is disabled.
+#[tauri::command]
+pub fn get_typing_stats() -> TypingStats {
+    STATS
+        .lock()
+        .ok()
+        .and_then(|s| s.clone())
+        .unwrap_or_default()
+}
@@ -2,30 +2,114 @@
 //!
 //! This crate drives the transparent, always-on-top desktop pet window.
 //! It initialises a full-screen transparent Tauri webview, sets up
-//! mouse-position polling for hit-testing, and exposes IPC commands for:
+//! global mouse tracking for hit-testing, and exposes IPC commands for:
 //!
-//! - Screen/window enumeration ([`screen`])
-//! - OpenClaw chat and webhook integration ([`openclaw`])
-//! - Persistent user configuration ([`config`])
-//! - Primary-screen size detection ([`window`])
-//! - Mouse coordinate broadcasting ([`hittest`])
+//! - Screen/window enumeration ([`screen`]), browser URL strategies ([`browsers`])
+//!   and user-defined redaction ([`redaction`])
+//! - OpenClaw chat and webhook integration ([`openclaw`]) and the local
+//!   gateway process ([`gateway`]), other chat backends such as OpenAI,
+//!   Anthropic and Ollama ([`llm`]), an optional built-in offline model
+//!   ([`local_llm`]), per-agent sessions and system prompts ([`agents`]), the
+//!   character's persona prompt ([`persona`]), context window budgeting with
+//!   summaries of older turns ([`budget`]), prompt debugging snapshots
+//!   ([`prompt_snapshot`]), an opt-in chat trace log ([`chatlog`]),
+//!   request rate and cost caps ([`usage`]) and a short-lived reply cache
+//!   ([`cache`])
+//! - Emotion and action tags parsed out of replies ([`tags`])
+//! - Optional end-to-end payload encryption ([`e2e`]) and webhook signatures
+//!   ([`signing`])
+//! - Persistent user configuration ([`config`]) with secrets in the OS
+//!   keychain ([`secrets`]), data files and chat history ([`memory`])
+//! - QR pairing with a mobile companion ([`pairing`])
+//! - Agent-written diary entries ([`diary`])
+//! - Primary-screen size detection and capture exclusion ([`window`])
+//! - Drag snapping geometry ([`snap`]), collision boundaries ([`boundaries`])
+//!   and walk path planning ([`walk`])
+//! - Mouse coordinate broadcasting ([`hittest`]), hot corners ([`hotcorners`])
+//!   and pointer gestures ([`shake`], [`gestures`])
+//! - Smoothed gaze-target stream ([`lookat`])
+//! - Opt-in typing-burst detection ([`keyboard`])
+//! - Audio level and frequency bands for music reactions ([`audio`], [`spectrum`]),
+//!   voice activity detection ([`vad`]), ambient scene classification
+//!   ([`scene`]) and character voice playback with lip-sync ([`lipsync`])
+//! - Now Playing metadata from media players ([`media`])
+//! - Local speech-to-text dictation ([`stt`]), wake-word activation
+//!   ([`wakeword`]) and text-to-speech engines ([`tts`]), speaking streamed
+//!   replies as they arrive ([`ttsstream`])
+//! - Battery-saver power profiles ([`power`])
+//! - Configurable tray icon behaviour ([`tray`])
+//! - User-facing error reports for the character to voice ([`user_error`])
+//! - A loopback HTTP API ([`localapi`]) serving subsystem health ([`health`]),
+//!   and a push server ([`push`]) for proactive messages from other tools
+//! - Outgoing event webhooks for other servers, e.g. home automation
+//!   ([`events`])
 
+mod agents;
 mod audio;
+mod audit;
+mod boundaries;
+mod browsers;
+mod budget;
+mod bundle;
+mod cache;
+mod chatlog;
 mod config;
+mod diary;
+mod e2e;
+mod events;
+mod gateway;
+mod gestures;
+mod health;
 mod hittest;
+mod hotcorners;
+mod keyboard;
+mod lipsync;
+mod llm;
+mod local_llm;
+mod localapi;
+mod lookat;
+mod media;
 mod memory;
+mod ollama;
+mod onboarding;
+mod openai_api;
 mod openclaw;
+mod pairing;
+mod persona;
+mod power;
+mod proactive;
+mod prompt_snapshot;
+mod push;
+mod quiet;
+mod redaction;
+mod scene;
 mod screen;
+mod secrets;
+mod shake;
+mod signing;
+mod snap;
+mod spectrum;
 mod stats;
+mod stt;
+mod tags;
+mod tray;
+mod tts;
+mod ttsstream;
+mod usage;
+mod user_error;
+mod validation;
+mod vad;
+mod wakeword;
+mod walk;
 mod window;
 
 use config::ConfigState;
 use openclaw::HttpClient;
 use std::sync::atomic::Ordering;
 use tauri::image::Image;
-use tauri::menu::{Menu, MenuItem};
+use tauri::menu::{CheckMenuItem, Menu, MenuItem};
 use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
-use tauri::{Emitter, Manager, WindowEvent};
+use tauri::{Emitter, Manager, RunEvent, WindowEvent};
 
 /// Bootstrap the Tauri application.
 ///
@@ -37,11 +121,16 @@ use tauri::{Emitter, Manager, WindowEvent};
 ///    to cover the entire primary screen.
 /// 3. **Close interception** — prevents the window-close event from terminating
 ///    the app; the window is hidden instead, so the tray icon stays alive.
-/// 4. **Mouse polling** — starts a 60 Hz background thread that emits
-///    `"mouse-move"` events to the frontend for raycaster hit-testing.
+/// 4. **Mouse tracking** — starts a background thread (native event tap, or
+///    60 Hz polling as a fallback) that emits `"mouse-move"` events to the
+///    frontend for raycaster hit-testing.
 /// 5. **System tray** — builds a tray icon with menu items (Show/Hide, Chat,
-///    Settings, Change Character, Quiet Mode, Quit) and wires up event handlers.
-/// 6. **Autostart plugin** — enables macOS Launch Agent auto-start.
+///    Settings, Change Character, Quiet Mode, Listen to Audio, Quit) and wires
+///    up event handlers.
+///    Where no tray host exists (stock GNOME) the window is kept visible
+///    instead (see [`tray::tray_available`]).
+/// 6. **Autostart plugin** — launch at login: a Launch Agent on macOS, an XDG
+///    autostart `.desktop` entry on Linux, a `Run` registry key on Windows.
 /// 7. **Invoke handler** — registers all `#[tauri::command]` functions so the
 ///    frontend can call them via `invoke()`.
 ///
@@ -55,16 +144,12 @@ pub fn run() {
             // Register shared HTTP client and config state for OpenClaw commands
             app.manage(HttpClient::new());
             app.manage(ConfigState::load());
+            app.manage(usage::UsageState::new());
+            health::mark_started();
 
-            // Position the main window at (0, 0) and resize to fill the screen.
+            // Position the main window to fill the configured screen.
+            window::place_overlay(app.handle());
             if let Some(main_window) = app.get_webview_window("main") {
-                let screen_size = window::get_screen_size();
-                let _ = main_window.set_position(tauri::LogicalPosition::new(0.0, 0.0));
-                let _ = main_window.set_size(tauri::LogicalSize::new(
-                    screen_size.width as f64,
-                    screen_size.height as f64,
-                ));
-
                 // Prevent window close from killing the app — hide instead
                 let win = main_window.clone();
                 main_window.on_window_event(move |event| {
@@ -75,15 +160,47 @@ pub fn run() {
                 });
             }
 
-            // Start mouse-position polling for hit-testing.
+            // Notice external edits to memory/config files.
+            if let Err(e) = memory::start_data_watcher(app.handle()) {
+                eprintln!("[memory] {}", e);
+            }
+
+            // Start global mouse tracking for hit-testing. The background
+            // threads below share its `running` flag and exit once "quit"
+            // clears it.
             let mouse_polling_running = hittest::start_mouse_polling(app.handle().clone());
+            lookat::start_look_target_stream(app.handle().clone(), mouse_polling_running.clone());
+            keyboard::start_keyboard_monitor(app.handle().clone(), mouse_polling_running.clone());
+            hittest::start_idle_monitor(app.handle().clone(), mouse_polling_running.clone());
+            boundaries::start_boundary_service(app.handle().clone(), mouse_polling_running.clone());
+            power::start_power_monitor(app.handle().clone(), mouse_polling_running.clone());
 
-            // Start audio level monitoring for music detection.
-            if audio::start_audio_monitoring() {
-                println!("[audio] Audio monitoring started");
-            } else {
-                eprintln!("[audio] Audio monitoring failed to start (may need permissions)");
-            }
+            // Optional loopback HTTP API (/healthz).
+            localapi::start_local_api(app.handle().clone());
+
+            // Optional push server for messages from OpenClaw hooks and tools.
+            push::start_push_server(app.handle().clone());
+
+            // Optional OpenAI-compatible API for other local tools.
+            openai_api::start_openai_api(app.handle().clone());
+
+            // Start the local gateway if the user lets the app manage it.
+            gateway::start_gateway_on_launch(app.handle().clone());
+
+            // Send chat and webhook messages queued while the gateway was down.
+            openclaw::start_outbox_flusher(app.handle().clone());
+
+            // Proactive check-ins, independent of the WebView being awake.
+            proactive::start_proactive_scheduler(app.handle().clone());
+            quiet::start_quiet_watcher(app.handle().clone());
+
+            // Forward backend events to the configured webhook endpoints.
+            events::start_event_dispatcher(app.handle().clone());
+            events::start_window_watcher(app.handle().clone(), mouse_polling_running.clone());
+
+            // Start audio level monitoring for music detection (schedule-aware).
+            audio::start_audio_monitoring(app.handle().clone(), mouse_polling_running.clone());
+            media::start_media_monitor(app.handle().clone(), mouse_polling_running.clone());
 
             // Load tray icon from bundled PNG
             let icon = Image::from_path("icons/icon.png")
@@ -109,6 +226,17 @@ pub fn run() {
                 true,
                 None::<&str>,
             )?;
+            let listen_audio = CheckMenuItem::with_id(
+                app,
+                "listen_audio",
+                "Listen to Audio",
+                true,
+                app.state::<ConfigState>()
+                    .get_settings()
+                    .map(|s| s.audio.enabled)
+                    .unwrap_or(true),
+                None::<&str>,
+            )?;
             let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
 
             let menu = Menu::with_items(
@@ -119,6 +247,7 @@ pub fn run() {
                     &settings,
                     &change_character,
                     &quiet_mode,
+                    &listen_audio,
                     &quit,
                 ],
             )?;
@@ -158,8 +287,18 @@ pub fn run() {
                         let _ = app.emit("tray-change-character", ());
                     }
                     "quiet_mode" => {
+                        quiet::quiet_for(app, std::time::Duration::from_secs(30 * 60));
                         let _ = app.emit("tray-quiet-mode", ());
                     }
+                    "listen_audio" => {
+                        // The check mark has already toggled; follow it.
+                        let enabled = listen_audio.is_checked().unwrap_or(true);
+                        if let Err(e) =
+                            audio::set_monitoring_enabled(&app.state::<ConfigState>(), enabled)
+                        {
+                            eprintln!("[audio] {}", e);
+                        }
+                    }
                     "quit" => {
                         mouse_polling_running.store(false, Ordering::Relaxed);
                         app.exit(0);
@@ -173,50 +312,151 @@ pub fn run() {
                         ..
                     } = event
                     {
-                        // Left-click tray icon: toggle window visibility
-                        let app = tray.app_handle();
-                        if let Some(w) = app.get_webview_window("main") {
-                            if w.is_visible().unwrap_or(false) {
-                                let _ = w.hide();
-                            } else {
-                                let _ = w.show();
-                                let _ = w.set_focus();
-                            }
-                        }
+                        // Left-click tray icon: configured action (default: toggle window)
+                        tray::on_left_click(tray.app_handle());
                     }
                 })
                 .build(app)?;
 
+            if !tray::tray_available() {
+                tray::report_missing_tray(app.handle());
+            }
+
             Ok(())
         })
         .plugin(tauri_plugin_autostart::init(
             tauri_plugin_autostart::MacosLauncher::LaunchAgent,
             None,
         ))
+        .plugin(tauri_plugin_notification::init())
         .invoke_handler(tauri::generate_handler![
             screen::get_window_list,
             screen::get_active_window,
             screen::get_browser_url,
             screen::check_screen_permission,
+            screen::capture_region,
             window::get_screen_size,
             window::get_all_monitors,
+            window::set_display_monitor,
             window::get_dock_info,
+            window::set_exclude_from_capture,
+            snap::get_snap_candidates,
+            boundaries::get_boundaries,
+            walk::plan_walk_path,
             openclaw::send_chat,
+            openclaw::send_chat_stream,
+            openclaw::send_chat_to,
+            openclaw::send_chat_stream_to,
+            openclaw::cancel_chat,
+            local_llm::list_local_models,
+            local_llm::download_local_model,
+            local_llm::delete_local_model,
+            ollama::check_ollama,
+            ollama::list_ollama_models,
+            ollama::pull_ollama_model,
+            ollama::use_ollama_model,
             openclaw::send_webhook,
             openclaw::check_openclaw_health,
             openclaw::setup_openclaw_hooks,
             openclaw::check_openclaw_installed,
             openclaw::list_openclaw_agents,
             openclaw::create_openclaw_agent,
+            gateway::start_gateway,
+            gateway::stop_gateway,
+            gateway::restart_gateway,
+            gateway::get_gateway_status,
+            gateway::get_gateway_logs,
+            agents::switch_agent,
+            agents::list_sessions,
+            persona::set_persona,
+            persona::preview_prompt,
+            quiet::set_quiet_mode,
+            quiet::is_quiet_now,
+            budget::get_context_usage,
+            prompt_snapshot::get_last_prompt_payload,
+            chatlog::get_chat_log,
+            usage::get_usage_stats,
+            cache::get_chat_cache_stats,
+            cache::clear_chat_cache,
             config::get_openclaw_config,
             config::save_openclaw_config,
+            config::get_effective_config,
+            config::get_config_encryption,
+            config::set_config_encryption,
+            config::get_config_format,
+            config::convert_config_format,
+            bundle::export_settings,
+            bundle::import_settings,
+            validation::get_config_issues,
+            audit::get_config_history,
+            onboarding::get_onboarding_state,
+            onboarding::start_onboarding,
+            onboarding::complete_onboarding_step,
+            config::get_app_settings,
+            config::save_app_settings,
+            e2e::generate_e2e_key,
+            pairing::start_pairing,
+            pairing::cancel_pairing,
+            pairing::list_paired_devices,
+            pairing::revoke_paired_device,
+            hittest::get_idle_seconds,
+            hittest::set_poll_interval_ms,
+            hittest::set_polling_enabled,
+            hittest::restart_polling,
+            hittest::set_hit_regions,
+            hittest::set_interactive_regions,
+            hittest::set_character_anchor,
+            hittest::get_cursor_kind,
             audio::get_audio_level,
+            audio::get_audio_levels,
+            audio::get_audio_scene,
+            audio::set_audio_event_thresholds,
+            audio::set_smoothing,
+            audio::calibrate_noise_floor,
+            audio::get_system_volume,
+            audio::set_system_volume,
+            audio::get_mute_state,
+            audio::get_playing_apps,
+            audio::get_audio_spectrum,
+            audio::list_devices,
+            audio::set_monitoring_device,
+            audio::play_voice,
+            audio::stop_voice,
+            audio::start_recording,
+            audio::stop_recording,
+            audio::check_mic_permission,
+            audio::request_mic_permission,
+            audio::stop_audio_monitoring,
+            audio::restart_audio_monitoring,
+            audio::get_audio_monitoring_status,
+            media::get_now_playing,
+            stt::start_dictation,
+            stt::stop_dictation,
+            tts::speak,
+            health::webview_heartbeat,
+            keyboard::get_typing_stats,
+            power::set_power_profile,
+            power::get_power_state,
             stats::get_process_stats,
             stats::read_file_bytes,
             memory::read_data_file,
             memory::write_data_file,
             memory::delete_data_file,
+            memory::get_chat_history,
+            memory::clear_chat_history,
+            memory::export_conversation,
+            memory::import_conversation,
+            diary::write_diary_entry,
+            diary::get_diary,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| {
+            // However the app quits (tray, menu, OS shutdown), leave nothing
+            // running or turned down behind it.
+            if let RunEvent::Exit = event {
+                gateway::stop();
+                audio::restore_ducked_volume();
+            }
+        });
 }
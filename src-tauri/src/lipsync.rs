@@ -0,0 +1,140 @@
+//! Viseme extraction for lip-sync.
+//!
+//! [`analyze`] turns a decoded voice clip into a [`VisemeFrame`] every
+//! [`HOP_MS`], and [`crate::audio`] emits them as `"viseme"` events at the
+//! moment playback reaches each frame, so the VRM mouth follows the audio
+//! without WebAudio analysis competing with the render loop.
+//!
+//! Each frame gets an amplitude envelope (how open the mouth is) and a rough
+//! vowel from the first two formants, taken as the strongest FFT bins in the
+//! usual F1 and F2 ranges:
+//!
+//! | shape | F1       | F2        |
+//! |-------|----------|-----------|
+//! | `aa`  | high     | any       |
+//! | `ee`  | low      | high      |
+//! | `ih`  | mid      | high      |
+//! | `oh`  | mid      | low       |
+//! | `ou`  | low      | low       |
+//!
+//! That is coarse phonetics, but mouth shapes only need to look right.
+
+use rustfft::num_complex::Complex;
+use rustfft::FftPlanner;
+use serde::Serialize;
+
+/// Spacing (and window length) of the frames.
+pub const HOP_MS: u32 = 40;
+
+/// Frames quieter than this share of the clip's loudest frame are silent.
+const SILENCE_RATIO: f32 = 0.08;
+
+/// Search range for the first formant.
+const F1_RANGE_HZ: (f32, f32) = (250.0, 1000.0);
+
+/// Search range for the second formant.
+const F2_RANGE_HZ: (f32, f32) = (850.0, 3000.0);
+
+/// VRM mouth expressions.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum VisemeShape {
+    Sil,
+    Aa,
+    Ih,
+    Ou,
+    Ee,
+    Oh,
+}
+
+/// Payload of `"viseme"` events.
+#[derive(Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct VisemeFrame {
+    /// Offset from the start of the clip.
+    pub time_ms: u64,
+    pub shape: VisemeShape,
+    /// Mouth openness, `0.0`–`1.0`.
+    pub weight: f32,
+}
+
+impl VisemeFrame {
+    pub const SILENT: Self = Self {
+        time_ms: 0,
+        shape: VisemeShape::Sil,
+        weight: 0.0,
+    };
+}
+
+/// Classify a vowel from its first two formants.
+fn vowel(f1: f32, f2: f32) -> VisemeShape {
+    match (f1, f2) {
+        (f1, _) if f1 >= 700.0 => VisemeShape::Aa,
+        (f1, f2) if f1 < 450.0 && f2 >= 1900.0 => VisemeShape::Ee,
+        (_, f2) if f2 >= 1600.0 => VisemeShape::Ih,
+        (f1, _) if f1 < 450.0 => VisemeShape::Ou,
+        _ => VisemeShape::Oh,
+    }
+}
+
+/// Analyse mono `samples` at `sample_rate` into one frame per [`HOP_MS`].
+pub fn analyze(samples: &[f32], sample_rate: u32) -> Vec<VisemeFrame> {
+    let hop = (sample_rate * HOP_MS / 1000).max(1) as usize;
+    let fft_size = hop.next_power_of_two();
+    let fft = FftPlanner::<f32>::new().plan_fft_forward(fft_size);
+    let bin_hz = sample_rate as f32 / fft_size as f32;
+    let bins = |(lo, hi): (f32, f32)| {
+        let lo = ((lo / bin_hz) as usize).clamp(1, fft_size / 2 - 1);
+        let hi = ((hi / bin_hz) as usize).clamp(lo + 1, fft_size / 2);
+        lo..hi
+    };
+
+    let rms: Vec<f32> = samples
+        .chunks(hop)
+        .map(|chunk| (chunk.iter().map(|s| s * s).sum::<f32>() / chunk.len() as f32).sqrt())
+        .collect();
+    let peak = rms.iter().copied().fold(0.0, f32::max);
+    if peak <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut buffer = vec![Complex::default(); fft_size];
+    samples
+        .chunks(hop)
+        .zip(rms)
+        .enumerate()
+        .map(|(i, (chunk, level))| {
+            let time_ms = (i * hop) as u64 * 1000 / sample_rate as u64;
+            let relative = level / peak;
+            if relative < SILENCE_RATIO {
+                return VisemeFrame {
+                    time_ms,
+                    ..VisemeFrame::SILENT
+                };
+            }
+
+            buffer.fill(Complex::default());
+            for (j, (out, s)) in buffer.iter_mut().zip(chunk).enumerate() {
+                let w = 0.5 - 0.5 * (std::f32::consts::TAU * j as f32 / chunk.len() as f32).cos();
+                *out = Complex::new(s * w, 0.0);
+            }
+            fft.process(&mut buffer);
+            let strongest = |range: std::ops::Range<usize>| {
+                let start = range.start;
+                buffer[range]
+                    .iter()
+                    .enumerate()
+                    .max_by(|a, b| a.1.norm_sqr().total_cmp(&b.1.norm_sqr()))
+                    .map_or(0.0, |(k, _)| (start + k) as f32 * bin_hz)
+            };
+            let f1 = strongest(bins(F1_RANGE_HZ));
+            let f2 = strongest(bins((F2_RANGE_HZ.0.max(f1 + 200.0), F2_RANGE_HZ.1)));
+
+            VisemeFrame {
+                time_ms,
+                shape: vowel(f1, f2),
+                weight: relative.sqrt().min(1.0),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,924 @@
+//! Chat backends for the character.
+//!
+//! Not everyone runs OpenClaw: [`ProviderConfig`] (part of
+//! [`OpenClawConfig`]) picks which model answers chat messages, and every
+//! backend implements [`ChatProvider`]:
+//!
+//! - **OpenClaw** ([`OpenClawGateway`]): the gateway's OpenAI-compatible
+//!   `/v1/chat/completions` endpoint, talking to the configured agent. The
+//!   gateway keeps the conversation itself (keyed by the session key).
+//!   [`crate::openclaw::send_chat`] still goes through the CLI for this
+//!   backend, which also covers end-to-end encryption.
+//! - **OpenAI** ([`OpenAi`]): any OpenAI-compatible `/chat/completions`
+//!   API — OpenAI itself, OpenRouter, LM Studio, vLLM, …
+//! - **Anthropic** ([`Anthropic`]): the Messages API.
+//! - **Ollama** ([`Ollama`]): a local Ollama server's `/api/chat`.
+//! - **Local** ([`LocalModel`]): a GGUF model run inside the app, in builds
+//!   with the `local_llm` feature ([`crate::local_llm`]).
+//!
+//! The OpenAI-style backends can call desktop tools during a reply (see
+//! [`crate::openclaw::ToolBridge`]).
+//!
+//! Messages can carry images ([`ImageAttachment`]) for vision models; each
+//! wire format gets them in its own shape, and the built-in model refuses
+//! them.
+//!
+//! The backends that do not remember the conversation themselves are resent
+//! the recent exchanges, and a summary of older ones, with every message
+//! (see [`crate::budget`]).
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize, Serializer};
+use std::future::Future;
+use tauri::{AppHandle, Manager};
+
+use crate::budget;
+use crate::config::{ConfigState, OpenClawConfig};
+use crate::local_llm::{LocalLlmConfig, LocalModel};
+use crate::openclaw::{OpenClawError, ToolBridge, TIMED_OUT};
+use crate::usage::UsageState;
+
+/// Which backend answers chat messages.
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    #[default]
+    OpenClaw,
+    OpenAi,
+    Anthropic,
+    Ollama,
+    /// The model built into the app ([`crate::local_llm`]).
+    Local,
+}
+
+/// Chat backend selection and per-backend settings.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProviderConfig {
+    pub kind: ProviderKind,
+    pub openai: OpenAiConfig,
+    pub anthropic: AnthropicConfig,
+    pub ollama: OllamaConfig,
+    pub local: LocalLlmConfig,
+    /// Exchanges resent as context to backends without server-side history.
+    pub history_turns: usize,
+    /// Estimated tokens a whole request may use with those backends (see
+    /// [`crate::budget`]).
+    pub context_tokens: usize,
+    /// Summarize turns that no longer fit instead of dropping them.
+    pub summarize: bool,
+}
+
+impl Default for ProviderConfig {
+    fn default() -> Self {
+        Self {
+            kind: ProviderKind::OpenClaw,
+            openai: OpenAiConfig::default(),
+            anthropic: AnthropicConfig::default(),
+            ollama: OllamaConfig::default(),
+            local: LocalLlmConfig::default(),
+            history_turns: 10,
+            context_tokens: 8000,
+            summarize: true,
+        }
+    }
+}
+
+/// An OpenAI-compatible API.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OpenAiConfig {
+    /// Base URL up to and including the version (e.g. `.../v1`).
+    pub base_url: String,
+    pub api_key: String,
+    pub model: String,
+}
+
+impl Default for OpenAiConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "https://api.openai.com/v1".to_string(),
+            api_key: String::new(),
+            model: "gpt-4o-mini".to_string(),
+        }
+    }
+}
+
+/// The Anthropic Messages API.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AnthropicConfig {
+    pub base_url: String,
+    pub api_key: String,
+    pub model: String,
+    /// Upper bound on the reply length; the API requires one.
+    pub max_tokens: u32,
+}
+
+impl Default for AnthropicConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "https://api.anthropic.com".to_string(),
+            api_key: String::new(),
+            model: "claude-3-5-haiku-latest".to_string(),
+            max_tokens: 1024,
+        }
+    }
+}
+
+/// A local Ollama server.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OllamaConfig {
+    pub url: String,
+    pub model: String,
+}
+
+impl Default for OllamaConfig {
+    fn default() -> Self {
+        Self {
+            url: "http://localhost:11434".to_string(),
+            model: "llama3.2".to_string(),
+        }
+    }
+}
+
+/// One message of a conversation, in the shape all three wire formats
+/// share. The tool fields only occur in OpenAI-style conversations.
+#[derive(Serialize, Clone, Debug)]
+pub struct ChatMessage {
+    pub role: &'static str,
+    pub content: String,
+    /// Calls the assistant made in this message.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// The call a `"tool"` message answers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    /// Pictures sent along with a user message; each wire format places
+    /// them itself (see [`openai_messages`]).
+    #[serde(skip)]
+    pub images: Vec<Image>,
+}
+
+/// Largest image accepted by [`ImageAttachment::resolve`] (20 MB).
+const MAX_IMAGE_BYTES: usize = 20 * 1024 * 1024;
+
+/// An image attached to a chat message by the frontend: base64 `data`
+/// (optionally as a `data:` URL) or a file `path`. The media type is
+/// always detected from the content.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageAttachment {
+    #[serde(default)]
+    pub data: Option<String>,
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+/// An image ready to send: base64 data and its media type.
+#[derive(Clone, Debug)]
+pub struct Image {
+    pub media_type: String,
+    pub data: String,
+}
+
+impl ImageAttachment {
+    /// Load and check the image.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if neither `data` nor `path` is given, the file cannot
+    /// be read, the data is not base64, or it is not a PNG, JPEG, GIF or
+    /// WebP image under 20 MB. Files are checked for size before they are
+    /// read.
+    pub fn resolve(&self) -> Result<Image, String> {
+        let bytes = match (&self.data, &self.path) {
+            (Some(data), _) => {
+                // Accept `data:image/png;base64,...` as well as bare base64.
+                let data = match data.split_once(',') {
+                    Some((header, data)) if header.starts_with("data:") => data,
+                    _ => data.as_str(),
+                };
+                BASE64
+                    .decode(data.trim())
+                    .map_err(|e| format!("Image data is not valid base64: {e}"))?
+            }
+            (None, Some(path)) => {
+                let len = std::fs::metadata(path)
+                    .map_err(|e| format!("Failed to read image {path}: {e}"))?
+                    .len();
+                check_image_size(usize::try_from(len).unwrap_or(usize::MAX))?;
+                std::fs::read(path).map_err(|e| format!("Failed to read image {path}: {e}"))?
+            }
+            (None, None) => return Err("Image attachment has neither data nor a path".to_string()),
+        };
+        check_image_size(bytes.len())?;
+        let media_type = sniff_image_type(&bytes)
+            .ok_or("Unsupported image format; use PNG, JPEG, GIF or WebP")?;
+        Ok(Image {
+            media_type: media_type.to_string(),
+            data: BASE64.encode(&bytes),
+        })
+    }
+}
+
+fn check_image_size(len: usize) -> Result<(), String> {
+    if len > MAX_IMAGE_BYTES {
+        return Err(format!(
+            "Image is too large ({} MB, at most {} MB)",
+            len / (1024 * 1024),
+            MAX_IMAGE_BYTES / (1024 * 1024)
+        ));
+    }
+    Ok(())
+}
+
+/// Media type of an image from its first bytes.
+fn sniff_image_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// OpenAI-style messages: images become `image_url` parts with `data:` URLs.
+fn openai_messages<S: Serializer>(
+    messages: &&[ChatMessage],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(messages.iter().map(|message| {
+        let mut value = serde_json::to_value(message).unwrap_or_default();
+        if !message.images.is_empty() {
+            let mut parts = vec![serde_json::json!({ "type": "text", "text": message.content })];
+            parts.extend(message.images.iter().map(|image| {
+                serde_json::json!({
+                    "type": "image_url",
+                    "image_url": { "url": format!("data:{};base64,{}", image.media_type, image.data) }
+                })
+            }));
+            value["content"] = serde_json::Value::Array(parts);
+        }
+        value
+    }))
+}
+
+/// Anthropic messages: images become base64 `image` blocks before the text.
+fn anthropic_messages<S: Serializer>(
+    messages: &&[ChatMessage],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(messages.iter().map(|message| {
+        let mut value = serde_json::to_value(message).unwrap_or_default();
+        if !message.images.is_empty() {
+            let mut parts: Vec<serde_json::Value> = message
+                .images
+                .iter()
+                .map(|image| {
+                    serde_json::json!({
+                        "type": "image",
+                        "source": {
+                            "type": "base64",
+                            "media_type": image.media_type,
+                            "data": image.data,
+                        }
+                    })
+                })
+                .collect();
+            parts.push(serde_json::json!({ "type": "text", "text": message.content }));
+            value["content"] = serde_json::Value::Array(parts);
+        }
+        value
+    }))
+}
+
+/// Ollama messages: images go in a separate list of bare base64 strings.
+fn ollama_messages<S: Serializer>(
+    messages: &&[ChatMessage],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(messages.iter().map(|message| {
+        let mut value = serde_json::to_value(message).unwrap_or_default();
+        if !message.images.is_empty() {
+            let images: Vec<&str> = message.images.iter().map(|i| i.data.as_str()).collect();
+            value["images"] = serde_json::json!(images);
+        }
+        value
+    }))
+}
+
+impl ChatMessage {
+    pub fn user(content: String) -> Self {
+        Self::new("user", content)
+    }
+
+    pub fn assistant(content: String) -> Self {
+        Self::new("assistant", content)
+    }
+
+    pub(crate) fn new(role: &'static str, content: String) -> Self {
+        Self {
+            role,
+            content,
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+            images: Vec::new(),
+        }
+    }
+}
+
+/// A function call requested by the model.
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct ToolCall {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub function: FunctionCall,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct FunctionCall {
+    pub name: String,
+    /// JSON object, as text.
+    pub arguments: String,
+}
+
+/// Tool-calling rounds allowed before a reply must be text.
+const MAX_TOOL_ROUNDS: usize = 5;
+
+/// Tool calls accepted in one reply; a stream with a higher call index is
+/// rejected rather than allocating for it.
+const MAX_TOOL_CALLS: usize = 64;
+
+/// A chat backend.
+pub trait ChatProvider {
+    /// Send the conversation and return the reply, passing each piece of it
+    /// to `on_delta` as it arrives.
+    fn chat(
+        &self,
+        messages: &[ChatMessage],
+        on_delta: &mut (dyn FnMut(&str) + Send),
+    ) -> impl Future<Output = Result<String, OpenClawError>> + Send;
+}
+
+/// Body of an OpenAI-style `POST /chat/completions`.
+#[derive(Serialize)]
+struct CompletionRequest<'a> {
+    model: &'a str,
+    stream: bool,
+    /// Keeps the conversation in one gateway session.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user: Option<&'a str>,
+    #[serde(serialize_with = "openai_messages")]
+    messages: &'a [ChatMessage],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<&'a [serde_json::Value]>,
+}
+
+/// The OpenClaw Gateway's OpenAI-compatible endpoint (enable
+/// `gateway.http.endpoints.chatCompletions` in OpenClaw).
+pub struct OpenClawGateway<'a> {
+    pub client: &'a reqwest::Client,
+    pub config: &'a OpenClawConfig,
+    pub tools: Option<&'a ToolBridge>,
+}
+
+impl ChatProvider for OpenClawGateway<'_> {
+    async fn chat(
+        &self,
+        messages: &[ChatMessage],
+        on_delta: &mut (dyn FnMut(&str) + Send),
+    ) -> Result<String, OpenClawError> {
+        let config = self.config;
+        if config.agent_id.is_empty() {
+            return Err(OpenClawError::invalid_config(
+                "Agent ID not configured. Open Settings to configure OpenClaw connection.",
+            ));
+        }
+        let model = format!("openclaw:{}", config.agent_id);
+        let token = if config.gateway_token.is_empty() {
+            &config.hooks_token
+        } else {
+            &config.gateway_token
+        };
+        let url = format!(
+            "{}/v1/chat/completions",
+            config.gateway_url.trim_end_matches('/')
+        );
+        let user = Some(config.session_key.as_str()).filter(|key| !key.is_empty());
+        let request = |messages: &[ChatMessage], tools: Option<&[serde_json::Value]>| {
+            let mut request = self
+                .client
+                .post(&url)
+                .header("x-openclaw-agent-id", &config.agent_id)
+                .json(&CompletionRequest {
+                    model: &model,
+                    stream: true,
+                    user,
+                    messages,
+                    tools,
+                });
+            if !token.is_empty() {
+                request = request.bearer_auth(token);
+            }
+            request
+        };
+        complete_with_tools(request, "OpenClaw Gateway", messages, self.tools, on_delta).await
+    }
+}
+
+/// An OpenAI-compatible API.
+pub struct OpenAi<'a> {
+    pub client: &'a reqwest::Client,
+    pub config: &'a OpenAiConfig,
+    pub tools: Option<&'a ToolBridge>,
+}
+
+impl ChatProvider for OpenAi<'_> {
+    async fn chat(
+        &self,
+        messages: &[ChatMessage],
+        on_delta: &mut (dyn FnMut(&str) + Send),
+    ) -> Result<String, OpenClawError> {
+        let url = format!(
+            "{}/chat/completions",
+            self.config.base_url.trim_end_matches('/')
+        );
+        let request = |messages: &[ChatMessage], tools: Option<&[serde_json::Value]>| {
+            let mut request = self.client.post(&url).json(&CompletionRequest {
+                model: &self.config.model,
+                stream: true,
+                user: None,
+                messages,
+                tools,
+            });
+            // Local servers usually take no key.
+            if !self.config.api_key.is_empty() {
+                request = request.bearer_auth(&self.config.api_key);
+            }
+            request
+        };
+        complete_with_tools(request, "the OpenAI API", messages, self.tools, on_delta).await
+    }
+}
+
+/// The Anthropic Messages API.
+pub struct Anthropic<'a> {
+    pub client: &'a reqwest::Client,
+    pub config: &'a AnthropicConfig,
+}
+
+#[derive(Serialize)]
+struct MessagesRequest<'a> {
+    model: &'a str,
+    max_tokens: u32,
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<&'a str>,
+    #[serde(serialize_with = "anthropic_messages")]
+    messages: &'a [ChatMessage],
+}
+
+impl ChatProvider for Anthropic<'_> {
+    async fn chat(
+        &self,
+        messages: &[ChatMessage],
+        on_delta: &mut (dyn FnMut(&str) + Send),
+    ) -> Result<String, OpenClawError> {
+        if self.config.api_key.is_empty() {
+            return Err(OpenClawError::invalid_config(
+                "No Anthropic API key configured",
+            ));
+        }
+        // The Messages API takes the system prompt separately.
+        let (system, messages) = match messages.split_first() {
+            Some((first, rest)) if first.role == "system" => (Some(first.content.as_str()), rest),
+            _ => (None, messages),
+        };
+        let request = self
+            .client
+            .post(format!(
+                "{}/v1/messages",
+                self.config.base_url.trim_end_matches('/')
+            ))
+            .header("x-api-key", &self.config.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .json(&MessagesRequest {
+                model: &self.config.model,
+                max_tokens: self.config.max_tokens,
+                stream: true,
+                system,
+                messages,
+            });
+        let mut reply = String::new();
+        read_lines(request, "the Anthropic API", |line| {
+            let Some(event) =
+                sse_data(line).and_then(|d| serde_json::from_str::<serde_json::Value>(d).ok())
+            else {
+                return Ok(true);
+            };
+            match event["type"].as_str() {
+                Some("content_block_delta") => {
+                    if let Some(delta) = event["delta"]["text"].as_str() {
+                        reply.push_str(delta);
+                        on_delta(delta);
+                    }
+                    Ok(true)
+                }
+                Some("message_stop") => Ok(false),
+                Some("error") => Err(OpenClawError::gateway_error(format!(
+                    "Anthropic API error: {}",
+                    event["error"]["message"].as_str().unwrap_or("unknown")
+                ))),
+                _ => Ok(true),
+            }
+        })
+        .await?;
+        non_empty(reply, "the Anthropic API")
+    }
+}
+
+/// A local Ollama server.
+pub struct Ollama<'a> {
+    pub client: &'a reqwest::Client,
+    pub config: &'a OllamaConfig,
+}
+
+#[derive(Serialize)]
+struct OllamaRequest<'a> {
+    model: &'a str,
+    stream: bool,
+    #[serde(serialize_with = "ollama_messages")]
+    messages: &'a [ChatMessage],
+}
+
+impl ChatProvider for Ollama<'_> {
+    async fn chat(
+        &self,
+        messages: &[ChatMessage],
+        on_delta: &mut (dyn FnMut(&str) + Send),
+    ) -> Result<String, OpenClawError> {
+        let request = self
+            .client
+            .post(format!(
+                "{}/api/chat",
+                self.config.url.trim_end_matches('/')
+            ))
+            .json(&OllamaRequest {
+                model: &self.config.model,
+                stream: true,
+                messages,
+            });
+        // One JSON object per line rather than SSE.
+        let mut reply = String::new();
+        read_lines(request, "Ollama", |line| {
+            let Ok(chunk) = serde_json::from_str::<serde_json::Value>(line) else {
+                return Ok(true);
+            };
+            if let Some(error) = chunk["error"].as_str() {
+                return Err(OpenClawError::gateway_error(format!(
+                    "Ollama error: {error}"
+                )));
+            }
+            if let Some(delta) = chunk["message"]["content"].as_str() {
+                if !delta.is_empty() {
+                    reply.push_str(delta);
+                    on_delta(delta);
+                }
+            }
+            Ok(!chunk["done"].as_bool().unwrap_or(false))
+        })
+        .await?;
+        non_empty(reply, "Ollama")
+    }
+}
+
+/// Send `request` and pass each line of the streamed body to `line` until
+/// the body ends or `line` returns `Ok(false)`.
+pub(crate) async fn read_lines(
+    request: reqwest::RequestBuilder,
+    service: &str,
+    mut line: impl FnMut(&str) -> Result<bool, OpenClawError> + Send,
+) -> Result<(), OpenClawError> {
+    // No overall timeout: a long answer streams for as long as it takes.
+    let mut response = request.send().await.map_err(|e| {
+        if e.is_timeout() {
+            OpenClawError::Timeout {
+                message: format!("{TIMED_OUT}: {service} did not answer"),
+            }
+        } else if e.is_connect() {
+            OpenClawError::NetworkUnreachable {
+                message: format!("Cannot connect to {service}. Check the URL in Settings."),
+            }
+        } else {
+            OpenClawError::other(format!("Chat request to {service} failed: {e}"))
+        }
+    })?;
+    let status = response.status().as_u16();
+    if !response.status().is_success() {
+        let body_text = response.text().await.unwrap_or_default();
+        let preview: String = body_text.chars().take(200).collect();
+        return Err(OpenClawError::from_status(
+            status,
+            format!("{service} returned status {status}: {preview}"),
+        ));
+    }
+
+    // Lines can be split across chunks, so bytes wait for their newline.
+    let mut pending: Vec<u8> = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| {
+        if e.is_timeout() {
+            OpenClawError::Timeout {
+                message: format!("{TIMED_OUT}: {service} stopped responding"),
+            }
+        } else {
+            OpenClawError::other(format!("Chat stream from {service} failed: {e}"))
+        }
+    })? {
+        pending.extend_from_slice(&chunk);
+        while let Some(end) = pending.iter().position(|&b| b == b'\n') {
+            let text: Vec<u8> = pending.drain(..=end).collect();
+            let text = String::from_utf8_lossy(&text);
+            let text = text.trim();
+            if !text.is_empty() && !line(text)? {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The payload of an SSE `data:` line.
+fn sse_data(line: &str) -> Option<&str> {
+    line.strip_prefix("data:").map(str::trim)
+}
+
+/// Run an OpenAI-style conversation, executing the tool calls the model
+/// makes through `tools` until it answers in text. Text from every round is
+/// passed to `on_delta` and makes up the reply.
+async fn complete_with_tools(
+    request: impl Fn(&[ChatMessage], Option<&[serde_json::Value]>) -> reqwest::RequestBuilder
+        + Send
+        + Sync,
+    service: &str,
+    messages: &[ChatMessage],
+    tools: Option<&ToolBridge>,
+    on_delta: &mut (dyn FnMut(&str) + Send),
+) -> Result<String, OpenClawError> {
+    let definitions = tools.map(ToolBridge::definitions);
+    let mut messages = messages.to_vec();
+    let mut reply = String::new();
+    for _ in 0..=MAX_TOOL_ROUNDS {
+        let (text, calls) = stream_completion(
+            request(&messages, definitions.as_deref()),
+            service,
+            on_delta,
+        )
+        .await?;
+        reply.push_str(&text);
+        let Some(tools) = tools.filter(|_| !calls.is_empty()) else {
+            return non_empty(reply, service);
+        };
+        let mut results = Vec::with_capacity(calls.len());
+        for call in &calls {
+            let result = tools
+                .call(&call.function.name, &call.function.arguments)
+                .await;
+            results.push(ChatMessage {
+                tool_call_id: Some(call.id.clone()),
+                ..ChatMessage::new("tool", result)
+            });
+        }
+        messages.push(ChatMessage {
+            tool_calls: calls,
+            ..ChatMessage::assistant(text)
+        });
+        messages.extend(results);
+    }
+    Err(OpenClawError::gateway_error(format!(
+        "{service} kept calling tools without answering"
+    )))
+}
+
+/// Read an OpenAI-style `chat.completion.chunk` stream into its text and
+/// tool calls.
+async fn stream_completion(
+    request: reqwest::RequestBuilder,
+    service: &str,
+    on_delta: &mut (dyn FnMut(&str) + Send),
+) -> Result<(String, Vec<ToolCall>), OpenClawError> {
+    let mut reply = String::new();
+    let mut calls: Vec<ToolCall> = Vec::new();
+    read_lines(request, service, |line| {
+        let Some(data) = sse_data(line) else {
+            return Ok(true);
+        };
+        if data == "[DONE]" {
+            return Ok(false);
+        }
+        let chunk: serde_json::Value = serde_json::from_str(data).unwrap_or_default();
+        let delta = &chunk["choices"][0]["delta"];
+        if let Some(text) = delta["content"].as_str() {
+            if !text.is_empty() {
+                reply.push_str(text);
+                on_delta(text);
+            }
+        }
+        // Calls arrive in fragments, matched up by index.
+        for fragment in delta["tool_calls"].as_array().into_iter().flatten() {
+            let index = fragment["index"].as_u64().unwrap_or(0);
+            let index = usize::try_from(index)
+                .ok()
+                .filter(|&i| i < MAX_TOOL_CALLS)
+                .ok_or_else(|| {
+                    OpenClawError::gateway_error(format!(
+                        "{service} sent an invalid tool call index {index}"
+                    ))
+                })?;
+            if calls.len() <= index {
+                calls.resize_with(index + 1, || ToolCall {
+                    kind: "function",
+                    ..ToolCall::default()
+                });
+            }
+            let call = &mut calls[index];
+            if let Some(id) = fragment["id"].as_str() {
+                call.id.push_str(id);
+            }
+            if let Some(name) = fragment["function"]["name"].as_str() {
+                call.function.name.push_str(name);
+            }
+            if let Some(arguments) = fragment["function"]["arguments"].as_str() {
+                call.function.arguments.push_str(arguments);
+            }
+        }
+        Ok(true)
+    })
+    .await?;
+    calls.retain(|call| !call.function.name.is_empty());
+    Ok((reply, calls))
+}
+
+fn non_empty(reply: String, service: &str) -> Result<String, OpenClawError> {
+    if reply.trim().is_empty() {
+        return Err(OpenClawError::gateway_error(format!(
+            "{service} returned an empty response"
+        )));
+    }
+    Ok(reply)
+}
+
+/// Send `message` to the configured backend, with `context` (persona,
+/// screen state, …) prepended for this turn only.
+///
+/// `system` ([`crate::persona::system_prompt`]) leads the conversation.
+/// Backends without server-side history get the session's summary and as
+/// many recent exchanges as fit the budget, and the new one is remembered
+/// once it succeeds ([`crate::budget`]). The request is counted in
+/// [`crate::usage`] as sent; callers admit it. `tools` is offered to the
+/// OpenAI-style backends (OpenClaw and OpenAI); `images` go with the
+/// message to backends that accept them (all but the built-in model).
+#[allow(clippy::too_many_arguments)]
+pub async fn chat(
+    app: &AppHandle,
+    client: &reqwest::Client,
+    config: &OpenClawConfig,
+    system: Option<String>,
+    message: String,
+    context: Option<String>,
+    images: Vec<Image>,
+    tools: Option<&ToolBridge>,
+    on_delta: &mut (dyn FnMut(&str) + Send),
+) -> Result<String, OpenClawError> {
+    let provider = &config.provider;
+    let full_message = match context {
+        Some(ctx) if !ctx.is_empty() => format!("{}\n\n[USER MESSAGE]\n{}", ctx, message),
+        _ => message.clone(),
+    };
+    crate::prompt_snapshot::record(config, "stream", &full_message);
+
+    let current = ChatMessage {
+        images,
+        ..ChatMessage::user(full_message)
+    };
+    let mut messages = Vec::new();
+    if provider.kind == ProviderKind::OpenClaw {
+        if let Some(system) = system {
+            messages.push(ChatMessage::new("system", system));
+        }
+    } else {
+        let reserved =
+            system.as_deref().map_or(0, budget::estimate_tokens) + budget::message_tokens(&current);
+        let (summary, history) = budget::context(config, reserved);
+        let system = match (system, summary) {
+            (Some(system), Some(summary)) => {
+                Some(format!("{system}\n\n{}", budget::summary_prompt(&summary)))
+            }
+            (None, Some(summary)) => Some(budget::summary_prompt(&summary)),
+            (system, None) => system,
+        };
+        if let Some(system) = system {
+            messages.push(ChatMessage::new("system", system));
+        }
+        messages.extend(history);
+    }
+    messages.push(current);
+
+    let reply = complete(client, config, &messages, tools, on_delta).await?;
+    app.state::<UsageState>().record(
+        &app.state::<ConfigState>(),
+        config,
+        budget::request_tokens(&messages),
+        &reply,
+    );
+    remember(app, client, config, message, reply.clone());
+    Ok(reply)
+}
+
+/// Add an exchange to the conversation history kept for HTTP providers
+/// (see [`budget`]), summarizing the turns it pushes out. The OpenClaw
+/// gateway keeps its own history.
+pub(crate) fn remember(
+    app: &AppHandle,
+    client: &reqwest::Client,
+    config: &OpenClawConfig,
+    message: String,
+    reply: String,
+) {
+    if config.provider.kind == ProviderKind::OpenClaw {
+        return;
+    }
+    let overflow = budget::remember(config, message, reply);
+    if !overflow.is_empty() && config.provider.summarize {
+        tauri::async_runtime::spawn(budget::summarize(
+            app.clone(),
+            client.clone(),
+            config.clone(),
+            overflow,
+        ));
+    }
+}
+
+/// Send `messages` as they are to the configured backend.
+pub(crate) async fn complete(
+    client: &reqwest::Client,
+    config: &OpenClawConfig,
+    messages: &[ChatMessage],
+    tools: Option<&ToolBridge>,
+    on_delta: &mut (dyn FnMut(&str) + Send),
+) -> Result<String, OpenClawError> {
+    let provider = &config.provider;
+    match provider.kind {
+        ProviderKind::OpenClaw => {
+            OpenClawGateway {
+                client,
+                config,
+                tools,
+            }
+            .chat(messages, on_delta)
+            .await
+        }
+        ProviderKind::OpenAi => {
+            OpenAi {
+                client,
+                config: &provider.openai,
+                tools,
+            }
+            .chat(messages, on_delta)
+            .await
+        }
+        ProviderKind::Anthropic => {
+            Anthropic {
+                client,
+                config: &provider.anthropic,
+            }
+            .chat(messages, on_delta)
+            .await
+        }
+        ProviderKind::Ollama => {
+            Ollama {
+                client,
+                config: &provider.ollama,
+            }
+            .chat(messages, on_delta)
+            .await
+        }
+        ProviderKind::Local => {
+            LocalModel {
+                config: &provider.local,
+            }
+            .chat(messages, on_delta)
+            .await
+        }
+    }
+}
@@ -0,0 +1,460 @@
+//! Built-in offline chat with a small quantized model.
+//!
+//! With the `local_llm` cargo feature the app links llama.cpp (through the
+//! `llama-cpp-2` bindings) and can answer chat messages on its own, with no
+//! gateway, API key or separate server. Select it with the `local` chat
+//! backend ([`crate::llm::ProviderKind::Local`]); [`send_chat`] and
+//! [`send_chat_stream`] then generate on this machine.
+//!
+//! Models are GGUF files in `<data dir>/models`, managed with
+//! [`list_local_models`], [`download_local_model`] (which fetches a small
+//! instruction-tuned model, [`DEFAULT_MODEL_URL`], unless given another URL)
+//! and [`delete_local_model`]. These work in every build; only generation
+//! needs the feature. The prompt is laid out with the chat template stored
+//! in the model file, and the model stays loaded after first use.
+//!
+//! [`send_chat`]: crate::openclaw::send_chat
+//! [`send_chat_stream`]: crate::openclaw::send_chat_stream
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, State};
+use tokio::io::AsyncWriteExt;
+
+use crate::config::ConfigState;
+use crate::llm::{ChatMessage, ChatProvider};
+use crate::openclaw::{HttpClient, OpenClawError};
+
+/// Fetched by [`download_local_model`] when no URL is given: Qwen2.5 0.5B
+/// Instruct, 4-bit (about 400 MB), small enough for any machine the app
+/// runs on.
+pub const DEFAULT_MODEL_URL: &str = "https://huggingface.co/Qwen/Qwen2.5-0.5B-Instruct-GGUF/resolve/main/qwen2.5-0.5b-instruct-q4_k_m.gguf";
+
+/// Minimum time between `"local-model-download-progress"` events.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Whether a model download is in progress.
+static DOWNLOADING: AtomicBool = AtomicBool::new(false);
+
+/// Settings for the built-in model.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LocalLlmConfig {
+    /// File name of the model in `<data dir>/models`.
+    pub model: String,
+    /// Context window in tokens; the prompt and reply must fit in it.
+    pub context_tokens: u32,
+    /// Longest reply, in tokens.
+    pub max_tokens: u32,
+    pub temperature: f32,
+}
+
+impl Default for LocalLlmConfig {
+    fn default() -> Self {
+        Self {
+            model: "qwen2.5-0.5b-instruct-q4_k_m.gguf".to_string(),
+            context_tokens: 4096,
+            max_tokens: 256,
+            temperature: 0.8,
+        }
+    }
+}
+
+/// A model file, as listed by [`list_local_models`].
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalModelFile {
+    pub name: String,
+    pub size_bytes: u64,
+}
+
+/// Payload of `"local-model-download-progress"` events.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadProgress {
+    pub name: String,
+    pub downloaded_bytes: u64,
+    /// `None` if the server did not say.
+    pub total_bytes: Option<u64>,
+}
+
+fn models_dir() -> PathBuf {
+    crate::memory::data_dir().join("models")
+}
+
+/// Path of the model called `name`, refusing anything that would leave the
+/// models directory.
+fn model_path(name: &str) -> Result<PathBuf, String> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(format!("Invalid model name: {name:?}"));
+    }
+    if !name.ends_with(".gguf") {
+        return Err(format!("Model files must be GGUF (.gguf): {name}"));
+    }
+    Ok(models_dir().join(name))
+}
+
+/// The built-in model as a chat backend.
+pub struct LocalModel<'a> {
+    pub config: &'a LocalLlmConfig,
+}
+
+impl ChatProvider for LocalModel<'_> {
+    async fn chat(
+        &self,
+        messages: &[ChatMessage],
+        on_delta: &mut (dyn FnMut(&str) + Send),
+    ) -> Result<String, OpenClawError> {
+        if messages.iter().any(|m| !m.images.is_empty()) {
+            return Err(OpenClawError::invalid_config(
+                "The built-in model cannot see images",
+            ));
+        }
+        let path = model_path(&self.config.model).map_err(OpenClawError::invalid_config)?;
+        if !path.exists() {
+            return Err(OpenClawError::invalid_config(format!(
+                "Local model {} is not downloaded. Download it in Settings.",
+                self.config.model
+            )));
+        }
+        let config = self.config.clone();
+        let messages = messages.to_vec();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+        // Generation stops once the receiver is gone, e.g. after cancel_chat
+        // drops this future.
+        let task = tokio::task::spawn_blocking(move || {
+            llama::generate(&path, &config, &messages, &mut |piece| {
+                tx.send(piece.to_string()).is_ok()
+            })
+        });
+        while let Some(piece) = rx.recv().await {
+            on_delta(&piece);
+        }
+        let reply = task
+            .await
+            .map_err(|e| OpenClawError::other(format!("Task join error: {e}")))??;
+        if reply.trim().is_empty() {
+            return Err(OpenClawError::other(
+                "The local model returned an empty response",
+            ));
+        }
+        Ok(reply)
+    }
+}
+
+#[cfg(feature = "local_llm")]
+mod llama {
+    use llama_cpp_2::context::params::LlamaContextParams;
+    use llama_cpp_2::llama_backend::LlamaBackend;
+    use llama_cpp_2::llama_batch::LlamaBatch;
+    use llama_cpp_2::model::params::LlamaModelParams;
+    use llama_cpp_2::model::{AddBos, LlamaChatMessage, LlamaModel, Special};
+    use llama_cpp_2::sampling::LlamaSampler;
+    use std::num::NonZeroU32;
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, Mutex, OnceLock};
+
+    use super::LocalLlmConfig;
+    use crate::llm::ChatMessage;
+
+    /// Loaded model and the path it came from.
+    static MODEL: Mutex<Option<(PathBuf, Arc<LlamaModel>)>> = Mutex::new(None);
+
+    /// llama.cpp may only be initialized once per process.
+    fn backend() -> Result<&'static LlamaBackend, String> {
+        static BACKEND: OnceLock<Result<LlamaBackend, String>> = OnceLock::new();
+        BACKEND
+            .get_or_init(|| {
+                LlamaBackend::init().map_err(|e| format!("Failed to start llama.cpp: {e}"))
+            })
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+
+    fn model(backend: &LlamaBackend, path: &Path) -> Result<Arc<LlamaModel>, String> {
+        let mut cached = MODEL.lock().map_err(|e| e.to_string())?;
+        if let Some((loaded, model)) = cached.as_ref() {
+            if loaded == path {
+                return Ok(model.clone());
+            }
+        }
+        // Release the previous model before loading the next one.
+        *cached = None;
+        let model = LlamaModel::load_from_file(backend, path, &LlamaModelParams::default())
+            .map_err(|e| format!("Failed to load model {}: {e}", path.display()))?;
+        let model = Arc::new(model);
+        *cached = Some((path.to_path_buf(), model.clone()));
+        Ok(model)
+    }
+
+    /// Drop the cached model if it was loaded from `path`.
+    pub(super) fn evict(path: &Path) {
+        if let Ok(mut cached) = MODEL.lock() {
+            if cached.as_ref().is_some_and(|(loaded, _)| loaded == path) {
+                *cached = None;
+            }
+        }
+    }
+
+    /// Generate a reply to `messages`, passing each piece to `emit` until it
+    /// returns `false`.
+    pub(super) fn generate(
+        path: &Path,
+        config: &LocalLlmConfig,
+        messages: &[ChatMessage],
+        emit: &mut dyn FnMut(&str) -> bool,
+    ) -> Result<String, String> {
+        let backend = backend()?;
+        let model = model(backend, path)?;
+
+        let chat = messages
+            .iter()
+            .map(|m| LlamaChatMessage::new(m.role.to_string(), m.content.clone()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Invalid chat message: {e}"))?;
+        let template = model
+            .chat_template(None)
+            .map_err(|e| format!("Model has no chat template: {e}"))?;
+        let prompt = model
+            .apply_chat_template(&template, &chat, true)
+            .map_err(|e| format!("Failed to apply chat template: {e}"))?;
+        let tokens = model
+            .str_to_token(&prompt, AddBos::Always)
+            .map_err(|e| format!("Failed to tokenize prompt: {e}"))?;
+        if tokens.len() + config.max_tokens as usize > config.context_tokens as usize {
+            return Err(format!(
+                "The conversation ({} tokens) does not fit the local model's context; \
+                 lower historyTurns or raise contextTokens",
+                tokens.len()
+            ));
+        }
+
+        let params =
+            LlamaContextParams::default().with_n_ctx(NonZeroU32::new(config.context_tokens));
+        let mut ctx = model
+            .new_context(backend, params)
+            .map_err(|e| format!("Failed to create llama.cpp context: {e}"))?;
+
+        let mut batch = LlamaBatch::new(tokens.len().max(512), 1);
+        let last = tokens.len() as i32 - 1;
+        for (i, token) in (0_i32..).zip(tokens) {
+            batch
+                .add(token, i, &[0], i == last)
+                .map_err(|e| format!("Failed to queue prompt: {e}"))?;
+        }
+        ctx.decode(&mut batch)
+            .map_err(|e| format!("Failed to evaluate prompt: {e}"))?;
+
+        let mut seed = [0u8; 4];
+        let _ = getrandom::getrandom(&mut seed);
+        let mut sampler = LlamaSampler::chain_simple([
+            LlamaSampler::temp(config.temperature),
+            LlamaSampler::dist(u32::from_le_bytes(seed)),
+        ]);
+
+        let mut reply = String::new();
+        // Token bytes can end inside a multi-byte character.
+        let mut pending: Vec<u8> = Vec::new();
+        let mut position = batch.n_tokens();
+        for _ in 0..config.max_tokens {
+            let token = sampler.sample(&ctx, batch.n_tokens() - 1);
+            sampler.accept(token);
+            if model.is_eog_token(token) {
+                break;
+            }
+            let bytes = model
+                .token_to_bytes(token, Special::Tokenize)
+                .map_err(|e| format!("Failed to decode token: {e}"))?;
+            pending.extend_from_slice(&bytes);
+            let piece = take_utf8(&mut pending);
+            if !piece.is_empty() {
+                reply.push_str(&piece);
+                if !emit(&piece) {
+                    break;
+                }
+            }
+
+            batch.clear();
+            batch
+                .add(token, position, &[0], true)
+                .map_err(|e| format!("Failed to queue token: {e}"))?;
+            position += 1;
+            ctx.decode(&mut batch)
+                .map_err(|e| format!("Failed to generate: {e}"))?;
+        }
+        Ok(reply)
+    }
+
+    /// Remove and return the complete UTF-8 text at the start of `bytes`,
+    /// leaving a trailing partial character for the next token.
+    fn take_utf8(bytes: &mut Vec<u8>) -> String {
+        let complete = match std::str::from_utf8(bytes) {
+            Ok(text) => text.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => bytes.len(),
+        };
+        let text = String::from_utf8_lossy(&bytes[..complete]).into_owned();
+        bytes.drain(..complete);
+        text
+    }
+}
+
+#[cfg(not(feature = "local_llm"))]
+mod llama {
+    use std::path::Path;
+
+    use super::LocalLlmConfig;
+    use crate::llm::ChatMessage;
+
+    pub(super) fn evict(_path: &Path) {}
+
+    pub(super) fn generate(
+        _path: &Path,
+        _config: &LocalLlmConfig,
+        _messages: &[ChatMessage],
+        _emit: &mut dyn FnMut(&str) -> bool,
+    ) -> Result<String, String> {
+        Err("This build does not include the local model (cargo feature `local_llm`)".to_string())
+    }
+}
+
+// ---------- Commands ----------
+
+/// IPC command: the downloaded models, by name.
+#[tauri::command]
+pub fn list_local_models() -> Result<Vec<LocalModelFile>, String> {
+    let entries = match std::fs::read_dir(models_dir()) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read models directory: {e}")),
+    };
+    let mut models: Vec<LocalModelFile> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let metadata = entry.metadata().ok()?;
+            (name.ends_with(".gguf") && metadata.is_file()).then_some(LocalModelFile {
+                name,
+                size_bytes: metadata.len(),
+            })
+        })
+        .collect();
+    models.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(models)
+}
+
+/// IPC command: download a GGUF model into the models directory and return
+/// its name. `url` defaults to [`DEFAULT_MODEL_URL`].
+///
+/// Emits `"local-model-download-progress"` while downloading. The file is
+/// written under a `.part` name and only renamed once complete, so an
+/// interrupted download never looks like a model.
+#[tauri::command]
+pub async fn download_local_model(
+    app: AppHandle,
+    http: State<'_, HttpClient>,
+    config_state: State<'_, ConfigState>,
+    url: Option<String>,
+) -> Result<String, String> {
+    let client = http.client(&config_state.get()?)?;
+    let url = url.unwrap_or_else(|| DEFAULT_MODEL_URL.to_string());
+    let name = url
+        .split(['?', '#'])
+        .next()
+        .and_then(|path| path.rsplit('/').next())
+        .unwrap_or_default()
+        .to_string();
+    let path = model_path(&name)?;
+    if DOWNLOADING.swap(true, Ordering::SeqCst) {
+        return Err("A model download is already in progress".to_string());
+    }
+    let result = download(&app, &client, &url, &name, &path).await;
+    DOWNLOADING.store(false, Ordering::SeqCst);
+    if let Err(e) = &result {
+        eprintln!("[local_llm] Download of {name} failed: {e}");
+    }
+    result.map(|()| name)
+}
+
+async fn download(
+    app: &AppHandle,
+    client: &reqwest::Client,
+    url: &str,
+    name: &str,
+    path: &std::path::Path,
+) -> Result<(), String> {
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|e| format!("Model download failed: {e}"))?;
+    let total_bytes = response.content_length();
+
+    tokio::fs::create_dir_all(models_dir())
+        .await
+        .map_err(|e| format!("Failed to create models directory: {e}"))?;
+    let partial = path.with_extension("gguf.part");
+    let mut file = tokio::fs::File::create(&partial)
+        .await
+        .map_err(|e| format!("Failed to create {}: {e}", partial.display()))?;
+
+    let mut downloaded_bytes = 0u64;
+    let mut last_progress = Instant::now();
+    let written: Result<(), String> = async {
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| format!("Model download failed: {e}"))?
+        {
+            file.write_all(&chunk)
+                .await
+                .map_err(|e| format!("Failed to write model: {e}"))?;
+            downloaded_bytes += chunk.len() as u64;
+            if last_progress.elapsed() >= PROGRESS_INTERVAL {
+                last_progress = Instant::now();
+                let _ = app.emit(
+                    "local-model-download-progress",
+                    DownloadProgress {
+                        name: name.to_string(),
+                        downloaded_bytes,
+                        total_bytes,
+                    },
+                );
+            }
+        }
+        file.flush()
+            .await
+            .map_err(|e| format!("Failed to write model: {e}"))
+    }
+    .await;
+    drop(file);
+    if let Err(e) = written {
+        let _ = tokio::fs::remove_file(&partial).await;
+        return Err(e);
+    }
+
+    let _ = app.emit(
+        "local-model-download-progress",
+        DownloadProgress {
+            name: name.to_string(),
+            downloaded_bytes,
+            total_bytes,
+        },
+    );
+    // A model being replaced must not stay loaded.
+    llama::evict(path);
+    tokio::fs::rename(&partial, path)
+        .await
+        .map_err(|e| format!("Failed to save model: {e}"))
+}
+
+/// IPC command: delete a downloaded model.
+#[tauri::command]
+pub fn delete_local_model(name: String) -> Result<(), String> {
+    let path = model_path(&name)?;
+    llama::evict(&path);
+    std::fs::remove_file(&path).map_err(|e| format!("Failed to delete {name}: {e}"))
+}
@@ -0,0 +1,254 @@
+//! Local HTTP API.
+//!
+//! A small HTTP/1.1 server on the loopback interface for scripts and tools
+//! running on the same machine. It is off by default and enabled via
+//! [`LocalApiConfig`] in [`crate::config::AppSettings`]; changes take effect
+//! on the next launch.
+//!
+//! Routes:
+//!
+//! - `GET /healthz` — [`crate::health::HealthReport`] as JSON; `200` when
+//!   every subsystem is healthy, `503` otherwise.
+//!
+//! Every connection serves a single request and is then closed.
+
+use crate::config::ConfigState;
+use crate::health;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Largest accepted request head (request line + headers).
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+
+/// Largest accepted request body.
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// Time allowed for a client to send its request.
+pub(crate) const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Local API section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LocalApiConfig {
+    pub enabled: bool,
+    /// Port on `127.0.0.1`.
+    pub port: u16,
+}
+
+impl Default for LocalApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 18790,
+        }
+    }
+}
+
+/// A parsed request.
+pub struct Request {
+    pub method: String,
+    /// Path without the query string.
+    pub path: String,
+    /// Header names are lowercased.
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// The first value of header `name` (lowercase).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// A response to write back.
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn json<T: Serialize>(status: u16, value: &T) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: serde_json::to_vec(value).unwrap_or_default(),
+        }
+    }
+
+    pub fn error(status: u16, message: &str) -> Self {
+        Self::json(status, &serde_json::json!({ "error": message }))
+    }
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "",
+    }
+}
+
+/// Start the server if enabled in settings.
+pub fn start_local_api(app: AppHandle) {
+    let config = app
+        .state::<ConfigState>()
+        .get_settings()
+        .map(|s| s.local_api)
+        .unwrap_or_default();
+    if !config.enabled {
+        return;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let listener = match TcpListener::bind(("127.0.0.1", config.port)).await {
+            Ok(l) => l,
+            Err(e) => {
+                eprintln!("[localapi] Failed to bind 127.0.0.1:{}: {e}", config.port);
+                return;
+            }
+        };
+        println!("[localapi] Listening on http://127.0.0.1:{}", config.port);
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = serve(&app, stream).await {
+                            eprintln!("[localapi] {e}");
+                        }
+                    });
+                }
+                Err(e) => eprintln!("[localapi] Accept failed: {e}"),
+            }
+        }
+    });
+}
+
+/// Read one request, route it and write the response.
+async fn serve(app: &AppHandle, mut stream: TcpStream) -> Result<(), String> {
+    let response = match tokio::time::timeout(READ_TIMEOUT, read_request(&mut stream)).await {
+        Ok(Ok(request)) => route(app, &request).await,
+        Ok(Err(response)) => response,
+        Err(_) => return Err("Client timed out".to_string()),
+    };
+    write_response(&mut stream, &response).await
+}
+
+/// Write `response` and close the connection.
+pub(crate) async fn write_response(
+    stream: &mut TcpStream,
+    response: &Response,
+) -> Result<(), String> {
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason_phrase(response.status),
+        response.content_type,
+        response.body.len()
+    );
+    stream
+        .write_all(head.as_bytes())
+        .await
+        .map_err(|e| format!("Write failed: {e}"))?;
+    stream
+        .write_all(&response.body)
+        .await
+        .map_err(|e| format!("Write failed: {e}"))?;
+    let _ = stream.shutdown().await;
+    Ok(())
+}
+
+/// Read and parse the request head and, if it has a `Content-Length`, the
+/// body.
+///
+/// Errors are returned as the response to send.
+pub(crate) async fn read_request(stream: &mut TcpStream) -> Result<Request, Response> {
+    let mut buf = Vec::with_capacity(1024);
+    let head_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        if buf.len() > MAX_HEAD_BYTES {
+            return Err(Response::error(413, "Request head too large"));
+        }
+        let mut chunk = [0u8; 1024];
+        let n = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|_| Response::error(400, "Read failed"))?;
+        if n == 0 {
+            return Err(Response::error(400, "Incomplete request"));
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&buf[..head_end]).into_owned();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let (Some(method), Some(target)) = (request_line.next(), request_line.next()) else {
+        return Err(Response::error(400, "Malformed request line"));
+    };
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+
+    let length = match headers.iter().find(|(n, _)| n == "content-length") {
+        Some((_, v)) => v
+            .parse::<usize>()
+            .map_err(|_| Response::error(400, "Invalid Content-Length"))?,
+        None => 0,
+    };
+    if length > MAX_BODY_BYTES {
+        return Err(Response::error(413, "Request body too large"));
+    }
+    let mut body = buf.split_off(head_end + 4);
+    while body.len() < length {
+        let mut chunk = [0u8; 4096];
+        let n = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|_| Response::error(400, "Read failed"))?;
+        if n == 0 {
+            return Err(Response::error(400, "Incomplete request body"));
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    body.truncate(length);
+
+    Ok(Request {
+        method: method.to_string(),
+        path: target.split('?').next().unwrap_or(target).to_string(),
+        headers,
+        body,
+    })
+}
+
+async fn route(app: &AppHandle, request: &Request) -> Response {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/healthz") => {
+            let report = health::report(app).await;
+            Response::json(if report.is_ok() { 200 } else { 503 }, &report)
+        }
+        (_, "/healthz") => Response::error(405, "Method not allowed"),
+        _ => Response::error(404, "Not found"),
+    }
+}
@@ -0,0 +1,185 @@
+//! Smoothed cursor stream for the character's gaze.
+//!
+//! Raw `"mouse-move"` events are jittery and arrive at the input device's
+//! rate. This module runs a separate thread that samples the latest cursor
+//! position from [`crate::hittest::last_cursor_position`] at a fixed,
+//! configurable rate, filters it, and emits `"look-target"` events the VRM
+//! look-at controller can consume directly.
+//!
+//! Two filters are available:
+//!
+//! - **One Euro** (default) — adaptive low-pass that is smooth when the cursor
+//!   moves slowly and responsive when it moves fast.
+//! - **Exponential** — fixed-factor smoothing, cheaper and more predictable.
+//!
+//! Events are only emitted while the filtered target is still moving, so a
+//! parked cursor stops waking the webview.
+
+use crate::config::ConfigState;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// How long the thread sleeps between settings checks while disabled.
+const DISABLED_SLEEP: Duration = Duration::from_millis(500);
+
+/// Minimum movement (logical pixels) of the filtered target worth emitting.
+const EMIT_EPSILON_PX: f64 = 0.5;
+
+/// Smoothing algorithm for the look-at stream.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum SmoothingMode {
+    #[default]
+    OneEuro,
+    Exponential,
+}
+
+/// Look-at section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LookAtConfig {
+    pub enabled: bool,
+    /// Emission rate of `"look-target"` events.
+    pub rate_hz: u32,
+    pub mode: SmoothingMode,
+    /// Exponential mode: weight of each new sample (0.0–1.0).
+    pub alpha: f64,
+    /// One Euro mode: cutoff frequency (Hz) at rest. Lower = smoother.
+    pub min_cutoff: f64,
+    /// One Euro mode: how quickly the cutoff rises with speed. Higher = less lag.
+    pub beta: f64,
+}
+
+impl Default for LookAtConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            rate_hz: 30,
+            mode: SmoothingMode::OneEuro,
+            alpha: 0.25,
+            min_cutoff: 1.0,
+            beta: 0.007,
+        }
+    }
+}
+
+/// Payload of `"look-target"` events (window-relative, logical pixels).
+#[derive(Clone, Serialize)]
+pub struct LookTarget {
+    pub x: f64,
+    pub y: f64,
+}
+
+// ---------- Filters ----------
+
+/// Cutoff for the One Euro derivative filter (Hz).
+const DERIVATIVE_CUTOFF: f64 = 1.0;
+
+/// Smoothing factor for a first-order low-pass at `cutoff` Hz.
+fn low_pass_alpha(cutoff: f64, dt: f64) -> f64 {
+    let tau = 1.0 / (2.0 * PI * cutoff);
+    1.0 / (1.0 + tau / dt)
+}
+
+/// One Euro filter for a single axis.
+#[derive(Default)]
+struct OneEuroAxis {
+    value: Option<f64>,
+    derivative: f64,
+}
+
+impl OneEuroAxis {
+    fn filter(&mut self, raw: f64, dt: f64, min_cutoff: f64, beta: f64) -> f64 {
+        let Some(prev) = self.value else {
+            self.value = Some(raw);
+            return raw;
+        };
+        let raw_derivative = (raw - prev) / dt;
+        let a_d = low_pass_alpha(DERIVATIVE_CUTOFF, dt);
+        self.derivative += a_d * (raw_derivative - self.derivative);
+
+        let cutoff = min_cutoff + beta * self.derivative.abs();
+        let filtered = prev + low_pass_alpha(cutoff, dt) * (raw - prev);
+        self.value = Some(filtered);
+        filtered
+    }
+}
+
+/// Per-axis filter state for both modes.
+#[derive(Default)]
+struct TargetFilter {
+    x: OneEuroAxis,
+    y: OneEuroAxis,
+}
+
+impl TargetFilter {
+    fn filter(&mut self, config: &LookAtConfig, raw: (f64, f64), dt: f64) -> (f64, f64) {
+        match config.mode {
+            SmoothingMode::OneEuro => (
+                self.x.filter(raw.0, dt, config.min_cutoff, config.beta),
+                self.y.filter(raw.1, dt, config.min_cutoff, config.beta),
+            ),
+            SmoothingMode::Exponential => {
+                let alpha = config.alpha.clamp(0.0, 1.0);
+                let step = |axis: &mut OneEuroAxis, raw: f64| {
+                    let next = axis.value.map_or(raw, |prev| prev + alpha * (raw - prev));
+                    axis.value = Some(next);
+                    next
+                };
+                (step(&mut self.x, raw.0), step(&mut self.y, raw.1))
+            }
+        }
+    }
+}
+
+// ---------- Thread ----------
+
+/// Start the look-target thread.
+///
+/// Settings are re-read every frame, so enabling the stream or changing its
+/// rate from Settings takes effect immediately.
+pub fn start_look_target_stream(app: AppHandle, running: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        let mut filter = TargetFilter::default();
+        let mut last_emitted: Option<(f64, f64)> = None;
+        let mut last_frame = Instant::now();
+
+        while running.load(Ordering::Relaxed) {
+            let config = app
+                .state::<ConfigState>()
+                .get_settings()
+                .map(|s| s.look_at)
+                .unwrap_or_default();
+            if !config.enabled {
+                filter = TargetFilter::default();
+                last_emitted = None;
+                thread::sleep(DISABLED_SLEEP);
+                last_frame = Instant::now();
+                continue;
+            }
+
+            let interval = Duration::from_secs_f64(1.0 / config.rate_hz.clamp(1, 240) as f64);
+            thread::sleep(interval);
+            let dt = last_frame.elapsed().as_secs_f64().max(1e-3);
+            last_frame = Instant::now();
+
+            let Some((x, y)) = crate::hittest::last_cursor_position() else {
+                continue;
+            };
+            let (fx, fy) = filter.filter(&config, (x as f64, y as f64), dt);
+
+            let moved = last_emitted.is_none_or(|(lx, ly)| {
+                (fx - lx).abs() >= EMIT_EPSILON_PX || (fy - ly).abs() >= EMIT_EPSILON_PX
+            });
+            if moved {
+                last_emitted = Some((fx, fy));
+                let _ = app.emit("look-target", LookTarget { x: fx, y: fy });
+            }
+        }
+    });
+}
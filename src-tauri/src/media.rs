@@ -0,0 +1,308 @@
+//! "Now Playing" metadata from the system's media players.
+//!
+//! [`get_now_playing`] returns the title, artist, album, artwork and playback
+//! state of whatever is playing so the character can name the song it is
+//! dancing to. A background thread polls every [`POLL_INTERVAL`] and emits
+//! `"media-changed"` (a [`NowPlaying`] or `null`) when any of it changes.
+//! While nothing is playing it backs off to [`IDLE_POLL_INTERVAL`], since
+//! every poll spawns a process on macOS and Linux; music picked up by
+//! [`crate::audio`] ([`wake`]) brings it back at once.
+//!
+//! Platform backends:
+//!
+//! - **macOS**: AppleScript against Spotify and Music. The MediaRemote
+//!   framework that backs Control Center no longer answers third-party apps
+//!   (macOS 15.4+), so the players are asked directly. Only running players
+//!   are queried, so neither is ever launched.
+//! - **Windows**: `GlobalSystemMediaTransportControlsSessionManager`, which
+//!   covers every app that shows up in the volume flyout. Artwork is written
+//!   to a temp file (read it with `read_file_bytes`).
+//! - **Linux**: MPRIS through `playerctl`, if installed.
+//!
+//! Players whose app is redacted ([`crate::redaction`]) are never reported.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config::ConfigState;
+
+/// How often the players are polled for `"media-changed"`.
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Longest gap between polls while nothing is playing; the interval
+/// doubles from [`POLL_INTERVAL`] up to this.
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Granularity of the thread's sleep, so [`wake`] applies promptly.
+const WAKE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Last value seen by the polling thread.
+static LATEST: Mutex<Option<NowPlaying>> = Mutex::new(None);
+
+/// Set by [`wake`].
+static WAKE: AtomicBool = AtomicBool::new(false);
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaybackState {
+    Playing,
+    Paused,
+    Stopped,
+}
+
+/// What a media player is currently playing.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NowPlaying {
+    pub title: String,
+    pub artist: String,
+    pub album: Option<String>,
+    /// Artwork URL, or a local file path on Windows.
+    pub artwork: Option<String>,
+    pub state: PlaybackState,
+    /// Player the metadata came from (e.g. `"Spotify"`).
+    pub source: String,
+}
+
+/// Parse the six-line format produced by the macOS and Linux backends:
+/// state, title, artist, album, artwork, source.
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), allow(dead_code))]
+fn parse_lines(output: &str) -> Option<NowPlaying> {
+    let mut lines = output.lines().map(str::trim);
+    let state = match lines.next()?.to_ascii_lowercase().as_str() {
+        "playing" => PlaybackState::Playing,
+        "paused" => PlaybackState::Paused,
+        _ => PlaybackState::Stopped,
+    };
+    let title = lines.next()?.to_string();
+    if title.is_empty() {
+        return None;
+    }
+    let mut optional = || {
+        lines
+            .next()
+            .filter(|s| !s.is_empty() && *s != "missing value")
+            .map(String::from)
+    };
+    let artist = optional().unwrap_or_default();
+    let album = optional();
+    let artwork = optional();
+    let source = optional().unwrap_or_default();
+    Some(NowPlaying {
+        title,
+        artist,
+        album,
+        artwork,
+        state,
+        source,
+    })
+}
+
+/// Query the platform backend, preferring a player that is playing.
+#[cfg(target_os = "macos")]
+fn query() -> Option<NowPlaying> {
+    const PLAYERS: [(&str, &str); 2] = [
+        ("Spotify", "artwork url of current track"),
+        ("Music", "\"\""),
+    ];
+
+    let run = |script: &str| -> Option<String> {
+        let output = std::process::Command::new("osascript")
+            .arg("-e")
+            .arg(script)
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+
+    let mut found: Vec<NowPlaying> = PLAYERS
+        .iter()
+        .filter(|(name, _)| {
+            run(&format!("application \"{name}\" is running")).is_some_and(|s| s.trim() == "true")
+        })
+        .filter_map(|(name, artwork)| {
+            let script = format!(
+                "tell application \"{name}\" to return (player state as text) & linefeed & \
+                 name of current track & linefeed & artist of current track & linefeed & \
+                 album of current track & linefeed & {artwork} & linefeed & \"{name}\""
+            );
+            run(&script).and_then(|out| parse_lines(&out))
+        })
+        .collect();
+    found.sort_by_key(|np| np.state != PlaybackState::Playing);
+    found.into_iter().next()
+}
+
+#[cfg(target_os = "windows")]
+fn query() -> Option<NowPlaying> {
+    use windows::Media::Control::{
+        GlobalSystemMediaTransportControlsSessionManager as SessionManager,
+        GlobalSystemMediaTransportControlsSessionPlaybackStatus as Status,
+    };
+    use windows::Win32::System::Com::{CoInitializeEx, CoUninitialize, COINIT_MULTITHREADED};
+
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+    }
+    let result = (|| -> windows::core::Result<Option<NowPlaying>> {
+        let manager = SessionManager::RequestAsync()?.get()?;
+        let Ok(session) = manager.GetCurrentSession() else {
+            return Ok(None);
+        };
+        let props = session.TryGetMediaPropertiesAsync()?.get()?;
+        let title = props.Title()?.to_string();
+        if title.is_empty() {
+            return Ok(None);
+        }
+        let state = match session.GetPlaybackInfo()?.PlaybackStatus()? {
+            Status::Playing => PlaybackState::Playing,
+            Status::Paused => PlaybackState::Paused,
+            _ => PlaybackState::Stopped,
+        };
+        let artist = props.Artist()?.to_string();
+        let album = props
+            .AlbumTitle()
+            .ok()
+            .map(|s| s.to_string())
+            .filter(|s| !s.is_empty());
+        let source = session.SourceAppUserModelId()?.to_string();
+        let artwork = save_thumbnail(&props, &title, &artist);
+        Ok(Some(NowPlaying {
+            title,
+            artist,
+            album,
+            artwork,
+            state,
+            source,
+        }))
+    })();
+    unsafe {
+        CoUninitialize();
+    }
+    result.unwrap_or_else(|e| {
+        eprintln!("[media] Media session query failed: {e}");
+        None
+    })
+}
+
+/// Write the session thumbnail to a temp file named after the track, once.
+#[cfg(target_os = "windows")]
+fn save_thumbnail(
+    props: &windows::Media::Control::GlobalSystemMediaTransportControlsSessionMediaProperties,
+    title: &str,
+    artist: &str,
+) -> Option<String> {
+    use std::hash::{Hash, Hasher};
+    use windows::Storage::Streams::{DataReader, InputStreamOptions};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    (title, artist).hash(&mut hasher);
+    let path = std::env::temp_dir().join(format!("openmaiwaifu-artwork-{:x}", hasher.finish()));
+    if path.exists() {
+        return Some(path.to_string_lossy().into_owned());
+    }
+
+    let stream = props.Thumbnail().ok()?.OpenReadAsync().ok()?.get().ok()?;
+    let size = stream.Size().ok()? as u32;
+    let reader = DataReader::CreateDataReader(&stream).ok()?;
+    reader
+        .SetInputStreamOptions(InputStreamOptions::ReadAhead)
+        .ok()?;
+    let loaded = reader.LoadAsync(size).ok()?.get().ok()?;
+    let mut bytes = vec![0u8; loaded as usize];
+    reader.ReadBytes(&mut bytes).ok()?;
+    std::fs::write(&path, bytes).ok()?;
+    Some(path.to_string_lossy().into_owned())
+}
+
+#[cfg(target_os = "linux")]
+fn query() -> Option<NowPlaying> {
+    let output = std::process::Command::new("playerctl")
+        .args([
+            "metadata",
+            "--format",
+            "{{status}}\n{{title}}\n{{artist}}\n{{album}}\n{{mpris:artUrl}}\n{{playerName}}",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_lines(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn query() -> Option<NowPlaying> {
+    None
+}
+
+/// Query the players, dropping redacted ones.
+fn now_playing(app: &AppHandle) -> Option<NowPlaying> {
+    let playing = query()?;
+    let redaction = app
+        .state::<ConfigState>()
+        .get_settings()
+        .map(|s| s.redaction)
+        .unwrap_or_default();
+    (!redaction.is_app_redacted(&playing.source)).then_some(playing)
+}
+
+/// Poll the players now instead of waiting out an idle interval.
+pub(crate) fn wake() {
+    WAKE.store(true, Ordering::Relaxed);
+}
+
+/// Start the `"media-changed"` polling thread.
+pub fn start_media_monitor(app: AppHandle, running: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        let mut last: Option<NowPlaying> = None;
+        let mut interval = POLL_INTERVAL;
+        while running.load(Ordering::Relaxed) {
+            let current = now_playing(&app);
+            let playing = current
+                .as_ref()
+                .is_some_and(|np| np.state == PlaybackState::Playing);
+            interval = if playing {
+                POLL_INTERVAL
+            } else {
+                (interval * 2).min(IDLE_POLL_INTERVAL)
+            };
+            if current != last {
+                let _ = app.emit("media-changed", current.clone());
+                crate::events::publish(&app, "media.changed", &current);
+                if let Ok(mut latest) = LATEST.lock() {
+                    latest.clone_from(&current);
+                }
+                last = current;
+            }
+            let started = Instant::now();
+            while started.elapsed() < interval
+                && running.load(Ordering::Relaxed)
+                && !WAKE.swap(false, Ordering::Relaxed)
+            {
+                thread::sleep(WAKE_INTERVAL);
+            }
+        }
+    });
+}
+
+/// What the polling thread last saw playing, without asking the players.
+pub(crate) fn latest() -> Option<NowPlaying> {
+    LATEST.lock().ok().and_then(|latest| latest.clone())
+}
+
+/// IPC command: what is playing right now, or `None`.
+#[tauri::command]
+pub async fn get_now_playing(app: AppHandle) -> Option<NowPlaying> {
+    tauri::async_runtime::spawn_blocking(move || now_playing(&app))
+        .await
+        .ok()
+        .flatten()
+}
@@ -1,7 +1,8 @@
 //! Persistent data file storage for memory backup.
 //!
 //! Provides generic key-value file storage in the same config directory
-//! used by [`crate::config`]:
+//! used by [`crate::config`] (unless moved with `AIDC_CONFIG_DIR` or
+//! `--config-dir`):
 //! ```text
 //! ~/.config/ai-desktop-companion/{key}.json
 //! ```
@@ -9,23 +10,80 @@
 //! The frontend uses these commands to persist localStorage data to disk,
 //! implementing a write-through cache strategy so memories survive
 //! WebView cache clears and app reinstalls.
+//!
+//! A file watcher ([`start_data_watcher`]) reports edits made outside the
+//! app — sync tools, manual edits — as `"data-file-changed"` events so the
+//! frontend can reload instead of silently overwriting them. Changes to
+//! `config.json` / `settings.json` (or their TOML versions) are also
+//! reloaded into [`crate::config::ConfigState`].
+//!
+//! Chat history is kept here too, one append-only JSON Lines file per
+//! session key under `history/` ([`get_chat_history`],
+//! [`clear_chat_history`]); the chat commands in [`crate::openclaw`] add
+//! every completed exchange, so the history no longer depends on the
+//! WebView's storage and every window sees the same conversation.
+//! [`export_conversation`] writes a session out as Markdown (to read) or
+//! JSON (to move to another machine), and [`import_conversation`] merges
+//! such a JSON export back in.
 
+use crate::config::ConfigState;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, State};
 
-/// Resolve the data directory with the same fallback chain as `config.rs`:
-///
-/// 1. `dirs::config_dir()` (e.g. `~/Library/Application Support` on macOS)
-/// 2. `dirs::home_dir() / .config`
-/// 3. `./.config`
-fn data_dir() -> PathBuf {
-    dirs::config_dir()
-        .unwrap_or_else(|| {
-            dirs::home_dir()
-                .unwrap_or_else(|| PathBuf::from("."))
-                .join(".config")
-        })
-        .join("ai-desktop-companion")
+/// Filesystem events within this long after our own write are ignored.
+const SELF_WRITE_GRACE: Duration = Duration::from_millis(1500);
+
+/// Bursts of events for one file (editors often write several times) are
+/// collapsed into one notification.
+const CHANGE_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// When each key was last written or deleted by this process.
+static OWN_WRITES: Mutex<Option<HashMap<String, Instant>>> = Mutex::new(None);
+
+/// Payload of `"data-file-changed"` events.
+#[derive(Clone, Serialize)]
+pub struct DataFileChanged {
+    pub key: String,
+    /// `"modified"` or `"removed"`.
+    pub kind: &'static str,
+}
+
+/// Keeps the data-dir watcher alive; registered as Tauri managed state.
+pub struct DataWatcher {
+    _watcher: Mutex<RecommendedWatcher>,
+}
+
+/// Mark `key` as written by the app, so the watcher ignores the change.
+pub(crate) fn note_own_write(key: &str) {
+    if let Ok(mut writes) = OWN_WRITES.lock() {
+        writes
+            .get_or_insert_with(HashMap::new)
+            .insert(key.to_string(), Instant::now());
+    }
+}
+
+/// Whether an event for `key` is likely an echo of our own write.
+fn is_own_write(key: &str) -> bool {
+    OWN_WRITES.lock().is_ok_and(|writes| {
+        writes
+            .as_ref()
+            .and_then(|w| w.get(key))
+            .is_some_and(|t| t.elapsed() < SELF_WRITE_GRACE)
+    })
+}
+
+/// The data directory: the config directory of [`crate::config`],
+/// including a custom one set with `AIDC_CONFIG_DIR` or `--config-dir`.
+pub(crate) fn data_dir() -> PathBuf {
+    crate::config::config_dir()
 }
 
 /// Validate that a key contains only safe characters (alphanumeric + underscore).
@@ -67,6 +125,7 @@ pub fn write_data_file(key: String, data: String) -> Result<(), String> {
     fs::create_dir_all(&dir)
         .map_err(|e| format!("Failed to create data dir: {}", e))?;
     let path = dir.join(format!("{}.json", key));
+    note_own_write(&key);
     fs::write(&path, data)
         .map_err(|e| format!("Failed to write {}.json: {}", key, e))?;
     Ok(())
@@ -80,8 +139,449 @@ pub fn delete_data_file(key: String) -> Result<(), String> {
     validate_key(&key)?;
     let path = data_dir().join(format!("{}.json", key));
     if path.exists() {
+        note_own_write(&key);
         fs::remove_file(&path)
             .map_err(|e| format!("Failed to delete {}.json: {}", key, e))?;
     }
     Ok(())
 }
+
+/// Data key for a path in the data dir, if it is a `<key>.json` file (or
+/// the config in TOML, see [`crate::config::ConfigFormat`]).
+fn key_for_path(path: &Path) -> Option<String> {
+    let key = path.file_stem()?.to_str()?.to_string();
+    match path.extension()?.to_str()? {
+        "json" => {}
+        "toml" if key == "config" || key == "settings" => {}
+        _ => return None,
+    }
+    validate_key(&key).ok()?;
+    Some(key)
+}
+
+/// Reload hook: files owned by backend state are re-read when edited.
+fn run_reload_hooks(app: &AppHandle, key: &str) {
+    if key == "config" || key == "settings" {
+        if let Err(e) = app.state::<ConfigState>().reload() {
+            eprintln!("[memory] Failed to reload {}.json: {}", key, e);
+        }
+    }
+}
+
+/// Start watching the data directory for external changes.
+///
+/// Emits `"data-file-changed"` for every `<key>.json` created, modified or
+/// removed by another process. Returns `Err` if the watcher cannot be set up
+/// (the app keeps working, only without change notifications).
+pub fn start_data_watcher(app: &AppHandle) -> Result<(), String> {
+    let dir = data_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+
+    let handle = app.clone();
+    let last_notified: Mutex<HashMap<String, Instant>> = Mutex::new(HashMap::new());
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        let event = match res {
+            Ok(event) => event,
+            Err(e) => {
+                eprintln!("[memory] Watch error: {}", e);
+                return;
+            }
+        };
+        let kind = match event.kind {
+            EventKind::Create(_) | EventKind::Modify(_) => "modified",
+            EventKind::Remove(_) => "removed",
+            _ => return,
+        };
+        for key in event.paths.iter().filter_map(|p| key_for_path(p)) {
+            if is_own_write(&key) {
+                continue;
+            }
+            if let Ok(mut last) = last_notified.lock() {
+                if last
+                    .get(&key)
+                    .is_some_and(|t| t.elapsed() < CHANGE_DEBOUNCE)
+                {
+                    continue;
+                }
+                last.insert(key.clone(), Instant::now());
+            }
+            eprintln!("[memory] {}.json {} externally", key, kind);
+            run_reload_hooks(&handle, &key);
+            let _ = handle.emit("data-file-changed", DataFileChanged { key, kind });
+        }
+    })
+    .map_err(|e| format!("Failed to create file watcher: {}", e))?;
+
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to watch data dir: {}", e))?;
+    app.manage(DataWatcher {
+        _watcher: Mutex::new(watcher),
+    });
+    Ok(())
+}
+
+// ---------- Chat History ----------
+
+/// Messages returned by [`get_chat_history`] when no limit is given.
+const DEFAULT_HISTORY_LIMIT: usize = 50;
+
+/// Serializes access to the history files.
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+/// Id of the last stored message; ids are millisecond timestamps, bumped
+/// to stay unique and increasing.
+static LAST_MESSAGE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// One stored chat message.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ChatHistoryMessage {
+    /// Increasing id, usable as the `before` cursor of [`get_chat_history`].
+    pub id: u64,
+    /// `"user"` or `"assistant"`.
+    pub role: String,
+    pub content: String,
+    /// Unix time in milliseconds.
+    pub timestamp: u64,
+}
+
+/// History file of `session`: `<data dir>/history/<session>.jsonl`, with
+/// characters that are unsafe in file names escaped as `~xx`.
+fn history_path(session: &str) -> PathBuf {
+    session_path(session, "jsonl")
+}
+
+/// Conversation summary of `session` ([`crate::budget`]), next to its
+/// history file.
+fn summary_path(session: &str) -> PathBuf {
+    session_path(session, "summary.txt")
+}
+
+fn session_path(session: &str, extension: &str) -> PathBuf {
+    let mut name = String::with_capacity(session.len());
+    for byte in session.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_' {
+            name.push(byte as char);
+        } else {
+            name.push_str(&format!("~{:02x}", byte));
+        }
+    }
+    data_dir()
+        .join("history")
+        .join(format!("{}.{}", name, extension))
+}
+
+fn next_message_id(timestamp: u64) -> u64 {
+    let mut last = LAST_MESSAGE_ID.load(Ordering::Relaxed);
+    loop {
+        let id = timestamp.max(last + 1);
+        match LAST_MESSAGE_ID.compare_exchange(last, id, Ordering::Relaxed, Ordering::Relaxed) {
+            Ok(_) => return id,
+            Err(current) => last = current,
+        }
+    }
+}
+
+fn read_history(session: &str) -> Result<Vec<ChatHistoryMessage>, String> {
+    let contents = match fs::read_to_string(history_path(session)) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read chat history: {}", e)),
+    };
+    // A line cut short by a crash is skipped rather than failing the rest.
+    Ok(contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Append messages to the history of `session`.
+pub(crate) fn append_chat_history(session: &str, messages: &[(&str, &str)]) -> Result<(), String> {
+    let _guard = HISTORY_LOCK.lock().map_err(|e| e.to_string())?;
+    let path = history_path(session);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create history dir: {}", e))?;
+    }
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    let mut lines = String::new();
+    for (role, content) in messages {
+        let message = ChatHistoryMessage {
+            id: next_message_id(now),
+            role: role.to_string(),
+            content: content.to_string(),
+            timestamp: now,
+        };
+        lines.push_str(&serde_json::to_string(&message).map_err(|e| e.to_string())?);
+        lines.push('\n');
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open chat history: {}", e))?;
+    file.write_all(lines.as_bytes())
+        .map_err(|e| format!("Failed to write chat history: {}", e))
+}
+
+/// Number of stored messages of `session` and the time of the last one.
+pub(crate) fn history_summary(session: &str) -> Result<(usize, Option<u64>), String> {
+    let _guard = HISTORY_LOCK.lock().map_err(|e| e.to_string())?;
+    let messages = read_history(session)?;
+    Ok((messages.len(), messages.last().map(|m| m.timestamp)))
+}
+
+/// The last `limit` stored messages of `session`, oldest first; empty if
+/// the history cannot be read.
+pub(crate) fn recent_chat_history(session: &str, limit: usize) -> Vec<ChatHistoryMessage> {
+    let Ok(_guard) = HISTORY_LOCK.lock() else {
+        return Vec::new();
+    };
+    let mut messages = read_history(session).unwrap_or_default();
+    let start = messages.len().saturating_sub(limit);
+    messages.split_off(start)
+}
+
+/// The stored conversation summary of `session`, if any.
+pub(crate) fn read_summary(session: &str) -> Option<String> {
+    fs::read_to_string(summary_path(session)).ok()
+}
+
+/// Replace the conversation summary of `session`.
+pub(crate) fn write_summary(session: &str, summary: &str) -> Result<(), String> {
+    let _guard = HISTORY_LOCK.lock().map_err(|e| e.to_string())?;
+    let path = summary_path(session);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create history dir: {}", e))?;
+    }
+    fs::write(&path, summary).map_err(|e| format!("Failed to write conversation summary: {}", e))
+}
+
+/// Store a completed exchange, logging instead of failing the chat.
+pub(crate) fn record_exchange(session: &str, message: &str, reply: &str) {
+    if let Err(e) = append_chat_history(session, &[("user", message), ("assistant", reply)]) {
+        eprintln!("[memory] {}", e);
+    }
+}
+
+/// IPC command: stored chat messages of `session`, oldest first.
+///
+/// Returns the last `limit` (default 50) messages with an id below
+/// `before`, so older pages are fetched by passing the first id of the
+/// previous page. `session` defaults to the configured session key.
+#[tauri::command]
+pub fn get_chat_history(
+    config_state: State<'_, ConfigState>,
+    session: Option<String>,
+    limit: Option<usize>,
+    before: Option<u64>,
+) -> Result<Vec<ChatHistoryMessage>, String> {
+    let session = match session {
+        Some(session) => session,
+        None => config_state.get()?.session_key,
+    };
+    let _guard = HISTORY_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut messages = read_history(&session)?;
+    if let Some(before) = before {
+        messages.retain(|m| m.id < before);
+    }
+    let limit = limit.unwrap_or(DEFAULT_HISTORY_LIMIT);
+    let start = messages.len().saturating_sub(limit);
+    Ok(messages.split_off(start))
+}
+
+/// IPC command: delete the stored chat history of `session` (default: the
+/// configured session key), along with its summary.
+///
+/// Silently succeeds if there is none.
+#[tauri::command]
+pub fn clear_chat_history(
+    config_state: State<'_, ConfigState>,
+    session: Option<String>,
+) -> Result<(), String> {
+    let session = match session {
+        Some(session) => session,
+        None => config_state.get()?.session_key,
+    };
+    crate::budget::forget(&session);
+    let _guard = HISTORY_LOCK.lock().map_err(|e| e.to_string())?;
+    let _ = fs::remove_file(summary_path(&session));
+    match fs::remove_file(history_path(&session)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(format!("Failed to delete chat history: {}", e))
+        }
+        _ => Ok(()),
+    }
+}
+
+// ---------- Export / Import ----------
+
+/// Version written in [`ConversationExport::version`].
+const EXPORT_VERSION: u32 = 1;
+
+/// Output format of [`export_conversation`].
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// For reading: one heading per message, with speaker and time.
+    Markdown,
+    /// For [`import_conversation`].
+    Json,
+}
+
+/// A conversation exported as JSON.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationExport {
+    pub version: u32,
+    pub session: String,
+    /// Unix time in milliseconds.
+    pub exported_at: u64,
+    pub messages: Vec<ChatHistoryMessage>,
+}
+
+/// Returned by [`import_conversation`].
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportResult {
+    pub session: String,
+    /// Messages added to the history.
+    pub imported: usize,
+    /// Messages already in the history.
+    pub skipped: usize,
+}
+
+fn markdown_transcript(session: &str, character: &str, messages: &[ChatHistoryMessage]) -> String {
+    use chrono::TimeZone;
+    let mut out = format!("# Conversation `{}`\n", session);
+    for message in messages {
+        let speaker = match message.role.as_str() {
+            "user" => "You",
+            _ if !character.is_empty() => character,
+            _ => "Companion",
+        };
+        let time = chrono::Local
+            .timestamp_millis_opt(message.timestamp as i64)
+            .single()
+            .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        out.push_str(&format!(
+            "\n### {} · {}\n\n{}\n",
+            speaker, time, message.content
+        ));
+    }
+    out
+}
+
+/// IPC command: the whole stored conversation of `session` (default: the
+/// configured session key) as Markdown or JSON text, for the frontend to
+/// save where the user picks.
+#[tauri::command]
+pub fn export_conversation(
+    config_state: State<'_, ConfigState>,
+    session: Option<String>,
+    format: ExportFormat,
+) -> Result<String, String> {
+    let session = match session {
+        Some(session) => session,
+        None => config_state.get()?.session_key,
+    };
+    let messages = {
+        let _guard = HISTORY_LOCK.lock().map_err(|e| e.to_string())?;
+        read_history(&session)?
+    };
+    match format {
+        ExportFormat::Markdown => {
+            let character = config_state.get_settings()?.persona.card.name;
+            Ok(markdown_transcript(&session, &character, &messages))
+        }
+        ExportFormat::Json => {
+            let export = ConversationExport {
+                version: EXPORT_VERSION,
+                exported_at: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_millis() as u64)
+                    .unwrap_or(0),
+                session,
+                messages,
+            };
+            serde_json::to_string_pretty(&export).map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// IPC command: merge a JSON export ([`export_conversation`]) from `path`
+/// into the stored history.
+///
+/// The messages go to `session` if given, else to the session they were
+/// exported from. They are merged by time with what is already there;
+/// messages already present (same role, text and time) are skipped, so
+/// importing the same file twice changes nothing. Message ids are
+/// reassigned.
+///
+/// # Errors
+///
+/// Returns `Err` if the file cannot be read or is not a conversation
+/// export.
+#[tauri::command]
+pub fn import_conversation(path: String, session: Option<String>) -> Result<ImportResult, String> {
+    let contents =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let export: ConversationExport =
+        serde_json::from_str(&contents).map_err(|e| format!("Not a conversation export: {}", e))?;
+    if export.version > EXPORT_VERSION {
+        return Err(format!(
+            "Conversation export version {} is newer than this app supports",
+            export.version
+        ));
+    }
+    let session = session.unwrap_or(export.session);
+    if session.is_empty() {
+        return Err("Session must not be empty".to_string());
+    }
+
+    let _guard = HISTORY_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut messages = read_history(&session)?;
+    let existing = messages.len();
+    let total = export.messages.len();
+    for message in export.messages {
+        let present = messages.iter().any(|m| {
+            m.timestamp == message.timestamp
+                && m.role == message.role
+                && m.content == message.content
+        });
+        if !present {
+            messages.push(message);
+        }
+    }
+    let imported = messages.len() - existing;
+    if imported > 0 {
+        messages.sort_by_key(|m| m.timestamp);
+        let mut lines = String::new();
+        for message in &mut messages {
+            message.id = next_message_id(message.timestamp);
+            lines.push_str(&serde_json::to_string(message).map_err(|e| e.to_string())?);
+            lines.push('\n');
+        }
+        let path = history_path(&session);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create history dir: {}", e))?;
+        }
+        // Written aside and renamed so a crash cannot leave half a history.
+        let partial = path.with_extension("jsonl.partial");
+        fs::write(&partial, lines).map_err(|e| format!("Failed to write chat history: {}", e))?;
+        fs::rename(&partial, &path).map_err(|e| format!("Failed to write chat history: {}", e))?;
+        crate::budget::forget(&session);
+    }
+    println!(
+        "[memory] Imported {} of {} messages into {}",
+        imported, total, session
+    );
+    Ok(ImportResult {
+        session,
+        imported,
+        skipped: total - imported,
+    })
+}
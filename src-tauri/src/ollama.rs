@@ -0,0 +1,265 @@
+//! Ollama model management.
+//!
+//! [`crate::llm::Ollama`] only chats with whatever model is configured. For
+//! a one-click "use a local model" path the settings UI also needs to know
+//! whether an Ollama daemon is running ([`check_ollama`]), which models it
+//! has ([`list_ollama_models`]), to fetch new ones ([`pull_ollama_model`],
+//! with `"ollama-pull-progress"` events) and to switch chat over to one
+//! ([`use_ollama_model`]).
+//!
+//! All of them talk to [`crate::llm::OllamaConfig::url`].
+
+use crate::config::ConfigState;
+use crate::llm::{self, ProviderKind};
+use crate::openclaw::{HttpClient, OpenClawError};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, State};
+
+/// Time allowed for the daemon to answer [`check_ollama`] and
+/// [`list_ollama_models`].
+const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Minimum time between `"ollama-pull-progress"` events.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Whether a pull is in progress.
+static PULLING: AtomicBool = AtomicBool::new(false);
+
+/// Returned by [`check_ollama`].
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OllamaStatus {
+    pub url: String,
+    pub running: bool,
+    /// Daemon version, when running.
+    pub version: Option<String>,
+}
+
+/// A model the daemon has, as listed by [`list_ollama_models`].
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OllamaModel {
+    /// Name to chat with, e.g. `"llama3.2:latest"`.
+    pub name: String,
+    pub size_bytes: u64,
+    /// e.g. `"3.2B"`.
+    pub parameter_size: Option<String>,
+    /// e.g. `"Q4_K_M"`.
+    pub quantization: Option<String>,
+    /// Whether chat currently uses this model.
+    pub active: bool,
+}
+
+/// Payload of `"ollama-pull-progress"` events.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PullProgress {
+    pub name: String,
+    /// Ollama's phase, e.g. `"pulling manifest"`, `"verifying sha256
+    /// digest"`, `"success"`.
+    pub status: String,
+    /// Bytes of the layer being downloaded, when there is one.
+    pub completed_bytes: Option<u64>,
+    pub total_bytes: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct Tags {
+    #[serde(default)]
+    models: Vec<Tag>,
+}
+
+#[derive(Deserialize)]
+struct Tag {
+    name: String,
+    #[serde(default)]
+    size: u64,
+    #[serde(default)]
+    details: TagDetails,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct TagDetails {
+    parameter_size: Option<String>,
+    quantization_level: Option<String>,
+}
+
+/// Base URL of the configured daemon, and a client for it.
+fn endpoint(
+    http: &HttpClient,
+    config_state: &ConfigState,
+) -> Result<(String, String, reqwest::Client), String> {
+    let config = config_state.get()?;
+    let ollama = &config.provider.ollama;
+    let url = ollama.url.trim_end_matches('/').to_string();
+    Ok((url, ollama.model.clone(), http.client(&config)?))
+}
+
+/// Whether `name` refers to `model`, allowing for the implicit `:latest`
+/// tag.
+fn same_model(name: &str, model: &str) -> bool {
+    let tagged = |n: &str| {
+        if n.contains(':') {
+            n.to_string()
+        } else {
+            format!("{n}:latest")
+        }
+    };
+    tagged(name) == tagged(model)
+}
+
+/// IPC command: whether an Ollama daemon answers at the configured URL.
+#[tauri::command]
+pub async fn check_ollama(
+    http: State<'_, HttpClient>,
+    config_state: State<'_, ConfigState>,
+) -> Result<OllamaStatus, String> {
+    let (url, _, client) = endpoint(&http, &config_state)?;
+    let response = client
+        .get(format!("{url}/api/version"))
+        .timeout(QUERY_TIMEOUT)
+        .send()
+        .await
+        .ok()
+        .filter(|r| r.status().is_success());
+    let version = match response {
+        Some(response) => Some(
+            response
+                .json::<serde_json::Value>()
+                .await
+                .ok()
+                .and_then(|v| v["version"].as_str().map(str::to_string))
+                .unwrap_or_default(),
+        ),
+        None => None,
+    };
+    Ok(OllamaStatus {
+        url,
+        running: version.is_some(),
+        version,
+    })
+}
+
+/// IPC command: the models the daemon has downloaded.
+///
+/// # Errors
+///
+/// Returns `Err` if the daemon cannot be reached.
+#[tauri::command]
+pub async fn list_ollama_models(
+    http: State<'_, HttpClient>,
+    config_state: State<'_, ConfigState>,
+) -> Result<Vec<OllamaModel>, String> {
+    let (url, active, client) = endpoint(&http, &config_state)?;
+    let response = client
+        .get(format!("{url}/api/tags"))
+        .timeout(QUERY_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| {
+            if e.is_connect() {
+                format!("Cannot connect to Ollama at {url}. Is it running?")
+            } else {
+                format!("Ollama request failed: {e}")
+            }
+        })?;
+    let status = response.status().as_u16();
+    if !response.status().is_success() {
+        return Err(format!("Ollama returned status {status}"));
+    }
+    let tags: Tags = response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid model list from Ollama: {e}"))?;
+    Ok(tags
+        .models
+        .into_iter()
+        .map(|tag| OllamaModel {
+            active: same_model(&tag.name, &active),
+            name: tag.name,
+            size_bytes: tag.size,
+            parameter_size: tag.details.parameter_size,
+            quantization: tag.details.quantization_level,
+        })
+        .collect())
+}
+
+/// IPC command: download `name` (e.g. `"llama3.2"`, `"qwen2.5:3b"`) into
+/// the daemon.
+///
+/// Emits `"ollama-pull-progress"` while it runs; the last event has status
+/// `"success"`. Resolves once the model is ready.
+///
+/// # Errors
+///
+/// Returns `Err` if another pull is running, the daemon cannot be reached
+/// or it reports an error (e.g. an unknown model).
+#[tauri::command]
+pub async fn pull_ollama_model(
+    app: AppHandle,
+    http: State<'_, HttpClient>,
+    config_state: State<'_, ConfigState>,
+    name: String,
+) -> Result<(), String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Model name must not be empty".to_string());
+    }
+    let (url, _, client) = endpoint(&http, &config_state)?;
+    if PULLING.swap(true, Ordering::SeqCst) {
+        return Err("A model pull is already in progress".to_string());
+    }
+    let request = client
+        .post(format!("{url}/api/pull"))
+        .json(&serde_json::json!({ "model": name, "stream": true }));
+    let mut last_progress: Option<Instant> = None;
+    let mut succeeded = false;
+    let result = llm::read_lines(request, "Ollama", |line| {
+        let Ok(event) = serde_json::from_str::<serde_json::Value>(line) else {
+            return Ok(true);
+        };
+        if let Some(error) = event["error"].as_str() {
+            return Err(OpenClawError::gateway_error(format!(
+                "Ollama could not pull {name}: {error}"
+            )));
+        }
+        let status = event["status"].as_str().unwrap_or_default().to_string();
+        succeeded = status == "success";
+        let due = last_progress.is_none_or(|t| t.elapsed() >= PROGRESS_INTERVAL);
+        if due || succeeded {
+            last_progress = Some(Instant::now());
+            let _ = app.emit(
+                "ollama-pull-progress",
+                PullProgress {
+                    name: name.clone(),
+                    status,
+                    completed_bytes: event["completed"].as_u64(),
+                    total_bytes: event["total"].as_u64(),
+                },
+            );
+        }
+        Ok(!succeeded)
+    })
+    .await;
+    PULLING.store(false, Ordering::SeqCst);
+    result.map_err(|e| e.to_string())?;
+    if !succeeded {
+        return Err(format!("Ollama stopped before {name} was complete"));
+    }
+    println!("[ollama] Pulled {name}");
+    Ok(())
+}
+
+/// IPC command: chat with `name` through Ollama from now on.
+#[tauri::command]
+pub fn use_ollama_model(config_state: State<'_, ConfigState>, name: String) -> Result<(), String> {
+    {
+        let mut config = config_state.config.write().map_err(|e| e.to_string())?;
+        config.provider.kind = ProviderKind::Ollama;
+        config.provider.ollama.model = name;
+    }
+    config_state.save()
+}
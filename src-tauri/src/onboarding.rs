@@ -0,0 +1,160 @@
+//! First-run onboarding progress.
+//!
+//! The setup flow is a handful of [`OnboardingStep`]s (permissions,
+//! microphone, gateway, character, the user's name). The frontend starts it
+//! with [`start_onboarding`] and marks steps done with
+//! [`complete_onboarding_step`] as the user gets through them. Progress is
+//! saved in `settings.json`, so an interrupted wizard picks up at
+//! [`OnboardingState::next`] on the next launch.
+//!
+//! While the wizard runs, backend features that need a step wait for it:
+//! audio monitoring, for one, does not open the microphone before
+//! [`OnboardingStep::Microphone`]. Outside the wizard nothing is held back,
+//! so a fresh install whose frontend never starts onboarding still works.
+//!
+//! Installs from before onboarding was tracked count as fully onboarded.
+
+use crate::config::ConfigState;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+/// A setup step, in the order the wizard goes through them.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum OnboardingStep {
+    /// Screen recording and accessibility access granted.
+    Permissions,
+    /// Microphone access granted.
+    Microphone,
+    /// OpenClaw gateway connected and hooks set up.
+    Gateway,
+    /// Character model chosen.
+    Character,
+    /// The user told the character their name.
+    Name,
+}
+
+impl OnboardingStep {
+    pub const ALL: [OnboardingStep; 5] = [
+        Self::Permissions,
+        Self::Microphone,
+        Self::Gateway,
+        Self::Character,
+        Self::Name,
+    ];
+}
+
+/// Onboarding section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OnboardingConfig {
+    /// The wizard has been started and not all steps are done yet.
+    pub active: bool,
+    /// Steps done so far, in the order they were completed.
+    pub completed: Vec<OnboardingStep>,
+}
+
+impl OnboardingConfig {
+    /// Used for a `settings.json` without this section: an install from
+    /// before onboarding was tracked, which has been set up already.
+    pub(crate) fn existing_install() -> Self {
+        Self {
+            active: false,
+            completed: OnboardingStep::ALL.to_vec(),
+        }
+    }
+
+    pub(crate) fn is_complete(&self, step: OnboardingStep) -> bool {
+        self.completed.contains(&step)
+    }
+
+    /// Whether features that need `step` should wait: the wizard is running
+    /// and has not got there yet.
+    pub(crate) fn waits_for(&self, step: OnboardingStep) -> bool {
+        self.active && !self.is_complete(step)
+    }
+}
+
+/// Returned by [`get_onboarding_state`] and [`complete_onboarding_step`].
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OnboardingState {
+    /// Whether the wizard is running.
+    pub active: bool,
+    pub completed: Vec<OnboardingStep>,
+    /// First step not done yet, where the wizard should resume.
+    pub next: Option<OnboardingStep>,
+    /// Whether every step is done.
+    pub finished: bool,
+}
+
+impl From<&OnboardingConfig> for OnboardingState {
+    fn from(config: &OnboardingConfig) -> Self {
+        let next = OnboardingStep::ALL
+            .into_iter()
+            .find(|&step| !config.is_complete(step));
+        Self {
+            active: config.active,
+            completed: config.completed.clone(),
+            next,
+            finished: next.is_none(),
+        }
+    }
+}
+
+/// IPC command: which onboarding steps are done.
+#[tauri::command]
+pub fn get_onboarding_state(
+    config_state: State<'_, ConfigState>,
+) -> Result<OnboardingState, String> {
+    Ok((&config_state.get_settings()?.onboarding).into())
+}
+
+/// IPC command: start (or resume) the wizard. From now on features wait
+/// for the steps they need until [`OnboardingState::finished`].
+#[tauri::command]
+pub fn start_onboarding(config_state: State<'_, ConfigState>) -> Result<OnboardingState, String> {
+    let state = {
+        let mut settings = config_state.settings.write().map_err(|e| e.to_string())?;
+        let state = OnboardingState::from(&settings.onboarding);
+        if state.finished || state.active {
+            return Ok(state);
+        }
+        settings.onboarding.active = true;
+        OnboardingState::from(&settings.onboarding)
+    };
+    config_state.save_settings()?;
+    println!("[onboarding] Started");
+    Ok(state)
+}
+
+/// Mark `step` as done and save the settings; the wizard ends with the
+/// last step.
+pub(crate) fn complete(
+    config_state: &ConfigState,
+    step: OnboardingStep,
+) -> Result<OnboardingState, String> {
+    let state = {
+        let mut settings = config_state.settings.write().map_err(|e| e.to_string())?;
+        if settings.onboarding.is_complete(step) {
+            return Ok((&settings.onboarding).into());
+        }
+        settings.onboarding.completed.push(step);
+        if OnboardingState::from(&settings.onboarding).finished {
+            settings.onboarding.active = false;
+        }
+        OnboardingState::from(&settings.onboarding)
+    };
+    config_state.save_settings()?;
+    println!("[onboarding] Completed {step:?}");
+    Ok(state)
+}
+
+/// IPC command: mark `step` as done. Completing a step twice is harmless.
+#[tauri::command]
+pub fn complete_onboarding_step(
+    config_state: State<'_, ConfigState>,
+    step: OnboardingStep,
+) -> Result<OnboardingState, String> {
+    complete(&config_state, step)
+}
@@ -0,0 +1,301 @@
+//! OpenAI-compatible façade over the companion's chat backend.
+//!
+//! Lets other local tools — editors, launchers, scripts that already speak
+//! the OpenAI API — talk *through* the companion: requests go to whatever
+//! backend [`crate::llm`] is configured with, with the character's system
+//! prompt ([`crate::persona`]) ahead of the caller's messages, and count
+//! towards [`crate::usage`]. The conversation is the caller's; it is not
+//! added to the companion's chat history.
+//!
+//! Routes:
+//!
+//! - `POST /v1/chat/completions` — text messages only; tools and sampling
+//!   parameters are ignored. With `"stream": true` the whole reply comes
+//!   as a single server-sent event followed by `[DONE]`.
+//! - `GET /v1/models` — one model, [`MODEL_ID`].
+//!
+//! The server is off by default, listens on `127.0.0.1` only and needs
+//! `Authorization: Bearer <token>` with the token from [`OpenAiApiConfig`]
+//! (generated on first start). It shares the HTTP handling of
+//! [`crate::localapi`]; changes take effect on the next launch.
+
+use crate::config::ConfigState;
+use crate::llm::{self, ChatMessage};
+use crate::localapi::{self, Request, Response};
+use crate::openclaw::HttpClient;
+use crate::usage::UsageState;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use tokio::net::{TcpListener, TcpStream};
+
+/// The only model listed by `GET /v1/models`; requests may name any model.
+pub const MODEL_ID: &str = "companion";
+
+/// OpenAI-compatible API section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OpenAiApiConfig {
+    pub enabled: bool,
+    /// Port on `127.0.0.1`.
+    pub port: u16,
+    /// Bearer token callers must send; generated when empty.
+    pub token: String,
+}
+
+impl Default for OpenAiApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 18792,
+            token: String::new(),
+        }
+    }
+}
+
+/// Body of `POST /v1/chat/completions`; other fields are ignored.
+#[derive(Deserialize)]
+struct CompletionRequest {
+    #[serde(default)]
+    model: Option<String>,
+    messages: Vec<IncomingMessage>,
+    #[serde(default)]
+    stream: bool,
+}
+
+#[derive(Deserialize)]
+struct IncomingMessage {
+    role: String,
+    /// A string, or a list of content parts of which the text ones are
+    /// used.
+    #[serde(default)]
+    content: serde_json::Value,
+}
+
+impl IncomingMessage {
+    fn text(&self) -> String {
+        match &self.content {
+            serde_json::Value::String(text) => text.clone(),
+            serde_json::Value::Array(parts) => parts
+                .iter()
+                .filter_map(|part| part.get("text").and_then(serde_json::Value::as_str))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            _ => String::new(),
+        }
+    }
+}
+
+/// Start the server if enabled in settings, generating its token first if
+/// needed.
+pub fn start_openai_api(app: AppHandle) {
+    let config_state = app.state::<ConfigState>();
+    let mut config = match config_state.get_settings() {
+        Ok(settings) => settings.openai_api,
+        Err(e) => {
+            eprintln!("[openai_api] Failed to read settings: {e}");
+            return;
+        }
+    };
+    if !config.enabled {
+        return;
+    }
+    if config.token.is_empty() {
+        match create_token(&config_state) {
+            Ok(token) => config.token = token,
+            Err(e) => {
+                eprintln!("[openai_api] Not starting without a token: {e}");
+                return;
+            }
+        }
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let listener = match TcpListener::bind(("127.0.0.1", config.port)).await {
+            Ok(l) => l,
+            Err(e) => {
+                eprintln!("[openai_api] Failed to bind 127.0.0.1:{}: {e}", config.port);
+                return;
+            }
+        };
+        println!(
+            "[openai_api] Listening on http://127.0.0.1:{}/v1",
+            config.port
+        );
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let app = app.clone();
+                    let token = config.token.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = serve(&app, &token, stream).await {
+                            eprintln!("[openai_api] {e}");
+                        }
+                    });
+                }
+                Err(e) => eprintln!("[openai_api] Accept failed: {e}"),
+            }
+        }
+    });
+}
+
+/// Generate a token and save it to the settings.
+fn create_token(config_state: &ConfigState) -> Result<String, String> {
+    let token = crate::openclaw::generate_token()?;
+    {
+        let mut settings = config_state.settings.write().map_err(|e| e.to_string())?;
+        settings.openai_api.token = token.clone();
+    }
+    config_state.save_settings()?;
+    Ok(token)
+}
+
+/// Read one request, route it and write the response.
+async fn serve(app: &AppHandle, token: &str, mut stream: TcpStream) -> Result<(), String> {
+    let read = tokio::time::timeout(localapi::READ_TIMEOUT, localapi::read_request(&mut stream));
+    let response = match read.await {
+        Ok(Ok(request)) if !crate::push::authorized(&request, token) => {
+            error(401, "invalid_api_key", "Missing or wrong bearer token")
+        }
+        Ok(Ok(request)) => route(app, &request).await,
+        Ok(Err(response)) => response,
+        Err(_) => return Err("Client timed out".to_string()),
+    };
+    localapi::write_response(&mut stream, &response).await
+}
+
+/// An error in OpenAI's shape.
+fn error(status: u16, kind: &str, message: &str) -> Response {
+    Response::json(
+        status,
+        &serde_json::json!({ "error": { "message": message, "type": kind } }),
+    )
+}
+
+fn unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+async fn route(app: &AppHandle, request: &Request) -> Response {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("POST", "/v1/chat/completions") => chat_completions(app, request).await,
+        ("GET", "/v1/models") => Response::json(
+            200,
+            &serde_json::json!({
+                "object": "list",
+                "data": [{ "id": MODEL_ID, "object": "model", "created": 0, "owned_by": "companion" }],
+            }),
+        ),
+        (_, "/v1/chat/completions" | "/v1/models") => {
+            error(405, "invalid_request_error", "Method not allowed")
+        }
+        _ => error(404, "invalid_request_error", "Not found"),
+    }
+}
+
+async fn chat_completions(app: &AppHandle, request: &Request) -> Response {
+    let body: CompletionRequest = match serde_json::from_slice(&request.body) {
+        Ok(body) => body,
+        Err(e) => return error(400, "invalid_request_error", &format!("Invalid body: {e}")),
+    };
+    let config_state = app.state::<ConfigState>();
+    let config = match config_state.get() {
+        Ok(config) => config,
+        Err(e) => return error(500, "server_error", &e),
+    };
+
+    let mut messages = Vec::new();
+    if let Some(system) = crate::persona::system_prompt(&config_state, &config) {
+        messages.push(ChatMessage::new("system", system));
+    }
+    for message in &body.messages {
+        let role = match message.role.as_str() {
+            "system" | "developer" => "system",
+            "user" => "user",
+            "assistant" => "assistant",
+            // Tool results need the tool calls they answer; not supported.
+            _ => continue,
+        };
+        messages.push(ChatMessage::new(role, message.text()));
+    }
+    if !messages.iter().any(|m| m.role == "user") {
+        return error(400, "invalid_request_error", "No user message");
+    }
+
+    let usage = app.state::<UsageState>();
+    if let Err(e) = usage.admit(app, &config_state, &config) {
+        return error(429, "rate_limit_exceeded", &e);
+    }
+    let client = match app.state::<HttpClient>().client(&config) {
+        Ok(client) => client,
+        Err(e) => return error(500, "server_error", &e),
+    };
+    let total = config.timeouts.total();
+    let reply = tokio::time::timeout(
+        total,
+        llm::complete(&client, &config, &messages, None, &mut |_: &str| {}),
+    )
+    .await;
+    let reply = match reply {
+        Ok(Ok(reply)) => reply,
+        Ok(Err(e)) => return error(502, "upstream_error", e.message()),
+        Err(_) => {
+            return error(
+                504,
+                "timeout",
+                &format!("No reply within {}s", total.as_secs()),
+            )
+        }
+    };
+    usage.record(
+        &config_state,
+        &config,
+        crate::budget::request_tokens(&messages),
+        &reply,
+    );
+
+    let id = format!(
+        "chatcmpl-{:x}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0)
+    );
+    let model = body.model.unwrap_or_else(|| MODEL_ID.to_string());
+    let created = unix_secs();
+    if body.stream {
+        let chunk = serde_json::json!({
+            "id": id,
+            "object": "chat.completion.chunk",
+            "created": created,
+            "model": model,
+            "choices": [{
+                "index": 0,
+                "delta": { "role": "assistant", "content": reply },
+                "finish_reason": "stop",
+            }],
+        });
+        return Response {
+            status: 200,
+            content_type: "text/event-stream",
+            body: format!("data: {chunk}\n\ndata: [DONE]\n\n").into_bytes(),
+        };
+    }
+    Response::json(
+        200,
+        &serde_json::json!({
+            "id": id,
+            "object": "chat.completion",
+            "created": created,
+            "model": model,
+            "choices": [{
+                "index": 0,
+                "message": { "role": "assistant", "content": reply },
+                "finish_reason": "stop",
+            }],
+        }),
+    )
+}
@@ -10,45 +10,325 @@
 //! 2. **HTTP POST** to `/hooks/agent` — used for fire-and-forget triggers
 //!    (e.g. screen-watch observations) where we don't need the reply.
 //!
+//! 3. **Streaming HTTP** to the gateway's OpenAI-compatible
+//!    `/v1/chat/completions` — [`send_chat_stream`] emits the reply token by
+//!    token (`"chat-chunk"`, then `"chat-complete"`) so the character can
+//!    start reacting before a long answer is finished.
+//!
+//! Over HTTP the model can also call desktop tools — the active window,
+//! what is playing, reminders — through [`ToolBridge`], for the tools
+//! enabled in [`OpenClawConfig::tools`].
+//!
+//! Chat and webhook messages are retried with jittered exponential backoff
+//! while the gateway is unreachable ([`RetryConfig`]); what still fails is
+//! kept in an on-disk queue and sent, with a `"queue-flushed"` event, once
+//! the gateway is back.
+//!
+//! Users without OpenClaw can point chat at another backend instead
+//! ([`OpenClawConfig::provider`], implemented in [`crate::llm`]).
+//!
+//! Commands fail with an [`OpenClawError`] whose `kind` tells the frontend
+//! what went wrong (unreachable gateway, rejected token, timeout, …).
+//!
 //! Authentication uses a Bearer token generated by [`setup_openclaw_hooks`]
 //! and shared between the app config and `~/.openclaw/openclaw.json`.
 
-use crate::config::ConfigState;
+use crate::chatlog::Trace;
+use crate::config::{ConfigState, OpenClawConfig};
+use crate::llm::{self, Image, ImageAttachment, ProviderKind};
+use crate::tags::ParsedReply;
+use crate::ttsstream::SpeechStream;
+use crate::usage::UsageState;
+use crate::user_error::{user_facing_error, Severity};
 use serde::{Deserialize, Serialize};
 use std::process::Stdio;
-use std::time::Duration;
-use tauri::State;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::oneshot;
+
+/// Upper bound for the health check, however long [`TimeoutConfig::total_secs`]
+/// is (10 seconds).
+const HTTP_TIMEOUT_SECS: u64 = 10;
 
-/// Timeout for the `openclaw agent` CLI subprocess (2 minutes).
-const CLI_TIMEOUT_SECS: u64 = 120;
+/// Start of the message of every [`OpenClawError::Timeout`] (e.g.
+/// `"Timed out: webhook request took longer than 60s"`).
+pub const TIMED_OUT: &str = "Timed out";
 
-/// Timeout for HTTP requests — health check and webhook (10 seconds).
-const HTTP_TIMEOUT_SECS: u64 = 10;
+fn timed_out(what: &str, after: Duration) -> OpenClawError {
+    OpenClawError::Timeout {
+        message: format!("{TIMED_OUT}: {what} took longer than {}s", after.as_secs()),
+    }
+}
+
+/// Why an OpenClaw command failed, serialized as
+/// `{ "kind": "authFailed", "message": "..." }` so the frontend can offer
+/// the right fix (open settings, start the gateway, try again) instead of
+/// showing the raw error.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum OpenClawError {
+    /// The gateway or backend could not be reached at all.
+    NetworkUnreachable { message: String },
+    /// The token or API key was rejected (HTTP 401/403).
+    AuthFailed { message: String },
+    /// The request ran out of time ([`TimeoutConfig`]).
+    Timeout { message: String },
+    /// The gateway, CLI or backend answered with an error; `status` is the
+    /// HTTP status where there is one.
+    GatewayError {
+        status: Option<u16>,
+        message: String,
+    },
+    /// Something the user has to set up first (agent ID, API key, …).
+    InvalidConfig { message: String },
+    /// An end-to-end encrypted reply could not be opened: the keys differ,
+    /// or the gateway does not encrypt.
+    DecryptFailed { message: String },
+    /// Cancelled with [`cancel_chat`].
+    Cancelled { message: String },
+    /// Anything else, e.g. a file that could not be written.
+    Other { message: String },
+}
+
+impl OpenClawError {
+    pub fn message(&self) -> &str {
+        match self {
+            Self::NetworkUnreachable { message }
+            | Self::AuthFailed { message }
+            | Self::Timeout { message }
+            | Self::GatewayError { message, .. }
+            | Self::InvalidConfig { message }
+            | Self::DecryptFailed { message }
+            | Self::Cancelled { message }
+            | Self::Other { message } => message,
+        }
+    }
+
+    pub(crate) fn invalid_config(message: impl Into<String>) -> Self {
+        Self::InvalidConfig {
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn other(message: impl Into<String>) -> Self {
+        Self::Other {
+            message: message.into(),
+        }
+    }
+
+    /// An error reported by the gateway, CLI or backend without an HTTP
+    /// status, e.g. an error event in a stream.
+    pub(crate) fn gateway_error(message: impl Into<String>) -> Self {
+        Self::GatewayError {
+            status: None,
+            message: message.into(),
+        }
+    }
+
+    fn cancelled() -> Self {
+        Self::Cancelled {
+            message: CANCELLED.to_string(),
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        matches!(self, Self::Cancelled { .. })
+    }
+
+    /// Classify an HTTP response status.
+    pub(crate) fn from_status(status: u16, message: String) -> Self {
+        match status {
+            401 | 403 => Self::AuthFailed { message },
+            _ => Self::GatewayError {
+                status: Some(status),
+                message,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for OpenClawError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+/// Errors from string-error helpers (config, memory, …) that have no more
+/// specific kind. Code that knows the kind constructs it directly.
+impl From<String> for OpenClawError {
+    fn from(message: String) -> Self {
+        Self::Other { message }
+    }
+}
+
+/// Request time limits for the gateway, part of [`OpenClawConfig`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TimeoutConfig {
+    /// Time to establish a connection.
+    pub connect_secs: u64,
+    /// Longest wait for the next bytes of a response; catches a gateway
+    /// that accepted the request and then hung.
+    pub read_secs: u64,
+    /// Whole request, including the CLI subprocess of [`send_chat`].
+    /// Streamed replies are exempt.
+    pub total_secs: u64,
+}
+
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        Self {
+            connect_secs: 5,
+            read_secs: 30,
+            total_secs: 120,
+        }
+    }
+}
+
+impl TimeoutConfig {
+    pub(crate) fn total(&self) -> Duration {
+        Duration::from_secs(self.total_secs.max(1))
+    }
+}
 
 // ---------- Shared HTTP Client ----------
 
 /// A shared `reqwest::Client` registered as Tauri managed state.
 ///
 /// Reusing a single client enables HTTP/2 connection pooling and avoids
-/// per-request TLS handshake overhead.
+/// per-request TLS handshake overhead. reqwest takes proxies,
+/// connect/read timeouts and TLS settings only per client, so the client is
+/// rebuilt when [`OpenClawConfig::proxy`], [`OpenClawConfig::timeouts`] or
+/// [`OpenClawConfig::tls`] change.
 pub struct HttpClient {
-    inner: reqwest::Client,
+    /// The current client and the settings it was built with.
+    inner: Mutex<Option<(ProxyConfig, TimeoutConfig, TlsConfig, reqwest::Client)>>,
+}
+
+/// Optional proxy for all HTTP traffic, part of [`OpenClawConfig`].
+///
+/// With no URL, reqwest's default applies: the `HTTP_PROXY` /
+/// `HTTPS_PROXY` / `NO_PROXY` environment variables.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProxyConfig {
+    /// `http://`, `https://`, `socks5://` or `socks5h://` (DNS through the
+    /// proxy) URL; empty for none.
+    pub url: String,
+    /// Comma-separated hosts, domains (`.corp.example`) and CIDR ranges
+    /// reached directly, e.g. `"localhost,127.0.0.1,.internal"`.
+    pub no_proxy: String,
+    /// Credentials for proxies that require authentication.
+    pub username: String,
+    pub password: String,
+}
+
+/// TLS settings for self-hosted gateways, part of [`OpenClawConfig`].
+///
+/// A gateway behind a home reverse proxy or on a tailnet often has a
+/// certificate from a private CA, or wants a client certificate. Files are
+/// PEM and read when the client is built.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TlsConfig {
+    /// Extra CA certificate(s) to trust besides the system ones.
+    pub ca_cert_path: String,
+    /// Client certificate for mutual TLS.
+    pub client_cert_path: String,
+    /// PKCS#8 private key of the client certificate; empty if it is in
+    /// the certificate file.
+    pub client_key_path: String,
+    /// Accept any server certificate, including expired and self-signed
+    /// ones. Only for testing: anyone on the network can then read and
+    /// change the traffic.
+    pub danger_accept_invalid_certs: bool,
+}
+
+impl TlsConfig {
+    fn apply(&self, mut builder: reqwest::ClientBuilder) -> Result<reqwest::ClientBuilder, String> {
+        let read = |path: &str| {
+            std::fs::read(path)
+                .map_err(|e| format!("Invalid TLS settings: cannot read {path}: {e}"))
+        };
+        if !self.ca_cert_path.is_empty() {
+            let pem = read(&self.ca_cert_path)?;
+            let certs = reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| {
+                format!(
+                    "Invalid TLS settings: bad CA certificate {}: {e}",
+                    self.ca_cert_path
+                )
+            })?;
+            for cert in certs {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+        if !self.client_cert_path.is_empty() {
+            let cert = read(&self.client_cert_path)?;
+            let key = if self.client_key_path.is_empty() {
+                cert.clone()
+            } else {
+                read(&self.client_key_path)?
+            };
+            let identity = reqwest::Identity::from_pkcs8_pem(&cert, &key).map_err(|e| {
+                format!(
+                    "Invalid TLS settings: bad client certificate {}: {e}",
+                    self.client_cert_path
+                )
+            })?;
+            builder = builder.identity(identity);
+        }
+        if self.danger_accept_invalid_certs {
+            eprintln!("[openclaw] TLS certificate verification is disabled");
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+        Ok(builder)
+    }
 }
 
 impl HttpClient {
-    /// Create a new HTTP client with default settings.
-    ///
-    /// # Panics
-    ///
-    /// Panics if the underlying `reqwest::Client::builder().build()` fails,
-    /// which should only happen if TLS initialization fails.
+    /// Create the shared client; the first request builds it.
     pub fn new() -> Self {
         Self {
-            inner: reqwest::Client::builder()
-                .build()
-                .expect("Failed to create reqwest::Client"),
+            inner: Mutex::new(None),
         }
     }
+
+    /// The client for requests under `config`'s proxy and timeouts.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the proxy URL is invalid or a TLS certificate or
+    /// key cannot be loaded.
+    pub(crate) fn client(&self, config: &OpenClawConfig) -> Result<reqwest::Client, String> {
+        let mut inner = self.inner.lock().map_err(|e| e.to_string())?;
+        if let Some((proxy, timeouts, tls, client)) = inner.as_ref() {
+            if *proxy == config.proxy && *timeouts == config.timeouts && *tls == config.tls {
+                return Ok(client.clone());
+            }
+        }
+        let timeouts = config.timeouts;
+        let mut builder = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(timeouts.connect_secs.max(1)))
+            .read_timeout(Duration::from_secs(timeouts.read_secs.max(1)));
+        let proxy = &config.proxy;
+        if !proxy.url.is_empty() {
+            let mut settings = reqwest::Proxy::all(&proxy.url)
+                .map_err(|e| format!("Invalid proxy URL {}: {e}", proxy.url))?
+                .no_proxy(reqwest::NoProxy::from_string(&proxy.no_proxy));
+            if !proxy.username.is_empty() {
+                settings = settings.basic_auth(&proxy.username, &proxy.password);
+            }
+            builder = builder.proxy(settings);
+        }
+        let builder = config.tls.apply(builder)?;
+        let client = builder
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+        *inner = Some((proxy.clone(), timeouts, config.tls.clone(), client.clone()));
+        Ok(client)
+    }
 }
 
 // ---------- Request/Response Types ----------
@@ -66,11 +346,22 @@ struct HooksAgentRequest {
 
 /// Chat response returned to the frontend via the `send_chat` IPC command.
 ///
-/// The frontend parses emotion/motion from the response text
-/// via `emotionParser.parseResponse()`.
+/// Emotion and action tags are parsed out of the reply ([`crate::tags`]);
+/// `response` keeps it as the model wrote it.
 #[derive(Deserialize, Serialize, Clone)]
 pub struct ChatResponse {
     pub response: String,
+    #[serde(flatten)]
+    pub reply: ParsedReply,
+}
+
+impl ChatResponse {
+    pub fn new(response: String) -> Self {
+        Self {
+            reply: crate::tags::parse(&response),
+            response,
+        }
+    }
 }
 
 // ---------- Commands ----------
@@ -95,24 +386,317 @@ pub struct ChatResponse {
 ///
 /// Returns `Err` if the agent ID is not configured, the CLI binary is not
 /// found, the subprocess exits with a non-zero status, or stdout is empty.
+/// With a `request_id`, [`cancel_chat`] kills the CLI and this returns
+/// [`OpenClawError::Cancelled`].
+/// A reply that takes longer than [`TimeoutConfig::total_secs`] fails with
+/// [`OpenClawError::Timeout`], its message starting with [`TIMED_OUT`].
+///
+/// When [`OpenClawConfig::provider`] selects another backend (OpenAI,
+/// Anthropic, Ollama), the message goes there over HTTP instead; see
+/// [`crate::llm`].
+///
+/// `images` (base64 or file paths) are sent along to vision models. With
+/// OpenClaw they take the gateway's HTTP chat endpoint rather than the CLI,
+/// which has no way to pass them, so they are refused under end-to-end
+/// encryption.
+///
+/// While the gateway is unreachable the message is retried per
+/// [`RetryConfig`], then queued to disk and sent once the gateway is back
+/// (see [`start_outbox_flusher`]).
 #[tauri::command]
 pub async fn send_chat(
+    app: AppHandle,
     config_state: State<'_, ConfigState>,
     message: String,
     context: Option<String>,
-) -> Result<ChatResponse, String> {
+    images: Option<Vec<ImageAttachment>>,
+    request_id: Option<String>,
+) -> Result<ChatResponse, OpenClawError> {
     let config = config_state.get()?;
+    chat(&app, config, message, context, images, request_id).await
+}
+
+/// [`send_chat`] to `agent_id` instead of the active agent, in that agent's
+/// own session and with its own system prompt (see [`crate::agents`]).
+///
+/// Runs alongside chats with the active agent, so the character can hand a
+/// long task to a second agent and keep talking meanwhile; cancel it with
+/// [`cancel_chat`] like any other request.
+#[tauri::command]
+pub async fn send_chat_to(
+    app: AppHandle,
+    config_state: State<'_, ConfigState>,
+    agent_id: String,
+    message: String,
+    context: Option<String>,
+    images: Option<Vec<ImageAttachment>>,
+    request_id: Option<String>,
+) -> Result<ChatResponse, OpenClawError> {
+    let config = crate::agents::config_for(&config_state, &agent_id)?;
+    chat(&app, config, message, context, images, request_id).await
+}
+
+/// Body of [`send_chat`] and [`send_chat_to`], with `config` holding the
+/// agent to talk to.
+async fn chat(
+    app: &AppHandle,
+    config: OpenClawConfig,
+    message: String,
+    context: Option<String>,
+    images: Option<Vec<ImageAttachment>>,
+    request_id: Option<String>,
+) -> Result<ChatResponse, OpenClawError> {
+    let http = app.state::<HttpClient>();
+    let config_state = app.state::<ConfigState>();
+    let usage = app.state::<UsageState>();
+    let images = resolve_images(&config, images)?;
+    let cache_key = images
+        .is_empty()
+        .then(|| crate::cache::key(&config, &message, context.as_deref()));
+    if let Some(reply) = cache_key.and_then(|key| crate::cache::get(&config_state, key)) {
+        crate::memory::record_exchange(&config.session_key, &message, &reply);
+        if config.provider.kind != ProviderKind::OpenClaw {
+            let client = http
+                .client(&config)
+                .map_err(OpenClawError::invalid_config)?;
+            llm::remember(app, &client, &config, message, reply.clone());
+        }
+        return Ok(ChatResponse::new(reply));
+    }
+    usage.admit(app, &config_state, &config)?;
+    let mut cancelled = request_id.as_deref().map(register_request);
+    let sent = message.clone();
+    let sent_context = context.clone();
+    let started = Instant::now();
+    let via_cli = config.provider.kind == ProviderKind::OpenClaw && images.is_empty();
+    let result = if via_cli {
+        run_chat_retrying(
+            &http,
+            &config_state,
+            &config,
+            message,
+            context,
+            cancelled.as_mut(),
+        )
+        .await
+    } else {
+        run_provider_chat(
+            app,
+            &http,
+            &config,
+            message,
+            context,
+            images,
+            cancelled.as_mut(),
+        )
+        .await
+    };
+    if let Some(id) = &request_id {
+        drop(cancelled);
+        unregister_request(id);
+    }
+    crate::chatlog::record(
+        &config_state,
+        &config,
+        Trace {
+            transport: if via_cli { "cli" } else { "http" },
+            started,
+            message: &sent,
+            context: sent_context.as_deref(),
+            result: result
+                .as_ref()
+                .map(|reply| reply.response.as_str())
+                .map_err(OpenClawError::message),
+        },
+    );
+    if let Ok(reply) = &result {
+        crate::memory::record_exchange(&config.session_key, &sent, &reply.response);
+        // Requests over HTTP are counted by `llm::chat` as sent.
+        if via_cli {
+            let request = format!("{}{sent}", sent_context.as_deref().unwrap_or_default());
+            let tokens = crate::budget::estimate_tokens(&request);
+            usage.record(&config_state, &config, tokens, &reply.response);
+        }
+        if let Some(key) = cache_key {
+            crate::cache::put(&config_state, key, &reply.response);
+        }
+    }
+    if let Err(e) = &result {
+        if !e.is_cancelled() {
+            user_facing_error(app, "openclaw", e.to_string(), Severity::Error);
+        }
+    }
+    result
+}
+
+/// Load the attachments of a chat command.
+fn resolve_images(
+    config: &OpenClawConfig,
+    images: Option<Vec<ImageAttachment>>,
+) -> Result<Vec<Image>, OpenClawError> {
+    let images = images
+        .unwrap_or_default()
+        .iter()
+        .map(ImageAttachment::resolve)
+        .collect::<Result<Vec<_>, _>>()?;
+    if !images.is_empty()
+        && config.provider.kind == ProviderKind::OpenClaw
+        && !crate::agents::e2e_key(config).is_empty()
+    {
+        return Err(OpenClawError::invalid_config(
+            "Images cannot be sent with end-to-end encryption",
+        ));
+    }
+    Ok(images)
+}
+
+/// [`send_chat`] over HTTP: backends other than OpenClaw, and OpenClaw
+/// messages with images.
+async fn run_provider_chat(
+    app: &AppHandle,
+    http: &HttpClient,
+    config: &OpenClawConfig,
+    message: String,
+    context: Option<String>,
+    images: Vec<Image>,
+    cancelled: Option<&mut oneshot::Receiver<()>>,
+) -> Result<ChatResponse, OpenClawError> {
+    let client = http.client(config).map_err(OpenClawError::invalid_config)?;
+    let total = config.timeouts.total();
+    let tools = ToolBridge::new(app, config);
+    let mut on_delta = |_: &str| {};
+    let reply = tokio::time::timeout(
+        total,
+        llm::chat(
+            app,
+            &client,
+            config,
+            crate::persona::system_prompt(&app.state::<ConfigState>(), config),
+            message,
+            context,
+            images,
+            tools.as_ref(),
+            &mut on_delta,
+        ),
+    );
+    tokio::select! {
+        result = reply => match result {
+            Ok(result) => result.map(ChatResponse::new),
+            Err(_) => Err(timed_out("chat request", total)),
+        },
+        _ = until_cancelled(cancelled) => Err(OpenClawError::cancelled()),
+    }
+}
+
+/// A reply the backend asks for on its own (e.g. [`crate::proactive`]),
+/// from the same backend as [`send_chat`] but without its cache, retries
+/// or cancellation. Counts towards [`crate::usage`]; only the reply goes
+/// into the chat history.
+pub(crate) async fn request_reply(
+    app: &AppHandle,
+    message: String,
+    context: Option<String>,
+) -> Result<ChatResponse, OpenClawError> {
+    let config_state = app.state::<ConfigState>();
+    let usage = app.state::<UsageState>();
+    let config = config_state.get()?;
+    usage.admit(app, &config_state, &config)?;
+    let reply = if config.provider.kind == ProviderKind::OpenClaw {
+        let request = format!("{}{message}", context.as_deref().unwrap_or_default());
+        let reply = run_chat(&config_state, &config, message, context, None).await?;
+        let tokens = crate::budget::estimate_tokens(&request);
+        usage.record(&config_state, &config, tokens, &reply.response);
+        reply
+    } else {
+        let http = app.state::<HttpClient>();
+        run_provider_chat(app, &http, &config, message, context, Vec::new(), None).await?
+    };
+    if let Err(e) =
+        crate::memory::append_chat_history(&config.session_key, &[("assistant", &reply.response)])
+    {
+        eprintln!("[openclaw] {e}");
+    }
+    Ok(reply)
+}
+
+/// [`run_chat`] with the retries and offline queueing of [`RetryConfig`].
+async fn run_chat_retrying(
+    http: &HttpClient,
+    config_state: &ConfigState,
+    config: &OpenClawConfig,
+    message: String,
+    context: Option<String>,
+    mut cancelled: Option<&mut oneshot::Receiver<()>>,
+) -> Result<ChatResponse, OpenClawError> {
+    let policy = config_state.get_settings()?.retry;
+    let mut attempt = 0;
+    loop {
+        let result = run_chat(
+            config_state,
+            config,
+            message.clone(),
+            context.clone(),
+            cancelled.as_deref_mut(),
+        )
+        .await;
+        match result {
+            Err(e) if !e.is_cancelled() && !gateway_reachable(http, config).await => {
+                attempt += 1;
+                if attempt >= policy.attempts {
+                    let queued = QueuedMessage::Chat {
+                        agent_id: config.agent_id.clone(),
+                        session_key: config.session_key.clone(),
+                        message,
+                        context,
+                    };
+                    return Err(offline(&policy, queued, e));
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(policy.delay(attempt)) => {}
+                    _ = until_cancelled(cancelled.as_deref_mut()) => return Err(OpenClawError::cancelled()),
+                }
+            }
+            result => return result,
+        }
+    }
+}
+
+/// The CLI chat behind [`send_chat`] for the OpenClaw backend, with the
+/// agent and session in `config`.
+async fn run_chat(
+    config_state: &ConfigState,
+    config: &OpenClawConfig,
+    message: String,
+    context: Option<String>,
+    cancelled: Option<&mut oneshot::Receiver<()>>,
+) -> Result<ChatResponse, OpenClawError> {
+    let cancelled = until_cancelled(cancelled);
 
     if config.agent_id.is_empty() {
-        return Err(
-            "Agent ID not configured. Open Settings to configure OpenClaw connection.".to_string(),
-        );
+        return Err(OpenClawError::invalid_config(
+            "Agent ID not configured. Open Settings to configure OpenClaw connection.",
+        ));
     }
 
     let full_message = match context {
         Some(ctx) if !ctx.is_empty() => format!("{}\n\n[USER MESSAGE]\n{}", ctx, message),
         _ => message,
     };
+    // The CLI has no system role; the system prompt leads instead.
+    let full_message = match crate::persona::system_prompt(config_state, config) {
+        Some(prompt) => format!("[CHARACTER]\n{}\n\n{}", prompt, full_message),
+        None => full_message,
+    };
+
+    crate::prompt_snapshot::record(config, "cli", &full_message);
+
+    // Seal the payload before it leaves the process when E2E is enabled.
+    let e2e_key = crate::agents::e2e_key(config);
+    let full_message = if e2e_key.is_empty() {
+        full_message
+    } else {
+        crate::e2e::seal(e2e_key, &full_message).map_err(OpenClawError::invalid_config)?
+    };
 
     let cli = if config.cli_path.is_empty() {
         "openclaw"
@@ -131,7 +715,7 @@ pub async fn send_chat(
         full_message.len(),
     );
 
-    let timeout = Duration::from_secs(CLI_TIMEOUT_SECS);
+    let timeout = config.timeouts.total();
 
     // Share the child process so we can kill it on timeout.
     // Uses Arc<Mutex<Option<Child>>> — the blocking thread holds a &mut ref
@@ -209,28 +793,41 @@ pub async fn send_chat(
             .wait()?;
 
         Ok(std::process::Output { status, stdout: stdout_buf, stderr: stderr_buf })
-    }))
-    .await;
+    }));
+
+    let output = tokio::select! {
+        output = output => output,
+        _ = cancelled => {
+            if let Ok(mut guard) = child_for_timeout.lock() {
+                if let Some(ref mut child) = *guard {
+                    eprintln!("[send_chat] Cancelled — killing child process");
+                    let _ = child.kill();
+                    let _ = child.wait();
+                }
+            }
+            return Err(OpenClawError::cancelled());
+        }
+    };
 
     // Handle timeout: kill the subprocess if it's still running
     let output: std::process::Output = match output {
-        Ok(join_result) => {
-            join_result
-                .map_err(|e| format!("Task join error: {e}"))?
-                .map_err(|e| {
-                    format!("Failed to run openclaw CLI: {e}. Is openclaw installed and in PATH?")
-                })?
-        }
+        Ok(join_result) => join_result
+            .map_err(|e| OpenClawError::other(format!("Task join error: {e}")))?
+            .map_err(|e| {
+                OpenClawError::invalid_config(format!(
+                    "Failed to run openclaw CLI: {e}. Is openclaw installed and in PATH?"
+                ))
+            })?,
         Err(_) => {
             // Timeout — kill the child process to prevent orphaning
             if let Ok(mut guard) = child_for_timeout.lock() {
                 if let Some(ref mut child) = *guard {
-                    eprintln!("[send_chat] CLI timed out after {CLI_TIMEOUT_SECS}s — killing child process");
+                    eprintln!("[send_chat] CLI timed out after {}s — killing child process", timeout.as_secs());
                     let _ = child.kill();
                     let _ = child.wait(); // Reap the zombie
                 }
             }
-            return Err(format!("openclaw CLI timed out after {CLI_TIMEOUT_SECS}s"));
+            return Err(timed_out("openclaw CLI", timeout));
         }
     };
 
@@ -246,7 +843,10 @@ pub async fn send_chat(
             format!("openclaw CLI error: {stderr_text}")
         };
         eprintln!("[send_chat] FAILED: {}", msg);
-        return Err(msg);
+        return Err(OpenClawError::GatewayError {
+            status: None,
+            message: msg,
+        });
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
@@ -261,35 +861,844 @@ pub async fn send_chat(
     eprintln!("[send_chat] stdout ({} chars): {}", stdout.len(), &stdout[..preview_end]);
 
     if stdout.is_empty() {
-        return Err("OpenClaw returned an empty response".to_string());
+        return Err(OpenClawError::GatewayError {
+            status: None,
+            message: "OpenClaw returned an empty response".to_string(),
+        });
     }
 
-    Ok(ChatResponse {
-        response: stdout,
-    })
+    let stdout = if e2e_key.is_empty() {
+        stdout
+    } else {
+        crate::e2e::open(e2e_key, &stdout)
+            .map_err(|message| OpenClawError::DecryptFailed { message })?
+    };
+
+    Ok(ChatResponse::new(stdout))
+}
+
+// ---------- Tool Bridge ----------
+
+/// Tools the model may call, by name; [`OpenClawConfig::tools`] picks the
+/// ones offered.
+pub const AVAILABLE_TOOLS: &[&str] = &[
+    "get_active_window",
+    "get_now_playing",
+    "capture_region",
+    "set_reminder",
+];
+
+/// Longest delay [`ToolBridge`] accepts for `set_reminder` (one day).
+const MAX_REMINDER_MINUTES: u64 = 24 * 60;
+
+/// Payload of `"reminder"` events, emitted when a reminder set by the
+/// model is due.
+#[derive(Serialize, Clone)]
+pub struct Reminder {
+    pub message: String,
+}
+
+/// Desktop commands offered to the model as function-calling tools.
+///
+/// Used by the HTTP chat paths ([`send_chat_stream`], and [`send_chat`] for
+/// OpenAI-compatible backends): [`crate::llm`] advertises
+/// [`definitions`](Self::definitions) with the request, runs the calls the
+/// model returns through [`call`](Self::call) and sends the results back
+/// until the model answers in text. The CLI path has the agent's own tools
+/// instead.
+pub struct ToolBridge {
+    app: AppHandle,
+    enabled: Vec<String>,
+}
+
+impl ToolBridge {
+    /// The bridge for the tools enabled in `config`, or `None` if there are
+    /// none.
+    pub fn new(app: &AppHandle, config: &OpenClawConfig) -> Option<Self> {
+        let enabled: Vec<String> = config
+            .tools
+            .iter()
+            .filter(|name| AVAILABLE_TOOLS.contains(&name.as_str()))
+            .cloned()
+            .collect();
+        (!enabled.is_empty()).then(|| Self {
+            app: app.clone(),
+            enabled,
+        })
+    }
+
+    /// OpenAI-style `tools` entries for the enabled tools.
+    pub fn definitions(&self) -> Vec<serde_json::Value> {
+        self.enabled
+            .iter()
+            .filter_map(|name| {
+                let (description, parameters) = match name.as_str() {
+                    "get_active_window" => (
+                        "Get the app name and title of the window the user is focused on.",
+                        serde_json::json!({ "type": "object", "properties": {} }),
+                    ),
+                    "get_now_playing" => (
+                        "Get the song or video currently playing on the user's computer.",
+                        serde_json::json!({ "type": "object", "properties": {} }),
+                    ),
+                    "capture_region" => (
+                        "Take a PNG screenshot of a rectangle of the user's screen.",
+                        serde_json::json!({
+                            "type": "object",
+                            "properties": {
+                                "x": { "type": "integer", "description": "Left edge." },
+                                "y": { "type": "integer", "description": "Top edge." },
+                                "width": { "type": "integer", "description": "Width (1 to 4096)." },
+                                "height": { "type": "integer", "description": "Height (1 to 4096)." }
+                            },
+                            "required": ["x", "y", "width", "height"]
+                        }),
+                    ),
+                    "set_reminder" => (
+                        "Remind the user of something after a delay.",
+                        serde_json::json!({
+                            "type": "object",
+                            "properties": {
+                                "message": {
+                                    "type": "string",
+                                    "description": "What to remind the user of."
+                                },
+                                "minutes": {
+                                    "type": "integer",
+                                    "description": "Delay in minutes (1 to 1440)."
+                                }
+                            },
+                            "required": ["message", "minutes"]
+                        }),
+                    ),
+                    _ => return None,
+                };
+                Some(serde_json::json!({
+                    "type": "function",
+                    "function": {
+                        "name": name,
+                        "description": description,
+                        "parameters": parameters,
+                    }
+                }))
+            })
+            .collect()
+    }
+
+    /// Run tool `name` with JSON `arguments` and return its result as JSON
+    /// text for the model. Failures are reported to the model, not raised.
+    pub async fn call(&self, name: &str, arguments: &str) -> String {
+        eprintln!("[openclaw] Tool call: {name}");
+        let result = if self.enabled.iter().any(|n| n == name) {
+            self.run(name, arguments).await
+        } else {
+            Err(format!("Unknown tool: {name}"))
+        };
+        match result {
+            Ok(value) => value.to_string(),
+            Err(e) => serde_json::json!({ "error": e }).to_string(),
+        }
+    }
+
+    async fn run(&self, name: &str, arguments: &str) -> Result<serde_json::Value, String> {
+        match name {
+            "get_active_window" => {
+                let window = crate::screen::get_active_window(self.app.state::<ConfigState>());
+                serde_json::to_value(window).map_err(|e| e.to_string())
+            }
+            "get_now_playing" => {
+                let playing = crate::media::get_now_playing(self.app.clone()).await;
+                serde_json::to_value(playing).map_err(|e| e.to_string())
+            }
+            "capture_region" => {
+                #[derive(Deserialize)]
+                struct Args {
+                    x: i32,
+                    y: i32,
+                    width: u32,
+                    height: u32,
+                }
+                let args: Args = serde_json::from_str(arguments)
+                    .map_err(|e| format!("Invalid arguments: {e}"))?;
+                let image =
+                    crate::screen::capture_region(args.x, args.y, args.width, args.height).await?;
+                Ok(serde_json::json!({ "image": image }))
+            }
+            "set_reminder" => {
+                #[derive(Deserialize)]
+                struct Args {
+                    message: String,
+                    minutes: u64,
+                }
+                let args: Args = serde_json::from_str(arguments)
+                    .map_err(|e| format!("Invalid arguments: {e}"))?;
+                let minutes = args.minutes.clamp(1, MAX_REMINDER_MINUTES);
+                let app = self.app.clone();
+                tauri::async_runtime::spawn(async move {
+                    tokio::time::sleep(Duration::from_secs(minutes * 60)).await;
+                    let _ = app.emit(
+                        "reminder",
+                        Reminder {
+                            message: args.message,
+                        },
+                    );
+                });
+                Ok(serde_json::json!({ "scheduled": true, "minutes": minutes }))
+            }
+            _ => Err(format!("Unknown tool: {name}")),
+        }
+    }
+}
+
+// ---------- Retries and Offline Queue ----------
+
+/// How often [`start_outbox_flusher`] checks for the gateway while messages
+/// are queued.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Retry policy for [`send_chat`] and [`send_webhook`] while the gateway
+/// is unreachable.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetryConfig {
+    /// Tries per message, including the first.
+    pub attempts: u32,
+    /// Delay before the first retry; doubles with every further one.
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+    /// Queue messages that still fail to disk and send them once the
+    /// gateway is back.
+    pub queue_offline: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            attempts: 4,
+            base_delay_ms: 500,
+            max_delay_ms: 8000,
+            queue_offline: true,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Backoff before retry number `attempt` (1-based), jittered to 50–100%
+    /// so several clients do not hammer a restarting gateway in step.
+    fn delay(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .base_delay_ms
+            .saturating_mul(1 << (attempt - 1).min(16))
+            .min(self.max_delay_ms);
+        let mut random = [0u8; 2];
+        let _ = getrandom::getrandom(&mut random);
+        let jitter = 0.5 + 0.5 * u16::from_le_bytes(random) as f64 / u16::MAX as f64;
+        Duration::from_millis((backoff as f64 * jitter) as u64)
+    }
+}
+
+/// A message waiting in the offline queue.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+enum QueuedMessage {
+    /// Sent to `agent_id` in `session_key` when flushed, whichever agent is
+    /// active by then. Both are empty in queues from older versions, which
+    /// go to the active agent.
+    #[serde(rename_all = "camelCase")]
+    Chat {
+        #[serde(default)]
+        agent_id: String,
+        #[serde(default)]
+        session_key: String,
+        message: String,
+        context: Option<String>,
+    },
+    Webhook {
+        message: String,
+    },
+}
+
+impl QueuedMessage {
+    fn message(&self) -> &str {
+        match self {
+            Self::Chat { message, .. } | Self::Webhook { message } => message,
+        }
+    }
+}
+
+/// A reply to a queued chat message, delivered with `"queue-flushed"`.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedReply {
+    /// The agent the message was sent to.
+    pub agent_id: String,
+    pub message: String,
+    #[serde(flatten)]
+    pub reply: ChatResponse,
+}
+
+/// Payload of `"queue-flushed"` events.
+#[derive(Serialize, Clone, Default)]
+pub struct QueueFlushed {
+    /// Messages delivered.
+    pub sent: usize,
+    /// Messages the gateway rejected for good (e.g. a removed agent).
+    pub dropped: usize,
+    /// Messages still queued because the gateway went away again.
+    pub remaining: usize,
+    pub replies: Vec<QueuedReply>,
+}
+
+/// Serializes reads and writes of the queue file.
+static OUTBOX: Mutex<()> = Mutex::new(());
+
+fn outbox_path() -> std::path::PathBuf {
+    crate::memory::data_dir().join("outbox.json")
+}
+
+fn load_outbox() -> Vec<QueuedMessage> {
+    std::fs::read_to_string(outbox_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_outbox(queue: &[QueuedMessage]) -> Result<(), String> {
+    let path = outbox_path();
+    if queue.is_empty() {
+        return match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(format!("Failed to clear outbox: {e}"))
+            }
+            _ => Ok(()),
+        };
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create data dir: {e}"))?;
+    }
+    let json = serde_json::to_string_pretty(queue).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write outbox: {e}"))
+}
+
+/// The error for a message that ran out of retries, queueing it if the
+/// policy allows.
+fn offline(policy: &RetryConfig, queued: QueuedMessage, error: OpenClawError) -> OpenClawError {
+    if !policy.queue_offline {
+        return error;
+    }
+    let saved = OUTBOX.lock().map_err(|e| e.to_string()).and_then(|_guard| {
+        let mut queue = load_outbox();
+        queue.push(queued);
+        save_outbox(&queue)
+    });
+    match saved {
+        Ok(()) => {
+            eprintln!("[openclaw] Gateway unreachable, queued message: {error}");
+            OpenClawError::NetworkUnreachable {
+                message: "OpenClaw Gateway is unreachable. The message was queued and will be sent when it is back."
+                    .to_string(),
+            }
+        }
+        Err(e) => OpenClawError::NetworkUnreachable {
+            message: format!("{error} (could not queue the message: {e})"),
+        },
+    }
+}
+
+/// Start the task that sends queued messages once the gateway answers
+/// again, emitting `"queue-flushed"` after each pass that sent anything.
+pub fn start_outbox_flusher(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(FLUSH_INTERVAL).await;
+            if let Err(e) = flush_outbox(&app).await {
+                eprintln!("[openclaw] Outbox flush failed: {e}");
+            }
+        }
+    });
+}
+
+/// The config a queued chat for `agent_id` in `session_key` was sent
+/// with; `active` for entries queued before they were recorded.
+fn queued_chat_config(
+    config_state: &ConfigState,
+    active: &OpenClawConfig,
+    agent_id: &str,
+    session_key: &str,
+) -> Result<OpenClawConfig, String> {
+    if agent_id.is_empty() {
+        return Ok(active.clone());
+    }
+    let mut config = crate::agents::config_for(config_state, agent_id)?;
+    if !session_key.is_empty() {
+        config.session_key = session_key.to_string();
+    }
+    Ok(config)
+}
+
+async fn flush_outbox(app: &AppHandle) -> Result<(), String> {
+    let queued = {
+        let _guard = OUTBOX.lock().map_err(|e| e.to_string())?;
+        load_outbox()
+    };
+    if queued.is_empty() {
+        return Ok(());
+    }
+    let http = app.state::<HttpClient>();
+    let config_state = app.state::<ConfigState>();
+    let config = config_state.get()?;
+    if !gateway_reachable(&http, &config).await {
+        return Ok(());
+    }
+
+    let mut flushed = QueueFlushed::default();
+    let mut unsent = Vec::new();
+    for (i, item) in queued.iter().enumerate() {
+        let result = match item {
+            QueuedMessage::Chat {
+                agent_id,
+                session_key,
+                message,
+                context,
+            } => {
+                let target = queued_chat_config(&config_state, &config, agent_id, session_key);
+                match target {
+                    Ok(target) => run_chat(
+                        &config_state,
+                        &target,
+                        message.clone(),
+                        context.clone(),
+                        None,
+                    )
+                    .await
+                    .map(|reply| Some((target, reply.response))),
+                    Err(e) => Err(e.into()),
+                }
+            }
+            QueuedMessage::Webhook { message } => {
+                run_webhook(&http, &config_state, message.clone())
+                    .await
+                    .map(|()| None)
+            }
+        };
+        match result {
+            Ok(response) => {
+                flushed.sent += 1;
+                if let Some((target, response)) = response {
+                    crate::memory::record_exchange(&target.session_key, item.message(), &response);
+                    flushed.replies.push(QueuedReply {
+                        agent_id: target.agent_id,
+                        message: item.message().to_string(),
+                        reply: ChatResponse::new(response),
+                    });
+                }
+            }
+            Err(_) if !gateway_reachable(&http, &config_state.get()?).await => {
+                unsent = queued[i..].to_vec();
+                break;
+            }
+            Err(e) => {
+                eprintln!("[openclaw] Dropping queued message: {e}");
+                flushed.dropped += 1;
+            }
+        }
+    }
+
+    {
+        let _guard = OUTBOX.lock().map_err(|e| e.to_string())?;
+        // Messages queued while this pass ran go after the unsent ones.
+        let mut queue = unsent;
+        queue.extend(load_outbox().into_iter().skip(queued.len()));
+        flushed.remaining = queue.len();
+        save_outbox(&queue)?;
+    }
+    if flushed.sent + flushed.dropped > 0 {
+        let _ = app.emit("queue-flushed", flushed);
+    }
+    Ok(())
+}
+
+/// Returned by chat requests stopped with [`cancel_chat`].
+const CANCELLED: &str = "Chat request cancelled";
+
+/// Cancellation senders of in-flight chat requests, by request id.
+static IN_FLIGHT: Mutex<Vec<(String, oneshot::Sender<()>)>> = Mutex::new(Vec::new());
+
+/// Make `id` cancellable. Reusing an id cancels the request that had it.
+fn register_request(id: &str) -> oneshot::Receiver<()> {
+    let (tx, rx) = oneshot::channel();
+    if let Ok(mut in_flight) = IN_FLIGHT.lock() {
+        if let Some(at) = in_flight.iter().position(|(other, _)| other == id) {
+            let (_, previous) = in_flight.swap_remove(at);
+            let _ = previous.send(());
+        }
+        in_flight.push((id.to_string(), tx));
+    }
+    rx
+}
+
+/// Forget a finished request. An entry whose receiver is still alive
+/// belongs to a newer request that reused the id.
+fn unregister_request(id: &str) {
+    if let Ok(mut in_flight) = IN_FLIGHT.lock() {
+        in_flight.retain(|(other, tx)| other != id || !tx.is_closed());
+    }
+}
+
+/// Resolves once the request is cancelled; never without a receiver.
+async fn until_cancelled(cancelled: Option<&mut oneshot::Receiver<()>>) {
+    match cancelled {
+        // A dropped sender means the request finished, not cancelled.
+        Some(rx) => {
+            if rx.await.is_err() {
+                std::future::pending::<()>().await;
+            }
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Abort an in-flight [`send_chat`] (by its `request_id`) or
+/// [`send_chat_stream`] (by stream id), so a stale reply cannot arrive
+/// after a follow-up message. Returns whether a request was cancelled.
+#[tauri::command]
+pub fn cancel_chat(request_id: String) -> bool {
+    let sender = IN_FLIGHT.lock().ok().and_then(|mut in_flight| {
+        let at = in_flight.iter().position(|(id, _)| *id == request_id)?;
+        Some(in_flight.swap_remove(at).1)
+    });
+    sender.is_some_and(|tx| tx.send(()).is_ok())
+}
+
+/// Source of the ids returned by [`send_chat_stream`].
+static NEXT_STREAM_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Payload of `"chat-chunk"` events.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatChunk {
+    pub stream_id: String,
+    /// The agent replying; not always the active one ([`send_chat_stream_to`]).
+    pub agent_id: String,
+    /// Text added since the previous chunk.
+    pub delta: String,
+}
+
+/// Payload of `"chat-complete"` events.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatComplete {
+    pub stream_id: String,
+    /// The agent replying; not always the active one ([`send_chat_stream_to`]).
+    pub agent_id: String,
+    /// The whole reply, as [`send_chat`] would have returned it.
+    #[serde(flatten)]
+    pub reply: ChatResponse,
+    /// Whether the backend is already speaking the reply
+    /// ([`crate::tts::TtsConfig::stream_chat`]).
+    pub spoken: bool,
+}
+
+/// Payload of `"chat-cancelled"` events.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatCancelled {
+    pub stream_id: String,
+    /// The agent replying; not always the active one ([`send_chat_stream_to`]).
+    pub agent_id: String,
+}
+
+/// Payload of `"chat-error"` events.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatStreamError {
+    pub stream_id: String,
+    /// The agent replying; not always the active one ([`send_chat_stream_to`]).
+    pub agent_id: String,
+    pub error: OpenClawError,
+}
+
+/// Send a chat message and stream the reply as it is generated.
+///
+/// Returns a stream id immediately. The reply arrives as `"chat-chunk"`
+/// events (one per token or so), followed by `"chat-complete"` with the full
+/// text, `"chat-error"` if the request fails, or `"chat-cancelled"` after
+/// [`cancel_chat`]; all carry the stream id.
+///
+/// Goes to the backend selected in [`OpenClawConfig::provider`]; for
+/// OpenClaw that is the gateway's OpenAI-compatible `/v1/chat/completions`
+/// endpoint (enable `gateway.http.endpoints.chatCompletions` in OpenClaw)
+/// instead of the CLI.
+///
+/// With [`crate::tts::TtsConfig::stream_chat`] the reply is also spoken
+/// sentence by sentence as it arrives ([`crate::ttsstream`]).
+///
+/// # Errors
+///
+/// With OpenClaw, returns `Err` if the agent ID is not configured or
+/// end-to-end encryption is on: sealed replies can only be opened as a
+/// whole, so use [`send_chat`] then.
+/// Also returns `Err` if an image in `images` cannot be loaded.
+#[tauri::command]
+pub async fn send_chat_stream(
+    app: AppHandle,
+    config_state: State<'_, ConfigState>,
+    message: String,
+    context: Option<String>,
+    images: Option<Vec<ImageAttachment>>,
+) -> Result<String, OpenClawError> {
+    let config = config_state.get()?;
+    let speak = config_state.get_settings()?.tts.stream_chat;
+    start_chat_stream(app, config, message, context, images, speak)
+}
+
+/// [`send_chat_stream`] to `agent_id` instead of the active agent, the way
+/// [`send_chat_to`] is to [`send_chat`]. Its events carry the agent id, so
+/// several streams can run at once and be told apart; only the active
+/// agent's replies are spoken.
+#[tauri::command]
+pub async fn send_chat_stream_to(
+    app: AppHandle,
+    config_state: State<'_, ConfigState>,
+    agent_id: String,
+    message: String,
+    context: Option<String>,
+    images: Option<Vec<ImageAttachment>>,
+) -> Result<String, OpenClawError> {
+    let active = config_state.get()?.agent_id;
+    let config = crate::agents::config_for(&config_state, &agent_id)?;
+    let speak = config.agent_id == active && config_state.get_settings()?.tts.stream_chat;
+    start_chat_stream(app, config, message, context, images, speak)
+}
+
+/// Body of [`send_chat_stream`] and [`send_chat_stream_to`], with `config`
+/// holding the agent to talk to; `speak` says the reply is spoken as it
+/// arrives.
+fn start_chat_stream(
+    app: AppHandle,
+    config: OpenClawConfig,
+    message: String,
+    context: Option<String>,
+    images: Option<Vec<ImageAttachment>>,
+    speak: bool,
+) -> Result<String, OpenClawError> {
+    let http = app.state::<HttpClient>();
+    let config_state = app.state::<ConfigState>();
+    if config.provider.kind == ProviderKind::OpenClaw {
+        if config.agent_id.is_empty() {
+            return Err(OpenClawError::invalid_config(
+                "Agent ID not configured. Open Settings to configure OpenClaw connection.",
+            ));
+        }
+        if !crate::agents::e2e_key(&config).is_empty() {
+            return Err(OpenClawError::invalid_config(
+                "Streaming chat is not available with end-to-end encryption",
+            ));
+        }
+    }
+
+    let images = resolve_images(&config, images)?;
+    let cache_key = images
+        .is_empty()
+        .then(|| crate::cache::key(&config, &message, context.as_deref()));
+    let cached = cache_key.and_then(|key| crate::cache::get(&config_state, key));
+    if cached.is_none() {
+        app.state::<UsageState>().admit(&app, &config_state, &config)?;
+    }
+
+    let stream_id = format!("chat-{}", NEXT_STREAM_ID.fetch_add(1, Ordering::Relaxed));
+    let client = http.client(&config).map_err(OpenClawError::invalid_config)?;
+    let id = stream_id.clone();
+    let mut cancelled = register_request(&stream_id);
+    let sent = message.clone();
+    let sent_context = context.clone();
+    let started = Instant::now();
+    let agent_id = config.agent_id.clone();
+    tauri::async_runtime::spawn(async move {
+        let hit = cached.is_some();
+        let speech = speak.then(|| SpeechStream::start(&app));
+        let result = match cached {
+            Some(reply) => {
+                let _ = app.emit(
+                    "chat-chunk",
+                    ChatChunk {
+                        stream_id: id.clone(),
+                        agent_id: agent_id.clone(),
+                        delta: reply.clone(),
+                    },
+                );
+                if let Some(speech) = &speech {
+                    speech.push(&reply);
+                }
+                Ok(reply)
+            }
+            None => {
+                let mut on_delta = |delta: &str| {
+                    let _ = app.emit(
+                        "chat-chunk",
+                        ChatChunk {
+                            stream_id: id.clone(),
+                            agent_id: agent_id.clone(),
+                            delta: delta.to_string(),
+                        },
+                    );
+                    if let Some(speech) = &speech {
+                        speech.push(delta);
+                    }
+                };
+                // Dropping the request future closes the connection.
+                tokio::select! {
+                    result = run_chat_stream(&app, &client, &config, message, context, images, &mut on_delta) => result,
+                    _ = until_cancelled(Some(&mut cancelled)) => Err(OpenClawError::cancelled()),
+                }
+            }
+        };
+        let spoken = result.is_ok() && speech.is_some();
+        if let (Ok(_), Some(speech)) = (&result, speech) {
+            speech.finish();
+        }
+        unregister_request(&id);
+        crate::chatlog::record(
+            &app.state::<ConfigState>(),
+            &config,
+            Trace {
+                transport: if hit { "cache" } else { "stream" },
+                started,
+                message: &sent,
+                context: sent_context.as_deref(),
+                result: result.as_deref().map_err(OpenClawError::message),
+            },
+        );
+        match result {
+            Err(e) if e.is_cancelled() => {
+                let _ = app.emit(
+                    "chat-cancelled",
+                    ChatCancelled {
+                        stream_id: id,
+                        agent_id,
+                    },
+                );
+            }
+            Ok(response) => {
+                crate::memory::record_exchange(&config.session_key, &sent, &response);
+                if hit {
+                    llm::remember(&app, &client, &config, sent.clone(), response.clone());
+                } else if let Some(key) = cache_key {
+                    crate::cache::put(&app.state::<ConfigState>(), key, &response);
+                }
+                let _ = app.emit(
+                    "chat-complete",
+                    ChatComplete {
+                        stream_id: id,
+                        agent_id,
+                        reply: ChatResponse::new(response),
+                        spoken,
+                    },
+                );
+            }
+            Err(e) => {
+                eprintln!("[send_chat_stream] FAILED: {e}");
+                user_facing_error(&app, "openclaw", e.to_string(), Severity::Error);
+                let _ = app.emit(
+                    "chat-error",
+                    ChatStreamError {
+                        stream_id: id,
+                        agent_id,
+                        error: e,
+                    },
+                );
+            }
+        }
+    });
+    Ok(stream_id)
+}
+
+/// Body of [`send_chat_stream`]: passes the chunks to `on_delta` and returns
+/// the reply.
+async fn run_chat_stream(
+    app: &AppHandle,
+    client: &reqwest::Client,
+    config: &OpenClawConfig,
+    message: String,
+    context: Option<String>,
+    images: Vec<Image>,
+    on_delta: &mut (dyn FnMut(&str) + Send),
+) -> Result<String, OpenClawError> {
+    let tools = ToolBridge::new(app, config);
+    llm::chat(
+        app,
+        client,
+        config,
+        crate::persona::system_prompt(&app.state::<ConfigState>(), config),
+        message,
+        context,
+        images,
+        tools.as_ref(),
+        on_delta,
+    )
+    .await
 }
 
 /// Fire-and-forget: send a message to the OpenClaw Gateway via POST /hooks/agent.
 ///
 /// Returns immediately after the gateway accepts the request (HTTP 202).
 /// Use this for background triggers where you don't need the agent's response.
+/// The body is signed (see [`crate::signing`]) when
+/// [`OpenClawConfig::webhook_secret`] is set.
 #[tauri::command]
 pub async fn send_webhook(
+    app: AppHandle,
     http: State<'_, HttpClient>,
     config_state: State<'_, ConfigState>,
     message: String,
-) -> Result<(), String> {
+) -> Result<(), OpenClawError> {
+    let policy = config_state.get_settings()?.retry;
+    let mut attempt = 0;
+    let result = loop {
+        match run_webhook(&http, &config_state, message.clone()).await {
+            Err(e) if !gateway_reachable(&http, &config_state.get()?).await => {
+                attempt += 1;
+                if attempt >= policy.attempts {
+                    break Err(offline(&policy, QueuedMessage::Webhook { message }, e));
+                }
+                tokio::time::sleep(policy.delay(attempt)).await;
+            }
+            result => break result,
+        }
+    };
+    if let Err(e) = &result {
+        user_facing_error(&app, "openclaw", e.to_string(), Severity::Warning);
+    }
+    result
+}
+
+/// Body of [`send_webhook`].
+async fn run_webhook(
+    http: &HttpClient,
+    config_state: &ConfigState,
+    message: String,
+) -> Result<(), OpenClawError> {
     let config = config_state.get()?;
 
     if config.agent_id.is_empty() {
-        return Err(
-            "Agent ID not configured. Open Settings to configure OpenClaw connection.".to_string(),
-        );
+        return Err(OpenClawError::invalid_config(
+            "Agent ID not configured. Open Settings to configure OpenClaw connection.",
+        ));
     }
 
     let base = config.gateway_url.trim_end_matches('/');
     let url = format!("{}/hooks/agent", base);
 
+    crate::prompt_snapshot::record(&config, "webhook", &message);
+
+    // Sealed like the CLI path's messages; the webhook has no reply to open.
+    let e2e_key = crate::agents::e2e_key(&config);
+    let message = if e2e_key.is_empty() {
+        message
+    } else {
+        crate::e2e::seal(e2e_key, &message).map_err(OpenClawError::invalid_config)?
+    };
+
     let body = HooksAgentRequest {
         message,
         agent_id: config.agent_id.clone(),
@@ -297,23 +1706,37 @@ pub async fn send_webhook(
         session_key: config.session_key.clone(),
     };
 
+    let total = config.timeouts.total();
+    let body = serde_json::to_vec(&body).map_err(|e| e.to_string())?;
     let mut request = http
-        .inner
+        .client(&config)
+        .map_err(OpenClawError::invalid_config)?
         .post(&url)
-        .timeout(Duration::from_secs(HTTP_TIMEOUT_SECS))
-        .json(&body);
+        .timeout(total)
+        .header(reqwest::header::CONTENT_TYPE, "application/json");
 
     if !config.hooks_token.is_empty() {
         request = request.bearer_auth(&config.hooks_token);
     }
+    if !config.webhook_secret.is_empty() {
+        for (name, value) in crate::signing::sign(&config.webhook_secret, &body) {
+            request = request.header(name, value);
+        }
+    }
+    let request = request.body(body);
 
     let response = request.send().await.map_err(|e| {
         if e.is_timeout() {
-            "Webhook request timed out".to_string()
+            timed_out("webhook request", total)
         } else if e.is_connect() {
-            "Cannot connect to OpenClaw Gateway. Check gateway URL in Settings.".to_string()
+            OpenClawError::NetworkUnreachable {
+                message: "Cannot connect to OpenClaw Gateway. Check gateway URL in Settings."
+                    .to_string(),
+            }
         } else {
-            format!("Webhook request failed: {e}")
+            OpenClawError::NetworkUnreachable {
+                message: format!("Webhook request failed: {e}"),
+            }
         }
     })?;
 
@@ -331,7 +1754,10 @@ pub async fn send_webhook(
         } else {
             body_text
         };
-        Err(format!("OpenClaw returned status {status}: {preview}"))
+        Err(OpenClawError::from_status(
+            status,
+            format!("OpenClaw returned status {status}: {preview}"),
+        ))
     }
 }
 
@@ -343,74 +1769,212 @@ pub async fn send_webhook(
 pub async fn check_openclaw_health(
     http: State<'_, HttpClient>,
     config_state: State<'_, ConfigState>,
-) -> Result<bool, String> {
+) -> Result<bool, OpenClawError> {
     let config = config_state.get()?;
-    let base = config.gateway_url.trim_end_matches('/');
+    Ok(gateway_reachable(&http, &config).await)
+}
 
-    match http
-        .inner
+/// Whether the gateway in `config` answers HTTP at all (see
+/// [`check_openclaw_health`]).
+pub async fn gateway_reachable(http: &HttpClient, config: &OpenClawConfig) -> bool {
+    let base = config.gateway_url.trim_end_matches('/');
+    let Ok(client) = http.client(config) else {
+        return false;
+    };
+    client
         .get(base)
-        .timeout(Duration::from_secs(HTTP_TIMEOUT_SECS))
+        .timeout(Duration::from_secs(HTTP_TIMEOUT_SECS).min(config.timeouts.total()))
         .send()
         .await
-    {
-        Ok(_) => Ok(true),
-        Err(_) => Ok(false),
+        .is_ok()
+}
+
+/// One change [`setup_openclaw_hooks`] makes, or would make.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SetupChange {
+    /// Where: a file, or a JSON path within it (`"openclaw.json: hooks.token"`).
+    pub target: String,
+    /// `"create"`, `"modify"` or `"unchanged"`.
+    pub action: &'static str,
+    /// Values before and after, with tokens masked.
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// Returned by [`setup_openclaw_hooks`].
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HooksSetup {
+    /// The hooks token, existing or new.
+    pub token: String,
+    pub changes: Vec<SetupChange>,
+    /// Whether the changes were written (`false` for a dry run).
+    pub applied: bool,
+}
+
+/// A token as shown in [`SetupChange`]: its last four characters.
+fn mask_token(token: &str) -> String {
+    let tail: String = token
+        .chars()
+        .rev()
+        .take(4)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    format!("…{tail}")
+}
+
+fn setup_change(target: &str, old: Option<String>, new: Option<String>) -> SetupChange {
+    let action = match (&old, &new) {
+        (None, Some(_)) => "create",
+        (old, new) if old == new => "unchanged",
+        _ => "modify",
+    };
+    SetupChange {
+        target: target.to_string(),
+        action,
+        old,
+        new,
     }
 }
 
 /// Configure OpenClaw hooks in ~/.openclaw/openclaw.json.
 ///
-/// Reads the existing config (if any), sets `hooks.enabled = true` with a
-/// freshly generated token, writes it back, and also saves the token into
-/// the app's own config so the webhook auth is automatically set up.
+/// Sets `hooks.enabled = true` and `hooks.token`, keeping everything else
+/// in the file — other `hooks` settings included — as it is, and saves the
+/// token into the app's own config so the webhook auth is automatically set
+/// up. Running it again is harmless: an existing token (the app's, else the
+/// file's) is reused rather than replaced, and a file that already matches
+/// is not rewritten.
 ///
-/// Returns the generated token string.
+/// Returns the token and the list of changes. With `dry_run` nothing is
+/// written, so the settings UI can preview them. If saving the app config
+/// fails after `openclaw.json` was written, the file is restored.
 #[tauri::command]
 pub async fn setup_openclaw_hooks(
     config_state: State<'_, ConfigState>,
-) -> Result<String, String> {
+    dry_run: Option<bool>,
+) -> Result<HooksSetup, OpenClawError> {
+    let dry_run = dry_run.unwrap_or(false);
     let openclaw_dir = dirs::home_dir()
-        .ok_or("Cannot determine home directory")?
+        .ok_or_else(|| OpenClawError::other("Cannot determine home directory"))?
         .join(".openclaw");
-
-    std::fs::create_dir_all(&openclaw_dir)
-        .map_err(|e| format!("Failed to create ~/.openclaw: {e}"))?;
-
     let config_path = openclaw_dir.join("openclaw.json");
 
     // Read existing config or start with empty object
-    let mut json: serde_json::Value = if config_path.exists() {
-        let content = std::fs::read_to_string(&config_path)
-            .map_err(|e| format!("Failed to read openclaw.json: {e}"))?;
-        serde_json::from_str(&content).unwrap_or(serde_json::json!({}))
+    let original = if config_path.exists() {
+        Some(
+            std::fs::read_to_string(&config_path)
+                .map_err(|e| format!("Failed to read openclaw.json: {e}"))?,
+        )
     } else {
-        serde_json::json!({})
+        None
+    };
+    let mut json: serde_json::Value = match &original {
+        Some(content) => serde_json::from_str(content).map_err(|e| {
+            OpenClawError::invalid_config(format!(
+                "openclaw.json is not valid JSON ({e}); fix or remove it first"
+            ))
+        })?,
+        None => serde_json::json!({}),
     };
+    if !json.is_object() {
+        return Err(OpenClawError::invalid_config(
+            "openclaw.json does not hold a JSON object",
+        ));
+    }
 
-    // Generate a random token
-    let token = generate_token()?;
+    let app_token = config_state.get()?.hooks_token;
+    let file_token = json["hooks"]["token"].as_str().map(str::to_string);
+    let file_enabled = json["hooks"]["enabled"].as_bool();
+    let token = match (&app_token, &file_token) {
+        (app, _) if !app.is_empty() => app.clone(),
+        (_, Some(file)) if !file.is_empty() => file.clone(),
+        _ => generate_token()?,
+    };
 
-    // Set hooks config
-    json["hooks"] = serde_json::json!({
-        "enabled": true,
-        "token": token,
-    });
+    let mut changes = Vec::new();
+    if original.is_none() {
+        changes.push(setup_change(
+            &config_path.display().to_string(),
+            None,
+            Some("new file".to_string()),
+        ));
+    }
+    changes.push(setup_change(
+        "openclaw.json: hooks.enabled",
+        file_enabled.map(|v| v.to_string()),
+        Some("true".to_string()),
+    ));
+    changes.push(setup_change(
+        "openclaw.json: hooks.token",
+        file_token.as_deref().map(mask_token),
+        Some(mask_token(&token)),
+    ));
+    changes.push(setup_change(
+        "app config: hooksToken",
+        (!app_token.is_empty()).then(|| mask_token(&app_token)),
+        Some(mask_token(&token)),
+    ));
+    let file_changed = file_enabled != Some(true) || file_token.as_deref() != Some(&token);
+
+    if dry_run {
+        return Ok(HooksSetup {
+            token,
+            changes,
+            applied: false,
+        });
+    }
 
-    // Write back
-    let pretty = serde_json::to_string_pretty(&json)
-        .map_err(|e| format!("Failed to serialize config: {e}"))?;
-    std::fs::write(&config_path, pretty)
-        .map_err(|e| format!("Failed to write openclaw.json: {e}"))?;
+    if file_changed {
+        if !json["hooks"].is_object() {
+            json["hooks"] = serde_json::json!({});
+        }
+        json["hooks"]["enabled"] = serde_json::json!(true);
+        json["hooks"]["token"] = serde_json::json!(token);
+
+        std::fs::create_dir_all(&openclaw_dir)
+            .map_err(|e| format!("Failed to create ~/.openclaw: {e}"))?;
+        let pretty = serde_json::to_string_pretty(&json)
+            .map_err(|e| format!("Failed to serialize config: {e}"))?;
+        std::fs::write(&config_path, pretty)
+            .map_err(|e| format!("Failed to write openclaw.json: {e}"))?;
+    }
 
     // Also save the token to our app config
-    {
-        let mut app_config = config_state.config.write().map_err(|e| e.to_string())?;
-        app_config.hooks_token = token.clone();
+    if app_token != token {
+        let saved = config_state
+            .config
+            .write()
+            .map_err(|e| e.to_string())
+            .map(|mut app_config| app_config.hooks_token = token.clone())
+            .and_then(|()| config_state.save());
+        if let Err(e) = saved {
+            if file_changed {
+                let restored = match &original {
+                    Some(content) => std::fs::write(&config_path, content),
+                    None => std::fs::remove_file(&config_path),
+                };
+                if let Err(restore) = restored {
+                    eprintln!("[openclaw] Failed to restore openclaw.json: {restore}");
+                }
+            }
+            if let Ok(mut app_config) = config_state.config.write() {
+                app_config.hooks_token = app_token;
+            }
+            return Err(OpenClawError::other(format!(
+                "Failed to save the hooks token, openclaw.json left as it was: {e}"
+            )));
+        }
     }
-    config_state.save()?;
 
-    Ok(token)
+    Ok(HooksSetup {
+        token,
+        changes,
+        applied: true,
+    })
 }
 
 // ---------- Setup Wizard Commands ----------
@@ -422,17 +1986,150 @@ pub struct InstalledCheck {
     pub version: String,
 }
 
-/// Basic agent info returned by `openclaw agents list --json`.
-#[derive(Deserialize, Serialize, Clone)]
+/// Agent info returned by `openclaw agents list --json`, completed with
+/// what the gateway reports about the agent's model.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct AgentInfo {
     pub id: String,
     pub name: String,
+    /// Model the agent runs on, if known.
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub capabilities: AgentCapabilities,
+    /// Context window in tokens, if known.
+    #[serde(default)]
+    pub context_length: Option<u32>,
+}
+
+/// What an agent can be sent, so the settings UI can grey out the rest.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AgentCapabilities {
+    /// Images ([`send_chat`]'s `images`).
+    pub vision: bool,
+    /// Desktop tools ([`ToolBridge`]).
+    pub tools: bool,
+    /// Streamed replies ([`send_chat_stream`]).
+    pub streaming: bool,
+}
+
+/// How long [`list_openclaw_agents`] reuses what it found (5 minutes).
+const AGENT_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Agents found by the last [`list_openclaw_agents`], with when and for
+/// which gateway.
+static AGENT_CACHE: Mutex<Option<(Instant, String, Vec<AgentInfo>)>> = Mutex::new(None);
+
+/// Model names, or parts of them, of models known to accept images; used
+/// when the gateway does not say.
+const VISION_MODELS: &[&str] = &[
+    "gpt-4o",
+    "gpt-4.1",
+    "gpt-5",
+    "claude-3",
+    "claude-sonnet",
+    "claude-opus",
+    "gemini",
+    "llava",
+    "vision",
+    "-vl",
+];
+
+/// An entry of the gateway's `GET /v1/models`. OpenClaw lists its agents
+/// there as `openclaw:<agent id>`; the other fields are read when present.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct GatewayModel {
+    id: String,
+    model: Option<String>,
+    #[serde(alias = "context_window", alias = "contextLength")]
+    context_length: Option<u32>,
+    /// Either `{ "vision": true, … }` or `["vision", …]`.
+    capabilities: Option<serde_json::Value>,
+}
+
+impl GatewayModel {
+    fn capability(&self, name: &str) -> Option<bool> {
+        match self.capabilities.as_ref()? {
+            serde_json::Value::Object(map) => map.get(name).and_then(serde_json::Value::as_bool),
+            serde_json::Value::Array(list) => Some(list.iter().any(|v| v.as_str() == Some(name))),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct GatewayModels {
+    data: Vec<GatewayModel>,
+}
+
+/// The gateway's model list, or `None` if it has no OpenAI-compatible
+/// endpoints enabled or cannot be reached.
+async fn gateway_models(http: &HttpClient, config: &OpenClawConfig) -> Option<Vec<GatewayModel>> {
+    let token = if config.gateway_token.is_empty() {
+        &config.hooks_token
+    } else {
+        &config.gateway_token
+    };
+    let url = format!("{}/v1/models", config.gateway_url.trim_end_matches('/'));
+    let mut request = http
+        .client(config)
+        .ok()?
+        .get(&url)
+        .timeout(Duration::from_secs(HTTP_TIMEOUT_SECS).min(config.timeouts.total()));
+    if !token.is_empty() {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send().await.ok()?;
+    if !response.status().is_success() {
+        eprintln!(
+            "[list_openclaw_agents] {url} returned status {}",
+            response.status()
+        );
+        return None;
+    }
+    response
+        .json::<GatewayModels>()
+        .await
+        .ok()
+        .map(|models| models.data)
+}
+
+/// Fill in `agent`'s model and capabilities from the gateway's model list.
+///
+/// Any agent the gateway serves can stream and take tools over its
+/// `/v1/chat/completions`; vision is guessed from the model name unless
+/// the gateway says.
+fn describe_agent(agent: &mut AgentInfo, models: Option<&[GatewayModel]>) {
+    let Some(models) = models else {
+        return;
+    };
+    let id = format!("openclaw:{}", agent.id);
+    let entry = models.iter().find(|m| m.id == id || m.id == agent.id);
+    if let Some(entry) = entry {
+        if entry.model.is_some() {
+            agent.model = entry.model.clone();
+        }
+        agent.context_length = entry.context_length.or(agent.context_length);
+    }
+    let guessed_vision = agent.model.as_deref().is_some_and(|model| {
+        let model = model.to_lowercase();
+        VISION_MODELS.iter().any(|name| model.contains(name))
+    });
+    let reported = |name: &str| entry.and_then(|e| e.capability(name));
+    agent.capabilities = AgentCapabilities {
+        vision: reported("vision").unwrap_or(guessed_vision || agent.capabilities.vision),
+        tools: reported("tools").unwrap_or(true),
+        streaming: reported("streaming").unwrap_or(true),
+    };
 }
 
 /// Build a `std::process::Command` pre-configured with PATH augmentation
 /// and environment variables suitable for running `openclaw` subprocesses
 /// from inside the Tauri app (which doesn't inherit the user's shell PATH).
-fn build_openclaw_cmd(cli: &str) -> std::process::Command {
+pub(crate) fn build_openclaw_cmd(cli: &str) -> std::process::Command {
     let mut cmd = std::process::Command::new(cli);
     cmd.stdin(Stdio::null());
     cmd.stdout(Stdio::piped());
@@ -466,7 +2163,7 @@ fn build_openclaw_cmd(cli: &str) -> std::process::Command {
 #[tauri::command]
 pub async fn check_openclaw_installed(
     config_state: State<'_, ConfigState>,
-) -> Result<InstalledCheck, String> {
+) -> Result<InstalledCheck, OpenClawError> {
     let config = config_state.get()?;
     let cli = if config.cli_path.is_empty() {
         "openclaw".to_string()
@@ -501,13 +2198,30 @@ pub async fn check_openclaw_installed(
 
 /// List existing OpenClaw agents by running `openclaw agents list --json`.
 ///
-/// Parses the JSON output into a `Vec<AgentInfo>`. Returns an empty vector
-/// if the command fails or produces no agents.
+/// Parses the JSON output into a `Vec<AgentInfo>` and asks the gateway
+/// (`GET /v1/models`) for each agent's model, capabilities and context
+/// length. Returns an empty vector if the command fails or produces no
+/// agents; without the gateway the capabilities stay as the CLI reported
+/// them (all off by default).
+///
+/// The result is reused for [`AGENT_CACHE_TTL`]; pass `refresh` to query
+/// again, e.g. after changing an agent's model.
 #[tauri::command]
 pub async fn list_openclaw_agents(
+    http: State<'_, HttpClient>,
     config_state: State<'_, ConfigState>,
-) -> Result<Vec<AgentInfo>, String> {
+    refresh: Option<bool>,
+) -> Result<Vec<AgentInfo>, OpenClawError> {
     let config = config_state.get()?;
+    if !refresh.unwrap_or(false) {
+        if let Ok(cache) = AGENT_CACHE.lock() {
+            if let Some((at, gateway, agents)) = cache.as_ref() {
+                if *gateway == config.gateway_url && at.elapsed() < AGENT_CACHE_TTL {
+                    return Ok(agents.clone());
+                }
+            }
+        }
+    }
     let cli = if config.cli_path.is_empty() {
         "openclaw".to_string()
     } else {
@@ -524,20 +2238,30 @@ pub async fn list_openclaw_agents(
     )
     .await;
 
-    match result {
+    let mut agents: Vec<AgentInfo> = match result {
         Ok(Ok(Ok(output))) if output.status.success() => {
             let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
             // Try parsing as array of AgentInfo
-            let agents: Vec<AgentInfo> = serde_json::from_str(&stdout).unwrap_or_default();
-            Ok(agents)
+            serde_json::from_str(&stdout).unwrap_or_default()
         }
         Ok(Ok(Ok(output))) => {
             let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
             eprintln!("[list_openclaw_agents] CLI failed: {stderr}");
-            Ok(vec![])
+            return Ok(vec![]);
         }
-        _ => Ok(vec![]),
+        _ => return Ok(vec![]),
+    };
+
+    if !agents.is_empty() {
+        let models = gateway_models(&http, &config).await;
+        for agent in &mut agents {
+            describe_agent(agent, models.as_deref());
+        }
+    }
+    if let Ok(mut cache) = AGENT_CACHE.lock() {
+        *cache = Some((Instant::now(), config.gateway_url.clone(), agents.clone()));
     }
+    Ok(agents)
 }
 
 /// Create a new OpenClaw agent by running `openclaw agents add <name> --non-interactive`.
@@ -547,7 +2271,7 @@ pub async fn list_openclaw_agents(
 pub async fn create_openclaw_agent(
     config_state: State<'_, ConfigState>,
     name: String,
-) -> Result<String, String> {
+) -> Result<String, OpenClawError> {
     let config = config_state.get()?;
     let cli = if config.cli_path.is_empty() {
         "openclaw".to_string()
@@ -568,14 +2292,24 @@ pub async fn create_openclaw_agent(
     .await;
 
     match result {
-        Ok(Ok(Ok(output))) if output.status.success() => Ok(name),
+        Ok(Ok(Ok(output))) if output.status.success() => {
+            if let Ok(mut cache) = AGENT_CACHE.lock() {
+                *cache = None;
+            }
+            Ok(name)
+        }
         Ok(Ok(Ok(output))) => {
             let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
-            Err(format!("Failed to create agent: {stderr}"))
+            Err(OpenClawError::GatewayError {
+                status: None,
+                message: format!("Failed to create agent: {stderr}"),
+            })
         }
-        Ok(Ok(Err(e))) => Err(format!("Failed to run openclaw CLI: {e}")),
-        Ok(Err(e)) => Err(format!("Task join error: {e}")),
-        Err(_) => Err("Agent creation timed out after 30s".to_string()),
+        Ok(Ok(Err(e))) => Err(OpenClawError::invalid_config(format!(
+            "Failed to run openclaw CLI: {e}"
+        ))),
+        Ok(Err(e)) => Err(OpenClawError::other(format!("Task join error: {e}"))),
+        Err(_) => Err(timed_out("agent creation", Duration::from_secs(30))),
     }
 }
 
@@ -583,7 +2317,7 @@ pub async fn create_openclaw_agent(
 ///
 /// This is used as the Bearer token for OpenClaw webhook authentication.
 /// The token is written to both the app config and `~/.openclaw/openclaw.json`.
-fn generate_token() -> Result<String, String> {
+pub(crate) fn generate_token() -> Result<String, String> {
     let mut buf = [0u8; 32];
     getrandom::getrandom(&mut buf)
         .map_err(|e| format!("Failed to generate random token: {}", e))?;
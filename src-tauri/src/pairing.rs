@@ -0,0 +1,335 @@
+//! QR pairing with a mobile companion app.
+//!
+//! A phone (the future official app, or a third-party client) pairs by
+//! scanning a QR code shown in Settings. The code carries only a
+//! short-lived, single-use pairing token and the address of the push
+//! server ([`crate::push`]), which must listen on the network:
+//!
+//! ```json
+//! {"v":3,"token":"…","url":"http://192.168.1.20:18791","expiresAt":1700000000000}
+//! ```
+//!
+//! The mobile app redeems the token with `POST /pair` on that server and
+//! receives a long-lived device token and signing secret. With the token as
+//! bearer token, and every request signed with the secret as described in
+//! [`crate::signing`], the phone can use the push routes and fetch the
+//! OpenClaw session to join from `GET /session`; session details never appear in the QR code, so a
+//! photo of it is worthless once redeemed or expired. Paired devices (and
+//! their tokens) are stored in the OS keychain — never in the plain-text
+//! config files — and can be listed and revoked from Settings.
+
+use crate::config::ConfigState;
+use crate::secrets::KEYRING_SERVICE;
+use qrcode::render::svg;
+use qrcode::QrCode;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, UdpSocket};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+/// Keychain account holding the paired-device list.
+const KEYRING_ACCOUNT: &str = "paired-devices";
+
+/// How long a QR code stays valid.
+const PAIRING_TTL: Duration = Duration::from_secs(300);
+
+/// Payload version, bumped on incompatible changes.
+const PAIRING_VERSION: u32 = 3;
+
+/// Content of the pairing QR code.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct PairingPayload {
+    v: u32,
+    token: String,
+    /// Base URL of the push server.
+    url: String,
+    expires_at: u64,
+}
+
+/// Returned by [`start_pairing`] for the Settings UI.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PairingOffer {
+    /// SVG markup of the QR code.
+    pub qr_svg: String,
+    /// The encoded payload, for copy/paste pairing.
+    pub payload: String,
+    /// Unix time in milliseconds.
+    pub expires_at: u64,
+}
+
+/// A phone that has completed pairing.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PairedDevice {
+    pub id: String,
+    pub name: String,
+    /// Unix time in milliseconds.
+    pub paired_at: u64,
+    /// Credential the device presents on later requests. Never sent to the
+    /// frontend (see [`DeviceSummary`]).
+    device_token: String,
+    /// Secret the device signs its requests with; empty for devices paired
+    /// before requests were signed, which must pair again.
+    #[serde(default)]
+    signing_secret: String,
+}
+
+/// [`PairedDevice`] without its credential, for listing in Settings.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceSummary {
+    pub id: String,
+    pub name: String,
+    pub paired_at: u64,
+}
+
+/// Body of `POST /pair`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairingRequest {
+    pub token: String,
+    #[serde(default)]
+    pub device_name: String,
+}
+
+/// Returned to the mobile app by `POST /pair` ([`redeem`]).
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PairingResult {
+    pub device_id: String,
+    pub device_token: String,
+    pub signing_secret: String,
+}
+
+/// Returned to a paired device by `GET /session` ([`session_info`]).
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    pub agent_id: String,
+    pub session_key: String,
+    /// The gateway, unless it only listens on this machine.
+    pub gateway_url: Option<String>,
+}
+
+/// The currently displayed pairing token and its expiry (Unix ms).
+static PENDING: Mutex<Option<(String, u64)>> = Mutex::new(None);
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+// ---------- Keychain ----------
+
+fn keyring_entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_ACCOUNT)
+        .map_err(|e| format!("Keychain unavailable: {e}"))
+}
+
+fn load_devices() -> Result<Vec<PairedDevice>, String> {
+    match keyring_entry()?.get_password() {
+        Ok(json) => serde_json::from_str(&json)
+            .map_err(|e| format!("Corrupt paired-device list in keychain: {e}")),
+        Err(keyring::Error::NoEntry) => Ok(Vec::new()),
+        Err(e) => Err(format!("Failed to read keychain: {e}")),
+    }
+}
+
+fn store_devices(devices: &[PairedDevice]) -> Result<(), String> {
+    let entry = keyring_entry()?;
+    if devices.is_empty() {
+        return match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("Failed to update keychain: {e}")),
+        };
+    }
+    let json = serde_json::to_string(devices)
+        .map_err(|e| format!("Failed to serialize paired devices: {e}"))?;
+    entry
+        .set_password(&json)
+        .map_err(|e| format!("Failed to write keychain: {e}"))
+}
+
+/// The signing secret of the paired device `token` belongs to, compared in
+/// constant time; `None` if no device has it. Checked by the push server
+/// for requests from the mobile app.
+pub(crate) fn device_signing_secret(token: &str) -> Option<String> {
+    let same = |a: &str, b: &str| {
+        a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0u8, |d, (x, y)| d | (x ^ y)) == 0
+    };
+    if token.is_empty() {
+        return None;
+    }
+    load_devices()
+        .ok()?
+        .into_iter()
+        .find(|d| same(&d.device_token, token))
+        .map(|d| d.signing_secret)
+}
+
+/// Address of this machine on the local network: the source address of a
+/// route to the internet. Connecting a UDP socket sends nothing.
+fn lan_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("8.8.8.8:80").ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_loopback() && !ip.is_unspecified()).then_some(ip)
+}
+
+/// Base URL at which a phone reaches the push server.
+fn push_url(config_state: &ConfigState) -> Result<String, String> {
+    let push = config_state.get_settings()?.push;
+    if !push.enabled {
+        return Err("Enable the push server before pairing a phone.".to_string());
+    }
+    let bind = push.bind_address.trim();
+    let ip = match bind.parse::<IpAddr>() {
+        Ok(ip) if ip.is_unspecified() => lan_address(),
+        Ok(ip) if !ip.is_loopback() => Some(ip),
+        _ => None,
+    }
+    .ok_or("The push server only listens on this computer. Set its bind address to 0.0.0.0 (or this computer's network address) and restart to pair a phone.")?;
+    Ok(match ip {
+        IpAddr::V4(ip) => format!("http://{ip}:{}", push.port),
+        IpAddr::V6(ip) => format!("http://[{ip}]:{}", push.port),
+    })
+}
+
+/// The OpenClaw session a paired device joins.
+pub(crate) fn session_info(config_state: &ConfigState) -> Result<SessionInfo, String> {
+    let config = config_state.get()?;
+    let local = reqwest::Url::parse(&config.gateway_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .is_none_or(|host| {
+            host == "localhost"
+                || host
+                    .trim_matches(['[', ']'])
+                    .parse::<IpAddr>()
+                    .is_ok_and(|ip| ip.is_loopback())
+        });
+    Ok(SessionInfo {
+        agent_id: config.agent_id,
+        session_key: config.session_key,
+        gateway_url: (!local).then_some(config.gateway_url),
+    })
+}
+
+// ---------- Commands ----------
+
+/// IPC command: create a fresh pairing token and render it as a QR code.
+///
+/// Any previously shown code stops working.
+///
+/// # Errors
+///
+/// Returns `Err` if no agent is configured, or the push server is off or
+/// only listens on loopback, as the phone could not reach it.
+#[tauri::command]
+pub fn start_pairing(config_state: State<'_, ConfigState>) -> Result<PairingOffer, String> {
+    let config = config_state.get()?;
+    if config.agent_id.is_empty() {
+        return Err("Configure an OpenClaw agent before pairing a phone.".to_string());
+    }
+    let url = push_url(&config_state)?;
+
+    let token = crate::openclaw::generate_token()?;
+    let expires_at = now_ms() + PAIRING_TTL.as_millis() as u64;
+    let payload = serde_json::to_string(&PairingPayload {
+        v: PAIRING_VERSION,
+        token: token.clone(),
+        url,
+        expires_at,
+    })
+    .map_err(|e| format!("Failed to encode pairing payload: {e}"))?;
+
+    let qr_svg = QrCode::new(payload.as_bytes())
+        .map_err(|e| format!("Failed to generate QR code: {e}"))?
+        .render::<svg::Color>()
+        .min_dimensions(256, 256)
+        .build();
+
+    *PENDING.lock().map_err(|e| e.to_string())? = Some((token, expires_at));
+    Ok(PairingOffer {
+        qr_svg,
+        payload,
+        expires_at,
+    })
+}
+
+/// IPC command: stop showing the pairing code and invalidate its token.
+#[tauri::command]
+pub fn cancel_pairing() -> Result<(), String> {
+    *PENDING.lock().map_err(|e| e.to_string())? = None;
+    Ok(())
+}
+
+/// Redeem a scanned pairing token, for `POST /pair` on the push server.
+///
+/// The token is single-use and must not be expired. On success the device is
+/// stored in the keychain and receives its long-lived token and signing
+/// secret.
+pub(crate) fn redeem(request: PairingRequest) -> Result<PairingResult, String> {
+    let PairingRequest { token, device_name } = request;
+    {
+        let mut pending = PENDING.lock().map_err(|e| e.to_string())?;
+        match pending.as_ref() {
+            Some((expected, expires_at)) if *expected == token && now_ms() < *expires_at => {
+                *pending = None;
+            }
+            Some((expected, _)) if *expected == token => {
+                *pending = None;
+                return Err("Pairing code expired. Show a new code and try again.".to_string());
+            }
+            _ => return Err("Invalid pairing code.".to_string()),
+        }
+    }
+
+    let device = PairedDevice {
+        id: crate::openclaw::generate_token()?[..16].to_string(),
+        name: if device_name.trim().is_empty() {
+            "Phone".to_string()
+        } else {
+            device_name.trim().to_string()
+        },
+        paired_at: now_ms(),
+        device_token: crate::openclaw::generate_token()?,
+        signing_secret: crate::openclaw::generate_token()?,
+    };
+    let mut devices = load_devices()?;
+    devices.push(device.clone());
+    store_devices(&devices)?;
+    eprintln!("[pairing] Paired device '{}'", device.name);
+
+    Ok(PairingResult {
+        device_id: device.id,
+        device_token: device.device_token,
+        signing_secret: device.signing_secret,
+    })
+}
+
+/// IPC command: paired devices, without their credentials.
+#[tauri::command]
+pub fn list_paired_devices() -> Result<Vec<DeviceSummary>, String> {
+    Ok(load_devices()?
+        .into_iter()
+        .map(|d| DeviceSummary {
+            id: d.id,
+            name: d.name,
+            paired_at: d.paired_at,
+        })
+        .collect())
+}
+
+/// IPC command: revoke a paired device. Succeeds if it was already gone.
+#[tauri::command]
+pub fn revoke_paired_device(id: String) -> Result<(), String> {
+    let mut devices = load_devices()?;
+    devices.retain(|d| d.id != id);
+    store_devices(&devices)
+}
@@ -0,0 +1,230 @@
+//! System prompt assembly for the character.
+//!
+//! The persona manager turns a character card ([`CharacterCard`], the
+//! common "character card" fields), the user's preferences and the current
+//! context (time of day, active window, what is playing) into the system
+//! prompt sent ahead of every chat message, whichever backend answers it.
+//! Building it here rather than in the frontend lets every chat path —
+//! including tool calling — share one prompt.
+//!
+//! The prompt comes from [`PersonaConfig::template`], with `{{placeholder}}`
+//! fields (see [`DEFAULT_TEMPLATE`]). A line with an empty placeholder is
+//! left out, as is a paragraph left with none of its placeholders, so a
+//! sparse card still gives a clean prompt. The active agent's own system
+//! prompt ([`crate::agents`]) comes first.
+//!
+//! The persona is off until [`set_persona`] is called, leaving prompting
+//! to the frontend's context and the agent.
+
+use crate::config::{ConfigState, OpenClawConfig};
+use chrono::{Local, Timelike};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+/// Template used when [`PersonaConfig::template`] is empty.
+///
+/// Placeholders: `char`, `user`, `description`, `personality`, `scenario`,
+/// `examples`, `language`, `time`, `time_of_day`, `window`, `music`.
+pub const DEFAULT_TEMPLATE: &str = "\
+You are {{char}}, a character living on {{user}}'s desktop.
+{{description}}
+Personality: {{personality}}
+Scenario: {{scenario}}
+Always reply in {{language}}.
+
+Example dialogue:
+{{examples}}
+
+It is {{time}} ({{time_of_day}}).
+{{user}} is looking at {{window}}.
+Now playing: {{music}}.";
+
+/// The character, in the fields character cards commonly carry.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CharacterCard {
+    pub name: String,
+    pub description: String,
+    pub personality: String,
+    pub scenario: String,
+    /// Sample exchanges showing how the character talks.
+    #[serde(alias = "mes_example")]
+    pub example_dialogue: String,
+}
+
+/// Persona section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PersonaConfig {
+    pub enabled: bool,
+    pub card: CharacterCard,
+    /// How the character addresses the user; `"the user"` when empty.
+    pub user_name: String,
+    /// Reply language (e.g. `"Japanese"`); empty leaves it to the model.
+    pub language: String,
+    /// Prompt template; [`DEFAULT_TEMPLATE`] when empty.
+    pub template: String,
+    /// Mention the active window. Redacted apps are never named.
+    pub include_window: bool,
+    /// Mention what is playing.
+    pub include_music: bool,
+}
+
+impl Default for PersonaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            card: CharacterCard::default(),
+            user_name: String::new(),
+            language: String::new(),
+            template: String::new(),
+            include_window: true,
+            include_music: true,
+        }
+    }
+}
+
+pub(crate) fn time_of_day(hour: u32) -> &'static str {
+    match hour {
+        5..=11 => "morning",
+        12..=16 => "afternoon",
+        17..=21 => "evening",
+        _ => "night",
+    }
+}
+
+/// Value of placeholder `name`, `None` if unknown.
+fn field(config_state: &ConfigState, persona: &PersonaConfig, name: &str) -> Option<String> {
+    let card = &persona.card;
+    let now = Local::now();
+    Some(match name {
+        "char" => card.name.clone(),
+        "user" if persona.user_name.is_empty() => "the user".to_string(),
+        "user" => persona.user_name.clone(),
+        "description" => card.description.clone(),
+        "personality" => card.personality.clone(),
+        "scenario" => card.scenario.clone(),
+        "examples" => card.example_dialogue.clone(),
+        "language" => persona.language.clone(),
+        "time" => now.format("%A %H:%M").to_string(),
+        "time_of_day" => time_of_day(now.hour()).to_string(),
+        "window" if persona.include_window => {
+            match crate::screen::redacted_active_window(config_state) {
+                Some(w) if w.title.is_empty() => w.app_name,
+                Some(w) => format!("{} ({})", w.app_name, w.title),
+                None => String::new(),
+            }
+        }
+        "music" if persona.include_music => match crate::media::latest() {
+            Some(playing) if playing.state == crate::media::PlaybackState::Playing => {
+                format!("{} by {}", playing.title, playing.artist)
+            }
+            _ => String::new(),
+        },
+        "window" | "music" => String::new(),
+        _ => return None,
+    })
+}
+
+/// Substitute the placeholders of `template`, dropping a line with an empty
+/// placeholder and a paragraph that loses all of its placeholder lines.
+fn render(template: &str, mut value: impl FnMut(&str) -> Option<String>) -> String {
+    let mut paragraphs = Vec::new();
+    for paragraph in template.split("\n\n") {
+        let mut lines = Vec::new();
+        let mut had_fields = false;
+        let mut kept_fields = false;
+        for line in paragraph.lines() {
+            let mut out = String::new();
+            let mut rest = line;
+            let mut fields = 0;
+            let mut empty = 0;
+            while let Some(start) = rest.find("{{") {
+                let Some(len) = rest[start + 2..].find("}}") else {
+                    break;
+                };
+                let name = rest[start + 2..start + 2 + len].trim();
+                out.push_str(&rest[..start]);
+                match value(name) {
+                    Some(v) => {
+                        fields += 1;
+                        if v.trim().is_empty() {
+                            empty += 1;
+                        }
+                        out.push_str(v.trim());
+                    }
+                    // Unknown placeholders are kept as written.
+                    None => out.push_str(&rest[start..start + 4 + len]),
+                }
+                rest = &rest[start + 4 + len..];
+            }
+            out.push_str(rest);
+            had_fields |= fields > 0;
+            if empty == 0 {
+                kept_fields |= fields > 0;
+                lines.push(out);
+            }
+        }
+        if !had_fields || kept_fields {
+            paragraphs.push(lines.join("\n"));
+        }
+    }
+    paragraphs
+        .into_iter()
+        .filter(|p| !p.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// The system prompt for the next chat message: the active agent's prompt
+/// followed by the persona, if enabled. `None` when there is neither.
+pub(crate) fn system_prompt(config_state: &ConfigState, config: &OpenClawConfig) -> Option<String> {
+    let persona = config_state
+        .get_settings()
+        .map(|s| s.persona)
+        .unwrap_or_default();
+    let mut parts = Vec::new();
+    if let Some(prompt) = crate::agents::system_prompt(config) {
+        parts.push(prompt.trim().to_string());
+    }
+    if persona.enabled {
+        let template = if persona.template.trim().is_empty() {
+            DEFAULT_TEMPLATE
+        } else {
+            &persona.template
+        };
+        let prompt = render(template, |name| field(config_state, &persona, name));
+        if !prompt.is_empty() {
+            parts.push(prompt);
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join("\n\n"))
+}
+
+/// IPC command: use `card` as the character and turn the persona on, or
+/// turn it off with `None`.
+#[tauri::command]
+pub fn set_persona(
+    config_state: State<'_, ConfigState>,
+    card: Option<CharacterCard>,
+) -> Result<(), String> {
+    {
+        let mut settings = config_state.settings.write().map_err(|e| e.to_string())?;
+        match card {
+            Some(card) => {
+                settings.persona.card = card;
+                settings.persona.enabled = true;
+            }
+            None => settings.persona.enabled = false,
+        }
+    }
+    config_state.save_settings()
+}
+
+/// IPC command: the system prompt the next chat message would get, with
+/// the current context filled in; `None` if there is none.
+#[tauri::command]
+pub fn preview_prompt(config_state: State<'_, ConfigState>) -> Result<Option<String>, String> {
+    let config = config_state.get()?;
+    Ok(system_prompt(&config_state, &config))
+}
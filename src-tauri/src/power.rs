@@ -0,0 +1,248 @@
+//! Power profiles — the battery-saver policy.
+//!
+//! One background thread owns the decision of whether the app runs in
+//! *saver* mode. It samples the battery every [`CHECK_INTERVAL`] and applies
+//! the effective profile in one place:
+//!
+//! - mouse tracking drops to 15 Hz and reports cursor proximity instead of
+//!   raw moves ([`crate::hittest::set_low_power_mode`])
+//! - audio level analysis stops ([`crate::audio::set_analysis_enabled`])
+//! - the frontend is told to cap its frame rate and pause sync jobs via a
+//!   `"power-profile-changed"` event
+//!
+//! [`PowerProfile::Auto`] enters saver mode when running on battery below
+//! [`PowerConfig::battery_threshold`]; `Performance` and `Saver` force one
+//! mode regardless of the battery.
+
+use crate::config::ConfigState;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// How often the battery is sampled.
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Granularity of the thread's sleep, so profile changes apply promptly.
+const WAKE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Frame-rate cap suggested to the frontend in saver mode.
+const SAVER_MAX_FPS: u32 = 30;
+
+/// Set by [`set_power_profile`] to force an immediate re-evaluation.
+static REEVALUATE: AtomicBool = AtomicBool::new(false);
+
+/// User-selected power profile.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum PowerProfile {
+    /// Saver on battery below the threshold, performance otherwise.
+    #[default]
+    Auto,
+    Performance,
+    Saver,
+}
+
+/// Power section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PowerConfig {
+    pub profile: PowerProfile,
+    /// Battery percentage below which `Auto` switches to saver mode.
+    pub battery_threshold: u8,
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        Self {
+            profile: PowerProfile::Auto,
+            battery_threshold: 30,
+        }
+    }
+}
+
+/// Battery reading. `percent` is `None` on machines without a battery.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct BatteryStatus {
+    on_battery: bool,
+    percent: Option<u8>,
+}
+
+/// Payload of `"power-profile-changed"` events.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerState {
+    pub profile: PowerProfile,
+    /// Whether saver mode is in effect.
+    pub saver_active: bool,
+    pub on_battery: bool,
+    pub battery_percent: Option<u8>,
+    /// Frame-rate cap the frontend should apply (`None` = uncapped).
+    pub max_fps: Option<u32>,
+    /// Whether background sync jobs should pause.
+    pub pause_sync: bool,
+}
+
+fn battery_status() -> BatteryStatus {
+    #[cfg(target_os = "macos")]
+    {
+        // `pmset -g batt` prints e.g.
+        //   Now drawing from 'Battery Power'
+        //    -InternalBattery-0 (id=1234)	85%; discharging; 4:12 remaining
+        if let Ok(output) = std::process::Command::new("pmset")
+            .args(["-g", "batt"])
+            .output()
+        {
+            let text = String::from_utf8_lossy(&output.stdout);
+            let on_battery = text.contains("'Battery Power'");
+            let percent = text
+                .split_whitespace()
+                .find_map(|word| word.strip_suffix("%;")?.parse().ok());
+            return BatteryStatus {
+                on_battery,
+                percent,
+            };
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+        let mut status = SYSTEM_POWER_STATUS::default();
+        if unsafe { GetSystemPowerStatus(&mut status) }.is_ok() {
+            return BatteryStatus {
+                on_battery: status.ACLineStatus == 0,
+                percent: (status.BatteryLifePercent <= 100).then_some(status.BatteryLifePercent),
+            };
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if let Ok(entries) = std::fs::read_dir("/sys/class/power_supply") {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let read = |name: &str| std::fs::read_to_string(path.join(name)).ok();
+                if read("type").is_some_and(|t| t.trim() == "Battery") {
+                    return BatteryStatus {
+                        on_battery: read("status").is_some_and(|s| s.trim() == "Discharging"),
+                        percent: read("capacity").and_then(|c| c.trim().parse().ok()),
+                    };
+                }
+            }
+        }
+    }
+
+    BatteryStatus {
+        on_battery: false,
+        percent: None,
+    }
+}
+
+fn saver_active(config: &PowerConfig, battery: BatteryStatus) -> bool {
+    match config.profile {
+        PowerProfile::Performance => false,
+        PowerProfile::Saver => true,
+        PowerProfile::Auto => {
+            battery.on_battery
+                && battery
+                    .percent
+                    .is_some_and(|p| p < config.battery_threshold)
+        }
+    }
+}
+
+/// Applies the effective profile to the subsystems it controls.
+#[derive(Default)]
+struct PowerPolicy {
+    /// Whether saver mode is currently applied.
+    saver: bool,
+    last_state: Option<(PowerProfile, bool, BatteryStatus)>,
+}
+
+impl PowerPolicy {
+    fn apply(&mut self, app: &AppHandle, config: &PowerConfig, battery: BatteryStatus) {
+        let saver = saver_active(config, battery);
+        if saver != self.saver {
+            self.saver = saver;
+            crate::audio::set_analysis_enabled(!saver);
+            crate::hittest::set_low_power_mode(saver);
+            eprintln!(
+                "[power] Saver mode {} (profile {:?}, battery {:?})",
+                if saver { "on" } else { "off" },
+                config.profile,
+                battery.percent
+            );
+        }
+
+        let state = (config.profile, saver, battery);
+        if self.last_state != Some(state) {
+            self.last_state = Some(state);
+            let _ = app.emit("power-profile-changed", power_state(config, battery));
+        }
+    }
+}
+
+fn power_state(config: &PowerConfig, battery: BatteryStatus) -> PowerState {
+    let saver = saver_active(config, battery);
+    PowerState {
+        profile: config.profile,
+        saver_active: saver,
+        on_battery: battery.on_battery,
+        battery_percent: battery.percent,
+        max_fps: saver.then_some(SAVER_MAX_FPS),
+        pause_sync: saver,
+    }
+}
+
+fn power_config(app: &AppHandle) -> PowerConfig {
+    app.state::<ConfigState>()
+        .get_settings()
+        .map(|s| s.power)
+        .unwrap_or_default()
+}
+
+/// Start the power policy thread.
+pub fn start_power_monitor(app: AppHandle, running: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        let mut policy = PowerPolicy::default();
+        let mut last_check: Option<Instant> = None;
+
+        while running.load(Ordering::Relaxed) {
+            let due = last_check.is_none_or(|t| t.elapsed() >= CHECK_INTERVAL);
+            if due || REEVALUATE.swap(false, Ordering::Relaxed) {
+                last_check = Some(Instant::now());
+                policy.apply(&app, &power_config(&app), battery_status());
+            }
+            thread::sleep(WAKE_INTERVAL);
+        }
+    });
+}
+
+/// IPC command: select the power profile and persist it.
+///
+/// The policy thread applies it within a second and emits
+/// `"power-profile-changed"`.
+#[tauri::command]
+pub fn set_power_profile(
+    state: State<'_, ConfigState>,
+    profile: PowerProfile,
+) -> Result<(), String> {
+    {
+        let mut settings = state.settings.write().map_err(|e| e.to_string())?;
+        settings.power.profile = profile;
+    }
+    state.save_settings()?;
+    REEVALUATE.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// IPC command: the current profile, battery reading and effective mode.
+#[tauri::command]
+pub fn get_power_state(state: State<'_, ConfigState>) -> Result<PowerState, String> {
+    let config = state.get_settings()?.power;
+    Ok(power_state(&config, battery_status()))
+}
@@ -0,0 +1,125 @@
+//! Proactive check-ins — the character speaking up on its own.
+//!
+//! The frontend used to decide when the character should say something
+//! unprompted, which stopped working whenever the WebView was hidden and
+//! throttled. A backend task now asks the agent for a short message every
+//! [`ProactiveConfig::interval_mins`], with a snapshot of what the user is
+//! doing (time of day, active app, what is playing), and emits the reply as
+//! `"proactive-message"`.
+//!
+//! Nothing is sent while the companion is quiet ([`crate::quiet`]: quiet
+//! mode from the tray, or quiet hours) or while the user is away (idle
+//! longer than [`crate::hittest::IdleConfig::threshold_secs`]); the next
+//! check-in waits until they are back.
+
+use crate::config::ConfigState;
+use crate::openclaw::ChatResponse;
+use chrono::{Local, Timelike};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// How often the task checks whether a check-in is due.
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Prompt used when [`ProactiveConfig::prompt`] is empty.
+pub const DEFAULT_PROMPT: &str = "Nobody asked you anything; this is a proactive check-in. \
+Say one or two short sentences to the user, in character, prompted by what they are doing \
+right now. Do not ask whether they need help.";
+
+/// Proactive section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProactiveConfig {
+    pub enabled: bool,
+    /// Minutes between check-ins; 0 disables them.
+    pub interval_mins: u64,
+    /// Instruction sent with the context snapshot; [`DEFAULT_PROMPT`] when
+    /// empty.
+    pub prompt: String,
+}
+
+impl Default for ProactiveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_mins: 45,
+            prompt: String::new(),
+        }
+    }
+}
+
+/// Payload of `"proactive-message"` events.
+#[derive(Serialize, Clone)]
+pub struct ProactiveMessage {
+    #[serde(flatten)]
+    pub reply: ChatResponse,
+}
+
+/// What the user is doing, as context for the check-in.
+fn snapshot(config_state: &ConfigState) -> String {
+    let now = Local::now();
+    let mut lines = vec![format!(
+        "Time: {} ({})",
+        now.format("%H:%M"),
+        crate::persona::time_of_day(now.hour())
+    )];
+    if let Some(window) = crate::screen::redacted_active_window(config_state) {
+        if !window.app_name.is_empty() || !window.title.is_empty() {
+            lines.push(format!(
+                "Active app: {} — {}",
+                window.app_name, window.title
+            ));
+        }
+    }
+    if let Some(playing) = crate::media::latest() {
+        lines.push(format!(
+            "Now playing: {} — {}",
+            playing.title, playing.artist
+        ));
+    }
+    format!("[CONTEXT]\n{}", lines.join("\n"))
+}
+
+/// Ask the agent for a check-in and emit it.
+async fn check_in(app: &AppHandle, config: &ProactiveConfig) {
+    let prompt = if config.prompt.trim().is_empty() {
+        DEFAULT_PROMPT.to_string()
+    } else {
+        config.prompt.clone()
+    };
+    let context = snapshot(&app.state::<ConfigState>());
+    match crate::openclaw::request_reply(app, prompt, Some(context)).await {
+        Ok(reply) => {
+            crate::events::publish(app, "proactive.message", &reply);
+            let _ = app.emit("proactive-message", ProactiveMessage { reply });
+        }
+        Err(e) => eprintln!("[proactive] Check-in failed: {e}"),
+    }
+}
+
+/// Start the check-in task.
+pub fn start_proactive_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last = Instant::now();
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            let Ok(settings) = app.state::<ConfigState>().get_settings() else {
+                continue;
+            };
+            let config = settings.proactive;
+            if !config.enabled || config.interval_mins == 0 {
+                continue;
+            }
+            if last.elapsed() < Duration::from_secs(config.interval_mins * 60) {
+                continue;
+            }
+            let away = crate::hittest::get_idle_seconds() >= settings.idle.threshold_secs as f64;
+            if crate::quiet::is_quiet(&app.state::<ConfigState>()) || away {
+                continue;
+            }
+            last = Instant::now();
+            check_in(&app, &config).await;
+        }
+    });
+}
@@ -0,0 +1,129 @@
+//! Snapshots of the last prompt sent to the gateway, for debugging.
+//!
+//! Users tuning their companion want to see exactly what context the app
+//! injected around their message. [`crate::openclaw`] records the fully
+//! assembled plaintext payload (before E2E sealing) for every message, keyed
+//! by session, and [`get_last_prompt_payload`] returns the most recent one.
+//!
+//! Secrets from the app config (tokens, E2E key, API keys) and anything
+//! that looks like an API key or bearer token are masked before the
+//! snapshot is stored, so nothing sensitive is kept in memory longer than
+//! the request itself.
+
+use crate::config::{ConfigState, OpenClawConfig};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+/// Replacement text for masked secrets.
+const REDACTED: &str = "[REDACTED]";
+
+/// Prefixes of common API key formats that are masked wherever they appear.
+const KEY_PREFIXES: &[&str] = &["sk-", "sk_", "xoxb-", "xoxp-", "ghp_", "gho_", "AKIA"];
+
+/// Shortest token after a prefix that is treated as a key.
+const MIN_KEY_LEN: usize = 16;
+
+/// The assembled payload of one outgoing message.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptSnapshot {
+    /// Session the message was sent on (`""` for the default session).
+    pub session: String,
+    pub agent_id: String,
+    /// `"cli"` for `send_chat`, `"webhook"` for `send_webhook`.
+    pub transport: &'static str,
+    /// The full message text, context included, with secrets redacted.
+    pub payload: String,
+    /// Whether the payload was E2E-sealed before leaving the process.
+    pub encrypted: bool,
+    /// Unix time in milliseconds.
+    pub timestamp: u64,
+}
+
+/// Most recent snapshot per session.
+static SNAPSHOTS: Mutex<Option<HashMap<String, PromptSnapshot>>> = Mutex::new(None);
+
+/// Mask config secrets and key-shaped tokens in `text`.
+pub(crate) fn redact(text: &str, config: &OpenClawConfig) -> String {
+    let mut out = text.to_string();
+    for secret in config.secret_values() {
+        out = out.replace(&secret, REDACTED);
+    }
+
+    let out: String = out
+        .split_inclusive(char::is_whitespace)
+        .map(|word| {
+            let token = word.trim_end();
+            let is_key = KEY_PREFIXES.iter().any(|p| {
+                token.starts_with(p)
+                    && token.len() >= p.len() + MIN_KEY_LEN
+                    && token[p.len()..]
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            });
+            if is_key {
+                format!("{}{}", REDACTED, &word[token.len()..])
+            } else {
+                word.to_string()
+            }
+        })
+        .collect();
+    redact_bearer(&out)
+}
+
+/// Mask the credential following each `Bearer `.
+fn redact_bearer(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(idx) = rest.find("Bearer ") {
+        let (head, tail) = rest.split_at(idx + "Bearer ".len());
+        out.push_str(head);
+        let end = tail.find(char::is_whitespace).unwrap_or(tail.len());
+        if end > 0 {
+            out.push_str(REDACTED);
+        }
+        rest = &tail[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Record the payload of a message about to be sent.
+pub fn record(config: &OpenClawConfig, transport: &'static str, payload: &str) {
+    let snapshot = PromptSnapshot {
+        session: config.session_key.clone(),
+        agent_id: config.agent_id.clone(),
+        transport,
+        payload: redact(payload, config),
+        encrypted: !crate::agents::e2e_key(config).is_empty(),
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+    };
+    if let Ok(mut snapshots) = SNAPSHOTS.lock() {
+        snapshots
+            .get_or_insert_with(HashMap::new)
+            .insert(snapshot.session.clone(), snapshot);
+    }
+}
+
+/// IPC command: the last payload sent on `session`.
+///
+/// `session` defaults to the configured session key. Returns `None` if
+/// nothing has been sent on that session since launch.
+#[tauri::command]
+pub fn get_last_prompt_payload(
+    config_state: State<'_, ConfigState>,
+    session: Option<String>,
+) -> Result<Option<PromptSnapshot>, String> {
+    let session = match session {
+        Some(s) => s,
+        None => config_state.get()?.session_key,
+    };
+    let snapshots = SNAPSHOTS.lock().map_err(|e| e.to_string())?;
+    Ok(snapshots.as_ref().and_then(|s| s.get(&session).cloned()))
+}
@@ -0,0 +1,289 @@
+//! Incoming push server for proactive messages.
+//!
+//! [`crate::openclaw`] only talks *to* the gateway. This listener lets
+//! OpenClaw hooks, scripts and other tools reach the companion instead: each
+//! route re-emits its JSON body to the frontend as an event, which decides
+//! how the character reacts.
+//!
+//! Routes (all `POST`, JSON body, answered with `202`):
+//!
+//! - `/notify` [`Notify`] → `"push-notify"` — show a message bubble
+//! - `/speak` [`Speak`] → `"push-speak"` — say something aloud
+//! - `/emote` [`Emote`] → `"push-emote"` — play an expression or motion
+//!
+//! While the companion is quiet ([`crate::quiet`]), `/notify` and `/speak`
+//! are accepted but dropped; the response then has `"suppressed": true`.
+//!
+//! Paired phones ([`crate::pairing`]) also use this server: `POST /pair`
+//! redeems the pairing code from the QR code for a device token, and
+//! `GET /session` returns the OpenClaw session to join.
+//!
+//! Every request but `/pair` needs `Authorization: Bearer <token>` with the
+//! token from [`PushConfig`] (one is generated on first start) or a paired
+//! device's token. Bodies must also be signed as described in
+//! [`crate::signing`], which stops replayed and altered requests on a
+//! shared network: always with a device's own secret from `/pair`, and with
+//! [`PushConfig::signing_secret`] for the push token if one is set. The
+//! server is off by default, listens on `127.0.0.1` unless [`PushConfig::bind_address`] says
+//! otherwise (e.g. for a gateway on another machine), and shares the HTTP
+//! handling of [`crate::localapi`]. Changes take effect on the next launch.
+
+use crate::config::ConfigState;
+use crate::localapi::{self, Request, Response};
+use crate::signing::{self, Verifier};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Push server section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PushConfig {
+    pub enabled: bool,
+    /// Interface to listen on; anything but loopback exposes the server to
+    /// the network, guarded only by the token.
+    pub bind_address: String,
+    pub port: u16,
+    /// Bearer token callers must send; generated when empty.
+    pub token: String,
+    /// Secret bodies must be signed with; empty accepts unsigned requests.
+    pub signing_secret: String,
+}
+
+impl Default for PushConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: "127.0.0.1".to_string(),
+            port: 18791,
+            token: String::new(),
+            signing_secret: String::new(),
+        }
+    }
+}
+
+/// Body of `POST /notify`, emitted as `"push-notify"`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Notify {
+    pub message: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Who sent it (e.g. `"openclaw"`, `"ci"`).
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+/// Body of `POST /speak`, emitted as `"push-speak"`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Speak {
+    pub text: String,
+    #[serde(default)]
+    pub voice: Option<String>,
+    /// Emotion tag as used by `speak` (`"happy"`, `"sad"`, …).
+    #[serde(default)]
+    pub emotion: Option<String>,
+}
+
+/// Body of `POST /emote`, emitted as `"push-emote"`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Emote {
+    pub emotion: String,
+    /// Motion to play along with the expression.
+    #[serde(default)]
+    pub motion: Option<String>,
+    /// How long to hold the expression; the frontend's default if absent.
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+}
+
+/// Start the server if enabled in settings, generating its token first if
+/// needed.
+pub fn start_push_server(app: AppHandle) {
+    let config_state = app.state::<ConfigState>();
+    let mut config = match config_state.get_settings() {
+        Ok(settings) => settings.push,
+        Err(e) => {
+            eprintln!("[push] Failed to read settings: {e}");
+            return;
+        }
+    };
+    if !config.enabled {
+        return;
+    }
+    if config.token.is_empty() {
+        match create_token(&config_state) {
+            Ok(token) => config.token = token,
+            Err(e) => {
+                eprintln!("[push] Not starting without a token: {e}");
+                return;
+            }
+        }
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let address = (config.bind_address.as_str(), config.port);
+        let listener = match TcpListener::bind(address).await {
+            Ok(l) => l,
+            Err(e) => {
+                eprintln!(
+                    "[push] Failed to bind {}:{}: {e}",
+                    config.bind_address, config.port
+                );
+                return;
+            }
+        };
+        println!(
+            "[push] Listening on http://{}:{}",
+            config.bind_address, config.port
+        );
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let app = app.clone();
+                    let config = config.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = serve(&app, &config, stream).await {
+                            eprintln!("[push] {e}");
+                        }
+                    });
+                }
+                Err(e) => eprintln!("[push] Accept failed: {e}"),
+            }
+        }
+    });
+}
+
+/// Generate a token and save it to the settings.
+fn create_token(config_state: &ConfigState) -> Result<String, String> {
+    let token = crate::openclaw::generate_token()?;
+    {
+        let mut settings = config_state.settings.write().map_err(|e| e.to_string())?;
+        settings.push.token = token.clone();
+    }
+    config_state.save_settings()?;
+    Ok(token)
+}
+
+/// Signatures already accepted, so each is only good once.
+static VERIFIER: Verifier = Verifier::new();
+
+/// Read one request, route it and write the response.
+async fn serve(app: &AppHandle, config: &PushConfig, mut stream: TcpStream) -> Result<(), String> {
+    let read = tokio::time::timeout(localapi::READ_TIMEOUT, localapi::read_request(&mut stream));
+    let response = match read.await {
+        Ok(Ok(request)) if request.path == "/pair" => pair(&request),
+        Ok(Ok(request)) if authorized(&request, &config.token) => {
+            match verify(&request, &config.signing_secret) {
+                Ok(()) => route(app, &request),
+                Err(e) => Response::error(401, e),
+            }
+        }
+        Ok(Ok(request)) => match device_signing_secret(&request) {
+            Some(secret) if secret.is_empty() => Response::error(
+                401,
+                "Device paired before requests were signed; pair it again",
+            ),
+            Some(secret) => match verify(&request, &secret) {
+                Ok(()) => route(app, &request),
+                Err(e) => Response::error(401, e),
+            },
+            None => Response::error(401, "Missing or wrong bearer token"),
+        },
+        Ok(Err(response)) => response,
+        Err(_) => return Err("Client timed out".to_string()),
+    };
+    localapi::write_response(&mut stream, &response).await
+}
+
+/// Whether the request carries the bearer token, compared in constant time.
+pub(crate) fn authorized(request: &Request, token: &str) -> bool {
+    let Some(sent) = request
+        .header("authorization")
+        .and_then(|v| v.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+    sent.len() == token.len()
+        && sent
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// The signing secret of the paired device whose token the request
+/// carries, if any.
+fn device_signing_secret(request: &Request) -> Option<String> {
+    request
+        .header("authorization")
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .and_then(crate::pairing::device_signing_secret)
+}
+
+/// `POST /pair`: redeem a pairing code; the code itself authenticates.
+fn pair(request: &Request) -> Response {
+    if request.method != "POST" {
+        return Response::error(405, "Method not allowed");
+    }
+    let body = match serde_json::from_slice(&request.body) {
+        Ok(body) => body,
+        Err(e) => return Response::error(400, &format!("Invalid body: {e}")),
+    };
+    match crate::pairing::redeem(body) {
+        Ok(result) => Response::json(200, &result),
+        Err(e) => Response::error(403, &e),
+    }
+}
+
+/// Check the body signature if a signing secret is configured.
+fn verify(request: &Request, secret: &str) -> Result<(), &'static str> {
+    if secret.is_empty() {
+        return Ok(());
+    }
+    VERIFIER.verify(
+        secret,
+        request.header(&signing::TIMESTAMP_HEADER.to_ascii_lowercase()),
+        request.header(&signing::SIGNATURE_HEADER.to_ascii_lowercase()),
+        &request.body,
+    )
+}
+
+fn route(app: &AppHandle, request: &Request) -> Response {
+    let result = match request.path.as_str() {
+        "/notify" | "/speak" | "/emote" if request.method != "POST" => {
+            return Response::error(405, "Method not allowed");
+        }
+        "/session" if request.method != "GET" => {
+            return Response::error(405, "Method not allowed");
+        }
+        "/session" => {
+            return match crate::pairing::session_info(&app.state::<ConfigState>()) {
+                Ok(session) => Response::json(200, &session),
+                Err(e) => Response::error(500, &e),
+            };
+        }
+        "/notify" | "/speak" if crate::quiet::is_quiet(&app.state::<ConfigState>()) => {
+            return Response::json(202, &serde_json::json!({ "ok": true, "suppressed": true }));
+        }
+        "/notify" => forward::<Notify>(app, request, "push-notify"),
+        "/speak" => forward::<Speak>(app, request, "push-speak"),
+        "/emote" => forward::<Emote>(app, request, "push-emote"),
+        _ => return Response::error(404, "Not found"),
+    };
+    match result {
+        Ok(()) => Response::json(202, &serde_json::json!({ "ok": true })),
+        Err(e) => Response::error(400, &e),
+    }
+}
+
+/// Parse the body as `T` and emit it as `event`.
+fn forward<T>(app: &AppHandle, request: &Request, event: &str) -> Result<(), String>
+where
+    T: for<'de> Deserialize<'de> + Serialize + Clone,
+{
+    let payload: T =
+        serde_json::from_slice(&request.body).map_err(|e| format!("Invalid body: {e}"))?;
+    app.emit(event, payload)
+        .map_err(|e| format!("Failed to deliver {event}: {e}"))
+}
@@ -0,0 +1,160 @@
+//! Quiet mode and quiet hours: times the character keeps to itself.
+//!
+//! The companion is quiet while either
+//!
+//! - quiet mode is on: for a while from the tray or [`set_quiet_mode`], or
+//! - the local time falls in one of the [`QuietHoursConfig::schedules`]
+//!   (e.g. 22–8 every day), when quiet hours are enabled.
+//!
+//! While quiet, no proactive check-ins are sent ([`crate::proactive`]), the
+//! character's voice stays silent ([`crate::tts`]) and pushed notifications
+//! and speech are dropped ([`crate::push`]). [`is_quiet_now`] reports the
+//! current state, and `"quiet-state-changed"` is emitted whenever it
+//! changes.
+
+use crate::audio::ExcludedHours;
+use crate::config::ConfigState;
+use chrono::{Datelike, Local, Timelike};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// How often the watcher checks for schedule boundaries.
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Longest quiet mode period [`set_quiet_mode`] accepts (a week).
+const MAX_QUIET_MINUTES: u64 = 7 * 24 * 60;
+
+/// Quiet hours section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct QuietHoursConfig {
+    pub enabled: bool,
+    /// Local-time windows that are quiet.
+    pub schedules: Vec<ExcludedHours>,
+}
+
+impl Default for QuietHoursConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            schedules: vec![ExcludedHours {
+                start: 22,
+                end: 8,
+                days: Vec::new(),
+            }],
+        }
+    }
+}
+
+/// Returned by [`is_quiet_now`] and the payload of `"quiet-state-changed"`.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct QuietState {
+    pub quiet: bool,
+    /// `"mode"` (quiet mode) or `"schedule"` (quiet hours) while quiet.
+    pub reason: Option<&'static str>,
+    /// Unix time in milliseconds at which quiet mode ends, while it is on.
+    pub quiet_mode_until: Option<u64>,
+}
+
+/// End of the current quiet mode period, if one is set, and the same as
+/// Unix time in milliseconds for [`QuietState::quiet_mode_until`].
+static QUIET_UNTIL: Mutex<Option<(Instant, u64)>> = Mutex::new(None);
+
+/// State last emitted as `"quiet-state-changed"`.
+static LAST_STATE: Mutex<Option<QuietState>> = Mutex::new(None);
+
+/// Whether `config` makes the present moment quiet.
+fn in_quiet_hours(config: &QuietHoursConfig) -> bool {
+    let now = Local::now();
+    let (weekday, hour) = (now.weekday().num_days_from_sunday() as u8, now.hour() as u8);
+    config.enabled && config.schedules.iter().any(|s| s.contains(weekday, hour))
+}
+
+/// The current state.
+pub(crate) fn current(config_state: &ConfigState) -> QuietState {
+    let until = QUIET_UNTIL
+        .lock()
+        .ok()
+        .and_then(|until| *until)
+        .filter(|(t, _)| Instant::now() < *t);
+    let quiet_mode_until = until.map(|(_, ms)| ms);
+    let schedule = config_state
+        .get_settings()
+        .is_ok_and(|s| in_quiet_hours(&s.quiet_hours));
+    let reason = if until.is_some() {
+        Some("mode")
+    } else if schedule {
+        Some("schedule")
+    } else {
+        None
+    };
+    QuietState {
+        quiet: reason.is_some(),
+        reason,
+        quiet_mode_until,
+    }
+}
+
+/// Whether the character should keep quiet right now.
+pub(crate) fn is_quiet(config_state: &ConfigState) -> bool {
+    current(config_state).quiet
+}
+
+/// Emit `"quiet-state-changed"` if the state differs from the last one.
+fn publish(app: &AppHandle) {
+    let state = current(&app.state::<ConfigState>());
+    let Ok(mut last) = LAST_STATE.lock() else {
+        return;
+    };
+    if last.as_ref() == Some(&state) {
+        return;
+    }
+    *last = Some(state.clone());
+    let _ = app.emit("quiet-state-changed", state);
+}
+
+/// Turn quiet mode on for `duration`; zero ends it.
+pub(crate) fn quiet_for(app: &AppHandle, duration: Duration) {
+    let deadline = Some(duration)
+        .filter(|d| !d.is_zero())
+        .and_then(|d| Instant::now().checked_add(d))
+        .map(|t| {
+            let ms = SystemTime::now()
+                .checked_add(duration)
+                .and_then(|end| end.duration_since(UNIX_EPOCH).ok())
+                .map_or(u64::MAX, |d| d.as_millis() as u64);
+            (t, ms)
+        });
+    if let Ok(mut until) = QUIET_UNTIL.lock() {
+        *until = deadline;
+    }
+    publish(app);
+}
+
+/// IPC command: whether the companion is quiet now, and why.
+#[tauri::command]
+pub fn is_quiet_now(config_state: State<'_, ConfigState>) -> QuietState {
+    current(&config_state)
+}
+
+/// IPC command: turn quiet mode on for `minutes` (at most a week); 0 ends
+/// it early.
+#[tauri::command]
+pub fn set_quiet_mode(app: AppHandle, minutes: u64) {
+    let minutes = minutes.min(MAX_QUIET_MINUTES);
+    quiet_for(&app, Duration::from_secs(minutes.saturating_mul(60)));
+}
+
+/// Start the task that emits `"quiet-state-changed"` when quiet hours begin
+/// or end, or quiet mode runs out.
+pub fn start_quiet_watcher(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            publish(&app);
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
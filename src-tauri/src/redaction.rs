@@ -0,0 +1,55 @@
+//! User-defined redaction for screen-awareness output.
+//!
+//! Users working with sensitive dashboards can list apps and screen
+//! rectangles in the `redaction` section of [`crate::config::AppSettings`].
+//! Before window metadata leaves [`crate::screen`], every window that
+//! belongs to a listed app or overlaps a zone has its title replaced, and
+//! browser URLs are withheld for listed apps. Geometry is kept so the
+//! character can still walk on those windows.
+//!
+//! The backend does not capture screen pixels yet; once it does, the same
+//! zones are meant to be blacked out in the image before it is returned.
+
+use crate::screen::WindowInfo;
+use crate::snap::Rect;
+use serde::{Deserialize, Serialize};
+
+/// Title shown for redacted windows.
+const REDACTED_TITLE: &str = "[redacted]";
+
+/// Redaction section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RedactionConfig {
+    /// App names (case-insensitive substring match) whose content is never
+    /// reported.
+    pub apps: Vec<String>,
+    /// Screen-space rectangles (top-left origin); windows overlapping any of
+    /// them are redacted.
+    pub zones: Vec<Rect>,
+}
+
+impl RedactionConfig {
+    /// Whether content from `app_name` must be withheld.
+    pub fn is_app_redacted(&self, app_name: &str) -> bool {
+        let lower = app_name.to_lowercase();
+        self.apps
+            .iter()
+            .any(|a| !a.is_empty() && lower.contains(&a.to_lowercase()))
+    }
+
+    fn overlaps_zone(&self, w: &WindowInfo) -> bool {
+        let (x, y) = (w.x as f64, w.y as f64);
+        let (right, bottom) = (x + w.width as f64, y + w.height as f64);
+        self.zones
+            .iter()
+            .any(|z| x < z.x + z.width && right > z.x && y < z.y + z.height && bottom > z.y)
+    }
+
+    /// Redact one window's metadata in place.
+    pub fn apply(&self, w: &mut WindowInfo) {
+        if !w.title.is_empty() && (self.is_app_redacted(&w.app_name) || self.overlaps_zone(w)) {
+            w.title = REDACTED_TITLE.to_string();
+        }
+    }
+}
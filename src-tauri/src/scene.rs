@@ -0,0 +1,252 @@
+//! Ambient sound classification on the monitoring stream.
+//!
+//! Whether the room is loud says little about what is going on: typing and
+//! video calls reach the same RMS as a song. [`SceneClassifier`] is fed the
+//! same interleaved samples as the level meter in [`crate::audio`] and
+//! sorts the last [`WINDOW_FRAMES`] of [`FRAME_MS`] frames into an
+//! [`AudioScene`] from how the level moves rather than how high it is:
+//!
+//! - **Silence**: even the louder frames stay under [`SILENCE_DB`].
+//! - **Keyboard**: short, sharp clicks (a frame peak far above its RMS, see
+//!   [`CLICK_CREST`]) a few times a second, with quiet in between.
+//! - **Talking**: the voice detector ([`crate::vad`]) hears the user, or —
+//!   on loopback sources such as a call — the level rises and falls at
+//!   syllable rate with voice-like zero crossings.
+//! - **Music**: sustained sound with hardly any gaps.
+//! - **Other**: anything else, e.g. a steady fan or a vacuum cleaner.
+//!
+//! A scene has to win [`CONFIRM_DECISIONS`] decisions in a row (one every
+//! [`DECISION_FRAMES`]) before it replaces the current one. Like the voice
+//! detector, this is a heuristic and not a trained model; it only needs to
+//! keep the character from dancing to a keyboard.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// Analysis frame length.
+const FRAME_MS: u32 = 20;
+
+/// Frames considered per decision (2 s).
+const WINDOW_FRAMES: usize = 100;
+
+/// Frames between decisions (0.5 s).
+const DECISION_FRAMES: usize = 25;
+
+/// Consecutive agreeing decisions before the scene changes.
+const CONFIRM_DECISIONS: u32 = 2;
+
+/// The loud end of a window quieter than this is silence.
+const SILENCE_DB: f32 = -50.0;
+
+/// Frames this far below the window's loud level count as a gap.
+const GAP_DB: f32 = 20.0;
+
+/// Jump in frame energy that counts as an onset (a click or syllable).
+const ONSET_DB: f32 = 10.0;
+
+/// Peak-to-RMS ratio of a frame holding a key click; voice and music
+/// frames stay well below it.
+const CLICK_CREST: f32 = 4.5;
+
+/// Key presses per second while typing.
+const TYPING_RATE: std::ops::RangeInclusive<f32> = 1.5..=15.0;
+
+/// Syllables per second in conversation.
+const SYLLABLE_RATE: std::ops::RangeInclusive<f32> = 2.0..=8.0;
+
+/// Plausible zero crossings per second for voiced speech.
+const VOICE_ZCR_HZ: std::ops::RangeInclusive<f32> = 100.0..=3500.0;
+
+/// Share of frames the voice detector must flag as speech.
+const SPEECH_SHARE: f32 = 0.3;
+
+/// Music has gaps in fewer frames than this share.
+const MAX_MUSIC_GAPS: f32 = 0.15;
+
+/// A level that moves less than this is steady noise, not music.
+const MIN_MUSIC_SPAN_DB: f32 = 4.0;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioScene {
+    Silence,
+    Keyboard,
+    Talking,
+    Music,
+    Other,
+}
+
+/// Features of one analysis frame.
+#[derive(Clone, Copy)]
+struct Frame {
+    db: f32,
+    /// Peak over RMS.
+    crest: f32,
+    zcr_hz: f32,
+    /// The voice detector reported speech.
+    speech: bool,
+}
+
+pub struct SceneClassifier {
+    channels: usize,
+    /// Mono samples per analysis frame.
+    frame_samples: usize,
+    sample_rate: f32,
+    /// Down-mix of the current (partial) multi-channel sample.
+    mix_sum: f32,
+    mix_len: usize,
+    /// Accumulators of the current analysis frame.
+    energy: f32,
+    peak: f32,
+    crossings: usize,
+    samples: usize,
+    last_positive: bool,
+    frames: VecDeque<Frame>,
+    since_decision: usize,
+    scene: AudioScene,
+    /// A different scene and how many decisions in a row it has won.
+    candidate: Option<(AudioScene, u32)>,
+}
+
+impl SceneClassifier {
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        Self {
+            channels: channels.max(1) as usize,
+            frame_samples: (sample_rate * FRAME_MS / 1000).max(1) as usize,
+            sample_rate: sample_rate as f32,
+            mix_sum: 0.0,
+            mix_len: 0,
+            energy: 0.0,
+            peak: 0.0,
+            crossings: 0,
+            samples: 0,
+            last_positive: false,
+            frames: VecDeque::with_capacity(WINDOW_FRAMES),
+            since_decision: 0,
+            scene: AudioScene::Silence,
+            candidate: None,
+        }
+    }
+
+    /// The current scene.
+    pub fn scene(&self) -> AudioScene {
+        self.scene
+    }
+
+    /// Feed one interleaved sample; `speech` is whether the voice detector
+    /// currently hears the user. Returns the new scene when it changes.
+    pub fn push(&mut self, sample: f32, speech: bool) -> Option<AudioScene> {
+        self.mix_sum += sample;
+        self.mix_len += 1;
+        if self.mix_len < self.channels {
+            return None;
+        }
+        let mono = self.mix_sum / self.channels as f32;
+        self.mix_sum = 0.0;
+        self.mix_len = 0;
+
+        let positive = mono >= 0.0;
+        if self.samples > 0 && positive != self.last_positive {
+            self.crossings += 1;
+        }
+        self.last_positive = positive;
+        self.energy += mono * mono;
+        self.peak = self.peak.max(mono.abs());
+        self.samples += 1;
+        if self.samples < self.frame_samples {
+            return None;
+        }
+
+        let rms = (self.energy / self.samples as f32).sqrt();
+        let frame = Frame {
+            db: 20.0 * (rms + 1e-5).log10(),
+            crest: if rms > 0.0 { self.peak / rms } else { 0.0 },
+            zcr_hz: self.crossings as f32 * self.sample_rate / self.samples as f32,
+            speech,
+        };
+        self.energy = 0.0;
+        self.peak = 0.0;
+        self.crossings = 0;
+        self.samples = 0;
+        self.frame(frame)
+    }
+
+    fn frame(&mut self, frame: Frame) -> Option<AudioScene> {
+        if self.frames.len() == WINDOW_FRAMES {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+        self.since_decision += 1;
+        if self.frames.len() < WINDOW_FRAMES || self.since_decision < DECISION_FRAMES {
+            return None;
+        }
+        self.since_decision = 0;
+
+        let heard = classify(&self.frames);
+        if heard == self.scene {
+            self.candidate = None;
+            return None;
+        }
+        let wins = match self.candidate {
+            Some((scene, wins)) if scene == heard => wins + 1,
+            _ => 1,
+        };
+        if wins < CONFIRM_DECISIONS {
+            self.candidate = Some((heard, wins));
+            return None;
+        }
+        self.candidate = None;
+        self.scene = heard;
+        Some(heard)
+    }
+}
+
+/// Classify one window of frames.
+fn classify(frames: &VecDeque<Frame>) -> AudioScene {
+    let n = frames.len() as f32;
+    let mut levels: Vec<f32> = frames.iter().map(|f| f.db).collect();
+    levels.sort_by(f32::total_cmp);
+    let quiet = levels[levels.len() / 10];
+    let loud = levels[levels.len() * 9 / 10];
+    if loud < SILENCE_DB {
+        return AudioScene::Silence;
+    }
+
+    let speech = frames.iter().filter(|f| f.speech).count() as f32 / n;
+    if speech >= SPEECH_SHARE {
+        return AudioScene::Talking;
+    }
+
+    let seconds = n * FRAME_MS as f32 / 1000.0;
+    let gaps = frames.iter().filter(|f| f.db < loud - GAP_DB).count() as f32 / n;
+    let onsets: Vec<&Frame> = frames
+        .iter()
+        .zip(frames.iter().skip(1))
+        .filter(|(a, b)| b.db - a.db >= ONSET_DB && b.db >= SILENCE_DB)
+        .map(|(_, b)| b)
+        .collect();
+    let onset_rate = onsets.len() as f32 / seconds;
+    let clicks = onsets.iter().filter(|f| f.crest >= CLICK_CREST).count();
+
+    if TYPING_RATE.contains(&onset_rate) && clicks * 2 >= onsets.len() && gaps >= 0.4 {
+        return AudioScene::Keyboard;
+    }
+
+    // Median zero-crossing rate of the louder half of the window.
+    let mut voiced: Vec<f32> = frames
+        .iter()
+        .filter(|f| f.db >= (quiet + loud) / 2.0)
+        .map(|f| f.zcr_hz)
+        .collect();
+    voiced.sort_by(f32::total_cmp);
+    let zcr = voiced.get(voiced.len() / 2).copied().unwrap_or(0.0);
+    if SYLLABLE_RATE.contains(&onset_rate) && gaps >= MAX_MUSIC_GAPS && VOICE_ZCR_HZ.contains(&zcr)
+    {
+        return AudioScene::Talking;
+    }
+
+    if gaps < MAX_MUSIC_GAPS && loud - quiet >= MIN_MUSIC_SPAN_DB {
+        return AudioScene::Music;
+    }
+    AudioScene::Other
+}
@@ -1,4 +1,5 @@
-//! Desktop window enumeration and screen-capture permission checks.
+//! Desktop window enumeration, screen-capture permission checks and region
+//! capture.
 //!
 //! On **macOS** the window list is obtained via CoreGraphics
 //! (`CGWindowListCopyWindowInfo`), which returns a CFArray of CFDictionary
@@ -10,7 +11,17 @@
 //! provides a safe Rust API but may panic on edge-case window manager
 //! configurations, hence the `catch_unwind` guards.
 
+use crate::config::ConfigState;
+use crate::redaction::RedactionConfig;
 use serde::Serialize;
+use tauri::State;
+
+fn redaction_settings(config_state: &ConfigState) -> RedactionConfig {
+    config_state
+        .get_settings()
+        .map(|s| s.redaction)
+        .unwrap_or_default()
+}
 
 /// Metadata about a single desktop window, serialized and sent to the frontend.
 ///
@@ -39,8 +50,20 @@ pub struct WindowInfo {
 ///
 /// On macOS, requires Screen Recording permission for window title access.
 /// Use [`check_screen_permission`] to verify before calling.
+///
+/// Titles are redacted according to the user's redaction settings
+/// ([`crate::redaction`]).
 #[tauri::command]
-pub fn get_window_list() -> Vec<WindowInfo> {
+pub fn get_window_list(config_state: State<'_, ConfigState>) -> Vec<WindowInfo> {
+    let redaction = redaction_settings(&config_state);
+    let mut windows = window_list();
+    windows.iter_mut().for_each(|w| redaction.apply(w));
+    windows
+}
+
+/// Unredacted window list, for backend geometry consumers
+/// ([`crate::snap`], [`crate::boundaries`]) that never surface titles.
+pub fn window_list() -> Vec<WindowInfo> {
     #[cfg(target_os = "macos")]
     {
         get_window_list_cg()
@@ -352,7 +375,18 @@ fn get_window_list_xwin() -> Vec<WindowInfo> {
 /// panics from propagating. Returns `None` if the active window has no
 /// title and no owner name, or if detection fails.
 #[tauri::command]
-pub fn get_active_window() -> Option<WindowInfo> {
+pub fn get_active_window(config_state: State<'_, ConfigState>) -> Option<WindowInfo> {
+    redacted_active_window(&config_state)
+}
+
+/// [`get_active_window`] for backend callers.
+pub(crate) fn redacted_active_window(config_state: &ConfigState) -> Option<WindowInfo> {
+    let mut window = active_window()?;
+    redaction_settings(config_state).apply(&mut window);
+    Some(window)
+}
+
+fn active_window() -> Option<WindowInfo> {
     match std::panic::catch_unwind(|| x_win::get_active_window()) {
         Ok(Ok(w)) => {
             if w.title.is_empty() && w.info.name.is_empty() {
@@ -381,37 +415,25 @@ pub fn get_active_window() -> Option<WindowInfo> {
 
 /// Get the current browser tab URL.
 ///
-/// Uses AppleScript on macOS and UI Automation on Windows.
+/// The browser is looked up in the strategy table ([`crate::browsers`]),
+/// which users can extend from settings. Uses AppleScript on macOS and UI
+/// Automation on Windows.
 /// Returns `None` on unsupported platforms, non-browser apps, or query failure.
 #[tauri::command]
-pub async fn get_browser_url(app_name: String) -> Option<String> {
+pub async fn get_browser_url(app: tauri::AppHandle, app_name: String) -> Option<String> {
+    use tauri::Manager;
+
+    let settings = app.state::<ConfigState>().get_settings().unwrap_or_default();
+    if settings.redaction.is_app_redacted(&app_name) {
+        return None;
+    }
+    let browsers = settings.browsers;
+    let rule = crate::browsers::lookup(&browsers, &app_name)?;
+
     #[cfg(target_os = "macos")]
     {
-        let lower = app_name.to_lowercase();
-
-        // Determine the AppleScript to run based on the browser.
-        // Safari uses "URL of current tab", Chromium browsers use "URL of active tab".
-        let script = if lower.contains("safari") {
-            format!(
-                r#"tell application "{}" to get URL of current tab of front window"#,
-                app_name
-            )
-        } else if lower.contains("chrome")
-            || lower.contains("arc")
-            || lower.contains("brave")
-            || lower.contains("edge")
-            || lower.contains("opera")
-        {
-            format!(
-                r#"tell application "{}" to get URL of active tab of front window"#,
-                app_name
-            )
-        } else if lower.contains("firefox") {
-            // Firefox does not support AppleScript tab URL queries
-            return None;
-        } else {
-            return None;
-        };
+        // Firefox-family browsers do not support AppleScript tab URL queries.
+        let script = rule.apple_script(&app_name)?;
 
         match tokio::process::Command::new("osascript")
             .arg("-e")
@@ -441,17 +463,9 @@ pub async fn get_browser_url(app_name: String) -> Option<String> {
 
     #[cfg(target_os = "windows")]
     {
-        let lower = app_name.to_lowercase();
-        if !lower.contains("chrome")
-            && !lower.contains("edge")
-            && !lower.contains("brave")
-            && !lower.contains("opera")
-            && !lower.contains("arc")
-            && !lower.contains("firefox")
-        {
-            return None;
-        }
-
+        // The address bar is found the same way in every browser, so any
+        // table entry (including custom-script ones) is queried.
+        let _ = rule;
         return tokio::task::spawn_blocking(move || -> Option<String> {
             use windows::Win32::System::Com::{
                 CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER,
@@ -510,7 +524,7 @@ pub async fn get_browser_url(app_name: String) -> Option<String> {
 
     #[cfg(not(any(target_os = "macos", target_os = "windows")))]
     {
-        let _ = app_name;
+        let _ = rule;
         None
     }
 }
@@ -540,3 +554,152 @@ pub fn check_screen_permission() -> bool {
         true
     }
 }
+
+/// Largest width or height [`capture_region`] accepts, in pixels.
+const MAX_CAPTURE_SIDE: u32 = 4096;
+
+/// Capture a rectangle of the screen as a PNG `data:` URL.
+///
+/// Coordinates are in the global screen space: logical points from the top
+/// left of the main display on macOS (`CGWindowListCreateImage`), physical
+/// pixels of the virtual screen on Windows (`BitBlt`). Fails without the
+/// Screen Recording permission (see [`check_screen_permission`]) and on
+/// other platforms.
+#[tauri::command]
+pub async fn capture_region(x: i32, y: i32, width: u32, height: u32) -> Result<String, String> {
+    use base64::engine::general_purpose::STANDARD as BASE64;
+    use base64::Engine;
+
+    if width == 0 || height == 0 || width > MAX_CAPTURE_SIDE || height > MAX_CAPTURE_SIDE {
+        return Err(format!(
+            "Region must be 1 to {MAX_CAPTURE_SIDE} pixels on each side"
+        ));
+    }
+    if !check_screen_permission() {
+        return Err("Screen Recording permission not granted".to_string());
+    }
+    let png = tokio::task::spawn_blocking(move || -> Result<Vec<u8>, String> {
+        let (width, height, rgba) = capture_rgba(x, y, width, height)?;
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&rgba))
+            .map_err(|e| format!("PNG encoding failed: {e}"))?;
+        Ok(png)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    Ok(format!("data:image/png;base64,{}", BASE64.encode(png)))
+}
+
+/// Read the pixels of a screen rectangle as `(width, height, RGBA rows)`.
+#[cfg(target_os = "macos")]
+fn capture_rgba(x: i32, y: i32, width: u32, height: u32) -> Result<(u32, u32, Vec<u8>), String> {
+    use core_graphics::display::{
+        kCGNullWindowID, kCGWindowImageNominalResolution, kCGWindowListOptionOnScreenOnly,
+        CGDisplay,
+    };
+    use core_graphics::geometry::{CGPoint, CGRect, CGSize};
+
+    let bounds = CGRect::new(
+        &CGPoint::new(x as f64, y as f64),
+        &CGSize::new(width as f64, height as f64),
+    );
+    let image = CGDisplay::screenshot(
+        bounds,
+        kCGWindowListOptionOnScreenOnly,
+        kCGNullWindowID,
+        kCGWindowImageNominalResolution,
+    )
+    .ok_or("Screen capture failed")?;
+    if image.bits_per_pixel() != 32 || image.bits_per_component() != 8 {
+        return Err("Unsupported screen pixel format".to_string());
+    }
+    let (width, height, stride) = (image.width(), image.height(), image.bytes_per_row());
+    let data = image.data();
+    let bytes = data.bytes();
+    let mut rgba = Vec::with_capacity(width * height * 4);
+    for row in bytes.chunks(stride).take(height) {
+        // Window server images are BGRA, premultiplied alpha first.
+        for bgra in row[..width * 4].chunks_exact(4) {
+            rgba.extend_from_slice(&[bgra[2], bgra[1], bgra[0], 255]);
+        }
+    }
+    Ok((width as u32, height as u32, rgba))
+}
+
+/// Read the pixels of a screen rectangle as `(width, height, RGBA rows)`.
+#[cfg(target_os = "windows")]
+fn capture_rgba(x: i32, y: i32, width: u32, height: u32) -> Result<(u32, u32, Vec<u8>), String> {
+    use windows::Win32::Graphics::Gdi::{
+        BitBlt, CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, DeleteObject, GetDC,
+        GetDIBits, ReleaseDC, SelectObject, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, CAPTUREBLT,
+        DIB_RGB_COLORS, SRCCOPY,
+    };
+
+    let (w, h) = (width as i32, height as i32);
+    let mut bgra = vec![0u8; width as usize * height as usize * 4];
+    // SAFETY: every handle created here is released before returning, and
+    // `bgra` holds exactly the 32-bit top-down rows `info` describes.
+    let copied = unsafe {
+        let screen = GetDC(None);
+        let memory = CreateCompatibleDC(Some(screen));
+        let bitmap = CreateCompatibleBitmap(screen, w, h);
+        let previous = SelectObject(memory, bitmap.into());
+        // CAPTUREBLT includes layered (e.g. transparent) windows.
+        let blitted = BitBlt(memory, 0, 0, w, h, Some(screen), x, y, SRCCOPY | CAPTUREBLT);
+        SelectObject(memory, previous);
+        let mut info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: w,
+                // Negative for top-down rows.
+                biHeight: -h,
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let lines = if blitted.is_ok() {
+            GetDIBits(
+                memory,
+                bitmap,
+                0,
+                height,
+                Some(bgra.as_mut_ptr().cast()),
+                &mut info,
+                DIB_RGB_COLORS,
+            )
+        } else {
+            0
+        };
+        let _ = DeleteObject(bitmap.into());
+        let _ = DeleteDC(memory);
+        ReleaseDC(None, screen);
+        lines == h
+    };
+    if !copied {
+        return Err("Screen capture failed".to_string());
+    }
+    for pixel in bgra.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+        pixel[3] = 255;
+    }
+    Ok((width, height, bgra))
+}
+
+/// Read the pixels of a screen rectangle as `(width, height, RGBA rows)`.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn capture_rgba(
+    _x: i32,
+    _y: i32,
+    _width: u32,
+    _height: u32,
+) -> Result<(u32, u32, Vec<u8>), String> {
+    Err("Region capture is not supported on this platform".to_string())
+}
@@ -0,0 +1,238 @@
+//! Tokens and API keys kept in the OS keychain.
+//!
+//! `config.json` and `settings.json` are plain text and easily end up in
+//! dotfile backups, so their secret fields (the hooks and gateway tokens,
+//! provider API keys, …) are stored in the macOS Keychain / Windows
+//! Credential Manager / Secret Service instead. [`crate::config`] blanks
+//! them before writing a file ([`stash`]) and fills them back in after
+//! reading it ([`restore`]); the rest of the app only ever sees the filled
+//! config.
+//!
+//! All secrets share one keychain entry holding a JSON object keyed by
+//! field name, so the OS asks for access at most once. Secrets of list
+//! entries are named `<field>/<entry>` (e.g. `e2eKey/<agent id>` for an
+//! agent profile's key) and are dropped with their entry. A value found in a
+//! file — written by an older version, or edited in by hand — is moved to
+//! the keychain on load. Where no keychain is available the fields simply
+//! stay in the files as before. When the keychain exists but cannot be read
+//! (e.g. it is locked), the fields stay blank, [`unavailable`] reports it,
+//! and saving leaves their stored values alone.
+//!
+//! With a custom config directory ([`crate::config::custom_config_dir`])
+//! the keychain is not used at all: a portable install on a USB stick
+//! must find its tokens on any machine, and test profiles must not share
+//! them. The secrets then stay in the files, and encryption at rest, whose
+//! key would live in one machine's keychain, is not available.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
+
+/// Keychain service name, shared with [`crate::pairing`].
+pub(crate) const KEYRING_SERVICE: &str = "ai-desktop-companion";
+
+/// Keychain account holding the secrets.
+const KEYRING_ACCOUNT: &str = "config-secrets";
+
+/// Serializes read-modify-write of the shared entry.
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Fields whose secret could not be read from the keychain on the last
+/// [`restore`]. [`stash`] never removes their stored values.
+static UNRESTORED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// Keychain account holding the key the config files are encrypted with,
+/// while encryption at rest is on (see [`crate::config`]).
+const CONFIG_KEY_ACCOUNT: &str = "config-key";
+
+/// The config key as last read from or written to the keychain.
+static CONFIG_KEY: Mutex<Option<Option<String>>> = Mutex::new(None);
+
+/// Secret fields of a config value, by name.
+pub(crate) type Fields<'a> = [(Cow<'static, str>, &'a mut String)];
+
+/// Whether secrets stay in the config files: the config directory was
+/// moved, e.g. for a portable install.
+fn in_files() -> bool {
+    crate::config::custom_config_dir().is_some()
+}
+
+fn keyring_entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_ACCOUNT)
+        .map_err(|e| format!("Keychain unavailable: {e}"))
+}
+
+fn load_all(entry: &keyring::Entry) -> Result<BTreeMap<String, String>, String> {
+    match entry.get_password() {
+        Ok(json) => {
+            serde_json::from_str(&json).map_err(|e| format!("Corrupt secrets in keychain: {e}"))
+        }
+        Err(keyring::Error::NoEntry) => Ok(BTreeMap::new()),
+        Err(e) => Err(format!("Failed to read keychain: {e}")),
+    }
+}
+
+fn store_all(entry: &keyring::Entry, secrets: &BTreeMap<String, String>) -> Result<(), String> {
+    if secrets.is_empty() {
+        return match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("Failed to update keychain: {e}")),
+        };
+    }
+    let json =
+        serde_json::to_string(secrets).map_err(|e| format!("Failed to serialize secrets: {e}"))?;
+    entry
+        .set_password(&json)
+        .map_err(|e| format!("Failed to write keychain: {e}"))
+}
+
+/// Fill `fields` from the keychain after reading a config file.
+///
+/// Fields that already have a value in the file are moved into the
+/// keychain instead. Returns whether that happened, i.e. whether the file
+/// should be rewritten without them.
+///
+/// # Errors
+///
+/// Returns `Err` if the keychain cannot be read. The blank fields are then
+/// remembered as unrestored until a later `restore` succeeds.
+pub(crate) fn restore(fields: &mut Fields) -> Result<bool, String> {
+    if in_files() {
+        return Ok(false);
+    }
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let result = keyring_entry().and_then(|entry| {
+        let mut secrets = load_all(&entry)?;
+        let mut migrated = false;
+        for (name, value) in fields.iter_mut() {
+            if !value.is_empty() {
+                secrets.insert(name.to_string(), value.clone());
+                migrated = true;
+            } else if let Some(secret) = secrets.get(name.as_ref()) {
+                **value = secret.clone();
+            }
+        }
+        if migrated {
+            store_all(&entry, &secrets)?;
+        }
+        Ok(migrated)
+    });
+    let mut unrestored = UNRESTORED.lock().unwrap_or_else(|e| e.into_inner());
+    for (name, _) in fields.iter() {
+        if result.is_ok() {
+            unrestored.remove(name.as_ref());
+        } else {
+            unrestored.insert(name.to_string());
+        }
+    }
+    result
+}
+
+/// Whether some secrets could not be read from the keychain, so the app
+/// runs without them.
+pub(crate) fn unavailable() -> bool {
+    !UNRESTORED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .is_empty()
+}
+
+/// Save `fields` to the keychain and blank them, before the rest of the
+/// config is written to disk. An empty field removes its secret, unless
+/// [`restore`] could not read it: then the stored value is kept. So does a
+/// missing list entry (`<field>/<entry>`) of a field in `fields`.
+///
+/// If the keychain cannot be used, or the secrets stay in the files, the
+/// fields keep their values, and so are written to the file.
+pub(crate) fn stash(fields: &mut Fields) {
+    if in_files() {
+        return;
+    }
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut unrestored = UNRESTORED.lock().unwrap_or_else(|e| e.into_inner());
+    let result = keyring_entry().and_then(|entry| {
+        let mut secrets = load_all(&entry)?;
+        for (name, value) in fields.iter() {
+            if !value.is_empty() {
+                secrets.insert(name.to_string(), value.to_string());
+            } else if !unrestored.contains(name.as_ref()) {
+                secrets.remove(name.as_ref());
+            }
+        }
+        let listed = |name: &str| fields.iter().any(|(n, _)| n == name);
+        secrets.retain(|name, _| match name.split_once('/') {
+            Some((field, _)) => !listed(field) || listed(name) || unrestored.contains(name),
+            None => true,
+        });
+        store_all(&entry, &secrets)
+    });
+    match result {
+        Ok(()) => {
+            for (name, value) in fields.iter_mut() {
+                if !value.is_empty() {
+                    unrestored.remove(name.as_ref());
+                }
+                value.clear();
+            }
+        }
+        Err(e) => eprintln!("[secrets] Keeping secrets in the config file: {e}"),
+    }
+}
+
+/// The key the config files are encrypted with, or `None` if encryption at
+/// rest is off.
+///
+/// # Errors
+///
+/// Returns `Err` if the keychain cannot be read, so that callers never take
+/// an unreadable keychain for encryption being off.
+pub(crate) fn config_key() -> Result<Option<String>, String> {
+    if in_files() {
+        return Ok(None);
+    }
+    let mut cached = CONFIG_KEY.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(key) = cached.as_ref() {
+        return Ok(key.clone());
+    }
+    let entry = keyring::Entry::new(KEYRING_SERVICE, CONFIG_KEY_ACCOUNT)
+        .map_err(|e| format!("Keychain unavailable: {e}"))?;
+    let key = match entry.get_password() {
+        Ok(key) => Some(key),
+        Err(keyring::Error::NoEntry) => None,
+        Err(e) => return Err(format!("Failed to read config key from keychain: {e}")),
+    };
+    *cached = Some(key.clone());
+    Ok(key)
+}
+
+/// Write files with `key` (`None`: in plain text) from now on, without
+/// changing the keychain.
+pub(crate) fn use_config_key(key: Option<String>) {
+    *CONFIG_KEY.lock().unwrap_or_else(|e| e.into_inner()) = Some(key);
+}
+
+/// Store `key` as the config encryption key, or remove it with `None`.
+///
+/// # Errors
+///
+/// Returns `Err` if the keychain cannot be written, or is not used because
+/// the config directory was moved.
+pub(crate) fn set_config_key(key: Option<&str>) -> Result<(), String> {
+    if in_files() {
+        return Err("Encryption is not available with a custom config directory, as its key would be tied to this computer's keychain.".to_string());
+    }
+    let mut cached = CONFIG_KEY.lock().unwrap_or_else(|e| e.into_inner());
+    let entry = keyring::Entry::new(KEYRING_SERVICE, CONFIG_KEY_ACCOUNT)
+        .map_err(|e| format!("Keychain unavailable: {e}"))?;
+    match key {
+        Some(key) => entry
+            .set_password(key)
+            .map_err(|e| format!("Failed to write keychain: {e}"))?,
+        None => match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => {}
+            Err(e) => return Err(format!("Failed to update keychain: {e}")),
+        },
+    }
+    *cached = Some(key.map(str::to_string));
+    Ok(())
+}
@@ -0,0 +1,133 @@
+//! Mouse-shake gesture — summon the companion by shaking the cursor.
+//!
+//! Like macOS's shake-to-find-cursor: rapid horizontal back-and-forth
+//! movement is detected inside the hit-test thread ([`crate::hittest`]),
+//! which feeds every raw cursor sample into a [`ShakeDetector`]. A shake is
+//! a number of direction reversals, each after a minimum travel, within a
+//! short window. When one is recognised a `"mouse-shake"` event is emitted
+//! and the configured [`ShakeAction`] is run.
+
+use crate::hotcorners::{self, HotCornerAction};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+/// What happens when a shake is detected, in addition to the event.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ShakeAction {
+    /// Only emit the `"mouse-shake"` event.
+    None,
+    /// Show the window; the frontend walks the character to the cursor.
+    #[default]
+    SummonToCursor,
+    /// Show the window and open the chat (same as the tray item).
+    OpenChat,
+}
+
+/// Shake section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ShakeConfig {
+    pub enabled: bool,
+    /// Direction reversals needed within `window_ms`.
+    pub min_reversals: u32,
+    /// Horizontal travel (logical pixels) between reversals for them to count.
+    pub min_travel_px: i32,
+    /// Time window in which the reversals must happen.
+    pub window_ms: u64,
+    /// Minimum time between two shakes.
+    pub cooldown_ms: u64,
+    pub action: ShakeAction,
+}
+
+impl Default for ShakeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_reversals: 4,
+            min_travel_px: 40,
+            window_ms: 700,
+            cooldown_ms: 1500,
+            action: ShakeAction::SummonToCursor,
+        }
+    }
+}
+
+/// Payload of `"mouse-shake"` events (window-relative, logical pixels).
+#[derive(Clone, Serialize)]
+pub struct ShakeEvent {
+    pub x: i32,
+    pub y: i32,
+    pub action: ShakeAction,
+}
+
+/// Velocity sign-flip counter for horizontal cursor movement.
+#[derive(Default)]
+pub struct ShakeDetector {
+    /// Direction of the current stroke (`-1` or `1`), if moving.
+    direction: i32,
+    /// Horizontal position where the current stroke started.
+    stroke_start: i32,
+    last_x: Option<i32>,
+    /// Times of recent qualifying reversals.
+    reversals: VecDeque<Instant>,
+    last_fired: Option<Instant>,
+}
+
+impl ShakeDetector {
+    /// Feed one cursor sample. Returns `true` when a shake is recognised.
+    pub fn update(&mut self, config: &ShakeConfig, x: i32) -> bool {
+        let now = Instant::now();
+        let Some(last_x) = self.last_x.replace(x) else {
+            self.stroke_start = x;
+            return false;
+        };
+        let dx = x - last_x;
+        if dx == 0 {
+            return false;
+        }
+
+        let direction = dx.signum();
+        if direction != self.direction {
+            if self.direction != 0 && (last_x - self.stroke_start).abs() >= config.min_travel_px {
+                self.reversals.push_back(now);
+            }
+            self.direction = direction;
+            self.stroke_start = last_x;
+        }
+
+        let window = Duration::from_millis(config.window_ms);
+        while self
+            .reversals
+            .front()
+            .is_some_and(|t| now.duration_since(*t) > window)
+        {
+            self.reversals.pop_front();
+        }
+
+        let cooled_down = self
+            .last_fired
+            .is_none_or(|t| now.duration_since(t) >= Duration::from_millis(config.cooldown_ms));
+        if cooled_down && self.reversals.len() as u32 >= config.min_reversals.max(1) {
+            self.last_fired = Some(now);
+            self.reversals.clear();
+            return true;
+        }
+        false
+    }
+}
+
+/// Run the side effect of a detected shake.
+pub fn run_action(app: &AppHandle, action: ShakeAction) {
+    match action {
+        ShakeAction::None => {}
+        ShakeAction::SummonToCursor => {
+            if let Some(w) = app.get_webview_window("main") {
+                let _ = w.show();
+            }
+        }
+        ShakeAction::OpenChat => hotcorners::run_action(app, HotCornerAction::OpenChat),
+    }
+}
@@ -0,0 +1,224 @@
+//! HMAC-SHA256 signatures on webhook bodies.
+//!
+//! A bearer token proves who sent a request, but anyone who sees one
+//! request on the LAN can replay it, or send a different body with the
+//! same token. With a shared secret, [`crate::openclaw::send_webhook`]
+//! signs what it sends and [`crate::push`] refuses what is not signed:
+//!
+//! - `X-Signature-Timestamp: <unix seconds>`
+//! - `X-Signature-256: sha256=<hex HMAC-SHA256 of "<timestamp>.<body>">`
+//!
+//! The timestamp is part of the signed data, so an old request cannot be
+//! re-sent with a new one; [`Verifier`] rejects timestamps more than
+//! [`MAX_SKEW_SECS`] away from the local clock and signatures it has
+//! already seen within that window.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const SIGNATURE_HEADER: &str = "X-Signature-256";
+
+pub const TIMESTAMP_HEADER: &str = "X-Signature-Timestamp";
+
+/// How far a request's timestamp may be from the local clock.
+pub const MAX_SKEW_SECS: u64 = 300;
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn mac(secret: &str, timestamp: u64, body: &[u8]) -> Hmac<Sha256> {
+    // HMAC takes keys of any length.
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac
+}
+
+/// The timestamp and signature headers for `body`, as `(name, value)`.
+pub fn sign(secret: &str, body: &[u8]) -> [(&'static str, String); 2] {
+    let timestamp = now_secs();
+    let digest = mac(secret, timestamp, body).finalize().into_bytes();
+    let hex: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+    [
+        (TIMESTAMP_HEADER, timestamp.to_string()),
+        (SIGNATURE_HEADER, format!("sha256={hex}")),
+    ]
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Checks incoming signatures and remembers the recent ones.
+pub struct Verifier {
+    /// Signatures accepted within [`MAX_SKEW_SECS`], with their timestamps.
+    seen: Mutex<VecDeque<(u64, String)>>,
+}
+
+impl Verifier {
+    pub const fn new() -> Self {
+        Self {
+            seen: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Check the header values sent with `body` against `secret`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` with the reason if a header is missing or malformed,
+    /// the timestamp is too far off, the signature does not match, or it
+    /// was already used.
+    pub fn verify(
+        &self,
+        secret: &str,
+        timestamp: Option<&str>,
+        signature: Option<&str>,
+        body: &[u8],
+    ) -> Result<(), &'static str> {
+        let (Some(timestamp), Some(signature)) = (timestamp, signature) else {
+            return Err("Missing signature");
+        };
+        let timestamp: u64 = timestamp
+            .trim()
+            .parse()
+            .map_err(|_| "Malformed signature timestamp")?;
+        let now = now_secs();
+        if timestamp.abs_diff(now) > MAX_SKEW_SECS {
+            return Err("Signature timestamp out of range");
+        }
+        let signature = signature.trim().to_ascii_lowercase();
+        let digest = signature
+            .strip_prefix("sha256=")
+            .and_then(decode_hex)
+            .ok_or("Malformed signature")?;
+        mac(secret, timestamp, body)
+            .verify_slice(&digest)
+            .map_err(|_| "Wrong signature")?;
+
+        let mut seen = self.seen.lock().map_err(|_| "Signature cache poisoned")?;
+        seen.retain(|(at, _)| at.abs_diff(now) <= MAX_SKEW_SECS);
+        if seen.iter().any(|(_, s)| *s == signature) {
+            return Err("Signature already used");
+        }
+        seen.push_back((timestamp, signature));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The signature header value for `body` sent at `timestamp`.
+    fn signature_at(secret: &str, timestamp: u64, body: &[u8]) -> String {
+        let digest = mac(secret, timestamp, body).finalize().into_bytes();
+        let hex: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+        format!("sha256={hex}")
+    }
+
+    #[test]
+    fn accepts_a_signed_body() {
+        let [(_, timestamp), (_, signature)] = sign("secret", b"{}");
+        let verifier = Verifier::new();
+        assert_eq!(
+            verifier.verify("secret", Some(&timestamp), Some(&signature), b"{}"),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn rejects_another_body_or_secret() {
+        let [(_, timestamp), (_, signature)] = sign("secret", b"{}");
+        let verifier = Verifier::new();
+        let verify =
+            |secret, body: &[u8]| verifier.verify(secret, Some(&timestamp), Some(&signature), body);
+        assert_eq!(verify("secret", b"{\"a\":1}"), Err("Wrong signature"));
+        assert_eq!(verify("other", b"{}"), Err("Wrong signature"));
+    }
+
+    #[test]
+    fn rejects_a_replay() {
+        let [(_, timestamp), (_, signature)] = sign("secret", b"{}");
+        let verifier = Verifier::new();
+        let verify = || verifier.verify("secret", Some(&timestamp), Some(&signature), b"{}");
+        assert_eq!(verify(), Ok(()));
+        assert_eq!(verify(), Err("Signature already used"));
+    }
+
+    #[test]
+    fn rejects_timestamps_outside_the_window() {
+        let verifier = Verifier::new();
+        for timestamp in [
+            now_secs() - MAX_SKEW_SECS - 10,
+            now_secs() + MAX_SKEW_SECS + 10,
+        ] {
+            let signature = signature_at("secret", timestamp, b"{}");
+            assert_eq!(
+                verifier.verify(
+                    "secret",
+                    Some(&timestamp.to_string()),
+                    Some(&signature),
+                    b"{}"
+                ),
+                Err("Signature timestamp out of range")
+            );
+        }
+    }
+
+    #[test]
+    fn accepts_timestamps_inside_the_window() {
+        let verifier = Verifier::new();
+        let timestamp = now_secs() - MAX_SKEW_SECS + 10;
+        // Hex digits in either case.
+        let signature = signature_at("secret", timestamp, b"{}").to_ascii_uppercase();
+        assert_eq!(
+            verifier.verify(
+                "secret",
+                Some(&timestamp.to_string()),
+                Some(&signature),
+                b"{}"
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn rejects_missing_or_malformed_headers() {
+        let verifier = Verifier::new();
+        let timestamp = now_secs().to_string();
+        let verify = |timestamp, signature| verifier.verify("secret", timestamp, signature, b"{}");
+        assert_eq!(verify(None, Some("sha256=00")), Err("Missing signature"));
+        assert_eq!(verify(Some(&timestamp), None), Err("Missing signature"));
+        assert_eq!(
+            verify(Some("yesterday"), Some("sha256=00")),
+            Err("Malformed signature timestamp")
+        );
+        assert_eq!(
+            verify(Some(&timestamp), Some("00")),
+            Err("Malformed signature")
+        );
+        assert_eq!(
+            verify(Some(&timestamp), Some("sha256=0g")),
+            Err("Malformed signature")
+        );
+        assert_eq!(
+            verify(Some(&timestamp), Some("sha256=000")),
+            Err("Malformed signature")
+        );
+    }
+}
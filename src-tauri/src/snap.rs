@@ -0,0 +1,230 @@
+//! Magnetic snapping geometry for dragging the character.
+//!
+//! While the user drags the character, the frontend calls
+//! [`get_snap_candidates`] with the character's screen-space bounding box and
+//! receives the nearby edges it could snap to: desktop window edges, monitor
+//! edges, and the Dock/taskbar boundary. All coordinates are screen-space with
+//! a top-left origin (the same space as [`crate::screen::WindowInfo`]).
+
+use crate::screen;
+use crate::window;
+use serde::{Deserialize, Serialize};
+
+/// Default search radius in pixels.
+const DEFAULT_MAX_DISTANCE: f64 = 48.0;
+
+/// Axis-aligned rectangle in screen pixels.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// What an edge belongs to.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapSource {
+    Window,
+    Screen,
+    Dock,
+}
+
+/// Side of a rectangle.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Edge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl Edge {
+    fn is_horizontal(self) -> bool {
+        matches!(self, Edge::Top | Edge::Bottom)
+    }
+}
+
+/// One edge the dragged rectangle could snap to.
+#[derive(Serialize, Clone, Debug)]
+pub struct SnapCandidate {
+    pub source: SnapSource,
+    /// Which side of the source this edge is (e.g. a window's `top`).
+    pub edge: Edge,
+    /// Which side of the dragged rectangle would align with it.
+    pub rect_edge: Edge,
+    /// Edge position: `y` for top/bottom edges, `x` for left/right edges.
+    pub position: f64,
+    /// Extent of the edge along its axis.
+    pub span_start: f64,
+    pub span_end: f64,
+    /// Distance from `rect_edge` to the edge, in pixels.
+    pub distance: f64,
+    /// For window edges.
+    pub window_id: Option<u32>,
+    pub app_name: Option<String>,
+}
+
+/// An edge before it is matched against the dragged rectangle.
+struct RawEdge {
+    source: SnapSource,
+    edge: Edge,
+    position: f64,
+    span_start: f64,
+    span_end: f64,
+    window_id: Option<u32>,
+    app_name: Option<String>,
+}
+
+fn rect_edges(
+    source: SnapSource,
+    r: Rect,
+    window_id: Option<u32>,
+    app_name: Option<&str>,
+) -> Vec<RawEdge> {
+    let make = |edge, position, span_start, span_end| RawEdge {
+        source,
+        edge,
+        position,
+        span_start,
+        span_end,
+        window_id,
+        app_name: app_name.map(str::to_string),
+    };
+    vec![
+        make(Edge::Top, r.y, r.x, r.x + r.width),
+        make(Edge::Bottom, r.y + r.height, r.x, r.x + r.width),
+        make(Edge::Left, r.x, r.y, r.y + r.height),
+        make(Edge::Right, r.x + r.width, r.y, r.y + r.height),
+    ]
+}
+
+/// Match an edge against the dragged rectangle.
+///
+/// The edge must overlap the rectangle along its own axis; the closer of
+/// the two parallel rectangle sides is reported.
+fn match_edge(raw: RawEdge, rect: Rect, max_distance: f64) -> Option<SnapCandidate> {
+    let (near, far, span_lo, span_hi, near_edge, far_edge) = if raw.edge.is_horizontal() {
+        (rect.y, rect.y + rect.height, rect.x, rect.x + rect.width, Edge::Top, Edge::Bottom)
+    } else {
+        (rect.x, rect.x + rect.width, rect.y, rect.y + rect.height, Edge::Left, Edge::Right)
+    };
+    if raw.span_end < span_lo || raw.span_start > span_hi {
+        return None;
+    }
+
+    let (distance, rect_edge) = {
+        let d_near = (raw.position - near).abs();
+        let d_far = (raw.position - far).abs();
+        if d_near <= d_far {
+            (d_near, near_edge)
+        } else {
+            (d_far, far_edge)
+        }
+    };
+    if distance > max_distance {
+        return None;
+    }
+
+    Some(SnapCandidate {
+        source: raw.source,
+        edge: raw.edge,
+        rect_edge,
+        position: raw.position,
+        span_start: raw.span_start,
+        span_end: raw.span_end,
+        distance,
+        window_id: raw.window_id,
+        app_name: raw.app_name,
+    })
+}
+
+/// Monitor rectangles in top-left-origin screen space.
+///
+/// `NSScreen` frames on macOS use a bottom-left origin relative to the
+/// primary screen, so they are flipped here. Background threads go through
+/// [`window::on_main_thread`].
+pub(crate) fn monitor_rects() -> Vec<Rect> {
+    let monitors = window::get_all_monitors();
+    #[cfg(target_os = "macos")]
+    let primary_height = monitors
+        .iter()
+        .find(|m| m.is_primary)
+        .map_or(0.0, |m| m.height as f64);
+
+    monitors
+        .iter()
+        .map(|m| {
+            #[cfg(target_os = "macos")]
+            let y = primary_height - (m.y as f64 + m.height as f64);
+            #[cfg(not(target_os = "macos"))]
+            let y = m.y as f64;
+            Rect {
+                x: m.x as f64,
+                y,
+                width: m.width as f64,
+                height: m.height as f64,
+            }
+        })
+        .collect()
+}
+
+/// The Dock/taskbar boundary on the primary screen, if visible.
+fn dock_edge() -> Option<RawEdge> {
+    let dock = window::get_dock_info();
+    if dock.is_hidden || dock.height == 0 {
+        return None;
+    }
+    let screen = window::get_screen_size();
+    let (w, h, d) = (screen.width as f64, screen.height as f64, dock.height as f64);
+    let (edge, position, span_end) = match dock.position.as_str() {
+        "left" => (Edge::Right, d, h),
+        "right" => (Edge::Left, w - d, h),
+        "top" => (Edge::Bottom, d, w),
+        _ => (Edge::Top, h - d, w),
+    };
+    Some(RawEdge {
+        source: SnapSource::Dock,
+        edge,
+        position,
+        span_start: 0.0,
+        span_end,
+        window_id: None,
+        app_name: None,
+    })
+}
+
+/// IPC command: list edges within `max_distance` pixels of `rect`, nearest first.
+///
+/// Sources:
+/// - every visible window from [`screen::window_list`] (all four edges)
+/// - every monitor from [`window::get_all_monitors`] (all four edges)
+/// - the Dock/taskbar boundary from [`window::get_dock_info`]
+#[tauri::command]
+pub fn get_snap_candidates(rect: Rect, max_distance: Option<f64>) -> Vec<SnapCandidate> {
+    let max_distance = max_distance.unwrap_or(DEFAULT_MAX_DISTANCE);
+    let mut edges = Vec::new();
+
+    for w in screen::window_list() {
+        let r = Rect {
+            x: w.x as f64,
+            y: w.y as f64,
+            width: w.width as f64,
+            height: w.height as f64,
+        };
+        edges.extend(rect_edges(SnapSource::Window, r, Some(w.window_id), Some(&w.app_name)));
+    }
+    for r in monitor_rects() {
+        edges.extend(rect_edges(SnapSource::Screen, r, None, None));
+    }
+    edges.extend(dock_edge());
+
+    let mut candidates: Vec<SnapCandidate> = edges
+        .into_iter()
+        .filter_map(|raw| match_edge(raw, rect, max_distance))
+        .collect();
+    candidates.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+    candidates
+}
@@ -0,0 +1,128 @@
+//! Frequency-band analysis for music visualization.
+//!
+//! [`SpectrumAnalyzer`] is fed mono-mixed samples from the cpal callback in
+//! [`crate::audio`]. Every [`FFT_SIZE`] samples it runs a Hann-windowed FFT
+//! and reduces the bins to [`BANDS`] log-spaced bands between
+//! [`MIN_BAND_HZ`] and [`MAX_BAND_HZ`], each a `0.0`–`1.0` value on a
+//! [`FLOOR_DB`]..0 dBFS scale with fast attack and slow decay, which is what
+//! dance and visualizer animations want.
+
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+use std::sync::Arc;
+
+/// Samples per FFT (~21 ms at 48 kHz).
+pub const FFT_SIZE: usize = 1024;
+
+/// Number of output bands.
+pub const BANDS: usize = 16;
+
+/// Lower edge of the first band.
+const MIN_BAND_HZ: f32 = 40.0;
+
+/// Upper edge of the last band (clamped to Nyquist).
+const MAX_BAND_HZ: f32 = 16_000.0;
+
+/// Level that maps to `0.0`.
+const FLOOR_DB: f32 = -70.0;
+
+/// Per-FFT decay factor when a band gets quieter.
+const DECAY: f32 = 0.85;
+
+pub struct SpectrumAnalyzer {
+    channels: usize,
+    fft: Arc<dyn Fft<f32>>,
+    window: Vec<f32>,
+    /// Bin range `[lo, hi)` of each band.
+    band_bins: Vec<(usize, usize)>,
+    /// Mono samples collected for the next FFT.
+    pending: Vec<f32>,
+    /// Down-mix of the current (partial) multi-channel frame.
+    frame_sum: f32,
+    frame_len: usize,
+    buffer: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+    bands: [f32; BANDS],
+}
+
+impl SpectrumAnalyzer {
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        let fft = FftPlanner::<f32>::new().plan_fft_forward(FFT_SIZE);
+        let scratch = vec![Complex::default(); fft.get_inplace_scratch_len()];
+        let window = (0..FFT_SIZE)
+            .map(|i| {
+                let phase = std::f32::consts::TAU * i as f32 / (FFT_SIZE - 1) as f32;
+                0.5 - 0.5 * phase.cos()
+            })
+            .collect();
+
+        let bin_hz = sample_rate as f32 / FFT_SIZE as f32;
+        let max_hz = MAX_BAND_HZ.min(sample_rate as f32 / 2.0);
+        let ratio = (max_hz / MIN_BAND_HZ).powf(1.0 / BANDS as f32);
+        let band_bins = (0..BANDS)
+            .map(|band| {
+                let lo_hz = MIN_BAND_HZ * ratio.powi(band as i32);
+                let hi_hz = lo_hz * ratio;
+                let lo = ((lo_hz / bin_hz) as usize).clamp(1, FFT_SIZE / 2 - 1);
+                let hi = ((hi_hz / bin_hz).ceil() as usize).clamp(lo + 1, FFT_SIZE / 2);
+                (lo, hi)
+            })
+            .collect();
+
+        Self {
+            channels: channels.max(1) as usize,
+            fft,
+            window,
+            band_bins,
+            pending: Vec::with_capacity(FFT_SIZE),
+            frame_sum: 0.0,
+            frame_len: 0,
+            buffer: vec![Complex::default(); FFT_SIZE],
+            scratch,
+            bands: [0.0; BANDS],
+        }
+    }
+
+    /// Feed one interleaved sample. Returns the new bands each time a full
+    /// FFT window has been collected.
+    pub fn push(&mut self, sample: f32) -> Option<[f32; BANDS]> {
+        self.frame_sum += sample;
+        self.frame_len += 1;
+        if self.frame_len < self.channels {
+            return None;
+        }
+        self.pending.push(self.frame_sum / self.channels as f32);
+        self.frame_sum = 0.0;
+        self.frame_len = 0;
+        if self.pending.len() < FFT_SIZE {
+            return None;
+        }
+        self.analyze();
+        self.pending.clear();
+        Some(self.bands)
+    }
+
+    fn analyze(&mut self) {
+        for ((out, sample), w) in self.buffer.iter_mut().zip(&self.pending).zip(&self.window) {
+            *out = Complex::new(sample * w, 0.0);
+        }
+        self.fft
+            .process_with_scratch(&mut self.buffer, &mut self.scratch);
+
+        for (band, &(lo, hi)) in self.bands.iter_mut().zip(&self.band_bins) {
+            let peak = self.buffer[lo..hi]
+                .iter()
+                .map(|c| c.norm())
+                .fold(0.0, f32::max);
+            // 2/N for a one-sided spectrum, x2 for the Hann window's gain.
+            let amplitude = peak * 4.0 / FFT_SIZE as f32;
+            let db = 20.0 * (amplitude + 1e-9).log10();
+            let value = ((db - FLOOR_DB) / -FLOOR_DB).clamp(0.0, 1.0);
+            *band = if value >= *band {
+                value
+            } else {
+                *band * DECAY + value * (1.0 - DECAY)
+            };
+        }
+    }
+}
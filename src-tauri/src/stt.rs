@@ -0,0 +1,342 @@
+//! Local speech-to-text dictation with whisper.cpp.
+//!
+//! [`start_dictation`] records the user's microphone (the device chosen for
+//! [`crate::audio`], unless that is a loopback source) until
+//! [`stop_dictation`] or [`SttConfig::max_seconds`], and transcribes it on
+//! this machine with a ggml Whisper model. No audio leaves the computer.
+//!
+//! Events:
+//!
+//! - `"dictation-started"` once the microphone is open
+//! - `"dictation-partial"` `{ text }` every [`PARTIAL_INTERVAL`] while
+//!   recording, re-transcribing everything heard so far
+//! - `"dictation-final"` `{ text }` after recording ends
+//! - `"dictation-error"` (a string) if the model or device cannot be used
+//!
+//! Each model is loaded on first use and kept for later dictations. Download
+//! one of the `ggml-*.bin` files from the whisper.cpp project to
+//! `<data dir>/models/ggml-base.bin`, or point [`SttConfig::model_path`] at
+//! it.
+//!
+//! Transcription needs the `stt` cargo feature, which links whisper.cpp;
+//! without it dictation reports `"dictation-error"`.
+
+use cpal::traits::{DeviceTrait, StreamTrait};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::audio;
+use crate::config::ConfigState;
+
+/// Whisper's input sample rate.
+pub(crate) const WHISPER_RATE: u32 = 16_000;
+
+/// How often a partial transcript is produced while recording.
+const PARTIAL_INTERVAL: Duration = Duration::from_millis(1500);
+
+/// Less audio than this (0.5 s) is not worth transcribing.
+const MIN_SAMPLES: usize = WHISPER_RATE as usize / 2;
+
+/// Whether a dictation is in progress.
+static DICTATING: AtomicBool = AtomicBool::new(false);
+
+/// Set by [`stop_dictation`].
+static STOP: AtomicBool = AtomicBool::new(false);
+
+/// Settings for dictation.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SttConfig {
+    /// ggml model file; `None` uses `<data dir>/models/ggml-base.bin`.
+    pub model_path: Option<String>,
+    /// Spoken language code (`"en"`, `"ja"`, …) or `"auto"`.
+    pub language: String,
+    /// Recording stops by itself after this long.
+    pub max_seconds: u32,
+}
+
+impl Default for SttConfig {
+    fn default() -> Self {
+        Self {
+            model_path: None,
+            language: "auto".to_string(),
+            max_seconds: 60,
+        }
+    }
+}
+
+impl SttConfig {
+    pub(crate) fn model(&self) -> PathBuf {
+        self.model_path.as_ref().map_or_else(
+            || {
+                crate::memory::data_dir()
+                    .join("models")
+                    .join("ggml-base.bin")
+            },
+            PathBuf::from,
+        )
+    }
+}
+
+/// Payload of `"dictation-partial"` and `"dictation-final"` events.
+#[derive(Serialize, Clone, Debug)]
+pub struct Transcript {
+    pub text: String,
+}
+
+/// Mixes interleaved input down to mono and resamples it to
+/// [`WHISPER_RATE`] by linear interpolation.
+pub(crate) struct Resampler {
+    channels: usize,
+    /// Input samples per output sample.
+    step: f64,
+    /// Position of the next output sample, in input samples after `prev`.
+    pos: f64,
+    frame_sum: f32,
+    frame_len: usize,
+    prev: f32,
+    pub samples: Vec<f32>,
+}
+
+impl Resampler {
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        Self {
+            channels: channels.max(1) as usize,
+            step: sample_rate as f64 / WHISPER_RATE as f64,
+            pos: 0.0,
+            frame_sum: 0.0,
+            frame_len: 0,
+            prev: 0.0,
+            samples: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, input: &mut dyn Iterator<Item = f32>) {
+        for sample in input {
+            self.push_sample(sample);
+        }
+    }
+
+    /// Feed one interleaved input sample.
+    pub fn push_sample(&mut self, sample: f32) {
+        self.frame_sum += sample;
+        self.frame_len += 1;
+        if self.frame_len < self.channels {
+            return;
+        }
+        let mono = self.frame_sum / self.channels as f32;
+        self.frame_sum = 0.0;
+        self.frame_len = 0;
+        while self.pos <= 1.0 {
+            let t = self.pos as f32;
+            self.samples.push(self.prev + (mono - self.prev) * t);
+            self.pos += self.step;
+        }
+        self.pos -= 1.0;
+        self.prev = mono;
+    }
+}
+
+pub(crate) use whisper::{context, transcribe};
+
+#[cfg(feature = "stt")]
+mod whisper {
+    use std::collections::BTreeMap;
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+
+    /// Loaded models by path. Dictation and the wake word may use different
+    /// ones; each stays loaded instead of evicting the other.
+    static CONTEXTS: Mutex<BTreeMap<PathBuf, Arc<WhisperContext>>> = Mutex::new(BTreeMap::new());
+
+    /// The Whisper model at `path`, loading it if needed.
+    pub(crate) fn context(path: &Path) -> Result<Arc<WhisperContext>, String> {
+        let mut cached = CONTEXTS.lock().map_err(|e| e.to_string())?;
+        if let Some(ctx) = cached.get(path) {
+            return Ok(ctx.clone());
+        }
+        let path_str = path.to_str().ok_or("Model path is not valid UTF-8")?;
+        let ctx = WhisperContext::new_with_params(path_str, WhisperContextParameters::default())
+            .map_err(|e| format!("Failed to load Whisper model {}: {e}", path.display()))?;
+        let ctx = Arc::new(ctx);
+        cached.insert(path.to_path_buf(), ctx.clone());
+        Ok(ctx)
+    }
+
+    /// Transcribe 16 kHz mono `audio`.
+    pub(crate) fn transcribe(
+        ctx: &WhisperContext,
+        audio: &[f32],
+        language: &str,
+    ) -> Result<String, String> {
+        let mut state = ctx
+            .create_state()
+            .map_err(|e| format!("Failed to create Whisper state: {e}"))?;
+        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+        params.set_language(Some(language));
+        params.set_n_threads(
+            thread::available_parallelism()
+                .map(|n| n.get().min(4) as i32)
+                .unwrap_or(2),
+        );
+        params.set_print_special(false);
+        params.set_print_progress(false);
+        params.set_print_realtime(false);
+        params.set_print_timestamps(false);
+        params.set_suppress_blank(true);
+        state
+            .full(params, audio)
+            .map_err(|e| format!("Transcription failed: {e}"))?;
+
+        let segments = state
+            .full_n_segments()
+            .map_err(|e| format!("Transcription failed: {e}"))?;
+        let mut text = String::new();
+        for i in 0..segments {
+            if let Ok(segment) = state.full_get_segment_text(i) {
+                text.push_str(&segment);
+            }
+        }
+        Ok(text.trim().to_string())
+    }
+}
+
+#[cfg(not(feature = "stt"))]
+mod whisper {
+    use std::path::Path;
+    use std::sync::Arc;
+
+    /// Stands in for the Whisper model, which this build cannot load.
+    pub(crate) enum Model {}
+
+    pub(crate) fn context(_path: &Path) -> Result<Arc<Model>, String> {
+        Err("This build does not include speech recognition (cargo feature `stt`)".to_string())
+    }
+
+    pub(crate) fn transcribe(
+        ctx: &Model,
+        _audio: &[f32],
+        _language: &str,
+    ) -> Result<String, String> {
+        match *ctx {}
+    }
+}
+
+/// Whether a dictation is recording or transcribing.
+pub(crate) fn is_dictating() -> bool {
+    DICTATING.load(Ordering::Relaxed)
+}
+
+/// Record and transcribe until stopped.
+fn run(app: &AppHandle, config: &SttConfig, device_id: Option<&str>) -> Result<(), String> {
+    let ctx = context(&config.model())?;
+    let device = audio::microphone(device_id)?;
+    let supported = device
+        .default_input_config()
+        .map_err(|e| format!("No input config available: {e}"))?;
+    let sample_format = supported.sample_format();
+    let stream_config: cpal::StreamConfig = supported.into();
+
+    let recording = Arc::new(Mutex::new(Resampler::new(
+        stream_config.sample_rate.0,
+        stream_config.channels,
+    )));
+    let sink = recording.clone();
+    let stream = audio::build_input_stream(
+        &device,
+        &stream_config,
+        sample_format,
+        move |samples| {
+            if let Ok(mut r) = sink.lock() {
+                r.push(samples);
+            }
+        },
+        |err| eprintln!("[stt] Stream error: {err}"),
+    )?;
+    stream
+        .play()
+        .map_err(|e| format!("Failed to start stream: {e}"))?;
+    let _ = app.emit("dictation-started", ());
+
+    let snapshot = || {
+        recording
+            .lock()
+            .map(|r| r.samples.clone())
+            .unwrap_or_default()
+    };
+    let limit = Duration::from_secs(config.max_seconds.max(1) as u64);
+    let started = Instant::now();
+    let mut last_partial = Instant::now();
+    let mut partial = String::new();
+    while !STOP.load(Ordering::Relaxed) && started.elapsed() < limit {
+        thread::sleep(Duration::from_millis(100));
+        if last_partial.elapsed() < PARTIAL_INTERVAL {
+            continue;
+        }
+        last_partial = Instant::now();
+        let audio = snapshot();
+        if audio.len() < MIN_SAMPLES {
+            continue;
+        }
+        let text = transcribe(&ctx, &audio, &config.language)?;
+        if text != partial {
+            let _ = app.emit("dictation-partial", Transcript { text: text.clone() });
+            partial = text;
+        }
+    }
+    // Release the microphone before the (slower) final pass.
+    drop(stream);
+
+    let audio = snapshot();
+    let text = if audio.len() < MIN_SAMPLES {
+        String::new()
+    } else {
+        transcribe(&ctx, &audio, &config.language)?
+    };
+    let _ = app.emit("dictation-final", Transcript { text });
+    Ok(())
+}
+
+/// Start recording for a dictation.
+///
+/// Transcripts arrive as events (see the module docs).
+///
+/// # Errors
+///
+/// Returns `Err` if a dictation is already running or the model file is
+/// missing. Later failures are emitted as `"dictation-error"`.
+#[tauri::command]
+pub fn start_dictation(app: AppHandle, state: State<'_, ConfigState>) -> Result<(), String> {
+    let settings = state.get_settings()?;
+    let config = settings.stt;
+    let model = config.model();
+    if !model.exists() {
+        return Err(format!("Whisper model not found at {}", model.display()));
+    }
+    if DICTATING.swap(true, Ordering::SeqCst) {
+        return Err("Dictation is already running".to_string());
+    }
+    STOP.store(false, Ordering::Relaxed);
+    let device_id = settings.audio.device;
+    thread::spawn(move || {
+        if let Err(e) = run(&app, &config, device_id.as_deref()) {
+            eprintln!("[stt] {e}");
+            let _ = app.emit("dictation-error", e);
+        }
+        DICTATING.store(false, Ordering::SeqCst);
+    });
+    Ok(())
+}
+
+/// Stop recording; the final transcript follows as `"dictation-final"`.
+#[tauri::command]
+pub fn stop_dictation() {
+    STOP.store(true, Ordering::Relaxed);
+}
@@ -0,0 +1,208 @@
+//! Emotion and action tags in model replies.
+//!
+//! The character's prompt asks the model to mark its mood and movements
+//! inline, e.g. `Hi there! [emotion:happy] [gesture:wave]`. [`parse`] pulls
+//! those tags out so every consumer gets the clean text plus typed lists
+//! instead of matching them itself:
+//!
+//! - `[emotion:<name>]` → [`ParsedReply::emotions`], if it is one of
+//!   [`EMOTIONS`]
+//! - `[motion:<name>]`, `[gesture:<name>]`, `[tool:<name>]` →
+//!   [`ParsedReply::actions`]
+//!
+//! Tags are matched case-insensitively and removed from the text even when
+//! their value is not recognised. Other bracketed text is left alone.
+
+use serde::{Deserialize, Serialize};
+
+/// Emotions the character can show, as understood by the frontend.
+pub const EMOTIONS: &[&str] = &[
+    "happy",
+    "sad",
+    "angry",
+    "surprised",
+    "neutral",
+    "relaxed",
+    "thinking",
+];
+
+/// Tag names that become [`Action`]s.
+const ACTION_TAGS: &[&str] = &["motion", "gesture", "tool"];
+
+/// Something the reply asks the character to do.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Action {
+    /// The tag name: `"motion"`, `"gesture"` or `"tool"`.
+    pub kind: String,
+    pub name: String,
+}
+
+/// A reply split into its text and tags.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParsedReply {
+    /// The reply without tags, whitespace runs collapsed.
+    pub text: String,
+    /// Recognised emotions in order of appearance; empty if none were
+    /// tagged.
+    pub emotions: Vec<String>,
+    pub actions: Vec<Action>,
+}
+
+/// The tag starting at the beginning of `s` (`"[name:value]..."`), as
+/// lowercase name, lowercase value and byte length.
+fn tag(s: &str) -> Option<(String, String, usize)> {
+    let end = s.find(']')?;
+    let (name, value) = s[1..end].split_once(':')?;
+    let valid = |part: &str| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    };
+    let (name, value) = (name.trim(), value.trim());
+    if !valid(name) || !valid(value) {
+        return None;
+    }
+    Some((
+        name.to_ascii_lowercase(),
+        value.to_ascii_lowercase(),
+        end + 1,
+    ))
+}
+
+/// Split `reply` into its text, emotions and actions.
+pub fn parse(reply: &str) -> ParsedReply {
+    let mut parsed = ParsedReply::default();
+    let mut text = String::with_capacity(reply.len());
+    let mut rest = reply;
+    while let Some(start) = rest.find('[') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match tag(rest) {
+            Some((name, value, len))
+                if name == "emotion" || ACTION_TAGS.contains(&name.as_str()) =>
+            {
+                if name == "emotion" {
+                    if EMOTIONS.contains(&value.as_str()) {
+                        parsed.emotions.push(value);
+                    }
+                } else {
+                    parsed.actions.push(Action {
+                        kind: name,
+                        name: value,
+                    });
+                }
+                rest = &rest[len..];
+            }
+            _ => {
+                text.push('[');
+                rest = &rest[1..];
+            }
+        }
+    }
+    text.push_str(rest);
+
+    // Collapse the gaps left by removed tags, as the frontend always has.
+    let mut collapsed = String::with_capacity(text.len());
+    let mut whitespace = String::new();
+    for c in text.trim().chars() {
+        if c.is_whitespace() {
+            whitespace.push(c);
+            continue;
+        }
+        match whitespace.chars().count() {
+            0 => {}
+            1 => collapsed.push_str(&whitespace),
+            _ => collapsed.push(' '),
+        }
+        whitespace.clear();
+        collapsed.push(c);
+    }
+    parsed.text = collapsed;
+    parsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(kind: &str, name: &str) -> Action {
+        Action {
+            kind: kind.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn tags_are_case_insensitive() {
+        let parsed = parse("[Emotion:HAPPY] Hello! [GESTURE:Wave]");
+        assert_eq!(parsed.text, "Hello!");
+        assert_eq!(parsed.emotions, ["happy"]);
+        assert_eq!(parsed.actions, [action("gesture", "wave")]);
+    }
+
+    #[test]
+    fn unknown_emotions_are_dropped_from_text_and_list() {
+        let parsed = parse("[emotion:ecstatic] Wow");
+        assert_eq!(parsed.text, "Wow");
+        assert!(parsed.emotions.is_empty());
+    }
+
+    #[test]
+    fn first_valid_emotion_comes_first() {
+        let parsed = parse("[emotion:bogus] Hmm [emotion:thinking] okay [emotion:happy]");
+        assert_eq!(parsed.text, "Hmm okay");
+        assert_eq!(parsed.emotions, ["thinking", "happy"]);
+    }
+
+    #[test]
+    fn actions_keep_their_order() {
+        let parsed = parse("[motion:nod] Sure [tool:timer] [gesture:wave]");
+        assert_eq!(parsed.text, "Sure");
+        assert_eq!(
+            parsed.actions,
+            [
+                action("motion", "nod"),
+                action("tool", "timer"),
+                action("gesture", "wave")
+            ]
+        );
+    }
+
+    #[test]
+    fn gaps_left_by_tags_collapse() {
+        assert_eq!(
+            parse("  Hi [emotion:happy] there.\nBye  ").text,
+            "Hi there.\nBye"
+        );
+        assert_eq!(parse("One\n\n[motion:nod]\n\nTwo").text, "One Two");
+    }
+
+    #[test]
+    fn unclosed_brackets_stay_in_text() {
+        let parsed = parse("Look [emotion:happy");
+        assert_eq!(parsed.text, "Look [emotion:happy");
+        assert!(parsed.emotions.is_empty());
+
+        let parsed = parse("[ [emotion:happy] Hi");
+        assert_eq!(parsed.text, "[ Hi");
+        assert_eq!(parsed.emotions, ["happy"]);
+    }
+
+    #[test]
+    fn other_brackets_stay_in_text() {
+        let reply = "See [1], [note: this] and [gesture:two words].";
+        let parsed = parse(reply);
+        assert_eq!(parsed.text, reply);
+        assert!(parsed.emotions.is_empty());
+        assert!(parsed.actions.is_empty());
+    }
+
+    #[test]
+    fn multi_byte_text_around_tags() {
+        let parsed = parse("こんにちは[emotion:happy]世界 🌸 [gesture:bow]「[emotion:幸せ]」");
+        assert_eq!(parsed.text, "こんにちは世界 🌸 「[emotion:幸せ]」");
+        assert_eq!(parsed.emotions, ["happy"]);
+        assert_eq!(parsed.actions, [action("gesture", "bow")]);
+    }
+}
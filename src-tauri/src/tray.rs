@@ -0,0 +1,128 @@
+//! System tray behaviour.
+//!
+//! The tray menu itself is built in [`crate::run`]; this module holds the
+//! user-configurable parts. A left click on the tray icon runs the
+//! [`TrayClickAction`] from [`TrayConfig`], read from
+//! [`crate::config::AppSettings`] at click time so changes apply immediately.
+//!
+//! # Linux
+//!
+//! The tray is a StatusNotifierItem (via libayatana-appindicator). Such
+//! trays only show the menu: click events are never delivered, so the
+//! left-click action does not apply there and "Show / Hide" is the first
+//! menu item instead. Stock GNOME has no StatusNotifierItem host at all
+//! (it needs the AppIndicator extension); [`tray_available`] detects that
+//! case so [`report_missing_tray`] can keep the window reachable.
+
+use crate::config::ConfigState;
+use crate::hotcorners::{self, HotCornerAction};
+use crate::user_error::{user_facing_error, Severity};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// What a left click on the tray icon does.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum TrayClickAction {
+    /// Show or hide the main window.
+    #[default]
+    ToggleWindow,
+    /// Show the window and open the chat (same as the tray item).
+    OpenChat,
+    /// Show the window and open Settings (same as the tray item).
+    OpenSettings,
+    /// Show the window and run [`TrayConfig::palette_action`] through the
+    /// frontend's command palette.
+    PaletteAction,
+}
+
+/// Tray section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TrayConfig {
+    pub left_click: TrayClickAction,
+    /// Command-palette action id for [`TrayClickAction::PaletteAction`].
+    pub palette_action: String,
+}
+
+/// Handle a left click on the tray icon.
+///
+/// `PaletteAction` emits `"tray-palette-action"` with the configured id; an
+/// empty id falls back to toggling the window.
+pub fn on_left_click(app: &AppHandle) {
+    let config = app
+        .state::<ConfigState>()
+        .get_settings()
+        .map(|s| s.tray)
+        .unwrap_or_default();
+    match config.left_click {
+        TrayClickAction::ToggleWindow => hotcorners::run_action(app, HotCornerAction::ToggleWindow),
+        TrayClickAction::OpenChat => hotcorners::run_action(app, HotCornerAction::OpenChat),
+        TrayClickAction::OpenSettings => hotcorners::run_action(app, HotCornerAction::OpenSettings),
+        TrayClickAction::PaletteAction if config.palette_action.is_empty() => {
+            hotcorners::run_action(app, HotCornerAction::ToggleWindow)
+        }
+        TrayClickAction::PaletteAction => {
+            if let Some(w) = app.get_webview_window("main") {
+                let _ = w.show();
+                let _ = w.set_focus();
+            }
+            let _ = app.emit("tray-palette-action", config.palette_action);
+        }
+    }
+}
+
+/// Whether a tray icon will actually be shown.
+///
+/// On Linux this asks the session bus whether a StatusNotifierItem host
+/// (`org.kde.StatusNotifierWatcher`) is running. If `gdbus` is missing the
+/// answer is assumed to be yes. Other platforms always have a tray.
+pub fn tray_available() -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        let output = std::process::Command::new("gdbus")
+            .args([
+                "call",
+                "--session",
+                "--dest",
+                "org.freedesktop.DBus",
+                "--object-path",
+                "/org/freedesktop/DBus",
+                "--method",
+                "org.freedesktop.DBus.NameHasOwner",
+                "org.kde.StatusNotifierWatcher",
+            ])
+            .output();
+        match output {
+            Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout).contains("true"),
+            _ => true,
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        true
+    }
+}
+
+/// Keep the app usable without a tray icon: show the window, tell the user
+/// why there is no tray, and emit `"tray-unavailable"` so the frontend can
+/// offer its own menu.
+pub fn report_missing_tray(app: &AppHandle) {
+    if let Some(w) = app.get_webview_window("main") {
+        let _ = w.show();
+    }
+    let desktop = std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+    let hint = if desktop.to_ascii_uppercase().contains("GNOME") {
+        " On GNOME, the AppIndicator extension adds one."
+    } else {
+        ""
+    };
+    user_facing_error(
+        app,
+        "tray",
+        format!("Your desktop has no system tray, so my menu lives in the app instead.{hint}"),
+        Severity::Info,
+    );
+    let _ = app.emit("tray-unavailable", ());
+}
@@ -0,0 +1,439 @@
+//! Text-to-speech for the character's voice.
+//!
+//! [`speak`] synthesizes text with the engine selected in [`TtsConfig`] and
+//! hands the clip to the voice player in [`crate::audio`], so it queues
+//! behind other speech, ducks sound effects and drives lip-sync through
+//! `"viseme"` events like any other voice clip.
+//!
+//! Engines implement [`TtsEngine`]:
+//!
+//! - **System** ([`SystemTts`]): the voices that ship with the OS — `say`
+//!   on macOS (the same voices as `AVSpeechSynthesizer`), SAPI through
+//!   `System.Speech` on Windows, `espeak-ng` on Linux.
+//! - **VOICEVOX** ([`Voicevox`]): a locally running VOICEVOX engine.
+//! - **HTTP** ([`HttpTts`]): an ElevenLabs-compatible
+//!   `POST /v1/text-to-speech/{voice}` API.
+//!
+//! `emotion` is the frontend's emotion tag (`"happy"`, `"sad"`, …). VOICEVOX
+//! maps it to a speaker style ([`VoicevoxConfig::styles`]), the HTTP engine
+//! to a more expressive delivery; system voices ignore it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::io::AsyncWriteExt;
+
+use crate::audio::{self, PlaybackKind};
+use crate::config::ConfigState;
+use crate::openclaw::HttpClient;
+use crate::user_error::{user_facing_error, Severity};
+
+/// Timeout for one synthesis request to VOICEVOX or the HTTP engine.
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Numbers the temp files of the system engine.
+static CLIP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Which engine [`speak`] uses.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TtsEngineKind {
+    #[default]
+    System,
+    Voicevox,
+    Http,
+}
+
+/// Text-to-speech settings.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TtsConfig {
+    pub engine: TtsEngineKind,
+    /// Playback volume of spoken clips.
+    pub volume: f32,
+    /// System voice name (e.g. `"Samantha"`); `None` uses the OS default.
+    pub system_voice: Option<String>,
+    pub voicevox: VoicevoxConfig,
+    pub http: HttpTtsConfig,
+    /// Speak streamed chat replies sentence by sentence as they arrive
+    /// (see [`crate::ttsstream`]).
+    pub stream_chat: bool,
+}
+
+impl Default for TtsConfig {
+    fn default() -> Self {
+        Self {
+            engine: TtsEngineKind::System,
+            volume: 1.0,
+            system_voice: None,
+            voicevox: VoicevoxConfig::default(),
+            http: HttpTtsConfig::default(),
+            stream_chat: false,
+        }
+    }
+}
+
+/// A VOICEVOX engine.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VoicevoxConfig {
+    /// Base URL of the engine.
+    pub url: String,
+    /// Speaker (style) id used when no voice or emotion style is given.
+    pub speaker: u32,
+    /// Speaker id per emotion tag, e.g. `{"happy": 1, "angry": 7}`.
+    pub styles: HashMap<String, u32>,
+}
+
+impl Default for VoicevoxConfig {
+    fn default() -> Self {
+        Self {
+            url: "http://127.0.0.1:50021".to_string(),
+            speaker: 3,
+            styles: HashMap::new(),
+        }
+    }
+}
+
+/// An ElevenLabs-compatible HTTP engine.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HttpTtsConfig {
+    /// Base URL; requests go to `{url}/v1/text-to-speech/{voice}`.
+    pub url: String,
+    /// Sent as `xi-api-key`; empty sends none.
+    pub api_key: String,
+    /// Voice used when [`speak`] is not given one.
+    pub voice_id: String,
+    pub model_id: String,
+}
+
+impl Default for HttpTtsConfig {
+    fn default() -> Self {
+        Self {
+            url: "https://api.elevenlabs.io".to_string(),
+            api_key: String::new(),
+            voice_id: String::new(),
+            model_id: "eleven_multilingual_v2".to_string(),
+        }
+    }
+}
+
+/// A speech synthesizer.
+pub trait TtsEngine {
+    /// Synthesize `text` into an encoded clip (WAV, MP3, …) that the voice
+    /// player can decode. `voice` overrides the engine's configured voice.
+    fn synthesize(
+        &self,
+        text: &str,
+        voice: Option<&str>,
+        emotion: Option<&str>,
+    ) -> impl Future<Output = Result<Vec<u8>, String>> + Send;
+}
+
+/// The operating system's own voices.
+pub struct SystemTts<'a> {
+    pub voice: Option<&'a str>,
+}
+
+impl SystemTts<'_> {
+    /// The platform's synthesizer, reading text from stdin and writing a
+    /// WAV file to `out`.
+    fn command(
+        voice: Option<&str>,
+        out: &std::path::Path,
+    ) -> Result<tokio::process::Command, String> {
+        #[cfg(target_os = "macos")]
+        {
+            let mut cmd = tokio::process::Command::new("say");
+            if let Some(voice) = voice {
+                cmd.arg("-v").arg(voice);
+            }
+            cmd.arg("--data-format=LEI16@22050")
+                .arg("-o")
+                .arg(out)
+                .args(["-f", "-"]);
+            Ok(cmd)
+        }
+        #[cfg(target_os = "windows")]
+        {
+            // Voice and path go through the environment so nothing needs
+            // quoting inside the script.
+            const SCRIPT: &str = "Add-Type -AssemblyName System.Speech; \
+                $s = New-Object System.Speech.Synthesis.SpeechSynthesizer; \
+                if ($env:TTS_VOICE) { $s.SelectVoice($env:TTS_VOICE) }; \
+                $s.SetOutputToWaveFile($env:TTS_OUT); \
+                $s.Speak([Console]::In.ReadToEnd()); $s.Dispose()";
+            let mut cmd = tokio::process::Command::new("powershell");
+            cmd.args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+                .env("TTS_VOICE", voice.unwrap_or_default())
+                .env("TTS_OUT", out);
+            Ok(cmd)
+        }
+        #[cfg(target_os = "linux")]
+        {
+            let mut cmd = tokio::process::Command::new("espeak-ng");
+            if let Some(voice) = voice {
+                cmd.arg("-v").arg(voice);
+            }
+            cmd.arg("-w").arg(out).arg("--stdin");
+            Ok(cmd)
+        }
+        #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+        {
+            let _ = (voice, out);
+            Err("System text-to-speech is not supported on this platform".to_string())
+        }
+    }
+}
+
+impl TtsEngine for SystemTts<'_> {
+    async fn synthesize(
+        &self,
+        text: &str,
+        voice: Option<&str>,
+        _emotion: Option<&str>,
+    ) -> Result<Vec<u8>, String> {
+        let out = std::env::temp_dir().join(format!(
+            "openmaiwaifu-tts-{}-{}.wav",
+            std::process::id(),
+            CLIP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let mut child = Self::command(voice.or(self.voice), &out)?
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to start system TTS: {e}"))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(text.as_bytes())
+                .await
+                .map_err(|e| format!("Failed to send text to system TTS: {e}"))?;
+        }
+        let output = child
+            .wait_with_output()
+            .await
+            .map_err(|e| format!("System TTS failed: {e}"))?;
+        let clip = std::fs::read(&out);
+        let _ = std::fs::remove_file(&out);
+        if !output.status.success() {
+            return Err(format!(
+                "System TTS failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        clip.map_err(|e| format!("System TTS produced no audio: {e}"))
+    }
+}
+
+/// A VOICEVOX engine over its local HTTP API.
+pub struct Voicevox<'a> {
+    pub client: &'a reqwest::Client,
+    pub config: &'a VoicevoxConfig,
+}
+
+impl TtsEngine for Voicevox<'_> {
+    /// An explicit `voice` (a speaker id) wins over the emotion's style,
+    /// which wins over [`VoicevoxConfig::speaker`].
+    async fn synthesize(
+        &self,
+        text: &str,
+        voice: Option<&str>,
+        emotion: Option<&str>,
+    ) -> Result<Vec<u8>, String> {
+        let speaker = match voice {
+            Some(v) => v
+                .parse::<u32>()
+                .map_err(|_| format!("VOICEVOX speaker must be a number, got {v:?}"))?,
+            None => emotion
+                .and_then(|e| self.config.styles.get(&e.to_lowercase()))
+                .copied()
+                .unwrap_or(self.config.speaker),
+        };
+        let url = self.config.url.trim_end_matches('/');
+        let speaker = speaker.to_string();
+
+        let query: serde_json::Value = self
+            .client
+            .post(format!("{url}/audio_query"))
+            .query(&[("text", text), ("speaker", &speaker)])
+            .timeout(HTTP_TIMEOUT)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| format!("VOICEVOX audio_query failed: {e}"))?
+            .json()
+            .await
+            .map_err(|e| format!("VOICEVOX audio_query failed: {e}"))?;
+        let clip = self
+            .client
+            .post(format!("{url}/synthesis"))
+            .query(&[("speaker", &speaker)])
+            .json(&query)
+            .timeout(HTTP_TIMEOUT)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| format!("VOICEVOX synthesis failed: {e}"))?
+            .bytes()
+            .await
+            .map_err(|e| format!("VOICEVOX synthesis failed: {e}"))?;
+        Ok(clip.to_vec())
+    }
+}
+
+/// An ElevenLabs-compatible text-to-speech API.
+pub struct HttpTts<'a> {
+    pub client: &'a reqwest::Client,
+    pub config: &'a HttpTtsConfig,
+}
+
+#[derive(Serialize)]
+struct HttpTtsRequest<'a> {
+    text: &'a str,
+    model_id: &'a str,
+    voice_settings: VoiceSettings,
+}
+
+#[derive(Serialize)]
+struct VoiceSettings {
+    stability: f32,
+    similarity_boost: f32,
+    style: f32,
+}
+
+impl TtsEngine for HttpTts<'_> {
+    async fn synthesize(
+        &self,
+        text: &str,
+        voice: Option<&str>,
+        emotion: Option<&str>,
+    ) -> Result<Vec<u8>, String> {
+        let voice = voice.unwrap_or(&self.config.voice_id);
+        if voice.is_empty() {
+            return Err("No voice id configured for the HTTP TTS engine".to_string());
+        }
+        // Any emotion other than neutral gets a looser, more styled read.
+        let expressive = emotion.is_some_and(|e| !e.eq_ignore_ascii_case("neutral"));
+        let body = HttpTtsRequest {
+            text,
+            model_id: &self.config.model_id,
+            voice_settings: VoiceSettings {
+                stability: if expressive { 0.35 } else { 0.5 },
+                similarity_boost: 0.75,
+                style: if expressive { 0.5 } else { 0.0 },
+            },
+        };
+        let mut request = self
+            .client
+            .post(format!(
+                "{}/v1/text-to-speech/{voice}",
+                self.config.url.trim_end_matches('/')
+            ))
+            .header("Accept", "audio/mpeg")
+            .json(&body)
+            .timeout(HTTP_TIMEOUT);
+        if !self.config.api_key.is_empty() {
+            request = request.header("xi-api-key", &self.config.api_key);
+        }
+        let clip = request
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| format!("TTS request failed: {e}"))?
+            .bytes()
+            .await
+            .map_err(|e| format!("TTS request failed: {e}"))?;
+        Ok(clip.to_vec())
+    }
+}
+
+/// Synthesize with the configured engine.
+async fn synthesize(
+    config: &TtsConfig,
+    client: &reqwest::Client,
+    text: &str,
+    voice: Option<&str>,
+    emotion: Option<&str>,
+) -> Result<Vec<u8>, String> {
+    match config.engine {
+        TtsEngineKind::System => {
+            SystemTts {
+                voice: config.system_voice.as_deref(),
+            }
+            .synthesize(text, voice, emotion)
+            .await
+        }
+        TtsEngineKind::Voicevox => {
+            Voicevox {
+                client,
+                config: &config.voicevox,
+            }
+            .synthesize(text, voice, emotion)
+            .await
+        }
+        TtsEngineKind::Http => {
+            HttpTts {
+                client,
+                config: &config.http,
+            }
+            .synthesize(text, voice, emotion)
+            .await
+        }
+    }
+}
+
+/// Synthesize `text` and queue it on the voice player.
+pub(crate) async fn say(
+    app: &AppHandle,
+    text: &str,
+    voice: Option<&str>,
+    emotion: Option<&str>,
+) -> Result<(), String> {
+    let config_state = app.state::<ConfigState>();
+    if text.trim().is_empty() || crate::quiet::is_quiet(&config_state) {
+        return Ok(());
+    }
+    let settings = config_state.get_settings()?;
+    let client = app.state::<HttpClient>().client(&config_state.get()?)?;
+    let clip = synthesize(&settings.tts, &client, text, voice, emotion).await?;
+    audio::play_clip(
+        app.clone(),
+        clip,
+        settings.tts.volume,
+        PlaybackKind::Voice,
+        settings.audio.output_device.clone(),
+        settings.audio.system_duck,
+    )
+    .await
+}
+
+/// Speak `text` in the character's voice.
+///
+/// Returns once the clip is queued; playback then emits `"viseme"` events
+/// like [`audio::play_voice`]. `voice` overrides the configured voice for
+/// this line (a voice name, VOICEVOX speaker id or HTTP voice id).
+///
+/// # Errors
+///
+/// Returns `Err` if the engine fails or returns audio that cannot be
+/// decoded; the failure is also reported through [`user_facing_error`].
+#[tauri::command]
+pub async fn speak(
+    app: AppHandle,
+    text: String,
+    voice: Option<String>,
+    emotion: Option<String>,
+) -> Result<(), String> {
+    let result = say(&app, &text, voice.as_deref(), emotion.as_deref()).await;
+    if let Err(e) = &result {
+        eprintln!("[tts] {e}");
+        user_facing_error(&app, "tts", e.clone(), Severity::Warning);
+    }
+    result
+}
@@ -0,0 +1,142 @@
+//! Speaking streamed chat replies while they are generated.
+//!
+//! Waiting for the whole reply before synthesizing it adds the model's full
+//! generation time to every spoken answer. With
+//! [`crate::tts::TtsConfig::stream_chat`] set,
+//! [`crate::openclaw::send_chat_stream`] feeds its chunks to a
+//! [`SpeechStream`], which cuts them into sentences ([`sentence_end`]) and
+//! speaks each one as soon as it is complete. Synthesis runs one sentence
+//! at a time in reply order, and each clip queues on the voice player, so
+//! the next sentence is synthesized while the previous one plays.
+//!
+//! Emotion tags ([`crate::tags`]) are stripped from the spoken text; the
+//! last one seen sets the delivery of the sentences that follow. When the
+//! chat is cancelled or fails, or a sentence cannot be synthesized, what
+//! was not yet synthesized is dropped.
+
+use crate::user_error::{user_facing_error, Severity};
+use tauri::AppHandle;
+use tokio::sync::mpsc;
+
+/// Sentences shorter than this are joined with the next one, so the voice
+/// does not stop after every "Oh!".
+const MIN_SENTENCE_CHARS: usize = 12;
+
+/// Without a sentence end, text is cut at a comma or space after this many
+/// characters.
+const MAX_SENTENCE_CHARS: usize = 200;
+
+/// Sentence-ending marks that need whitespace after them (so `3.14` and
+/// `e.g.x` stay whole).
+const SPACED_ENDS: &[char] = &['.', '!', '?', '…'];
+
+/// Marks that end a sentence on their own (CJK punctuation, line breaks).
+const HARD_ENDS: &[char] = &['。', '！', '？', '\n'];
+
+/// Byte offset just past the first sentence of `text` that is at least
+/// [`MIN_SENTENCE_CHARS`] long, or `None` if more text is needed.
+/// Brackets are never split, so a tag cut across chunks stays whole.
+pub fn sentence_end(text: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut chars = 0;
+    let mut fallback = None;
+    let mut iter = text.char_indices().peekable();
+    while let Some((i, c)) = iter.next() {
+        chars += 1;
+        match c {
+            '[' => depth += 1,
+            ']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        if depth > 0 {
+            continue;
+        }
+        let end = i + c.len_utf8();
+        let is_end = HARD_ENDS.contains(&c)
+            || (SPACED_ENDS.contains(&c) && iter.peek().is_some_and(|(_, n)| n.is_whitespace()));
+        if is_end && chars >= MIN_SENTENCE_CHARS {
+            return Some(end);
+        }
+        if c == ',' || c == '、' || c.is_whitespace() {
+            fallback = Some(end);
+        }
+        if chars >= MAX_SENTENCE_CHARS {
+            if let Some(end) = fallback {
+                return Some(end);
+            }
+        }
+    }
+    None
+}
+
+/// A reply being spoken as it streams in. Dropping it without
+/// [`finish`](Self::finish) discards the unspoken rest.
+pub(crate) struct SpeechStream {
+    sender: mpsc::UnboundedSender<Option<String>>,
+}
+
+impl SpeechStream {
+    /// Start the speaking task.
+    pub fn start(app: &AppHandle) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tauri::async_runtime::spawn(speak_sentences(app.clone(), receiver));
+        Self { sender }
+    }
+
+    /// Add a chunk of the reply.
+    pub fn push(&self, delta: &str) {
+        let _ = self.sender.send(Some(delta.to_string()));
+    }
+
+    /// The reply is complete: speak what is left.
+    pub fn finish(self) {
+        let _ = self.sender.send(None);
+    }
+}
+
+/// Receive chunks (`None` when the reply is complete) and speak them
+/// sentence by sentence.
+async fn speak_sentences(app: AppHandle, mut receiver: mpsc::UnboundedReceiver<Option<String>>) {
+    let mut buffer = String::new();
+    let mut emotion = None;
+    loop {
+        match receiver.recv().await {
+            Some(Some(delta)) => {
+                buffer.push_str(&delta);
+                while let Some(end) = sentence_end(&buffer) {
+                    let sentence: String = buffer.drain(..end).collect();
+                    if let Err(e) = speak(&app, &sentence, &mut emotion).await {
+                        report(&app, e);
+                        return;
+                    }
+                }
+            }
+            Some(None) => {
+                if let Err(e) = speak(&app, &buffer, &mut emotion).await {
+                    report(&app, e);
+                }
+                return;
+            }
+            // Cancelled or failed.
+            None => return,
+        }
+    }
+}
+
+async fn speak(
+    app: &AppHandle,
+    sentence: &str,
+    emotion: &mut Option<String>,
+) -> Result<(), String> {
+    let parsed = crate::tags::parse(sentence);
+    if let Some(last) = parsed.emotions.last() {
+        *emotion = Some(last.clone());
+    }
+    crate::tts::say(app, &parsed.text, None, emotion.as_deref()).await
+}
+
+/// Give up on the rest of the reply after a failed sentence.
+fn report(app: &AppHandle, error: String) {
+    eprintln!("[ttsstream] {error}");
+    user_facing_error(app, "tts", error, Severity::Warning);
+}
@@ -0,0 +1,299 @@
+//! Request rate limits and cost tracking per chat backend.
+//!
+//! Proactive features (idle chatter, push messages, reactions) send chat
+//! messages without the user typing anything, which with a paid API can run
+//! up a bill unattended. [`UsageState`], kept in managed state, counts every
+//! chat request per backend and agent, estimates its token cost from
+//! [`UsageConfig::prices`] and refuses requests beyond
+//! [`UsageConfig::max_requests_per_minute`] or
+//! [`UsageConfig::max_daily_cost`], emitting `"rate-limited"`.
+//!
+//! Token counts are estimates ([`crate::budget::request_tokens`]) of the
+//! messages as sent to the backend (system prompt, history and images
+//! included) and of the reply; through the OpenClaw CLI, which keeps the
+//! history itself, only the message and context are known. The counters
+//! start over when the app restarts, and the daily cost at local midnight.
+
+use crate::config::{ConfigState, OpenClawConfig};
+use crate::llm::ProviderKind;
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, State};
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Cost of a backend in US dollars per million tokens.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Price {
+    pub input: f64,
+    pub output: f64,
+}
+
+/// Usage limits section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UsageConfig {
+    /// Chat requests allowed per backend in any minute; 0 for no cap.
+    pub max_requests_per_minute: u32,
+    /// Estimated US dollars all backends may cost per day; 0 for no cap.
+    pub max_daily_cost: f64,
+    /// Per-backend prices; backends not listed are free.
+    pub prices: BTreeMap<ProviderKind, Price>,
+}
+
+impl Default for UsageConfig {
+    fn default() -> Self {
+        Self {
+            max_requests_per_minute: 20,
+            max_daily_cost: 0.0,
+            prices: BTreeMap::from([
+                (
+                    ProviderKind::OpenAi,
+                    Price {
+                        input: 0.15,
+                        output: 0.6,
+                    },
+                ),
+                (
+                    ProviderKind::Anthropic,
+                    Price {
+                        input: 3.0,
+                        output: 15.0,
+                    },
+                ),
+            ]),
+        }
+    }
+}
+
+/// Totals of one backend and agent, as returned by [`get_usage_stats`].
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageEntry {
+    pub provider: ProviderKind,
+    pub agent_id: String,
+    pub requests: u64,
+    pub request_tokens: u64,
+    pub response_tokens: u64,
+    /// Estimated US dollars.
+    pub cost: f64,
+}
+
+/// Returned by [`get_usage_stats`].
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageStats {
+    /// Unix time in milliseconds the counters started.
+    pub since: u64,
+    /// Requests per backend in the last minute.
+    pub last_minute: BTreeMap<ProviderKind, usize>,
+    /// Estimated US dollars spent today.
+    pub today_cost: f64,
+    pub entries: Vec<UsageEntry>,
+}
+
+/// Payload of `"rate-limited"`.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct RateLimited {
+    provider: ProviderKind,
+    agent_id: String,
+    /// `"requests"` or `"cost"`.
+    limit: &'static str,
+    /// Seconds until a request would be allowed again.
+    retry_after_secs: u64,
+    message: String,
+}
+
+#[derive(Default)]
+struct Totals {
+    requests: u64,
+    request_tokens: u64,
+    response_tokens: u64,
+    cost: f64,
+}
+
+struct Usage {
+    since: u64,
+    /// Start times of the requests of the last minute.
+    recent: VecDeque<(Instant, ProviderKind)>,
+    totals: BTreeMap<(ProviderKind, String), Totals>,
+    day: NaiveDate,
+    today_cost: f64,
+}
+
+impl Usage {
+    fn prune(&mut self, now: Instant) {
+        while self
+            .recent
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) >= WINDOW)
+        {
+            self.recent.pop_front();
+        }
+        let today = Local::now().date_naive();
+        if today != self.day {
+            self.day = today;
+            self.today_cost = 0.0;
+        }
+    }
+}
+
+/// Request counters, managed by Tauri.
+pub struct UsageState {
+    inner: Mutex<Usage>,
+}
+
+impl UsageState {
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(Usage {
+                since: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_millis() as u64)
+                    .unwrap_or(0),
+                recent: VecDeque::new(),
+                totals: BTreeMap::new(),
+                day: Local::now().date_naive(),
+                today_cost: 0.0,
+            }),
+        }
+    }
+
+    /// Admit a chat request to the configured backend, or emit
+    /// `"rate-limited"` and return `Err` if it is over a cap.
+    pub(crate) fn admit(
+        &self,
+        app: &AppHandle,
+        config_state: &ConfigState,
+        config: &OpenClawConfig,
+    ) -> Result<(), String> {
+        let limits = config_state.get_settings()?.usage;
+        let provider = config.provider.kind;
+        let now = Instant::now();
+        let mut usage = self.inner.lock().map_err(|e| e.to_string())?;
+        usage.prune(now);
+
+        let refused = if limits.max_daily_cost > 0.0 && usage.today_cost >= limits.max_daily_cost {
+            let midnight = Local::now()
+                .date_naive()
+                .succ_opt()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .and_then(|t| t.and_local_timezone(Local).earliest());
+            let retry_after = midnight.map_or(0, |t| (t - Local::now()).num_seconds().max(0));
+            Some((
+                "cost",
+                retry_after as u64,
+                format!(
+                    "Daily cost limit of ${:.2} reached; chat is paused until tomorrow",
+                    limits.max_daily_cost
+                ),
+            ))
+        } else {
+            let max = limits.max_requests_per_minute as usize;
+            let mut recent = usage
+                .recent
+                .iter()
+                .filter(|(_, kind)| *kind == provider)
+                .map(|(at, _)| *at);
+            let oldest = recent.next();
+            let count = oldest.map_or(0, |_| recent.count() + 1);
+            match oldest {
+                Some(oldest) if max > 0 && count >= max => {
+                    let retry_after = WINDOW.saturating_sub(now.duration_since(oldest));
+                    Some((
+                        "requests",
+                        retry_after.as_secs() + 1,
+                        format!("Rate limit of {max} requests per minute reached"),
+                    ))
+                }
+                _ => None,
+            }
+        };
+
+        if let Some((limit, retry_after_secs, message)) = refused {
+            drop(usage);
+            eprintln!("[usage] {message}");
+            let _ = app.emit(
+                "rate-limited",
+                RateLimited {
+                    provider,
+                    agent_id: config.agent_id.clone(),
+                    limit,
+                    retry_after_secs,
+                    message: message.clone(),
+                },
+            );
+            return Err(message);
+        }
+        usage.recent.push_back((now, provider));
+        Ok(())
+    }
+
+    /// Count a finished request of about `request_tokens` and its
+    /// estimated cost.
+    pub(crate) fn record(
+        &self,
+        config_state: &ConfigState,
+        config: &OpenClawConfig,
+        request_tokens: usize,
+        response: &str,
+    ) {
+        let provider = config.provider.kind;
+        let price = config_state
+            .get_settings()
+            .ok()
+            .and_then(|s| s.usage.prices.get(&provider).copied())
+            .unwrap_or_default();
+        let request_tokens = request_tokens as u64;
+        let response_tokens = crate::budget::estimate_tokens(response) as u64;
+        let cost = (request_tokens as f64 * price.input + response_tokens as f64 * price.output)
+            / 1_000_000.0;
+        let Ok(mut usage) = self.inner.lock() else {
+            return;
+        };
+        usage.prune(Instant::now());
+        usage.today_cost += cost;
+        let totals = usage
+            .totals
+            .entry((provider, config.agent_id.clone()))
+            .or_default();
+        totals.requests += 1;
+        totals.request_tokens += request_tokens;
+        totals.response_tokens += response_tokens;
+        totals.cost += cost;
+    }
+}
+
+/// IPC command: requests, tokens and estimated cost per backend and agent
+/// since the app started.
+#[tauri::command]
+pub fn get_usage_stats(usage: State<'_, UsageState>) -> Result<UsageStats, String> {
+    let mut usage = usage.inner.lock().map_err(|e| e.to_string())?;
+    usage.prune(Instant::now());
+    let mut last_minute = BTreeMap::new();
+    for (_, kind) in &usage.recent {
+        *last_minute.entry(*kind).or_insert(0) += 1;
+    }
+    Ok(UsageStats {
+        since: usage.since,
+        last_minute,
+        today_cost: usage.today_cost,
+        entries: usage
+            .totals
+            .iter()
+            .map(|((provider, agent_id), totals)| UsageEntry {
+                provider: *provider,
+                agent_id: agent_id.clone(),
+                requests: totals.requests,
+                request_tokens: totals.request_tokens,
+                response_tokens: totals.response_tokens,
+                cost: totals.cost,
+            })
+            .collect(),
+    })
+}
@@ -0,0 +1,106 @@
+//! Structured, user-facing error reports.
+//!
+//! Subsystems report problems the user should know about through
+//! [`user_facing_error`] instead of only logging them. Each report is logged
+//! to stderr and emitted as a `"user-facing-error"` event so the character
+//! can explain it in its own words ("I can't reach the gateway").
+//!
+//! Identical reports from the same source are suppressed for
+//! [`REPEAT_COOLDOWN`] so a failing background loop does not make the
+//! character repeat itself.
+//!
+//! While the main window is hidden nobody would hear the character, so
+//! warnings and errors are also sent as a desktop notification.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+/// Minimum time between two identical reports.
+const REPEAT_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// How serious a reported problem is.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Degraded but working (e.g. a fallback is in use).
+    Info,
+    /// A feature is unavailable.
+    Warning,
+    /// A user action failed.
+    Error,
+}
+
+/// Payload of `"user-facing-error"` events.
+#[derive(Serialize, Clone, Debug)]
+pub struct UserFacingError {
+    /// Reporting subsystem, e.g. `"openclaw"`, `"audio"`.
+    pub source: String,
+    pub message: String,
+    pub severity: Severity,
+    /// Unix time in milliseconds.
+    pub timestamp: u64,
+}
+
+/// When each `(source, message)` pair was last emitted.
+static LAST_REPORTED: Mutex<Option<HashMap<(String, String), Instant>>> = Mutex::new(None);
+
+/// Report a problem to the user.
+///
+/// Returns `true` if the report was emitted, `false` if it was suppressed as
+/// a repeat.
+pub fn user_facing_error(
+    app: &AppHandle,
+    source: &str,
+    message: impl Into<String>,
+    severity: Severity,
+) -> bool {
+    let message = message.into();
+    eprintln!("[{}] {:?}: {}", source, severity, message);
+
+    if let Ok(mut last) = LAST_REPORTED.lock() {
+        let last = last.get_or_insert_with(HashMap::new);
+        let key = (source.to_string(), message.clone());
+        if last
+            .get(&key)
+            .is_some_and(|t| t.elapsed() < REPEAT_COOLDOWN)
+        {
+            return false;
+        }
+        last.insert(key, Instant::now());
+    }
+
+    let window_visible = app
+        .get_webview_window("main")
+        .is_some_and(|w| w.is_visible().unwrap_or(true));
+    if !window_visible && severity != Severity::Info {
+        desktop_notification(app, &message);
+    }
+
+    let report = UserFacingError {
+        source: source.to_string(),
+        message,
+        severity,
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+    };
+    app.emit("user-facing-error", report).is_ok()
+}
+
+/// Show `message` as an OS notification, best effort.
+fn desktop_notification(app: &AppHandle, message: &str) {
+    if let Err(e) = app
+        .notification()
+        .builder()
+        .title("OpenMaiWaifu")
+        .body(message)
+        .show()
+    {
+        eprintln!("[user_error] Failed to show notification: {e}");
+    }
+}
@@ -0,0 +1,181 @@
+//! Voice activity detection on the monitored microphone.
+//!
+//! [`VoiceDetector`] is fed the same interleaved samples as the level meter
+//! and spectrum in [`crate::audio`]. It mixes them to mono and looks at
+//! [`FRAME_MS`] frames:
+//!
+//! - **Energy** must be [`MIN_SNR_DB`] above an adaptive noise floor.
+//! - **Zero-crossing rate** must fall in [`ZCR_RANGE_HZ`], which rejects
+//!   mains hum below and fan hiss above.
+//! - **Modulation**: speech rises and falls with every syllable, so the
+//!   frame energy over the last second must span [`MIN_MODULATION_DB`].
+//!   Music, even through a microphone, stays loud and fails this check —
+//!   which is what keeps Spotify from sounding like the user.
+//!
+//! Speech starts after [`ONSET_FRAMES`] consecutive voiced frames and stops
+//! after [`HANGOVER_FRAMES`] without one. This is a heuristic, not a trained
+//! model; it is meant to turn the character toward the user, not to
+//! transcribe.
+
+use std::collections::VecDeque;
+
+/// Analysis frame length.
+const FRAME_MS: u32 = 20;
+
+/// Energy a frame must have above the noise floor to count as voiced.
+const MIN_SNR_DB: f32 = 12.0;
+
+/// Frames quieter than this are never voiced, whatever the floor says.
+const MIN_ENERGY_DB: f32 = -55.0;
+
+/// Plausible zero crossings per second for voiced speech.
+const ZCR_RANGE_HZ: std::ops::RangeInclusive<f32> = 100.0..=3500.0;
+
+/// Spread between the loudest and quietest frame of the last second.
+const MIN_MODULATION_DB: f32 = 15.0;
+
+/// Frames of history for the modulation check (1 s).
+const HISTORY_FRAMES: usize = 50;
+
+/// Consecutive voiced frames before speech starts (100 ms).
+const ONSET_FRAMES: u32 = 5;
+
+/// Unvoiced frames before speech stops (500 ms).
+const HANGOVER_FRAMES: u32 = 25;
+
+/// A change in whether the user is speaking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoiceTransition {
+    Started,
+    /// `duration_ms` excludes the trailing hangover.
+    Stopped {
+        duration_ms: u64,
+    },
+}
+
+pub struct VoiceDetector {
+    channels: usize,
+    /// Mono samples per analysis frame.
+    frame_samples: usize,
+    sample_rate: f32,
+    /// Down-mix of the current (partial) multi-channel sample.
+    mix_sum: f32,
+    mix_len: usize,
+    /// Accumulators of the current analysis frame.
+    energy: f32,
+    crossings: usize,
+    samples: usize,
+    last_positive: bool,
+    noise_floor_db: f32,
+    history: VecDeque<f32>,
+    voiced_run: u32,
+    unvoiced_run: u32,
+    speaking: bool,
+    speech_frames: u32,
+}
+
+impl VoiceDetector {
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        Self {
+            channels: channels.max(1) as usize,
+            frame_samples: (sample_rate * FRAME_MS / 1000).max(1) as usize,
+            sample_rate: sample_rate as f32,
+            mix_sum: 0.0,
+            mix_len: 0,
+            energy: 0.0,
+            crossings: 0,
+            samples: 0,
+            last_positive: false,
+            noise_floor_db: MIN_ENERGY_DB,
+            history: VecDeque::with_capacity(HISTORY_FRAMES),
+            voiced_run: 0,
+            unvoiced_run: 0,
+            speaking: false,
+            speech_frames: 0,
+        }
+    }
+
+    /// Whether speech is currently detected.
+    pub fn is_speaking(&self) -> bool {
+        self.speaking
+    }
+
+    /// Feed one interleaved sample. Returns a transition when speech starts
+    /// or stops.
+    pub fn push(&mut self, sample: f32) -> Option<VoiceTransition> {
+        self.mix_sum += sample;
+        self.mix_len += 1;
+        if self.mix_len < self.channels {
+            return None;
+        }
+        let mono = self.mix_sum / self.channels as f32;
+        self.mix_sum = 0.0;
+        self.mix_len = 0;
+
+        let positive = mono >= 0.0;
+        if self.samples > 0 && positive != self.last_positive {
+            self.crossings += 1;
+        }
+        self.last_positive = positive;
+        self.energy += mono * mono;
+        self.samples += 1;
+        if self.samples < self.frame_samples {
+            return None;
+        }
+
+        let db = 10.0 * (self.energy / self.samples as f32 + 1e-10).log10();
+        let zcr_hz = self.crossings as f32 * self.sample_rate / self.samples as f32;
+        self.energy = 0.0;
+        self.crossings = 0;
+        self.samples = 0;
+        self.frame(db, zcr_hz)
+    }
+
+    fn frame(&mut self, db: f32, zcr_hz: f32) -> Option<VoiceTransition> {
+        if self.history.len() == HISTORY_FRAMES {
+            self.history.pop_front();
+        }
+        self.history.push_back(db);
+
+        // The floor drops immediately and creeps up slowly, and not at all
+        // while the user talks.
+        if db < self.noise_floor_db {
+            self.noise_floor_db = db;
+        } else if !self.speaking {
+            self.noise_floor_db += (db - self.noise_floor_db) * 0.005;
+        }
+
+        let (min, max) = self
+            .history
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+        let modulated = max - min >= MIN_MODULATION_DB;
+        let voiced = modulated
+            && db >= MIN_ENERGY_DB
+            && db - self.noise_floor_db >= MIN_SNR_DB
+            && ZCR_RANGE_HZ.contains(&zcr_hz);
+
+        if !self.speaking {
+            self.voiced_run = if voiced { self.voiced_run + 1 } else { 0 };
+            if self.voiced_run >= ONSET_FRAMES {
+                self.speaking = true;
+                self.speech_frames = self.voiced_run;
+                self.unvoiced_run = 0;
+                return Some(VoiceTransition::Started);
+            }
+            return None;
+        }
+
+        self.speech_frames += 1;
+        self.unvoiced_run = if voiced { 0 } else { self.unvoiced_run + 1 };
+        if self.unvoiced_run < HANGOVER_FRAMES {
+            return None;
+        }
+        self.speaking = false;
+        self.voiced_run = 0;
+        let frames = self.speech_frames - self.unvoiced_run;
+        Some(VoiceTransition::Stopped {
+            duration_ms: frames as u64 * FRAME_MS as u64,
+        })
+    }
+}
@@ -0,0 +1,267 @@
+//! Checks on [`OpenClawConfig`] and [`AppSettings`] values.
+//!
+//! The settings commands ([`crate::config::save_openclaw_config`],
+//! [`crate::config::save_app_settings`]) refuse values with problems and
+//! return them all at once as [`ConfigError::Invalid`], each naming the
+//! field (as a camelCase path such as `"provider.ollama.url"`) so the
+//! settings UI can mark it. Problems in the files found at startup are
+//! logged and available from [`get_config_issues`]; the app still starts
+//! with them.
+
+use crate::config::{AppSettings, ConfigState, OpenClawConfig};
+use serde::Serialize;
+use std::net::IpAddr;
+use std::path::Path;
+use tauri::State;
+
+/// Lowest port the local servers may use; lower ones need privileges.
+const MIN_PORT: u16 = 1024;
+
+/// One problem with a config value.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigIssue {
+    /// Path of the field, e.g. `"gatewayUrl"` or `"events.endpoints.0.url"`.
+    pub field: String,
+    /// `"required"`, `"invalidUrl"`, `"invalidFormat"`, `"outOfRange"`,
+    /// `"conflict"` or `"notFound"`.
+    pub code: &'static str,
+    pub message: String,
+}
+
+/// Error of the settings commands, serialized with a `kind` tag.
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ConfigError {
+    /// The value was refused; nothing was saved.
+    Invalid { issues: Vec<ConfigIssue> },
+    /// Reading or writing the config failed.
+    Other { message: String },
+}
+
+impl From<String> for ConfigError {
+    fn from(message: String) -> Self {
+        Self::Other { message }
+    }
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Invalid { issues } => {
+                let messages: Vec<&str> = issues.iter().map(|i| i.message.as_str()).collect();
+                write!(f, "{}", messages.join("; "))
+            }
+            Self::Other { message } => write!(f, "{message}"),
+        }
+    }
+}
+
+/// `Ok` if `issues` is empty, else [`ConfigError::Invalid`].
+pub(crate) fn check(issues: Vec<ConfigIssue>) -> Result<(), ConfigError> {
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(ConfigError::Invalid { issues })
+    }
+}
+
+fn issue(field: impl Into<String>, code: &'static str, message: impl Into<String>) -> ConfigIssue {
+    ConfigIssue {
+        field: field.into(),
+        code,
+        message: message.into(),
+    }
+}
+
+/// Check that `url` parses and uses one of `schemes`.
+fn check_url(issues: &mut Vec<ConfigIssue>, field: &str, label: &str, url: &str, schemes: &[&str]) {
+    match reqwest::Url::parse(url.trim()) {
+        Ok(parsed) if schemes.contains(&parsed.scheme()) => {}
+        Ok(parsed) => issues.push(issue(
+            field,
+            "invalidUrl",
+            format!(
+                "{label} must start with {}:// (not {}://)",
+                schemes.join("://, "),
+                parsed.scheme()
+            ),
+        )),
+        Err(e) => issues.push(issue(
+            field,
+            "invalidUrl",
+            format!("{label} is not a valid URL: {e}"),
+        )),
+    }
+}
+
+/// Problems with `config`.
+pub fn validate_config(config: &OpenClawConfig) -> Vec<ConfigIssue> {
+    let mut issues = Vec::new();
+    let http = ["http", "https"];
+
+    if config.gateway_url.trim().is_empty() {
+        issues.push(issue("gatewayUrl", "required", "Gateway URL is required"));
+    } else {
+        check_url(
+            &mut issues,
+            "gatewayUrl",
+            "Gateway URL",
+            &config.gateway_url,
+            &http,
+        );
+    }
+    if !config.hooks_token.is_empty() && config.agent_id.trim().is_empty() {
+        issues.push(issue(
+            "agentId",
+            "required",
+            "Agent ID is required once hooks are set up",
+        ));
+    }
+    // A bare name is looked up on PATH when the CLI runs; only check paths.
+    let cli = config.cli_path.trim();
+    if cli.contains(['/', '\\']) && !Path::new(cli).is_file() {
+        issues.push(issue(
+            "cliPath",
+            "notFound",
+            format!("OpenClaw CLI not found at {cli}"),
+        ));
+    }
+    let e2e_keys = std::iter::once(("e2eKey".to_string(), &config.e2e_key)).chain(
+        config
+            .agents
+            .iter()
+            .enumerate()
+            .map(|(i, profile)| (format!("agents.{i}.e2eKey"), &profile.e2e_key)),
+    );
+    for (field, key) in e2e_keys {
+        if !key.is_empty() && (key.len() != 64 || !key.chars().all(|c| c.is_ascii_hexdigit())) {
+            issues.push(issue(
+                field,
+                "invalidFormat",
+                "Encryption key must be 64 hex characters",
+            ));
+        }
+    }
+    if !config.proxy.url.trim().is_empty() {
+        check_url(
+            &mut issues,
+            "proxy.url",
+            "Proxy URL",
+            &config.proxy.url,
+            &["http", "https", "socks5", "socks5h"],
+        );
+    }
+    let provider = &config.provider;
+    for (field, label, url) in [
+        (
+            "provider.openai.baseUrl",
+            "OpenAI base URL",
+            &provider.openai.base_url,
+        ),
+        (
+            "provider.anthropic.baseUrl",
+            "Anthropic base URL",
+            &provider.anthropic.base_url,
+        ),
+        ("provider.ollama.url", "Ollama URL", &provider.ollama.url),
+    ] {
+        if !url.trim().is_empty() {
+            check_url(&mut issues, field, label, url, &http);
+        }
+    }
+    issues
+}
+
+/// Problems with `settings`.
+pub fn validate_settings(settings: &AppSettings) -> Vec<ConfigIssue> {
+    let mut issues = Vec::new();
+
+    let servers = [
+        (
+            "localApi.port",
+            "Local API",
+            settings.local_api.enabled,
+            settings.local_api.port,
+        ),
+        (
+            "push.port",
+            "Push server",
+            settings.push.enabled,
+            settings.push.port,
+        ),
+        (
+            "openaiApi.port",
+            "OpenAI-compatible API",
+            settings.openai_api.enabled,
+            settings.openai_api.port,
+        ),
+    ];
+    for (i, &(field, label, enabled, port)) in servers.iter().enumerate() {
+        if port < MIN_PORT {
+            issues.push(issue(
+                field,
+                "outOfRange",
+                format!("{label} port must be between {MIN_PORT} and 65535"),
+            ));
+        } else if let Some((_, other, _, _)) =
+            servers[..i]
+                .iter()
+                .find(|(_, _, other_enabled, other_port)| {
+                    enabled && *other_enabled && *other_port == port
+                })
+        {
+            issues.push(issue(
+                field,
+                "conflict",
+                format!("{label} and {other} cannot both use port {port}"),
+            ));
+        }
+    }
+    let bind = settings.push.bind_address.trim();
+    if bind != "localhost" && bind.parse::<IpAddr>().is_err() {
+        issues.push(issue(
+            "push.bindAddress",
+            "invalidFormat",
+            "Push server bind address must be an IP address or localhost",
+        ));
+    }
+    for (i, schedule) in settings.quiet_hours.schedules.iter().enumerate() {
+        if schedule.start > 23 || schedule.end > 23 || schedule.days.iter().any(|&d| d > 6) {
+            issues.push(issue(
+                format!("quietHours.schedules.{i}"),
+                "outOfRange",
+                "Quiet hours must use hours 0-23 and days 0-6",
+            ));
+        }
+    }
+    for (i, endpoint) in settings.events.endpoints.iter().enumerate() {
+        check_url(
+            &mut issues,
+            &format!("events.endpoints.{i}.url"),
+            "Event webhook URL",
+            &endpoint.url,
+            &["http", "https"],
+        );
+    }
+    issues
+}
+
+/// Log the problems of the loaded config.
+pub(crate) fn report(config: &OpenClawConfig, settings: &AppSettings) {
+    for issue in validate_config(config)
+        .into_iter()
+        .chain(validate_settings(settings))
+    {
+        eprintln!("[config] {}: {}", issue.field, issue.message);
+    }
+}
+
+/// IPC command: problems with the current config and settings, e.g. from
+/// editing the files by hand.
+#[tauri::command]
+pub fn get_config_issues(state: State<'_, ConfigState>) -> Result<Vec<ConfigIssue>, String> {
+    let mut issues = validate_config(&state.get()?);
+    issues.extend(validate_settings(&state.get_settings()?));
+    Ok(issues)
+}
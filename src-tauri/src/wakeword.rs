@@ -0,0 +1,223 @@
+//! Opt-in wake-word activation ("hey Claire").
+//!
+//! Rather than a dedicated keyword model, this piggybacks on pieces that
+//! already run: the voice detector in [`crate::audio`] finds short
+//! utterances on the microphone, and only those ([`MAX_UTTERANCE`] or
+//! shorter) are transcribed locally with a small Whisper model
+//! ([`crate::stt`]) and compared with [`WakeWordConfig::phrases`]. Silence
+//! and music cost nothing beyond the level meter, and no audio leaves the
+//! machine.
+//!
+//! On a match the main window is shown and `"wake-word-detected"` is
+//! emitted so the frontend can open chat or start dictation. Nothing runs
+//! unless [`WakeWordConfig::enabled`] is set, and nothing is recognized in
+//! builds without the `stt` cargo feature.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config::ConfigState;
+use crate::stt::{self, Resampler, WHISPER_RATE};
+use crate::vad::VoiceTransition;
+
+/// Longer utterances are conversation, not a wake word.
+const MAX_UTTERANCE: Duration = Duration::from_millis(2500);
+
+/// Audio kept from before speech was detected, so the first syllable is
+/// not lost to the detector's onset delay.
+const PRE_ROLL: Duration = Duration::from_millis(400);
+
+/// Whether listening is enabled, refreshed by the audio monitoring thread.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Queue of the transcription worker, started on first use.
+static WORKER: OnceLock<SyncSender<Vec<f32>>> = OnceLock::new();
+
+/// Wake-word settings.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WakeWordConfig {
+    /// Listen for the wake word at all.
+    pub enabled: bool,
+    /// Phrases that wake the character, matched case- and
+    /// punctuation-insensitively.
+    pub phrases: Vec<String>,
+    /// ggml model file; `None` uses `<data dir>/models/ggml-tiny.bin`, or
+    /// the dictation model if that is missing.
+    pub model_path: Option<String>,
+}
+
+impl Default for WakeWordConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            phrases: vec!["hey claire".to_string()],
+            model_path: None,
+        }
+    }
+}
+
+/// Payload of `"wake-word-detected"` events.
+#[derive(Serialize, Clone, Debug)]
+pub struct WakeWordDetected {
+    pub phrase: String,
+    /// What was heard.
+    pub transcript: String,
+}
+
+/// Enable or disable listening (called with the current config).
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether listening is enabled.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Lower-case words only, so "Hey, Claire!" matches "hey claire".
+fn normalize(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_lowercase().next().unwrap_or(c)
+            } else {
+                ' '
+            }
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The first configured phrase contained in `transcript`.
+fn matching_phrase<'a>(phrases: &'a [String], transcript: &str) -> Option<&'a String> {
+    let heard = format!(" {} ", normalize(transcript));
+    phrases.iter().find(|phrase| {
+        let phrase = normalize(phrase);
+        !phrase.is_empty() && heard.contains(&format!(" {phrase} "))
+    })
+}
+
+/// Collects utterances from the monitoring stream; lives in the cpal
+/// callback next to the voice detector.
+pub struct WakeListener {
+    resampler: Resampler,
+    speaking: bool,
+}
+
+impl WakeListener {
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        Self {
+            resampler: Resampler::new(sample_rate, channels),
+            speaking: false,
+        }
+    }
+
+    /// Feed one interleaved sample.
+    pub fn push(&mut self, sample: f32) {
+        self.resampler.push_sample(sample);
+        let samples = &mut self.resampler.samples;
+        let keep = if self.speaking {
+            MAX_UTTERANCE + PRE_ROLL
+        } else {
+            PRE_ROLL
+        };
+        let keep = (keep.as_secs_f32() * WHISPER_RATE as f32) as usize;
+        // Trim in chunks rather than per sample.
+        if samples.len() > keep * 2 {
+            samples.drain(..samples.len() - keep);
+        }
+    }
+
+    /// Follow the voice detector; a finished short utterance goes to the
+    /// transcription worker.
+    pub fn on_transition(&mut self, app: &AppHandle, transition: VoiceTransition) {
+        match transition {
+            VoiceTransition::Started => self.speaking = true,
+            VoiceTransition::Stopped { duration_ms } => {
+                self.speaking = false;
+                let utterance = std::mem::take(&mut self.resampler.samples);
+                if duration_ms <= MAX_UTTERANCE.as_millis() as u64 && !stt::is_dictating() {
+                    // A full queue means the worker is behind; drop the clip.
+                    let _ = worker(app).try_send(utterance);
+                }
+            }
+        }
+    }
+}
+
+fn model_path(config: &WakeWordConfig, app: &AppHandle) -> PathBuf {
+    if let Some(path) = &config.model_path {
+        return PathBuf::from(path);
+    }
+    let tiny = crate::memory::data_dir()
+        .join("models")
+        .join("ggml-tiny.bin");
+    if tiny.exists() {
+        return tiny;
+    }
+    app.state::<ConfigState>()
+        .get_settings()
+        .map(|s| s.stt.model())
+        .unwrap_or(tiny)
+}
+
+fn worker(app: &AppHandle) -> &'static SyncSender<Vec<f32>> {
+    WORKER.get_or_init(|| {
+        let (tx, rx) = mpsc::sync_channel::<Vec<f32>>(2);
+        let app = app.clone();
+        thread::spawn(move || {
+            let mut reported_missing = false;
+            for utterance in rx {
+                let Ok(settings) = app.state::<ConfigState>().get_settings() else {
+                    continue;
+                };
+                let config = settings.wake_word;
+                if !config.enabled {
+                    continue;
+                }
+                let path = model_path(&config, &app);
+                let ctx = match stt::context(&path) {
+                    Ok(ctx) => ctx,
+                    Err(e) => {
+                        if !reported_missing {
+                            eprintln!("[wakeword] {e}");
+                            reported_missing = true;
+                        }
+                        continue;
+                    }
+                };
+                let transcript = match stt::transcribe(&ctx, &utterance, &settings.stt.language) {
+                    Ok(t) => t,
+                    Err(e) => {
+                        eprintln!("[wakeword] {e}");
+                        continue;
+                    }
+                };
+                if let Some(phrase) = matching_phrase(&config.phrases, &transcript) {
+                    println!("[wakeword] Heard {phrase:?}");
+                    if let Some(w) = app.get_webview_window("main") {
+                        let _ = w.show();
+                        let _ = w.set_focus();
+                    }
+                    let _ = app.emit(
+                        "wake-word-detected",
+                        WakeWordDetected {
+                            phrase: phrase.clone(),
+                            transcript,
+                        },
+                    );
+                }
+            }
+        });
+        tx
+    })
+}
@@ -0,0 +1,403 @@
+//! Walk path planning across the desktop.
+//!
+//! [`plan_walk_path`] turns "walk from here to there" into a short list of
+//! waypoints using the current [`crate::boundaries`] snapshot, so walking
+//! behaviour is planned once in Rust instead of querying geometry from JS
+//! every frame.
+//!
+//! The character stands on *surfaces*: the floor of the work area (just above
+//! the Dock/taskbar) and the visible parts of window tops. It walks along a
+//! surface, drops off either end onto whatever is below, or jumps up to a
+//! higher surface within [`WalkOptions::max_jump_px`] vertically and
+//! [`WalkOptions::max_gap_px`] horizontally. Points are the character's feet
+//! in screen space (top-left origin, as in [`crate::boundaries`]).
+
+use crate::boundaries::{self, Boundaries};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// Default maximum height of a jump, in pixels.
+const DEFAULT_MAX_JUMP_PX: f64 = 160.0;
+
+/// Default maximum horizontal gap a jump can cross, in pixels.
+const DEFAULT_MAX_GAP_PX: f64 = 120.0;
+
+/// Cost per pixel climbed or dropped, relative to one pixel walked, so flat
+/// routes are preferred.
+const VERTICAL_COST: f64 = 2.0;
+
+/// How far above a surface a point may be and still count as standing on it.
+const SNAP_TOLERANCE_PX: f64 = 8.0;
+
+/// Visible window tops narrower than this are not used as surfaces.
+const MIN_SURFACE_WIDTH: f64 = 24.0;
+
+/// A screen-space point (the character's feet).
+#[derive(Deserialize, Serialize, Clone, Copy, Debug)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Tuning for [`plan_walk_path`].
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WalkOptions {
+    pub max_jump_px: f64,
+    pub max_gap_px: f64,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        Self {
+            max_jump_px: DEFAULT_MAX_JUMP_PX,
+            max_gap_px: DEFAULT_MAX_GAP_PX,
+        }
+    }
+}
+
+/// How to get to a waypoint from the previous one.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Move {
+    Walk,
+    Jump,
+    Drop,
+}
+
+/// One step of a [`WalkPath`].
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Waypoint {
+    pub x: f64,
+    pub y: f64,
+    pub action: Move,
+    /// Window whose top the waypoint is on; `None` for the floor.
+    pub window_id: Option<u32>,
+}
+
+/// Result of [`plan_walk_path`].
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WalkPath {
+    /// [`Boundaries::version`] the path was planned against. Replan when a
+    /// `"boundaries-changed"` event carries a newer version.
+    pub boundaries_version: u64,
+    /// Steps after the start point, in order. Empty if already there.
+    pub waypoints: Vec<Waypoint>,
+    /// Planner cost (pixels walked plus weighted climbing and dropping).
+    pub cost: f64,
+}
+
+/// A horizontal segment the character can stand on.
+struct Surface {
+    y: f64,
+    x0: f64,
+    x1: f64,
+    window_id: Option<u32>,
+}
+
+impl Surface {
+    fn clamp(&self, x: f64) -> f64 {
+        x.clamp(self.x0, self.x1)
+    }
+
+    fn covers(&self, x: f64) -> bool {
+        x >= self.x0 && x <= self.x1
+    }
+}
+
+/// Remove `[a, b]` from every span.
+fn subtract(spans: Vec<(f64, f64)>, a: f64, b: f64) -> Vec<(f64, f64)> {
+    let mut out = Vec::with_capacity(spans.len() + 1);
+    for (s, e) in spans {
+        if b <= s || a >= e {
+            out.push((s, e));
+            continue;
+        }
+        if a > s {
+            out.push((s, a));
+        }
+        if b < e {
+            out.push((b, e));
+        }
+    }
+    out
+}
+
+/// The floor plus the visible parts of every window top inside the work area.
+fn surfaces(b: &Boundaries) -> Vec<Surface> {
+    let Some(work) = b.work_area else {
+        return Vec::new();
+    };
+    let floor = work.y + work.height;
+    let (left, right) = (work.x, work.x + work.width);
+    let mut out = vec![Surface {
+        y: floor,
+        x0: left,
+        x1: right,
+        window_id: None,
+    }];
+
+    for (i, p) in b.platforms.iter().enumerate() {
+        if p.y <= work.y || p.y >= floor {
+            continue;
+        }
+        let mut spans = vec![(p.x.max(left), (p.x + p.width).min(right))];
+        // Platforms are front-most first: anything earlier that spans this
+        // top's height hides part of it.
+        for front in &b.platforms[..i] {
+            if front.y < p.y && p.y < front.y + front.height {
+                spans = subtract(spans, front.x, front.x + front.width);
+            }
+        }
+        out.extend(
+            spans
+                .into_iter()
+                .filter(|(x0, x1)| x1 - x0 >= MIN_SURFACE_WIDTH)
+                .map(|(x0, x1)| Surface {
+                    y: p.y,
+                    x0,
+                    x1,
+                    window_id: Some(p.window_id),
+                }),
+        );
+    }
+    out
+}
+
+/// The highest surface at `x` whose top is at or below `y` (within the snap
+/// tolerance).
+fn surface_below(surfaces: &[Surface], x: f64, y: f64) -> Option<usize> {
+    surfaces
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| s.covers(x) && s.y >= y - SNAP_TOLERANCE_PX)
+        .min_by(|(_, a), (_, b)| a.y.total_cmp(&b.y))
+        .map(|(i, _)| i)
+}
+
+/// Search graph: points on surfaces joined by walk, jump and drop edges.
+#[derive(Default)]
+struct Graph {
+    /// `(surface, x)` of every node.
+    nodes: Vec<(usize, f64)>,
+    edges: Vec<Vec<(usize, f64, Move)>>,
+}
+
+impl Graph {
+    fn add_node(&mut self, surface: usize, x: f64) -> usize {
+        self.nodes.push((surface, x));
+        self.edges.push(Vec::new());
+        self.nodes.len() - 1
+    }
+
+    /// Add a one-way transition between surfaces.
+    fn add_transition(
+        &mut self,
+        surfaces: &[Surface],
+        (from, from_x): (usize, f64),
+        (to, to_x): (usize, f64),
+        action: Move,
+    ) {
+        let cost =
+            (to_x - from_x).abs() + (surfaces[to].y - surfaces[from].y).abs() * VERTICAL_COST;
+        let a = self.add_node(from, from_x);
+        let b = self.add_node(to, to_x);
+        self.edges[a].push((b, cost, action));
+    }
+
+    /// Join neighbouring nodes on the same surface with walk edges.
+    fn connect_walks(&mut self, surface_count: usize) {
+        let mut by_surface: Vec<Vec<usize>> = vec![Vec::new(); surface_count];
+        for (i, &(s, _)) in self.nodes.iter().enumerate() {
+            by_surface[s].push(i);
+        }
+        for mut ids in by_surface {
+            ids.sort_by(|&a, &b| self.nodes[a].1.total_cmp(&self.nodes[b].1));
+            for pair in ids.windows(2) {
+                let (a, b) = (pair[0], pair[1]);
+                let cost = (self.nodes[b].1 - self.nodes[a].1).abs();
+                self.edges[a].push((b, cost, Move::Walk));
+                self.edges[b].push((a, cost, Move::Walk));
+            }
+        }
+    }
+}
+
+/// Dijkstra queue entry, ordered by lowest cost first.
+struct Entry {
+    cost: f64,
+    node: usize,
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+/// Plan a path over `boundaries` (see [`plan_walk_path`]).
+fn plan(
+    boundaries: &Boundaries,
+    from: Point,
+    to: Point,
+    options: &WalkOptions,
+) -> Result<WalkPath, String> {
+    let surfaces = surfaces(boundaries);
+    let start =
+        surface_below(&surfaces, from.x, from.y).ok_or("Start point is not above any surface")?;
+    let goal =
+        surface_below(&surfaces, to.x, to.y).ok_or("Target point is not above any surface")?;
+
+    let mut graph = Graph::default();
+    let start_node = graph.add_node(start, surfaces[start].clamp(from.x));
+    let goal_node = graph.add_node(goal, surfaces[goal].clamp(to.x));
+
+    for (i, s) in surfaces.iter().enumerate() {
+        // Drop off either end onto the next surface below.
+        for (edge_x, off_x) in [(s.x0, s.x0 - 1.0), (s.x1, s.x1 + 1.0)] {
+            if let Some(j) = surface_below(&surfaces, off_x, s.y + SNAP_TOLERANCE_PX + 1.0) {
+                graph.add_transition(
+                    &surfaces,
+                    (i, edge_x),
+                    (j, surfaces[j].clamp(off_x)),
+                    Move::Drop,
+                );
+            }
+        }
+        // Jump up to higher surfaces in range.
+        for (j, t) in surfaces.iter().enumerate() {
+            let rise = s.y - t.y;
+            if rise <= 0.0 || rise > options.max_jump_px {
+                continue;
+            }
+            let gap = (t.x0 - s.x1).max(s.x0 - t.x1);
+            if gap > options.max_gap_px {
+                continue;
+            }
+            if gap <= 0.0 {
+                // Overlapping: jump straight up at either end of the overlap.
+                for x in [s.x0.max(t.x0), s.x1.min(t.x1)] {
+                    graph.add_transition(&surfaces, (i, x), (j, x), Move::Jump);
+                }
+            } else if t.x0 > s.x1 {
+                graph.add_transition(&surfaces, (i, s.x1), (j, t.x0), Move::Jump);
+            } else {
+                graph.add_transition(&surfaces, (i, s.x0), (j, t.x1), Move::Jump);
+            }
+        }
+    }
+    graph.connect_walks(surfaces.len());
+
+    let mut best = vec![f64::INFINITY; graph.nodes.len()];
+    let mut prev: Vec<Option<(usize, Move)>> = vec![None; graph.nodes.len()];
+    let mut queue = BinaryHeap::new();
+    best[start_node] = 0.0;
+    queue.push(Entry {
+        cost: 0.0,
+        node: start_node,
+    });
+    while let Some(Entry { cost, node }) = queue.pop() {
+        if node == goal_node {
+            break;
+        }
+        if cost > best[node] {
+            continue;
+        }
+        for &(next, step, action) in &graph.edges[node] {
+            let next_cost = cost + step;
+            if next_cost < best[next] {
+                best[next] = next_cost;
+                prev[next] = Some((node, action));
+                queue.push(Entry {
+                    cost: next_cost,
+                    node: next,
+                });
+            }
+        }
+    }
+    if !best[goal_node].is_finite() {
+        return Err("No walkable path to the target".to_string());
+    }
+
+    // Walk back from the goal, then merge consecutive walk steps.
+    let mut steps = Vec::new();
+    let mut node = goal_node;
+    while let Some((from_node, action)) = prev[node] {
+        steps.push((node, action));
+        node = from_node;
+    }
+    steps.reverse();
+
+    let mut waypoints: Vec<(usize, Waypoint)> = Vec::new();
+    if from.y < surfaces[start].y - SNAP_TOLERANCE_PX {
+        waypoints.push((
+            start,
+            waypoint(&surfaces, graph.nodes[start_node], Move::Drop),
+        ));
+    }
+    for (node, action) in steps {
+        let (surface, _) = graph.nodes[node];
+        let point = waypoint(&surfaces, graph.nodes[node], action);
+        match waypoints.last_mut() {
+            Some((s, last))
+                if action == Move::Walk && last.action == Move::Walk && *s == surface =>
+            {
+                *last = point;
+            }
+            _ => waypoints.push((surface, point)),
+        }
+    }
+
+    Ok(WalkPath {
+        boundaries_version: boundaries.version,
+        waypoints: waypoints.into_iter().map(|(_, w)| w).collect(),
+        cost: best[goal_node],
+    })
+}
+
+fn waypoint(surfaces: &[Surface], (surface, x): (usize, f64), action: Move) -> Waypoint {
+    let s = &surfaces[surface];
+    Waypoint {
+        x,
+        y: s.y,
+        action,
+        window_id: s.window_id,
+    }
+}
+
+/// IPC command: plan a walk from `from` to `to` over the current desktop.
+///
+/// Both points are snapped down onto the surface below them. Returns `Err`
+/// if either point has no surface below it or the target surface cannot be
+/// reached (e.g. a window top higher than the character can jump).
+#[tauri::command]
+pub fn plan_walk_path(
+    app: tauri::AppHandle,
+    from: Point,
+    to: Point,
+    options: Option<WalkOptions>,
+) -> Result<WalkPath, String> {
+    plan(
+        &boundaries::get_boundaries(app),
+        from,
+        to,
+        &options.unwrap_or_default(),
+    )
+}
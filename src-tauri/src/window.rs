@@ -3,8 +3,25 @@
 //! Used at startup to size the transparent overlay window to cover the full
 //! screen, and by the frontend to convert screen-pixel coordinates to
 //! Three.js world-space.
+//!
+//! Also lets the overlay opt out of screen capture
+//! ([`set_exclude_from_capture`]) for users who stream or present.
+//!
+//! The overlay covers the primary screen unless [`DisplayConfig::monitor`]
+//! picks another one ([`place_overlay`], [`set_display_monitor`]).
 
-use serde::Serialize;
+use crate::config::ConfigState;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+/// Display section of [`crate::config::AppSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DisplayConfig {
+    /// Index into [`get_all_monitors`] of the monitor the overlay covers;
+    /// `None`, or a monitor that is no longer connected, uses the primary.
+    pub monitor: Option<usize>,
+}
 
 /// Primary screen dimensions in pixels.
 #[derive(Debug, Clone, Serialize)]
@@ -76,6 +93,110 @@ pub struct MonitorInfo {
     pub height: u32,
     pub scale_factor: f64,
     pub is_primary: bool,
+    /// Physical panel width in millimetres (from EDID), if the display reports it.
+    pub physical_width_mm: Option<f64>,
+    /// Physical panel height in millimetres (from EDID), if the display reports it.
+    pub physical_height_mm: Option<f64>,
+    /// Multiplier for the character's on-screen size so it appears the same
+    /// physical size as on the primary monitor. `1.0` when unknown.
+    pub scale_compensation: f64,
+}
+
+/// Smallest plausible panel width. Projectors and some TVs report their
+/// aspect ratio (e.g. 16x9 cm) instead of a real size.
+#[cfg_attr(not(any(target_os = "macos", target_os = "windows")), allow(dead_code))]
+const MIN_PLAUSIBLE_WIDTH_MM: f64 = 100.0;
+
+/// Fill in `scale_compensation` for every monitor, relative to the primary.
+///
+/// `mm_per_point[i]` is the physical width of one logical pixel on
+/// `monitors[i]`, or `None` if the panel size is unknown. A character that is
+/// `S` logical pixels tall on the primary should be `S * compensation` on
+/// another monitor to keep the same physical height.
+#[cfg_attr(not(any(target_os = "macos", target_os = "windows")), allow(dead_code))]
+fn apply_scale_compensation(monitors: &mut [MonitorInfo], mm_per_point: &[Option<f64>]) {
+    let reference = monitors
+        .iter()
+        .zip(mm_per_point)
+        .find(|(m, _)| m.is_primary)
+        .and_then(|(_, mm)| *mm);
+
+    for (monitor, mm) in monitors.iter_mut().zip(mm_per_point) {
+        monitor.scale_compensation = match (reference, mm) {
+            (Some(r), Some(mm)) if *mm > 0.0 => r / mm,
+            _ => 1.0,
+        };
+    }
+}
+
+/// Physical size of one logical pixel, if the panel width is plausible.
+#[cfg_attr(not(any(target_os = "macos", target_os = "windows")), allow(dead_code))]
+fn mm_per_point(physical_width_mm: Option<f64>, logical_width: f64) -> Option<f64> {
+    physical_width_mm
+        .filter(|mm| *mm >= MIN_PLAUSIBLE_WIDTH_MM && logical_width > 0.0)
+        .map(|mm| mm / logical_width)
+}
+
+/// Frame the overlay should cover, top-left origin: the configured monitor,
+/// else the primary screen.
+fn overlay_frame(config: &DisplayConfig) -> crate::snap::Rect {
+    config
+        .monitor
+        .and_then(|index| crate::snap::monitor_rects().get(index).copied())
+        .unwrap_or_else(|| {
+            let size = get_screen_size();
+            crate::snap::Rect {
+                x: 0.0,
+                y: 0.0,
+                width: size.width as f64,
+                height: size.height as f64,
+            }
+        })
+}
+
+/// Move and resize the main window to cover the monitor chosen in
+/// [`DisplayConfig`].
+pub fn place_overlay(app: &tauri::AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let config = app
+        .state::<ConfigState>()
+        .get_settings()
+        .map(|s| s.display)
+        .unwrap_or_default();
+    let frame = overlay_frame(&config);
+    let _ = window.set_position(tauri::LogicalPosition::new(frame.x, frame.y));
+    let _ = window.set_size(tauri::LogicalSize::new(frame.width, frame.height));
+}
+
+/// IPC command: put the overlay on monitor `monitor` (an index into
+/// [`get_all_monitors`]), or back on the primary with `None`, and remember
+/// the choice.
+///
+/// # Errors
+///
+/// Returns `Err` if no such monitor is connected or the settings cannot be
+/// saved.
+#[tauri::command]
+pub fn set_display_monitor(
+    app: tauri::AppHandle,
+    config_state: tauri::State<'_, ConfigState>,
+    monitor: Option<usize>,
+) -> Result<(), String> {
+    if let Some(index) = monitor {
+        let count = get_all_monitors().len();
+        if index >= count {
+            return Err(format!("No monitor {index}; {count} connected"));
+        }
+    }
+    {
+        let mut settings = config_state.settings.write().map_err(|e| e.to_string())?;
+        settings.display.monitor = monitor;
+    }
+    config_state.save_settings()?;
+    place_overlay(&app);
+    Ok(())
 }
 
 /// Information about the macOS Dock (or equivalent taskbar).
@@ -184,7 +305,11 @@ pub fn get_dock_info() -> DockInfo {
 /// Returns all connected monitors with their positions, dimensions, and scale factors.
 ///
 /// On macOS, enumerates via `NSScreen::screens()`. The first screen in the
-/// array is always the primary monitor.
+/// array is always the primary monitor. Physical panel sizes come from
+/// `CGDisplayScreenSize`, which reads the display's EDID.
+///
+/// On Windows, physical sizes come from `GetDeviceCaps(HORZSIZE/VERTSIZE)` on
+/// the monitor's device context, which the driver derives from EDID.
 ///
 /// On non-macOS platforms, returns a single fallback monitor at (0,0) 1920x1080.
 #[tauri::command]
@@ -192,14 +317,19 @@ pub fn get_all_monitors() -> Vec<MonitorInfo> {
     #[cfg(target_os = "macos")]
     {
         use cocoa::appkit::NSScreen;
-        use cocoa::base::nil;
-        use cocoa::foundation::{NSArray, NSRect};
+        use cocoa::base::{id, nil};
+        use cocoa::foundation::{NSArray, NSRect, NSString};
+        use core_graphics::display::CGDisplay;
+        use objc::{msg_send, sel, sel_impl};
 
         let mut monitors = Vec::new();
+        let mut mm_per_pt = Vec::new();
 
         // SAFETY: NSScreen::screens() returns an autoreleased NSArray of NSScreen
         // objects. We iterate over them and read their frame/backingScaleFactor,
-        // which are plain struct copies and floats.
+        // which are plain struct copies and floats. `deviceDescription` is an
+        // autoreleased NSDictionary whose "NSScreenNumber" entry is an NSNumber
+        // holding the CGDirectDisplayID.
         unsafe {
             let screens = NSScreen::screens(nil);
             let count = screens.count();
@@ -207,6 +337,22 @@ pub fn get_all_monitors() -> Vec<MonitorInfo> {
                 let screen = screens.objectAtIndex(i);
                 let frame: NSRect = NSScreen::frame(screen);
                 let scale = NSScreen::backingScaleFactor(screen);
+
+                let description: id = msg_send![screen, deviceDescription];
+                let key = NSString::alloc(nil).init_str("NSScreenNumber");
+                let number: id = msg_send![description, objectForKey: key];
+                let _: () = msg_send![key, release];
+                let physical = if number != nil {
+                    let display_id: u32 = msg_send![number, unsignedIntValue];
+                    let size = CGDisplay::new(display_id).screen_size();
+                    Some((size.width, size.height))
+                } else {
+                    None
+                };
+
+                let physical_width_mm = physical.map(|(w, _)| w).filter(|w| *w > 0.0);
+                let physical_height_mm = physical.map(|(_, h)| h).filter(|h| *h > 0.0);
+                mm_per_pt.push(mm_per_point(physical_width_mm, frame.size.width));
                 monitors.push(MonitorInfo {
                     x: frame.origin.x as i32,
                     y: frame.origin.y as i32,
@@ -214,20 +360,26 @@ pub fn get_all_monitors() -> Vec<MonitorInfo> {
                     height: frame.size.height as u32,
                     scale_factor: scale,
                     is_primary: i == 0,
+                    physical_width_mm,
+                    physical_height_mm,
+                    scale_compensation: 1.0,
                 });
             }
         }
 
         if !monitors.is_empty() {
+            apply_scale_compensation(&mut monitors, &mm_per_pt);
             return monitors;
         }
     }
 
     #[cfg(target_os = "windows")]
     {
+        use windows::core::{w, PCWSTR};
         use windows::Win32::Foundation::{BOOL, LPARAM, RECT};
         use windows::Win32::Graphics::Gdi::{
-            EnumDisplayMonitors, GetMonitorInfoW, HDC, HMONITOR, MONITORINFO, MONITORINFOEXW,
+            CreateDCW, DeleteDC, EnumDisplayMonitors, GetDeviceCaps, GetMonitorInfoW, HDC,
+            HMONITOR, HORZSIZE, MONITORINFO, MONITORINFOEXW, VERTSIZE,
         };
         use windows::Win32::UI::HiDpi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI};
 
@@ -235,6 +387,7 @@ pub fn get_all_monitors() -> Vec<MonitorInfo> {
 
         struct MonitorData {
             monitors: Vec<MonitorInfo>,
+            mm_per_point: Vec<Option<f64>>,
         }
 
         unsafe extern "system" fn enum_cb(
@@ -253,13 +406,35 @@ pub fn get_all_monitors() -> Vec<MonitorInfo> {
                 let mut dpi_y: u32 = 96;
                 let _ = GetDpiForMonitor(hmon, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y);
                 let scale = dpi_x as f64 / 96.0;
+
+                // Physical size in mm from the monitor's own device context.
+                let hdc = CreateDCW(w!("DISPLAY"), PCWSTR(mi.szDevice.as_ptr()), PCWSTR::null(), None);
+                let (width_mm, height_mm) = if hdc.is_invalid() {
+                    (0, 0)
+                } else {
+                    let size = (
+                        GetDeviceCaps(Some(hdc), HORZSIZE),
+                        GetDeviceCaps(Some(hdc), VERTSIZE),
+                    );
+                    let _ = DeleteDC(hdc);
+                    size
+                };
+                let physical_width_mm = Some(width_mm as f64).filter(|w| *w > 0.0);
+                let physical_height_mm = Some(height_mm as f64).filter(|h| *h > 0.0);
+
+                let width = (rc.right - rc.left) as u32;
+                data.mm_per_point
+                    .push(mm_per_point(physical_width_mm, width as f64 / scale));
                 data.monitors.push(MonitorInfo {
                     x: rc.left,
                     y: rc.top,
-                    width: (rc.right - rc.left) as u32,
+                    width,
                     height: (rc.bottom - rc.top) as u32,
                     scale_factor: scale,
                     is_primary,
+                    physical_width_mm,
+                    physical_height_mm,
+                    scale_compensation: 1.0,
                 });
             }
             BOOL(1)
@@ -267,6 +442,7 @@ pub fn get_all_monitors() -> Vec<MonitorInfo> {
 
         let mut data = MonitorData {
             monitors: Vec::new(),
+            mm_per_point: Vec::new(),
         };
         unsafe {
             let _ = EnumDisplayMonitors(
@@ -277,6 +453,7 @@ pub fn get_all_monitors() -> Vec<MonitorInfo> {
             );
         }
         if !data.monitors.is_empty() {
+            apply_scale_compensation(&mut data.monitors, &data.mm_per_point);
             return data.monitors;
         }
     }
@@ -289,5 +466,107 @@ pub fn get_all_monitors() -> Vec<MonitorInfo> {
         height: 1080,
         scale_factor: 1.0,
         is_primary: true,
+        physical_width_mm: None,
+        physical_height_mm: None,
+        scale_compensation: 1.0,
     }]
 }
+
+/// Run `f`, which queries the screens, from any thread.
+///
+/// `NSScreen` may only be used on the main thread, so on macOS a call from
+/// another thread runs `f` there and waits for it. Elsewhere `f` runs in
+/// place. `None` if the event loop has shut down.
+pub(crate) fn on_main_thread<T: Send + 'static>(
+    app: &tauri::AppHandle,
+    f: impl FnOnce() -> T + Send + 'static,
+) -> Option<T> {
+    #[cfg(target_os = "macos")]
+    {
+        use objc::runtime::{BOOL, NO};
+        use objc::{class, msg_send, sel, sel_impl};
+
+        // SAFETY: +[NSThread isMainThread] takes no arguments.
+        let is_main: BOOL = unsafe { msg_send![class!(NSThread), isMainThread] };
+        if is_main == NO {
+            let (tx, rx) = std::sync::mpsc::sync_channel(1);
+            app.run_on_main_thread(move || {
+                let _ = tx.send(f());
+            })
+            .ok()?;
+            return rx.recv().ok();
+        }
+    }
+    let _ = app;
+    Some(f())
+}
+
+/// IPC command: hide the overlay window from screen capture.
+///
+/// With `exclude = true` the window stays visible on the user's display but
+/// is left out of screenshots, screen recordings and screen sharing:
+///
+/// - macOS: `NSWindow.sharingType = NSWindowSharingNone`.
+/// - Windows: `SetWindowDisplayAffinity(WDA_EXCLUDEFROMCAPTURE)` (Windows 10
+///   2004 or later; older versions fall back to `WDA_MONITOR`, which shows
+///   the window as black in captures).
+///
+/// # Errors
+///
+/// Returns `Err` if the main window is missing, the OS call fails, or on
+/// platforms without a capture-exclusion API.
+#[tauri::command]
+pub fn set_exclude_from_capture(app: tauri::AppHandle, exclude: bool) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found")?;
+    apply_capture_exclusion(&window, exclude)
+}
+
+#[cfg(target_os = "macos")]
+fn apply_capture_exclusion(window: &tauri::WebviewWindow, exclude: bool) -> Result<(), String> {
+    use objc::{msg_send, sel, sel_impl};
+
+    /// `NSWindowSharingNone` / `NSWindowSharingReadOnly`.
+    const SHARING_NONE: u64 = 0;
+    const SHARING_READ_ONLY: u64 = 1;
+
+    let ns_window = window.ns_window().map_err(|e| e.to_string())? as usize;
+    let sharing = if exclude { SHARING_NONE } else { SHARING_READ_ONLY };
+    // AppKit must be touched from the main thread.
+    window
+        .run_on_main_thread(move || {
+            // SAFETY: `ns_window` is the live NSWindow backing the main
+            // webview window; setSharingType: takes a plain integer.
+            unsafe {
+                let ns_window = ns_window as cocoa::base::id;
+                let _: () = msg_send![ns_window, setSharingType: sharing];
+            }
+        })
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn apply_capture_exclusion(window: &tauri::WebviewWindow, exclude: bool) -> Result<(), String> {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SetWindowDisplayAffinity, WDA_EXCLUDEFROMCAPTURE, WDA_MONITOR, WDA_NONE,
+    };
+
+    let hwnd = HWND(window.hwnd().map_err(|e| e.to_string())?.0);
+    // SAFETY: `hwnd` is the live top-level window handle of the main window.
+    unsafe {
+        if !exclude {
+            SetWindowDisplayAffinity(hwnd, WDA_NONE)
+        } else {
+            SetWindowDisplayAffinity(hwnd, WDA_EXCLUDEFROMCAPTURE)
+                .or_else(|_| SetWindowDisplayAffinity(hwnd, WDA_MONITOR))
+        }
+    }
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn apply_capture_exclusion(_window: &tauri::WebviewWindow, _exclude: bool) -> Result<(), String> {
+    Err("Capture exclusion is not supported on this platform".to_string())
+}
@@ -11,8 +11,9 @@ import { useScreenWatch } from "./hooks/useScreenWatch.ts";
 import type { AppSession } from "./hooks/useScreenWatch.ts";
 import { useFTUE } from "./hooks/useFTUE.ts";
 import { CommentEngine } from "./lib/commentEngine.ts";
-import { sendChat, getBrowserUrl } from "./lib/openclaw.ts";
-import { parseResponse } from "./lib/emotionParser.ts";
+import { sendChat, getBrowserUrl, webviewHeartbeat, getPowerState } from "./lib/openclaw.ts";
+import type { PowerState } from "./lib/openclaw.ts";
+import { fromChatResponse } from "./lib/emotionParser.ts";
 import { PrivacyManager } from "./lib/privacyManager.ts";
 import type { PrivacySettings } from "./lib/privacyManager.ts";
 import { MemoryManager } from "./lib/memoryManager.ts";
@@ -30,6 +31,7 @@ import {
   RECALL_HAPPY_TRANSITION_MS,
   COMMENT_EVAL_INTERVAL_MS,
   MEMORY_WORKER_INTERVAL_MS,
+  WEBVIEW_HEARTBEAT_INTERVAL_MS,
   QUIET_MODE_DURATION_MS,
   FTUE_BUBBLE_WIDTH,
   FTUE_SCREEN_MARGIN,
@@ -238,6 +240,35 @@ function App() {
 
     // Reset sense of self session counter
     senseOfSelf.resetSession();
+  }, [memoryManager, privacyManager, islandManager, senseOfSelf, processNewM0]);
+
+  // Battery saver: caps the render loop and pauses the memory workers
+  const [powerState, setPowerState] = useState<PowerState | null>(null);
+
+  useEffect(() => {
+    let cancelled = false;
+    let unlisten: (() => void) | undefined;
+
+    getPowerState()
+      .then((state) => { if (!cancelled) setPowerState(state); })
+      .catch((err) => log.warn("[App] Failed to get power state:", err));
+    listen<PowerState>("power-profile-changed", (event) => {
+      setPowerState(event.payload);
+    }).then((fn) => {
+      if (cancelled) fn();
+      else unlisten = fn;
+    });
+
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, []);
+
+  const pauseSync = powerState?.pauseSync ?? false;
+
+  useEffect(() => {
+    if (pauseSync) return;
 
     // Memory workers: run promotion + expiration + belief extraction hourly
     const workerInterval = setInterval(async () => {
@@ -262,7 +293,17 @@ function App() {
     }, MEMORY_WORKER_INTERVAL_MS);
 
     return () => clearInterval(workerInterval);
-  }, [memoryManager, privacyManager, islandManager, senseOfSelf, processNewM0]);
+  }, [memoryManager, islandManager, senseOfSelf, processNewM0, pauseSync]);
+
+  // Heartbeat for the backend health report
+  useEffect(() => {
+    const beat = () => {
+      webviewHeartbeat().catch((err) => log.warn("[App] Heartbeat failed:", err));
+    };
+    beat();
+    const heartbeatInterval = setInterval(beat, WEBVIEW_HEARTBEAT_INTERVAL_MS);
+    return () => clearInterval(heartbeatInterval);
+  }, []);
 
   const pollingInterval = privacyManager.getPollingInterval();
 
@@ -422,7 +463,7 @@ function App() {
         };
 
         buildReactivePrompt().then((res) => {
-          const parsed = parseResponse(res.response);
+          const parsed = fromChatResponse(res);
           // Filter out CLI noise (status words like "complete", "done", etc.)
           const isCliNoise = parsed.text && /^(completed?|done|ok|error|success(ful)?|failed|ready|processing)$/i.test(parsed.text.trim());
           if (parsed.text && !isCliNoise) {
@@ -726,6 +767,7 @@ function App() {
         characterScreenPosRef={characterScreenPosRef}
         onDockHeightChange={setDockHeight}
         personality={motionPersonality}
+        maxFps={powerState?.maxFps}
         onModelLoaded={(filename) => {
           setCurrentModelName(filename);
           showSpeechBubble(locale().model_loaded(filename));
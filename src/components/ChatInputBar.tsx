@@ -1,6 +1,7 @@
 import { useState, useRef, useEffect, useCallback } from "react";
 import { useChatSend } from "../hooks/useChatSend.ts";
 import { INPUT_FOCUS_DELAY_MS } from "../lib/constants.ts";
+import { describeOpenClawError, isOpenClawError } from "../lib/openclaw.ts";
 import type { MemoryManager } from "../lib/memoryManager.ts";
 import type { SoulManager } from "../lib/soulIdentity.ts";
 import type { SenseOfSelfManager } from "../lib/senseOfSelf.ts";
@@ -82,7 +83,11 @@ export default function ChatInputBar({
     try {
       await send(text);
     } catch (err) {
-      const msg = err instanceof Error ? err.message : "";
+      // OpenClaw commands reject with a typed error (see OpenClawError)
+      if (isOpenClawError(err) && err.kind === "cancelled") return;
+      const msg = isOpenClawError(err)
+        ? describeOpenClawError(err)
+        : err instanceof Error ? err.message : "";
       const display = msg.length > 0 && msg !== "undefined"
         ? `Sorry, something went wrong: ${msg.slice(0, 80)}`
         : "...";
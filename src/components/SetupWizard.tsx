@@ -7,7 +7,9 @@ import {
   saveOpenclawConfig,
   getOpenclawConfig,
   setupOpenclawHooks,
+  completeOnboardingStep,
   sendChat,
+  describeOpenClawError,
 } from "../lib/openclaw.ts";
 import type { AgentInfo } from "../lib/openclaw.ts";
 import { log } from "../lib/logger.ts";
@@ -166,13 +168,14 @@ export default function SetupWizard({
 
       setSetupProgress("Setting up hooks...");
       await setupOpenclawHooks();
+      await completeOnboardingStep("gateway");
 
       setSetupProgress("Done!");
       // Auto-advance to step 4 after a brief pause
       setTimeout(() => setStep(4), 800);
     } catch (err) {
       log.error("[SetupWizard] Setup failed:", err);
-      setSetupError(String(err));
+      setSetupError(describeOpenClawError(err));
     }
   }, [createNew, selectedAgent]);
 
@@ -188,7 +191,7 @@ export default function SetupWizard({
       setTestResult(res.response);
     } catch (err) {
       log.error("[SetupWizard] Test failed:", err);
-      setTestError(String(err));
+      setTestError(describeOpenClawError(err));
     } finally {
       setTesting(false);
     }
@@ -64,6 +64,8 @@ interface VRMViewerProps {
   onDockHeightChange?: (height: number) => void;
   /** Motion personality type for selecting personality-specific VRMA animations. */
   personality?: MotionPersonality;
+  /** Frame-rate cap from the backend power profile (`null` = uncapped). */
+  maxFps?: number | null;
 }
 
 // ---------- Component ----------
@@ -79,6 +81,7 @@ export default function VRMViewer({
   onModelError,
   onDockHeightChange,
   personality,
+  maxFps,
 }: VRMViewerProps) {
   const containerRef = useRef<HTMLDivElement>(null);
 
@@ -267,6 +270,7 @@ export default function VRMViewer({
     vrmScene,
     vrmError,
     onFrame: onFrameRef,
+    maxFps,
   });
 
   // ---- Initialize particle system when scene is available ----
@@ -4,6 +4,7 @@ import {
   getOpenclawConfig,
   saveOpenclawConfig,
   setupOpenclawHooks,
+  describeOpenClawError,
 } from "../../lib/openclaw.ts";
 import type { OpenClawConfig } from "../../lib/openclaw.ts";
 
@@ -95,7 +96,7 @@ export default function OpenClawSettings({ isOpen, onOpenSetupWizard }: OpenClaw
         setOpenclawOnline(false);
       }
     } catch (e) {
-      setOcConfigMsg({ type: "err", text: String(e) });
+      setOcConfigMsg({ type: "err", text: describeOpenClawError(e) });
     } finally {
       setOcConfigSaving(false);
     }
@@ -104,9 +105,15 @@ export default function OpenClawSettings({ isOpen, onOpenSetupWizard }: OpenClaw
   const handleSetupHooks = useCallback(async () => {
     setHooksSetupRunning(true);
     try {
-      const token = await setupOpenclawHooks();
+      const { token, changes } = await setupOpenclawHooks();
       setOcConfig((prev) => (prev ? { ...prev, hooksToken: token } : prev));
-      setOcConfigMsg({ type: "ok", text: "Hooks configured! Token saved." });
+      const changed = changes.filter((c) => c.action !== "unchanged").length;
+      setOcConfigMsg({
+        type: "ok",
+        text: changed
+          ? "Hooks configured! Token saved."
+          : "Hooks were already configured.",
+      });
       try {
         const healthy = await checkHealth();
         setOpenclawOnline(healthy);
@@ -114,7 +121,7 @@ export default function OpenClawSettings({ isOpen, onOpenSetupWizard }: OpenClaw
         setOpenclawOnline(false);
       }
     } catch (e) {
-      setOcConfigMsg({ type: "err", text: String(e) });
+      setOcConfigMsg({ type: "err", text: describeOpenClawError(e) });
     } finally {
       setHooksSetupRunning(false);
     }
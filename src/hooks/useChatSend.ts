@@ -1,6 +1,6 @@
 import { useState, useCallback } from "react";
 import { sendChat } from "../lib/openclaw.ts";
-import { parseResponse } from "../lib/emotionParser.ts";
+import { fromChatResponse } from "../lib/emotionParser.ts";
 import { composeContext } from "../lib/contextComposer.ts";
 import { detectConversationSignal } from "../lib/conversationSignals.ts";
 import type { MemoryManager } from "../lib/memoryManager.ts";
@@ -63,8 +63,7 @@ export function useChatSend({
         if (soulManager && memoryManager) {
           context = await composeContext(soulManager, memoryManager, senseOfSelf, islandManager);
         }
-        const rawResponse = await sendChat(text, context);
-        const parsed = parseResponse(rawResponse.response);
+        const parsed = fromChatResponse(await sendChat(text, context));
 
         // Skip callbacks for empty responses (backend returned no content)
         if (!parsed.text) {
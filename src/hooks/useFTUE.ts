@@ -4,6 +4,13 @@ import type { ChatMessage } from "../components/ChatWindow";
 import type { MemoryManager } from "../lib/memoryManager.ts";
 import { FTUE_AUTO_OPEN_DELAY_MS, FTUE_WAVE_DELAY_MS } from "../lib/constants.ts";
 import { locale } from "../lib/i18n";
+import {
+  completeOnboardingStep,
+  getOnboardingState,
+  startOnboarding,
+} from "../lib/openclaw.ts";
+import type { OnboardingState } from "../lib/openclaw.ts";
+import { log } from "../lib/logger.ts";
 
 // ---------- Constants ----------
 
@@ -40,6 +47,32 @@ function isFtueComplete(): boolean {
   return localStorage.getItem(FTUE_COMPLETE_KEY) === "true";
 }
 
+/**
+ * Go through the onboarding steps that need no conversation: permissions,
+ * microphone and character. Steps already done are skipped; a permission
+ * that is not granted leaves its step open, so the next launch asks again.
+ */
+async function runSetupSteps(state: OnboardingState): Promise<void> {
+  const done = state.completed;
+  if (!done.includes("permissions")) {
+    const granted = await invoke<boolean>("check_screen_permission");
+    if (granted) {
+      await completeOnboardingStep("permissions");
+    } else {
+      log.warn("[useFTUE] Screen recording permission not granted");
+    }
+  }
+  if (!done.includes("microphone")) {
+    // The backend records this step itself once access is granted.
+    const mic = await invoke<string>("request_mic_permission");
+    log.info("[useFTUE] Microphone permission:", mic);
+  }
+  if (!done.includes("character")) {
+    // The character on screen (the default model unless changed) is the choice.
+    await completeOnboardingStep("character");
+  }
+}
+
 // ---------- Hook ----------
 
 /**
@@ -59,6 +92,26 @@ export function useFTUE({
   );
   const [ftueMessages, setFtueMessages] = useState<ChatMessage[]>([]);
   const ftueStartedRef = useRef(false);
+  const onboardingResumedRef = useRef(false);
+
+  // Resume an onboarding wizard that was interrupted after the FTUE chat
+  // (e.g. the app quit during gateway setup).
+  useEffect(() => {
+    if (!isFtueComplete() || onboardingResumedRef.current) return;
+    onboardingResumedRef.current = true;
+    getOnboardingState()
+      .then(async (state) => {
+        if (!state.active) return;
+        await runSetupSteps(state);
+        if (!state.completed.includes("name")) {
+          await completeOnboardingStep("name");
+        }
+        if (!state.completed.includes("gateway")) {
+          onOpenClawSetupNeeded?.();
+        }
+      })
+      .catch((err) => log.warn("[useFTUE] Could not resume onboarding:", err));
+  }, [onOpenClawSetupNeeded]);
 
   // FTUE flow: greeting -> open chat with name question
   // Note: cleanup resets ftueStartedRef so React StrictMode's
@@ -67,6 +120,11 @@ export function useFTUE({
     if (isFtueComplete() || ftueStartedRef.current) return;
     ftueStartedRef.current = true;
 
+    // Onboarding: permissions, microphone and character before the chat.
+    startOnboarding()
+      .then(runSetupSteps)
+      .catch((err) => log.warn("[useFTUE] Could not run onboarding steps:", err));
+
     // Phase 1: Show greeting speech bubble + wave animation
     setFtuePhase("greeting");
     showSpeechBubble(locale().ftue_greeting);
@@ -104,6 +162,9 @@ export function useFTUE({
         if (userName) {
           localStorage.setItem(USER_NAME_KEY, userName);
           localStorage.setItem(FTUE_COMPLETE_KEY, "true");
+          completeOnboardingStep("name").catch((err) =>
+            log.warn("[useFTUE] Could not record onboarding step:", err),
+          );
 
           const responseText = locale().ftue_name_response(userName);
 
@@ -135,7 +196,12 @@ export function useFTUE({
           // Check OpenClaw CLI health — trigger setup wizard if not configured
           invoke("check_openclaw_health")
             .then((healthy) => {
-              if (!healthy && onOpenClawSetupNeeded) {
+              if (healthy) {
+                // Already connected; the setup wizard is not needed.
+                completeOnboardingStep("gateway").catch((err) =>
+                  log.warn("[useFTUE] Could not record onboarding step:", err),
+                );
+              } else if (onOpenClawSetupNeeded) {
                 onOpenClawSetupNeeded();
               }
             })
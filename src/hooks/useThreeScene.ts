@@ -27,6 +27,8 @@ export interface UseThreeSceneOptions {
   vrmError: string | null;
   /** Called each frame with delta time. Set `.current` before mount. */
   onFrame: MutableRefObject<((delta: number) => void) | null>;
+  /** Upper bound on the frame rate (e.g. in battery-saver mode). */
+  maxFps?: number | null;
 }
 
 export interface UseThreeSceneReturn {
@@ -97,6 +99,7 @@ export function useThreeScene({
   vrmScene,
   vrmError,
   onFrame,
+  maxFps,
 }: UseThreeSceneOptions): UseThreeSceneReturn {
   const sceneRef = useRef<THREE.Scene | null>(null);
   const cameraRef = useRef<THREE.PerspectiveCamera | null>(null);
@@ -106,6 +109,10 @@ export function useThreeScene({
   // raycasting is unreliable when bones are animated away from T-pose).
   const hitboxRef = useRef<THREE.Mesh | null>(null);
 
+  // Read by the animation loop, so changing the cap doesn't rebuild the scene.
+  const maxFpsRef = useRef(maxFps);
+  maxFpsRef.current = maxFps;
+
   const getHitTestTargets = useCallback((): THREE.Mesh[] => {
     return hitboxRef.current ? [hitboxRef.current] : [];
   }, []);
@@ -262,7 +269,8 @@ export function useThreeScene({
     function animate(now: number) {
       animationFrameId = requestAnimationFrame(animate);
 
-      const frameInterval = 1000 / targetFps;
+      const fps = Math.min(targetFps, maxFpsRef.current ?? Infinity);
+      const frameInterval = 1000 / fps;
       if (now - lastFrameTime < frameInterval) return;
       lastFrameTime = now;
 
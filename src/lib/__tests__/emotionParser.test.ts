@@ -1,114 +1,101 @@
 import { describe, it, expect } from "vitest";
-import { parseResponse } from "../emotionParser";
+import { fromChatResponse } from "../emotionParser";
+import type { ChatAction } from "../openclaw";
+
+/** A `send_chat` reply as the backend returns it, tags already parsed. */
+function reply(text: string, emotions: string[] = [], actions: ChatAction[] = []) {
+  return { text, emotions, actions };
+}
+
+const motion = (name: string): ChatAction => ({ kind: "motion", name });
 
 describe("emotionParser", () => {
-  describe("parseResponse", () => {
-    // Test 1: Extracts emotion and motion tags correctly
-    it("extracts [emotion:happy] and [motion:wave] tags", () => {
-      const result = parseResponse(
-        "[emotion:happy][motion:wave] 오랜만이다!",
-      );
+  describe("fromChatResponse", () => {
+    // Test 1: Reads the parsed emotion and motion
+    it("reads the tagged emotion and motion", () => {
+      const result = fromChatResponse(reply("오랜만이다!", ["happy"], [motion("wave")]));
       expect(result.emotion).toBe("happy");
       expect(result.motion).toBe("wave");
       expect(result.text).toBe("오랜만이다!");
     });
 
-    // Test 2: Handles empty string
-    it("handles empty string input", () => {
-      const result = parseResponse("");
+    // Test 2: Handles empty and missing replies
+    it("handles an empty reply", () => {
+      const result = fromChatResponse(reply(""));
       expect(result.text).toBe("");
       expect(result.emotion).toBe("neutral");
       expect(result.motion).toBeNull();
     });
 
-    // Test 2 continued: Handles no tags, invalid tags, multiple tags
-    it("handles text with no tags", () => {
-      const result = parseResponse("그냥 평범한 대화야");
-      expect(result.text).toBe("그냥 평범한 대화야");
+    it("handles a missing reply", () => {
+      const result = fromChatResponse(undefined);
+      expect(result.text).toBe("");
       expect(result.emotion).toBe("neutral");
       expect(result.motion).toBeNull();
     });
 
-    it("ignores invalid emotion values", () => {
-      const result = parseResponse("[emotion:INVALID] test");
-      expect(result.emotion).toBe("neutral"); // falls back to inference
-      expect(result.text).toBe("test");
+    it("handles a reply without tags", () => {
+      const result = fromChatResponse(reply("그냥 평범한 대화야"));
+      expect(result.text).toBe("그냥 평범한 대화야");
+      expect(result.emotion).toBe("neutral");
+      expect(result.motion).toBeNull();
     });
 
-    it("ignores invalid motion values", () => {
-      const result = parseResponse("[emotion:happy][motion:dance] test");
+    it("ignores unsupported motions", () => {
+      const result = fromChatResponse(reply("test", ["happy"], [motion("dance")]));
       expect(result.emotion).toBe("happy");
       expect(result.motion).toBeNull(); // 'dance' is not valid
     });
 
-    it("uses first valid tag when multiple are present", () => {
-      const result = parseResponse(
-        "[emotion:sad][emotion:happy] 복잡한 감정",
-      );
-      expect(result.emotion).toBe("sad"); // first valid one
-    });
-
-    it("handles tags case-insensitively", () => {
-      const result = parseResponse("[Emotion:HAPPY][Motion:WAVE] hi");
-      expect(result.emotion).toBe("happy");
-      expect(result.motion).toBe("wave");
+    it("ignores actions that are not motions", () => {
+      const result = fromChatResponse(reply("test", [], [{ kind: "gesture", name: "wave" }]));
+      expect(result.motion).toBeNull();
     });
 
-    it("strips tags and collapses extra whitespace", () => {
-      const result = parseResponse(
-        "[emotion:happy]  hello  [motion:nod]  world",
+    it("uses the first emotion and motion when several are tagged", () => {
+      const result = fromChatResponse(
+        reply("복잡한 감정", ["sad", "happy"], [motion("dance"), motion("nod"), motion("wave")]),
       );
-      expect(result.text).toBe("hello world");
+      expect(result.emotion).toBe("sad");
+      expect(result.motion).toBe("nod"); // first supported one
     });
 
     // Sentiment inference tests
     it("infers happy from Korean keywords", () => {
-      const result = parseResponse("하하 재밌다!");
+      const result = fromChatResponse(reply("하하 재밌다!"));
       expect(result.emotion).toBe("happy");
     });
 
     it("infers sad from Korean keywords", () => {
-      const result = parseResponse("너무 슬프다...");
+      const result = fromChatResponse(reply("너무 슬프다..."));
       expect(result.emotion).toBe("sad");
     });
 
     it("infers angry from Korean keywords", () => {
-      const result = parseResponse("진짜 짜증나");
+      const result = fromChatResponse(reply("진짜 짜증나"));
       expect(result.emotion).toBe("angry");
     });
 
     it("infers surprised from Korean keywords", () => {
-      const result = parseResponse("대박! 진짜?");
+      const result = fromChatResponse(reply("대박! 진짜?"));
       expect(result.emotion).toBe("surprised");
     });
 
     it("defaults to neutral when no keywords match", () => {
-      const result = parseResponse("내일 회의 있어");
+      const result = fromChatResponse(reply("내일 회의 있어"));
       expect(result.emotion).toBe("neutral");
     });
 
-    // All supported emotions
-    it("supports all 7 emotion values", () => {
-      const emotions = [
-        "happy",
-        "sad",
-        "angry",
-        "surprised",
-        "neutral",
-        "relaxed",
-        "thinking",
-      ];
-      for (const e of emotions) {
-        const result = parseResponse(`[emotion:${e}] test`);
-        expect(result.emotion).toBe(e);
-      }
+    it("prefers the tagged emotion over sentiment", () => {
+      const result = fromChatResponse(reply("하하 재밌다!", ["thinking"]));
+      expect(result.emotion).toBe("thinking");
     });
 
     // All supported motions
     it("supports all 4 motion values", () => {
       const motions = ["wave", "nod", "shake", "idle"];
       for (const m of motions) {
-        const result = parseResponse(`[motion:${m}] test`);
+        const result = fromChatResponse(reply("test", [], [motion(m)]));
         expect(result.motion).toBe(m);
       }
     });
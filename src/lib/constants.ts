@@ -135,6 +135,9 @@ export const COMMENT_EVAL_INTERVAL_MS = 30_000;
 /** Interval for memory worker tasks (ms) — promotion + expiration. */
 export const MEMORY_WORKER_INTERVAL_MS = 60 * 60 * 1000;
 
+/** Interval for webview heartbeats to the backend health report (ms); it times out after 30 s. */
+export const WEBVIEW_HEARTBEAT_INTERVAL_MS = 10_000;
+
 /** Quiet-mode mute duration (ms). */
 export const QUIET_MODE_DURATION_MS = 30 * 60 * 1000;
 
@@ -11,25 +11,11 @@ export interface ParsedResponse {
 
 // ---------- Constants ----------
 
-/** Supported emotion values. */
-const VALID_EMOTIONS = new Set([
-  "happy",
-  "sad",
-  "angry",
-  "surprised",
-  "neutral",
-  "relaxed",
-  "thinking",
-]);
-
 /** Supported motion values. */
 const VALID_MOTIONS = new Set(["wave", "nod", "shake", "idle"]);
 
-/** Tag extraction patterns. */
-const EMOTION_TAG_REGEX = /\[emotion:(\w+)\]/gi;
-const MOTION_TAG_REGEX = /\[motion:(\w+)\]/gi;
-
 import { locale } from "./i18n";
+import type { ChatResponse } from "./openclaw.ts";
 
 // ---------- Sentiment keyword map ----------
 
@@ -51,57 +37,28 @@ function getSentimentRules(): SentimentRule[] {
 // ---------- Parser ----------
 
 /**
- * Parse emotion and motion tags from an LLM response.
+ * Read the emotion and motion from a chat reply.
  *
- * Extracts `[emotion:xxx]` and `[motion:xxx]` tags, strips them from
- * the text, validates against known values, and falls back to simple
- * sentiment inference when no emotion tag is present.
+ * The backend has already stripped the tags (tags.rs) and returns them as
+ * `emotions` and `actions`. This takes the first tagged emotion and the
+ * first supported `motion` action, and falls back to simple sentiment
+ * inference when no emotion was tagged.
  *
- * @param raw - The raw response text from the LLM.
- * @returns Parsed response with clean text, emotion, and motion.
+ * @param reply - The `send_chat` response.
+ * @returns Clean text, emotion, and motion.
  */
-export function parseResponse(raw: string | null | undefined): ParsedResponse {
-  // Defend against null/undefined/empty input from malformed backend responses
-  if (!raw) {
+export function fromChatResponse(
+  reply: Pick<ChatResponse, "text" | "emotions" | "actions"> | null | undefined,
+): ParsedResponse {
+  // Defend against malformed backend responses
+  if (!reply?.text) {
     return { text: "", emotion: "neutral", motion: null };
   }
 
-  let emotion: string | null = null;
-  let motion: string | null = null;
-
-  // Extract the first valid emotion tag
-  const emotionMatches = [...raw.matchAll(EMOTION_TAG_REGEX)];
-  for (const match of emotionMatches) {
-    const value = match[1].toLowerCase();
-    if (VALID_EMOTIONS.has(value)) {
-      emotion = value;
-      break;
-    }
-  }
-
-  // Extract the first valid motion tag
-  const motionMatches = [...raw.matchAll(MOTION_TAG_REGEX)];
-  for (const match of motionMatches) {
-    const value = match[1].toLowerCase();
-    if (VALID_MOTIONS.has(value)) {
-      motion = value;
-      break;
-    }
-  }
-
-  // Strip all tags from the text
-  let text = raw
-    .replace(EMOTION_TAG_REGEX, "")
-    .replace(MOTION_TAG_REGEX, "")
-    .trim();
-
-  // Collapse multiple spaces/newlines left by tag removal
-  text = text.replace(/\s{2,}/g, " ").trim();
-
-  // If no emotion tag found, infer from sentiment
-  if (!emotion) {
-    emotion = inferSentiment(text);
-  }
+  const text = reply.text.trim();
+  const emotion = reply.emotions?.[0] ?? inferSentiment(text);
+  const motion =
+    reply.actions?.find((a) => a.kind === "motion" && VALID_MOTIONS.has(a.name))?.name ?? null;
 
   return {
     text,
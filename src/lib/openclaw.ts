@@ -3,8 +3,84 @@ import { log } from "./logger.ts";
 
 // ---------- Types ----------
 
+export interface ChatAction {
+  /** Tag name: "motion", "gesture" or "tool". */
+  kind: string;
+  name: string;
+}
+
 export interface ChatResponse {
+  /** The reply as the model wrote it, tags included. */
   response: string;
+  /** The reply with emotion/action tags stripped. */
+  text: string;
+  /** Tagged emotions in order of appearance. */
+  emotions: string[];
+  actions: ChatAction[];
+}
+
+/** Error thrown by the OpenClaw commands (see `OpenClawError` in openclaw.rs). */
+export interface OpenClawError {
+  kind:
+    | "networkUnreachable"
+    | "authFailed"
+    | "timeout"
+    | "gatewayError"
+    | "invalidConfig"
+    | "decryptFailed"
+    | "cancelled"
+    | "other";
+  message: string;
+  /** HTTP status, for `gatewayError`. */
+  status?: number | null;
+}
+
+export function isOpenClawError(err: unknown): err is OpenClawError {
+  return typeof err === "object" && err !== null && "kind" in err && "message" in err;
+}
+
+/** One problem with a config value (see `ConfigIssue` in validation.rs). */
+export interface ConfigIssue {
+  /** camelCase path of the field, e.g. "gatewayUrl" or "provider.ollama.url". */
+  field: string;
+  code: "required" | "invalidUrl" | "invalidFormat" | "outOfRange" | "conflict" | "notFound";
+  message: string;
+}
+
+/** Error thrown by the config save commands (see `ConfigError` in validation.rs). */
+export type ConfigError =
+  | { kind: "invalid"; issues: ConfigIssue[] }
+  | { kind: "other"; message: string };
+
+/** The field problems in a config save error, if that is what `err` is. */
+export function configIssues(err: unknown): ConfigIssue[] {
+  if (typeof err === "object" && err !== null && "issues" in err) {
+    return (err as { issues: ConfigIssue[] }).issues;
+  }
+  return [];
+}
+
+/**
+ * A message for the user that says what to do about `err`.
+ */
+export function describeOpenClawError(err: unknown): string {
+  const issues = configIssues(err);
+  if (issues.length > 0) return issues.map((i) => i.message).join("; ");
+  if (!isOpenClawError(err)) return String(err);
+  switch (err.kind) {
+    case "networkUnreachable":
+      return `${err.message} Is the OpenClaw Gateway running?`;
+    case "authFailed":
+      return "The token or API key was rejected — open Settings to update it.";
+    case "timeout":
+      return `${err.message}. Try again, or raise the timeout in Settings.`;
+    case "invalidConfig":
+      return `${err.message} (open Settings to fix it)`;
+    case "decryptFailed":
+      return `${err.message}. Check that the encryption key matches the gateway's.`;
+    default:
+      return err.message;
+  }
 }
 
 export interface OpenClawConfig {
@@ -37,6 +113,18 @@ export async function sendChat(message: string, context?: string): Promise<ChatR
   }
 }
 
+/**
+ * Send a chat message to `agentId` instead of the active agent, in that
+ * agent's own session. Runs alongside chats with the active agent.
+ */
+export async function sendChatTo(
+  agentId: string,
+  message: string,
+  context?: string,
+): Promise<ChatResponse> {
+  return invoke<ChatResponse>("send_chat_to", { agentId, message, context });
+}
+
 /**
  * Fire-and-forget webhook to POST /hooks/agent (async, returns immediately).
  * Use for background triggers where you don't need the response.
@@ -52,6 +140,34 @@ export async function checkHealth(): Promise<boolean> {
   return invoke<boolean>("check_openclaw_health");
 }
 
+/**
+ * Tell the backend the webview is alive, for the `/healthz` report.
+ */
+export async function webviewHeartbeat(): Promise<void> {
+  return invoke<void>("webview_heartbeat");
+}
+
+// ---------- Power API ----------
+
+/** Payload of `get_power_state` and `"power-profile-changed"` events. */
+export interface PowerState {
+  profile: "auto" | "performance" | "saver";
+  saverActive: boolean;
+  onBattery: boolean;
+  batteryPercent: number | null;
+  /** Frame-rate cap for the render loop (`null` = uncapped). */
+  maxFps: number | null;
+  /** Whether background jobs such as the memory workers should pause. */
+  pauseSync: boolean;
+}
+
+/**
+ * Get the current battery-saver state.
+ */
+export async function getPowerState(): Promise<PowerState> {
+  return invoke<PowerState>("get_power_state");
+}
+
 // ---------- Browser URL API ----------
 
 /**
@@ -77,11 +193,80 @@ export async function getOpenclawConfig(): Promise<OpenClawConfig> {
 
 /**
  * Save OpenClaw configuration to disk via the backend.
+ * Rejects with a `ConfigError` listing every invalid field.
  */
 export async function saveOpenclawConfig(config: OpenClawConfig): Promise<void> {
   return invoke<void>("save_openclaw_config", { config });
 }
 
+/** Problems with the saved config, e.g. from editing the files by hand. */
+export async function getConfigIssues(): Promise<ConfigIssue[]> {
+  return invoke<ConfigIssue[]>("get_config_issues");
+}
+
+/** A changed field in a `ConfigChange`; tokens show only a short SHA-256 fingerprint. */
+export interface FieldChange {
+  /** camelCase path of the field, e.g. "gatewayUrl" or "push.port". */
+  field: string;
+  old: unknown;
+  new: unknown;
+}
+
+/** One recorded change to config.json or settings.json (see audit.rs). */
+export interface ConfigChange {
+  /** Unix time in milliseconds. */
+  timestamp: number;
+  file: "config" | "settings";
+  /** "app" when saved by the app, "external" when edited by something else. */
+  source: "app" | "external";
+  fields: FieldChange[];
+}
+
+/** Recorded config changes, newest first. */
+export async function getConfigHistory(limit?: number): Promise<ConfigChange[]> {
+  return invoke<ConfigChange[]>("get_config_history", { limit });
+}
+
+/** Format of config and settings files (see `ConfigFormat` in config.rs). */
+export type ConfigFormat = "json" | "toml";
+
+export async function getConfigFormat(): Promise<ConfigFormat> {
+  return invoke<ConfigFormat>("get_config_format");
+}
+
+/** Rewrite config and settings files as `to`; converting to JSON drops TOML comments. */
+export async function convertConfigFormat(to: ConfigFormat): Promise<void> {
+  return invoke<void>("convert_config_format", { to });
+}
+
+// ---------- Onboarding API ----------
+
+/** First-run setup steps (see onboarding.rs), in wizard order. */
+export type OnboardingStep = "permissions" | "microphone" | "gateway" | "character" | "name";
+
+export interface OnboardingState {
+  /** Whether the wizard is running; only then do features wait for their steps. */
+  active: boolean;
+  completed: OnboardingStep[];
+  /** First step not done yet, where the wizard should resume. */
+  next: OnboardingStep | null;
+  finished: boolean;
+}
+
+export async function getOnboardingState(): Promise<OnboardingState> {
+  return invoke<OnboardingState>("get_onboarding_state");
+}
+
+/** Start (or resume) the onboarding wizard. */
+export async function startOnboarding(): Promise<OnboardingState> {
+  return invoke<OnboardingState>("start_onboarding");
+}
+
+/** Mark a setup step as done; during onboarding audio monitoring waits for "microphone". */
+export async function completeOnboardingStep(step: OnboardingStep): Promise<OnboardingState> {
+  return invoke<OnboardingState>("complete_onboarding_step", { step });
+}
+
 // ---------- Setup Wizard API ----------
 
 export interface InstalledCheck {
@@ -89,9 +274,20 @@ export interface InstalledCheck {
   version: string;
 }
 
+export interface AgentCapabilities {
+  vision: boolean;
+  tools: boolean;
+  streaming: boolean;
+}
+
 export interface AgentInfo {
   id: string;
   name: string;
+  /** Model the agent runs on, if the gateway reports it. */
+  model: string | null;
+  capabilities: AgentCapabilities;
+  /** Context window in tokens, if known. */
+  contextLength: number | null;
 }
 
 /**
@@ -102,10 +298,12 @@ export async function checkOpenclawInstalled(): Promise<InstalledCheck> {
 }
 
 /**
- * List existing OpenClaw agents via `openclaw agents list --json`.
+ * List existing OpenClaw agents via `openclaw agents list --json`, with the
+ * model and capabilities the gateway reports. Cached for a few minutes;
+ * pass `refresh` to query again.
  */
-export async function listOpenclawAgents(): Promise<AgentInfo[]> {
-  return invoke<AgentInfo[]>("list_openclaw_agents");
+export async function listOpenclawAgents(refresh = false): Promise<AgentInfo[]> {
+  return invoke<AgentInfo[]>("list_openclaw_agents", { refresh });
 }
 
 /**
@@ -117,11 +315,27 @@ export async function createOpenclawAgent(name: string): Promise<string> {
 
 // ---------- Setup API ----------
 
+/** One change hooks setup makes, or would make. Tokens are masked. */
+export interface SetupChange {
+  target: string;
+  action: "create" | "modify" | "unchanged";
+  old: string | null;
+  new: string | null;
+}
+
+export interface HooksSetup {
+  token: string;
+  changes: SetupChange[];
+  /** False for a dry run. */
+  applied: boolean;
+}
+
 /**
  * Configure OpenClaw hooks in ~/.openclaw/openclaw.json.
- * Generates a random token, writes hooks config, and returns the token.
- * The token is also automatically saved to the app's config.
+ * Enables hooks and sets the token, reusing an existing one, without
+ * touching the rest of the file. The token is also saved to the app's config.
+ * With `dryRun` nothing is written; the result lists what would change.
  */
-export async function setupOpenclawHooks(): Promise<string> {
-  return invoke<string>("setup_openclaw_hooks");
+export async function setupOpenclawHooks(dryRun = false): Promise<HooksSetup> {
+  return invoke<HooksSetup>("setup_openclaw_hooks", { dryRun });
 }